@@ -0,0 +1,179 @@
+use sdl2::rect::Rect;
+
+use crate::widget::WidgetPath;
+use crate::{Toolkit, Widget, WidgetId, WidgetState};
+
+// What a widget is, for screen readers and other assistive technology
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Role {
+    Window,
+    Tab,
+    // a container, or a frame with a title
+    Group,
+    Button,
+    RadioButton,
+    CheckBox,
+    Label,
+    TextInput,
+    PasswordInput,
+    TextArea,
+    Slider,
+    SpinBox,
+    ComboBox,
+    ListBox,
+    Table,
+    ColorPicker,
+    Image,
+    Canvas,
+    ScrollArea,
+    Splitter,
+    Link,
+    // a reading on a scale that the user can't change
+    Meter,
+    Separator,
+    // anything that doesn't say
+    Generic,
+}
+
+// What can be done to a widget on behalf of assistive technology
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessAction {
+    Focus,
+    // the same as clicking it, or selecting it for a tab
+    Click,
+}
+
+// One widget in the accessibility tree. Geometry is in the same units as
+// widget rects.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessNode {
+    pub role: Role,
+    // what Toolkit::access_action takes to find it again
+    pub name: String,
+    // text a screen reader would read out
+    pub label: String,
+    pub value: Option<String>,
+    // for check boxes, radio buttons and the like
+    pub checked: Option<bool>,
+    // for tabs, the one showing
+    pub selected: bool,
+    pub focusable: bool,
+    pub state: WidgetState,
+    pub rect: Rect,
+    // tab items have one, widgets nested in them don't
+    pub id: Option<WidgetId>,
+    pub children: Vec<AccessNode>,
+}
+
+impl AccessNode {
+    fn of(w: &dyn Widget, id: Option<WidgetId>) -> AccessNode {
+        let state = w.widget_state().copied().unwrap_or(WidgetState {enabled: w.is_enabled(), ..WidgetState::default()});
+        AccessNode {
+            role: w.role(),
+            name: w.name().to_string(),
+            label: w.access_label().unwrap_or_else(|| w.name().to_string()),
+            value: w.access_value(),
+            checked: w.access_checked(),
+            selected: false,
+            focusable: w.focusable(),
+            state,
+            rect: w.rect(),
+            id,
+            children: w.children().iter().map(|c| AccessNode::of(c.as_ref(), None)).collect(),
+        }
+    }
+
+    // This node and everything below it, depth first
+    pub fn find(&self, name: &str) -> Option<&AccessNode> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter().find_map(|c| c.find(name))
+    }
+}
+
+// Indices down to the first widget called `name`
+fn path_of(items: &[Box<dyn Widget>], name: &str) -> Option<WidgetPath> {
+    for (i, w) in items.iter().enumerate() {
+        if w.name() == name {
+            return Some(vec![i]);
+        }
+        if let Some(mut path) = path_of(w.children(), name) {
+            path.insert(0, i);
+            return Some(path);
+        }
+    }
+    None
+}
+
+impl Toolkit {
+    // The current window as assistive technology would see it: the window,
+    // a node per tab, and the widgets of the tab showing below its node
+    pub fn access_tree(&self) -> AccessNode {
+        let (w, h) = self.size();
+        let tabs = self.tabs.iter().zip(self.tab_bar_rects()).enumerate().map(|(i, (tab, rect))| {
+            let selected = i == self.tab_pos;
+            let children = if selected {
+                tab.items.iter().zip(tab.ids.iter()).map(|(w, id)| AccessNode::of(w.as_ref(), Some(*id))).collect()
+            } else {
+                Vec::new()
+            };
+            AccessNode {
+                role: Role::Tab,
+                name: tab.name().to_string(),
+                label: tab.name().to_string(),
+                value: None,
+                checked: None,
+                selected,
+                focusable: false,
+                state: WidgetState::default(),
+                rect,
+                id: None,
+                children,
+            }
+        }).collect();
+        AccessNode {
+            role: Role::Window,
+            name: String::new(),
+            label: self.canvas.window().title().to_string(),
+            value: None,
+            checked: None,
+            selected: false,
+            focusable: false,
+            state: WidgetState::default(),
+            rect: Rect::new(0, 0, w, h),
+            id: None,
+            children: tabs,
+        }
+    }
+
+    // Acts on the widget called `name` in the current tab, or on the tab
+    // called that. False if there is no such thing or it is disabled.
+    pub fn access_action(&mut self, name: &str, action: AccessAction) -> bool {
+        let Some(tab) = self.tabs.get(self.tab_pos) else {
+            return false;
+        };
+        let Some(path) = path_of(&tab.items, name) else {
+            return action == AccessAction::Click && self.select_tab(name).is_ok();
+        };
+        let Some(w) = tab.get(&path).filter(|w| w.is_enabled()) else {
+            return false;
+        };
+        self.dirty = true;
+        match action {
+            AccessAction::Focus => {
+                if !w.focusable() {
+                    return false;
+                }
+                self.set_focus(Some(path));
+            },
+            AccessAction::Click => {
+                if !w.clickable() {
+                    return false;
+                }
+                self.activate(&path);
+            },
+        }
+        true
+    }
+}
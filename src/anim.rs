@@ -0,0 +1,406 @@
+use std::fmt;
+use std::fmt::Debug;
+use std::time::Duration;
+
+use sdl2::rect::Rect;
+
+use crate::{Callback, Toolkit, WidgetId, WindowId};
+
+// How an animation gets from start to end, t goes from 0 to 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    InQuad,
+    OutQuad,
+    InOutQuad,
+    InCubic,
+    OutCubic,
+    InOutCubic,
+}
+
+impl Easing {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::InQuad => t * t,
+            Easing::OutQuad => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::InOutQuad => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            },
+            Easing::InCubic => t * t * t,
+            Easing::OutCubic => 1.0 - (1.0 - t).powi(3),
+            Easing::InOutCubic => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            },
+        }
+    }
+}
+
+// What of a widget an animation changes. Position and size are in pixels,
+// Alpha goes from 0 (gone) to 255 (opaque). The scroll properties count
+// pixels scrolled since the animation started, so `from` is usually 0.
+// Value is what widgets like Gauge show, see Widget::animate_value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Property {
+    X,
+    Y,
+    Width,
+    Height,
+    Alpha,
+    ScrollX,
+    ScrollY,
+    Value,
+}
+
+// How the content changes when another tab is picked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    // straight away
+    None,
+    // the new tab pushes the old one out, coming in from the side its
+    // header is on
+    Slide,
+    // one fades out while the other fades in
+    Crossfade,
+}
+
+// The tab being switched away from while a transition runs
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TabSwitch {
+    pub(crate) from: usize,
+    // 0 when it starts and 1 when the new tab has taken over
+    pub(crate) progress: f32,
+}
+
+// What an animation drives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Widget(WidgetId, Property),
+    TabSwitch(WindowId),
+}
+
+// Handle to a running animation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AnimationId(u64);
+
+pub(crate) struct Animation {
+    id: AnimationId,
+    target: Target,
+    from: f32,
+    to: f32,
+    duration: Duration,
+    easing: Easing,
+    // None until the first frame it is part of, so time spent idle before
+    // doesn't count
+    elapsed: Option<Duration>,
+    // value applied on the previous frame, scrolling moves by the difference
+    last: f32,
+    // whether the widget turned up in any window this frame
+    found: bool,
+    done: Option<Callback>,
+}
+
+impl Animation {
+    fn value(&self) -> f32 {
+        let elapsed = self.elapsed.unwrap_or(Duration::ZERO);
+        let t = if self.duration.is_zero() {
+            1.0
+        } else {
+            elapsed.as_secs_f32() / self.duration.as_secs_f32()
+        };
+        self.from + (self.to - self.from) * self.easing.apply(t)
+    }
+
+    fn finished(&self) -> bool {
+        self.elapsed.is_some_and(|e| e >= self.duration)
+    }
+}
+
+// Animations in progress across all windows, advanced once per frame
+#[derive(Default)]
+pub(crate) struct Animations {
+    list: Vec<Animation>,
+    next_id: u64,
+}
+
+impl Animations {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    fn add(&mut self, target: Target, from: f32, to: f32, duration: Duration, easing: Easing) -> AnimationId {
+        // a new animation of the same property takes over from the old one
+        self.list.retain(|a| a.target != target);
+        let id = AnimationId(self.next_id);
+        self.next_id += 1;
+        self.list.push(Animation {
+            id,
+            target,
+            from,
+            to,
+            duration,
+            easing,
+            elapsed: None,
+            last: from,
+            found: false,
+            done: None,
+        });
+        id
+    }
+
+    fn advance(&mut self, delta: Duration) {
+        for anim in self.list.iter_mut() {
+            anim.elapsed = Some(anim.elapsed.map_or(Duration::ZERO, |e| e + delta));
+            anim.found = false;
+        }
+    }
+
+    // Those that reached their end, and those whose widget is gone
+    fn take_finished(&mut self) -> Vec<Animation> {
+        let (done, rest) = std::mem::take(&mut self.list).into_iter().partition::<Vec<_>, _>(|a| a.finished() || !a.found);
+        self.list = rest;
+        done
+    }
+}
+
+impl Debug for Animations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Animations")
+            .field("running", &self.list.len())
+            .finish()
+    }
+}
+
+impl Toolkit {
+    // Moves `property` of a tab item from `from` to `to` over `duration`, a
+    // step every frame. While it runs it wins over the layout, items sized
+    // with set_size get theirs back on the next relayout after.
+    pub fn animate(&mut self, id: WidgetId, property: Property, from: f32, to: f32, duration: Duration, easing: Easing) -> AnimationId {
+        self.dirty = true;
+        self.animations.add(Target::Widget(id, property), from, to, duration, easing)
+    }
+
+    // Runs `f` once the animation has reached its end. Returns false if it
+    // is already over or was cancelled.
+    pub fn on_animation_done<F: FnMut(&mut Toolkit) + 'static>(&mut self, anim: AnimationId, f: F) -> bool {
+        match self.animations.list.iter_mut().find(|a| a.id == anim) {
+            Some(a) => {
+                a.done = Some(Box::new(f));
+                true
+            },
+            None => false,
+        }
+    }
+
+    // Stops the animation where it is, without running its done callback
+    pub fn cancel_animation(&mut self, anim: AnimationId) -> bool {
+        let len = self.animations.list.len();
+        self.animations.list.retain(|a| a.id != anim);
+        self.animations.list.len() != len
+    }
+
+    pub fn is_animating(&self, id: WidgetId) -> bool {
+        self.animations.list.iter().any(|a| matches!(a.target, Target::Widget(w, _) if w == id))
+    }
+
+    // Animates switching tabs from now on. Transition::None or a zero
+    // duration switch straight away, which is the default.
+    pub fn set_tab_transition(&mut self, transition: Transition, duration: Duration) {
+        self.transition = transition;
+        self.transition_time = duration;
+    }
+
+    pub fn tab_transition(&self) -> (Transition, Duration) {
+        (self.transition, self.transition_time)
+    }
+
+    // Starts the transition away from tab `from` in the current window
+    pub(crate) fn start_tab_switch(&mut self, from: usize) {
+        if self.transition == Transition::None || self.transition_time.is_zero() {
+            self.tab_switch = None;
+            return;
+        }
+        self.tab_switch = Some(TabSwitch {from, progress: 0.0});
+        self.animations.add(Target::TabSwitch(self.window), 0.0, 1.0, self.transition_time, Easing::OutCubic);
+    }
+
+    // Draws the widget see-through, 0 hides it and 255 is opaque
+    pub fn set_opacity(&mut self, id: WidgetId, alpha: u8) {
+        self.dirty = true;
+        if alpha == u8::MAX {
+            self.opacity.remove(&id);
+        } else {
+            self.opacity.insert(id, alpha);
+        }
+    }
+
+    pub fn opacity(&self, id: WidgetId) -> u8 {
+        self.opacity.get(&id).copied().unwrap_or(u8::MAX)
+    }
+
+    // Once per frame, before any window is updated
+    pub(crate) fn advance_animations(&mut self) {
+        if !self.animations.is_empty() {
+            self.animations.advance(self.delta);
+        }
+    }
+
+    // Applies the current values to the widgets of the current window
+    pub(crate) fn step_animations(&mut self) {
+        for anim in self.animations.list.iter_mut() {
+            let (id, property) = match anim.target {
+                Target::Widget(id, property) => (id, property),
+                Target::TabSwitch(window) => {
+                    if window != self.window {
+                        continue;
+                    }
+                    // gone if the tabs changed under it
+                    if let Some(switch) = self.tab_switch.as_mut() {
+                        anim.found = true;
+                        self.dirty = true;
+                        switch.progress = anim.value();
+                    }
+                    if anim.finished() {
+                        self.tab_switch = None;
+                    }
+                    continue;
+                },
+            };
+            let Some(w) = self.tabs.iter_mut().find_map(|tab| {
+                let pos = tab.position(id)?;
+                Some(tab.items[pos].as_mut())
+            }) else {
+                continue;
+            };
+            anim.found = true;
+            self.dirty = true;
+
+            let value = anim.value();
+            let rect = w.rect();
+            match property {
+                Property::X => w.set_rect(Rect::new(value.round() as i32, rect.y(), rect.width(), rect.height())),
+                Property::Y => w.set_rect(Rect::new(rect.x(), value.round() as i32, rect.width(), rect.height())),
+                Property::Width => w.set_rect(Rect::new(rect.x(), rect.y(), value.round().max(1.0) as u32, rect.height())),
+                Property::Height => w.set_rect(Rect::new(rect.x(), rect.y(), rect.width(), value.round().max(1.0) as u32)),
+                Property::Alpha => {
+                    let alpha = value.round().clamp(0.0, 255.0) as u8;
+                    if alpha == u8::MAX {
+                        self.opacity.remove(&id);
+                    } else {
+                        self.opacity.insert(id, alpha);
+                    }
+                },
+                // content moves the opposite way to the view
+                Property::ScrollX => {
+                    w.scroll_pixels(anim.last.round() as i32 - value.round() as i32, 0);
+                },
+                Property::ScrollY => {
+                    w.scroll_pixels(0, anim.last.round() as i32 - value.round() as i32);
+                },
+                Property::Value => w.animate_value(value as f64),
+            }
+            anim.last = value;
+        }
+    }
+
+    // Drops animations that are over, after every window had its step
+    pub(crate) fn finish_animations(&mut self) {
+        for anim in self.animations.take_finished() {
+            if let (true, Some(mut done)) = (anim.found, anim.done) {
+                done(self);
+                self.dirty = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Easing; 7] = [
+        Easing::Linear,
+        Easing::InQuad,
+        Easing::OutQuad,
+        Easing::InOutQuad,
+        Easing::InCubic,
+        Easing::OutCubic,
+        Easing::InOutCubic,
+    ];
+
+    #[test]
+    fn easings_start_at_0_and_end_at_1() {
+        for easing in ALL {
+            assert_eq!(easing.apply(0.0), 0.0, "{:?}", easing);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-6, "{:?}", easing);
+            // out of range t is clamped
+            assert_eq!(easing.apply(-1.0), 0.0, "{:?}", easing);
+            assert!((easing.apply(2.0) - 1.0).abs() < 1e-6, "{:?}", easing);
+        }
+    }
+
+    #[test]
+    fn easings_never_go_back() {
+        for easing in ALL {
+            let values: Vec<f32> = (0..=100).map(|i| easing.apply(i as f32 / 100.0)).collect();
+            assert!(values.windows(2).all(|w| w[0] <= w[1]), "{:?}", easing);
+        }
+    }
+
+    #[test]
+    fn in_out_easings_are_halfway_at_the_middle() {
+        for easing in [Easing::Linear, Easing::InOutQuad, Easing::InOutCubic] {
+            assert!((easing.apply(0.5) - 0.5).abs() < 1e-6, "{:?}", easing);
+        }
+    }
+
+    fn animation(duration: Duration, easing: Easing) -> Animations {
+        let mut anims = Animations::default();
+        anims.add(Target::TabSwitch(WindowId(1)), 10.0, 20.0, duration, easing);
+        anims
+    }
+
+    #[test]
+    fn value_follows_elapsed_time() {
+        let mut anims = animation(Duration::from_millis(100), Easing::Linear);
+        // the first frame it is part of counts as its start
+        anims.advance(Duration::from_secs(5));
+        assert_eq!(anims.list[0].value(), 10.0);
+        anims.advance(Duration::from_millis(50));
+        assert!((anims.list[0].value() - 15.0).abs() < 1e-4);
+        assert!(!anims.list[0].finished());
+        anims.advance(Duration::from_millis(60));
+        assert_eq!(anims.list[0].value(), 20.0);
+        assert!(anims.list[0].finished());
+    }
+
+    #[test]
+    fn zero_length_animation_jumps_to_the_end() {
+        for easing in ALL {
+            let mut anims = animation(Duration::ZERO, easing);
+            assert_eq!(anims.list[0].value(), 20.0);
+            anims.advance(Duration::ZERO);
+            assert_eq!(anims.list[0].value(), 20.0);
+            assert!(anims.list[0].finished());
+        }
+    }
+
+    #[test]
+    fn a_new_animation_of_the_same_target_takes_over() {
+        let mut anims = animation(Duration::from_secs(1), Easing::Linear);
+        let id = anims.add(Target::TabSwitch(WindowId(1)), 0.0, 1.0, Duration::from_secs(1), Easing::Linear);
+        assert_eq!(anims.list.len(), 1);
+        assert_eq!(anims.list[0].id, id);
+        anims.add(Target::TabSwitch(WindowId(2)), 0.0, 1.0, Duration::from_secs(1), Easing::Linear);
+        assert_eq!(anims.list.len(), 2);
+    }
+}
@@ -0,0 +1,64 @@
+use std::fmt;
+use std::fmt::Debug;
+use std::ops::{Deref, DerefMut};
+
+use sdl2::event::Event;
+
+use crate::{Toolkit, ToolkitError, UiEvent};
+
+// A Toolkit past its setup phase, from Toolkit::start. All the split
+// guarantees is that the main loop only runs once there is a tab: tick and
+// run are only here. Everything else on Toolkit, adding and removing tabs and
+// widgets included, is still reachable through Deref and DerefMut, as
+// callbacks and the code between ticks need it. Nothing else is checked at
+// compile time.
+pub struct RunningApp {
+    tk: Toolkit,
+}
+
+impl RunningApp {
+    pub(crate) fn new(tk: Toolkit) -> RunningApp {
+        RunningApp { tk }
+    }
+
+    // Handles pending input, timers and messages and draws a frame. Returns
+    // false once the app was asked to quit.
+    pub fn tick(&mut self) -> Result<bool, ToolkitError> {
+        self.tk.tick()
+    }
+
+    pub fn tick_events(&mut self) -> Result<Vec<UiEvent>, ToolkitError> {
+        self.tk.tick_events()
+    }
+
+    pub fn run<F: FnMut(&mut Toolkit, &Event)>(&mut self, f: F) -> Result<(), ToolkitError> {
+        self.tk.run(f)
+    }
+
+    // Back to setup, e.g. to rebuild the tabs from scratch before starting
+    // again
+    pub fn stop(self) -> Toolkit {
+        self.tk
+    }
+}
+
+impl Deref for RunningApp {
+    type Target = Toolkit;
+
+    fn deref(&self) -> &Toolkit {
+        &self.tk
+    }
+}
+
+// Widgets are still changed while running, so there is no read-only view
+impl DerefMut for RunningApp {
+    fn deref_mut(&mut self) -> &mut Toolkit {
+        &mut self.tk
+    }
+}
+
+impl Debug for RunningApp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RunningApp").field(&self.tk).finish()
+    }
+}
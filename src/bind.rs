@@ -0,0 +1,244 @@
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use log::warn;
+
+use crate::{ComboBox, ListBox, RichText, TextSpan, Toolkit};
+
+type Observer<T> = Box<dyn FnMut(&T)>;
+
+struct Shared<T> {
+    value: T,
+    // goes up with every change, for bindings to notice one
+    version: u64,
+    observers: Vec<Observer<T>>,
+}
+
+// A value that can be watched. Clones share the value, so one can be kept
+// by the application while another is bound to a widget with one of the
+// Toolkit::bind_ methods. A bound widget shows every value that is set,
+// and what the user enters is set in turn, with no change handlers needed
+// on either side.
+pub struct Property<T> {
+    shared: Rc<RefCell<Shared<T>>>,
+}
+
+impl<T: Clone + PartialEq + 'static> Property<T> {
+    pub fn new(value: T) -> Property<T> {
+        Property {
+            shared: Rc::new(RefCell::new(Shared {
+                value,
+                version: 0,
+                observers: Vec::new(),
+            })),
+        }
+    }
+
+    pub fn get(&self) -> T {
+        self.shared.borrow().value.clone()
+    }
+
+    pub fn with<R, F: FnOnce(&T) -> R>(&self, f: F) -> R {
+        f(&self.shared.borrow().value)
+    }
+
+    // Tells the observers and bound widgets if `value` is a change, and
+    // returns whether it was
+    pub fn set(&self, value: T) -> bool {
+        {
+            let mut shared = self.shared.borrow_mut();
+            if shared.value == value {
+                return false;
+            }
+            shared.value = value;
+            shared.version += 1;
+        }
+        // observers may set it again, or add more observers
+        let mut observers = std::mem::take(&mut self.shared.borrow_mut().observers);
+        let value = self.get();
+        for f in observers.iter_mut() {
+            f(&value);
+        }
+        let mut shared = self.shared.borrow_mut();
+        observers.append(&mut shared.observers);
+        shared.observers = observers;
+        true
+    }
+
+    pub fn update<F: FnOnce(&mut T)>(&self, f: F) -> bool {
+        let mut value = self.get();
+        f(&mut value);
+        self.set(value)
+    }
+
+    // Runs `f` with every new value
+    pub fn subscribe<F: FnMut(&T) + 'static>(&self, f: F) {
+        self.shared.borrow_mut().observers.push(Box::new(f));
+    }
+
+    pub(crate) fn version(&self) -> u64 {
+        self.shared.borrow().version
+    }
+}
+
+impl<T> Clone for Property<T> {
+    fn clone(&self) -> Property<T> {
+        Property {shared: self.shared.clone()}
+    }
+}
+
+impl<T: Clone + PartialEq + Default + 'static> Default for Property<T> {
+    fn default() -> Property<T> {
+        Property::new(T::default())
+    }
+}
+
+impl<T: Debug> Debug for Property<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let shared = self.shared.borrow();
+        f.debug_struct("Property")
+            .field("value", &shared.value)
+            .field("version", &shared.version)
+            .finish()
+    }
+}
+
+type Apply = Box<dyn FnMut(&mut Toolkit)>;
+type Read<T> = Box<dyn Fn(&Toolkit, &str) -> Option<T>>;
+
+// A property bound to a widget, checked once per frame
+pub(crate) struct Binding {
+    // version last pushed into the widget or taken from it
+    seen: Rc<Cell<u64>>,
+    version: Box<dyn Fn() -> u64>,
+    apply: Apply,
+    // cleared by the handle
+    live: Rc<Cell<bool>>,
+}
+
+// Returned by the Toolkit::bind_ methods. Dropping it leaves the binding be,
+// unbind() ends it, e.g. before the widget is removed or its name reused.
+#[derive(Debug, Clone)]
+pub struct BindingHandle {
+    live: Rc<Cell<bool>>,
+}
+
+impl BindingHandle {
+    pub fn unbind(&self) {
+        self.live.set(false);
+    }
+
+    pub fn is_bound(&self) -> bool {
+        self.live.get()
+    }
+}
+
+impl Toolkit {
+    // Keeps the text of input or text area `name` and `prop` the same
+    pub fn bind_text(&mut self, name: &str, prop: &Property<String>) -> BindingHandle {
+        self.bind(name, prop, |tk, name, value: &String| {
+            tk.set_text_value(name, value);
+        }, Some(Box::new(|tk, name| tk.text_value(name).map(str::to_string))))
+    }
+
+    // Shows `prop` on button or rich text `name`. Rich text keeps the
+    // color and style of its first span.
+    pub fn bind_label(&mut self, name: &str, prop: &Property<String>) -> BindingHandle {
+        self.bind(name, prop, |tk, name, value: &String| {
+            let result = match tk.find_widget::<RichText>(name) {
+                Some(rich) => {
+                    let mut span = rich.spans().first().cloned().unwrap_or_else(|| TextSpan::new("", tk.theme.text));
+                    span.text = value.clone();
+                    tk.set_rich_text(name, vec![span])
+                },
+                None => tk.set_btn_label(name, value),
+            };
+            if let Err(e) = result {
+                warn!("could not update the label of {}: {}", name, e.report());
+            }
+        }, None)
+    }
+
+    // For a slider or spin box. Values out of the widget's range are
+    // clamped in the widget but not in `prop`.
+    pub fn bind_value(&mut self, name: &str, prop: &Property<f64>) -> BindingHandle {
+        self.bind(name, prop, |tk, name, value: &f64| {
+            if !tk.set_slider_value(name, *value) {
+                tk.set_spin_value(name, *value);
+            }
+        }, Some(Box::new(|tk, name| tk.slider_value(name).or_else(|| tk.spin_value(name)))))
+    }
+
+    pub fn bind_checked(&mut self, name: &str, prop: &Property<bool>) -> BindingHandle {
+        self.bind(name, prop, |tk, name, value: &bool| {
+            tk.set_checked(name, *value);
+        }, Some(Box::new(|tk, name| tk.is_checked(name))))
+    }
+
+    // For a combo box or list box
+    pub fn bind_selection(&mut self, name: &str, prop: &Property<Option<usize>>) -> BindingHandle {
+        self.bind(name, prop, |tk, name, value: &Option<usize>| {
+            if let Some(combo) = tk.find_widget_mut::<ComboBox>(name) {
+                combo.set_selected(*value);
+            } else if let Some(list) = tk.find_widget_mut::<ListBox>(name) {
+                list.set_selected(*value);
+            }
+        }, Some(Box::new(|tk, name| {
+            tk.find_widget::<ComboBox>(name).map(|combo| combo.selected())
+                .or_else(|| tk.find_widget::<ListBox>(name).map(|list| list.selected()))
+        })))
+    }
+
+    // Puts the value into the widget now and whenever it changes, and with
+    // `read` takes the widget's value whenever the user changes it
+    fn bind<T, F>(&mut self, name: &str, prop: &Property<T>, mut apply: F, read: Option<Read<T>>) -> BindingHandle
+    where
+        T: Clone + PartialEq + 'static,
+        F: FnMut(&mut Toolkit, &str, &T) + 'static,
+    {
+        apply(self, name, &prop.get());
+        let seen = Rc::new(Cell::new(prop.version()));
+        let live = Rc::new(Cell::new(true));
+        if let Some(read) = read {
+            let (prop, seen, live, widget) = (prop.clone(), seen.clone(), live.clone(), name.to_string());
+            self.on_change(name, move |tk| {
+                if !live.get() {
+                    return;
+                }
+                if let Some(value) = read(tk, &widget) {
+                    prop.set(value);
+                    // the widget has it already
+                    seen.set(prop.version());
+                }
+            });
+        }
+        let version = prop.clone();
+        let (prop, name) = (prop.clone(), name.to_string());
+        self.property_bindings.push(Binding {
+            seen,
+            version: Box::new(move || version.version()),
+            apply: Box::new(move |tk| apply(tk, &name, &prop.get())),
+            live: live.clone(),
+        });
+        BindingHandle {live}
+    }
+
+    // Brings bound widgets up to date with properties set since last time
+    pub(crate) fn sync_bindings(&mut self) {
+        let mut bindings = std::mem::take(&mut self.property_bindings);
+        for binding in bindings.iter_mut().filter(|b| b.live.get()) {
+            let version = (binding.version)();
+            if version != binding.seen.get() {
+                binding.seen.set(version);
+                (binding.apply)(self);
+                self.dirty = true;
+            }
+        }
+        // bound while applying
+        bindings.append(&mut self.property_bindings);
+        bindings.retain(|b| b.live.get());
+        self.property_bindings = bindings;
+    }
+}
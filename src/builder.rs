@@ -0,0 +1,323 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use log::{debug, warn};
+use sdl2::render::TextureCreator;
+use sdl2::ttf::Sdl2TtfContext;
+use sdl2::video::WindowContext;
+
+use crate::font;
+use crate::window;
+#[cfg(feature = "gamepad")]
+use crate::gamepad::Gamepads;
+use crate::anim::Animations;
+use crate::busy::BusyState;
+use crate::click::ClickCounter;
+use crate::debug::DebugOverlay;
+use crate::layout::Placements;
+use crate::timer::Timers;
+use crate::touch::Touch;
+use crate::{Anchor, ErrorContext, EventSender, FontManager, FontSource, WindowId, RedrawMode, RendererKind, TextRenderer, Theme, Toolkit, ToolkitError, Transition};
+
+const DEFAULT_FONT_SIZE: u16 = 28;
+
+// Window options that have to be known before the window is created
+#[derive(Debug, Clone)]
+pub struct ToolkitBuilder {
+    title: String,
+    width: u32,
+    height: u32,
+    resizable: bool,
+    fullscreen: bool,
+    borderless: bool,
+    vsync: bool,
+    headless: bool,
+    renderer: RendererKind,
+    #[cfg(feature = "opengl")]
+    opengl: bool,
+    // None picks one from the display DPI
+    scale: Option<f32>,
+    font: FontSource,
+    font_size: u16,
+    fallback_fonts: Vec<PathBuf>,
+    system_fallbacks: bool,
+    theme: Theme,
+}
+
+impl Default for ToolkitBuilder {
+    fn default() -> ToolkitBuilder {
+        ToolkitBuilder {
+            title: "rtoolkit window".to_string(),
+            width: 480,
+            height: 320,
+            resizable: false,
+            fullscreen: false,
+            borderless: false,
+            vsync: true,
+            headless: false,
+            renderer: RendererKind::Accelerated,
+            #[cfg(feature = "opengl")]
+            opengl: false,
+            scale: None,
+            font: FontSource::Embedded,
+            font_size: DEFAULT_FONT_SIZE,
+            fallback_fonts: Vec::new(),
+            system_fallbacks: true,
+            theme: Theme::default(),
+        }
+    }
+}
+
+impl ToolkitBuilder {
+    pub fn new() -> ToolkitBuilder {
+        ToolkitBuilder::default()
+    }
+
+    pub fn title(mut self, title: &str) -> ToolkitBuilder {
+        self.title = title.to_string();
+        self
+    }
+
+    pub fn size(mut self, width: u32, height: u32) -> ToolkitBuilder {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> ToolkitBuilder {
+        self.resizable = resizable;
+        self
+    }
+
+    // Takes over the desktop resolution rather than switching video modes
+    pub fn fullscreen(mut self, fullscreen: bool) -> ToolkitBuilder {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    pub fn borderless(mut self, borderless: bool) -> ToolkitBuilder {
+        self.borderless = borderless;
+        self
+    }
+
+    // Fixed UI scale instead of one worked out from the display
+    pub fn scale(mut self, scale: f32) -> ToolkitBuilder {
+        self.scale = Some(scale);
+        self
+    }
+
+    pub fn vsync(mut self, vsync: bool) -> ToolkitBuilder {
+        self.vsync = vsync;
+        self
+    }
+
+    // Renders in software to windows that are never shown, for CI and
+    // screenshot tests. Picks SDL's dummy video driver unless SDL_VIDEODRIVER
+    // says otherwise. SDL before 2.0.22 only reads the variable, so there it
+    // is up to the caller to set SDL_VIDEODRIVER=dummy.
+    pub fn headless(mut self, headless: bool) -> ToolkitBuilder {
+        self.headless = headless;
+        self
+    }
+
+    // Software drawing works without a GPU, e.g. in VMs or on a plain
+    // framebuffer. Accelerated, the default, still falls back to it if it
+    // has to.
+    pub fn renderer(mut self, kind: RendererKind) -> ToolkitBuilder {
+        self.renderer = kind;
+        self
+    }
+
+    // Asks SDL for its OpenGL renderer, which GlViewport needs
+    #[cfg(feature = "opengl")]
+    pub fn opengl(mut self, opengl: bool) -> ToolkitBuilder {
+        self.opengl = opengl;
+        self
+    }
+
+    pub fn font<P: AsRef<Path>>(mut self, path: P, pt_size: u16) -> ToolkitBuilder {
+        self.font = FontSource::File(path.as_ref().to_path_buf());
+        self.font_size = pt_size;
+        self
+    }
+
+    // Use whatever font the system has instead of the embedded one
+    pub fn system_font(mut self) -> ToolkitBuilder {
+        self.font = FontSource::System;
+        self
+    }
+
+    // Font for characters the main font has no glyph for, tried before the
+    // system's CJK and symbol fonts. Can be given more than once.
+    pub fn fallback_font<P: AsRef<Path>>(mut self, path: P) -> ToolkitBuilder {
+        self.fallback_fonts.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    // Whether to look for fallback fonts on the system, on by default
+    pub fn system_fallbacks(mut self, system: bool) -> ToolkitBuilder {
+        self.system_fallbacks = system;
+        self
+    }
+
+    pub fn font_size(mut self, pt_size: u16) -> ToolkitBuilder {
+        self.font_size = pt_size;
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> ToolkitBuilder {
+        self.theme = theme;
+        self
+    }
+
+    pub fn build(self) -> Result<Toolkit, ToolkitError> {
+        // touches are handled as such, not as the mouse events SDL makes of them
+        sdl2::hint::set("SDL_TOUCH_MOUSE_EVENTS", "0");
+        if self.headless {
+            // the environment variable wins over the hint if it is set
+            sdl2::hint::set("SDL_VIDEODRIVER", "dummy");
+        }
+        let sdl2 = sdl2::init().context("initializing SDL")?;
+        let video = sdl2.video().context("starting the video subsystem")?;
+
+        let mut window = video.window(&self.title, self.width, self.height);
+        window.allow_highdpi();
+        if self.resizable {
+            window.resizable();
+        }
+        if self.fullscreen {
+            window.fullscreen_desktop();
+        }
+        if self.borderless {
+            window.borderless();
+        }
+        if self.headless {
+            window.hidden();
+        }
+        #[cfg(feature = "opengl")]
+        if self.opengl {
+            sdl2::hint::set("SDL_RENDER_DRIVER", "opengl");
+        }
+        // headless there's no display to sync to
+        let (renderer, vsync) = if self.headless { (RendererKind::Software, false) } else { (self.renderer, self.vsync) };
+        let mut canvas = window::build_canvas(&window, renderer, vsync)?;
+        debug!("using video driver {} and renderer {}", video.current_video_driver(), canvas.info().name);
+        let pump = sdl2.event_pump()?;
+
+        // an event type of our own to wake up the loop when a message arrives,
+        // registering one is only unsafe because of what User events may carry
+        let events = sdl2.event()?;
+        let wake_type = unsafe { events.register_event()? };
+        let (tx, messages) = mpsc::channel();
+        let sender = EventSender::new(tx, events.event_sender(), wake_type);
+
+        // Fonts and textures borrow their contexts, leak those so the
+        // resulting objects can live inside the Toolkit itself
+        let ttf: &'static Sdl2TtfContext = Box::leak(Box::new(sdl2::ttf::init().context("initializing SDL_ttf")?));
+        let creator: &'static TextureCreator<WindowContext> = Box::leak(Box::new(canvas.texture_creator()));
+        let fallbacks = font::fallback_fonts(&self.fallback_fonts, self.system_fallbacks);
+        // a font that won't load shouldn't keep the app from starting
+        let mut font_fallback = false;
+        let fonts = match FontManager::new(ttf, &self.font, self.font_size, fallbacks.clone()) {
+            Ok(fonts) => fonts,
+            Err(e) if self.font != FontSource::Embedded => {
+                warn!("{}, using the embedded font instead", e.report());
+                font_fallback = true;
+                FontManager::new(ttf, &FontSource::Embedded, self.font_size, fallbacks)?
+            },
+            Err(e) => return Err(e),
+        };
+        let fonts = Rc::new(fonts);
+        let text = TextRenderer::new(fonts, creator);
+
+        canvas.set_draw_color(self.theme.background);
+        canvas.clear();
+        canvas.present();
+
+        let mut tk = Toolkit {
+            tabs: Vec::new(),
+            tab_pos: 0,
+            items: Vec::new(),
+            z_index: HashMap::new(),
+            placements: Placements::default(),
+            run: true,
+            mouse: (0, 0),
+            mouse_inside: true,
+            hover: None,
+            pressed: None,
+            focus: None,
+            clicks: Vec::new(),
+            ui_events: Vec::new(),
+            callbacks: HashMap::new(),
+            change_callbacks: HashMap::new(),
+            double_click_callbacks: HashMap::new(),
+            click_counter: ClickCounter::default(),
+            radio_groups: HashMap::new(),
+            next_id: 0,
+            redraw_mode: RedrawMode::EveryFrame,
+            frame_budget: None,
+            last_frame: Instant::now(),
+            delta: Duration::ZERO,
+            dirty: true,
+            cursor: None,
+            timers: Timers::default(),
+            animations: Animations::default(),
+            opacity: HashMap::new(),
+            transition: Transition::None,
+            transition_time: Duration::ZERO,
+            tab_switch: None,
+            messages,
+            sender,
+            dialogs: Vec::new(),
+            toasts: Vec::new(),
+            busy_state: BusyState::new(),
+            property_bindings: Vec::new(),
+            immediate_panels: HashMap::new(),
+            toast_corner: Anchor::BottomRight,
+            menu: None,
+            menu_bar: Vec::new(),
+            status: None,
+            context_menus: HashMap::new(),
+            drop_callbacks: HashMap::new(),
+            drop_target: None,
+            draggable: HashSet::new(),
+            close_callbacks: Vec::new(),
+            touch: Touch::default(),
+            pinch_callbacks: Vec::new(),
+            bindings: HashMap::new(),
+            drag_callbacks: HashMap::new(),
+            drag_press: None,
+            drag: None,
+            #[cfg(feature = "gamepad")]
+            gamepads: Gamepads::new(&sdl2)?,
+            sdl: sdl2,
+            video,
+            window: WindowId(canvas.window().id()),
+            windows: Vec::new(),
+            spare_windows: Vec::new(),
+            canvas,
+            pump,
+            text,
+            debug: DebugOverlay::default(),
+            vsync: self.vsync,
+            font_fallback,
+            headless: self.headless,
+            renderer,
+            scale: self.scale,
+            ui_scale: 1.0,
+            theme: self.theme,
+        };
+        // the size asked for is in scaled units, like everything else
+        tk.update_scale();
+        let scale = tk.scale();
+        if scale != 1.0 && !self.fullscreen {
+            let (w, h) = ((self.width as f32 * scale) as u32, (self.height as f32 * scale) as u32);
+            tk.canvas.window_mut().set_size(w, h)?;
+        }
+        tk.set_quit_on_escape(true);
+        Ok(tk)
+    }
+}
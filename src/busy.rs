@@ -0,0 +1,112 @@
+use std::f32::consts::TAU;
+use std::time::Instant;
+
+use sdl2::event::Event;
+use sdl2::pixels::Color;
+use sdl2::rect::Point;
+use sdl2::render::BlendMode;
+
+use crate::{DrawCtx, EventSender, Theme, Toolkit, ToolkitError};
+
+// Dots around the spinner, and how long it takes to go round once
+const DOTS: usize = 8;
+const PERIOD: f32 = 1.0;
+const RADIUS: i32 = 18;
+const DOT_RADIUS: i32 = 4;
+
+// Whether the toolkit is waiting on something. It is busy while set_busy(true)
+// is in effect or any BusyGuard is alive.
+#[derive(Debug)]
+pub(crate) struct BusyState {
+    set: bool,
+    guards: usize,
+    // when it last became busy, where the spinner starts from
+    since: Instant,
+}
+
+impl BusyState {
+    pub(crate) fn new() -> BusyState {
+        BusyState {
+            set: false,
+            guards: 0,
+            since: Instant::now(),
+        }
+    }
+
+    pub(crate) fn is_busy(&self) -> bool {
+        self.set || self.guards > 0
+    }
+
+    fn update(&mut self, f: impl FnOnce(&mut BusyState)) {
+        let was = self.is_busy();
+        f(self);
+        if !was && self.is_busy() {
+            self.since = Instant::now();
+        }
+    }
+}
+
+// Keeps the toolkit busy until dropped, from whichever thread it ends up
+// in. Hand it to the thread doing the work and it clears itself when the
+// work is done, or panics.
+#[derive(Debug)]
+pub struct BusyGuard {
+    sender: EventSender,
+}
+
+impl Drop for BusyGuard {
+    fn drop(&mut self) {
+        // nothing left to clear once the Toolkit is gone
+        self.sender.send(|tk| tk.busy_state.guards -= 1).ok();
+    }
+}
+
+// Input that would reach widgets, as opposed to the window itself
+pub(crate) fn is_input(event: &Event) -> bool {
+    event.is_mouse() || event.is_keyboard() || event.is_text() || event.is_controller() || event.is_joy()
+        || matches!(event, Event::FingerDown {..} | Event::FingerUp {..} | Event::FingerMotion {..}
+            | Event::MultiGesture {..} | Event::DollarGesture {..} | Event::DropFile {..} | Event::DropText {..})
+}
+
+// Dims the window and draws a ring of spinning dots around `center`
+pub(crate) fn draw(ctx: &mut DrawCtx, state: &BusyState, center: Point, theme: &Theme) -> Result<(), ToolkitError> {
+    ctx.canvas().set_blend_mode(BlendMode::Blend);
+    ctx.canvas().set_draw_color(Color::RGBA(0, 0, 0, 96));
+    ctx.canvas().fill_rect(None)?;
+    let turn = state.since.elapsed().as_secs_f32() / PERIOD;
+    let lead = (turn.fract() * DOTS as f32) as usize;
+    for n in 0..DOTS {
+        let angle = n as f32 / DOTS as f32 * TAU;
+        let x = center.x() + (angle.sin() * RADIUS as f32).round() as i32;
+        let y = center.y() - (angle.cos() * RADIUS as f32).round() as i32;
+        // the dots fade out behind the leading one
+        let behind = (lead + DOTS - n) % DOTS;
+        let alpha = 255 - (behind * 200 / DOTS) as u8;
+        let color = theme.active;
+        ctx.fill_circle((x, y), DOT_RADIUS, Color::RGBA(color.r, color.g, color.b, alpha))?;
+    }
+    ctx.canvas().set_blend_mode(BlendMode::None);
+    Ok(())
+}
+
+impl Toolkit {
+    // While busy a spinner goes round over the window and no input gets
+    // through to widgets, dialogs or menus. Quitting and closing windows
+    // still work. See also busy_guard.
+    pub fn set_busy(&mut self, busy: bool) {
+        self.busy_state.update(|s| s.set = busy);
+        self.dirty = true;
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.busy_state.is_busy()
+    }
+
+    // Busy until the guard is dropped, independently of set_busy and of any
+    // other guards
+    pub fn busy_guard(&mut self) -> BusyGuard {
+        self.busy_state.update(|s| s.guards += 1);
+        self.dirty = true;
+        BusyGuard {sender: self.event_sender()}
+    }
+}
@@ -0,0 +1,183 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::fmt::Debug;
+
+use log::warn;
+use sdl2::event::Event;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Texture};
+
+use crate::{DragData, DrawCtx, Drawable, FontId, TextRenderer, Theme, ToolkitError, Widget};
+
+// Keeps what a widget drew in a texture and copies that each frame, only
+// drawing the widget again once it may have changed. Anything that gets at
+// the widget mutably counts as a change, as do a new size or scale, and
+// widgets asking for redraws (e.g. a blinking cursor) are drawn every time.
+// Worth it for big tables and text areas in UIs that mostly sit still.
+pub struct CachedWidget {
+    children: Vec<Box<dyn Widget>>,
+    texture: RefCell<Option<Texture<'static>>>,
+    dirty: Cell<bool>,
+}
+
+impl CachedWidget {
+    pub fn new<W: Widget>(widget: W) -> CachedWidget {
+        CachedWidget::boxed(Box::new(widget))
+    }
+
+    pub fn boxed(widget: Box<dyn Widget>) -> CachedWidget {
+        CachedWidget {
+            children: vec![widget],
+            texture: RefCell::new(None),
+            dirty: Cell::new(true),
+        }
+    }
+
+    pub fn inner(&self) -> &dyn Widget { self.children[0].as_ref() }
+
+    pub fn inner_mut(&mut self) -> &mut dyn Widget {
+        self.dirty.set(true);
+        self.children[0].as_mut()
+    }
+
+    // For changes the wrapper can't see, like a table model updated from
+    // elsewhere
+    pub fn invalidate(&self) {
+        self.dirty.set(true);
+    }
+
+    // Draws the widget into the window's offscreen layer and keeps the part
+    // it covers. False if there was no layer to be had.
+    fn refresh(&self, ctx: &mut DrawCtx, rect: Rect, size: (u32, u32)) -> Result<bool, ToolkitError> {
+        if !ctx.has_targets() {
+            return Ok(false);
+        }
+        let text = ctx.text();
+        let (w, h) = ctx.canvas().output_size()?;
+        let Some(mut layer) = text.layer(w, h)? else {
+            return Ok(false);
+        };
+        let mut texture = self.texture.borrow_mut();
+        if texture.as_ref().is_none_or(|t| (t.query().width, t.query().height) != size) {
+            let mut t = text.creator.create_texture_target(PixelFormatEnum::ARGB8888, size.0, size.1)?;
+            t.set_blend_mode(BlendMode::Blend);
+            *texture = Some(t);
+        }
+        let Some(cache) = texture.as_mut() else {
+            return Ok(false);
+        };
+
+        let scale = ctx.canvas().scale();
+        let mut result = Ok(());
+        ctx.canvas().with_texture_canvas(&mut layer, |canvas| {
+            if let Err(e) = canvas.set_scale(scale.0, scale.1) {
+                warn!("could not scale the offscreen layer: {}", e);
+            }
+            canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+            canvas.clear();
+            result = self.inner().draw(&mut DrawCtx::new(canvas, text));
+        })?;
+        result?;
+        // straight copy, the layer's alpha becomes the cache's
+        layer.set_blend_mode(BlendMode::None);
+        let src = Rect::new((rect.x() as f32 * scale.0) as i32, (rect.y() as f32 * scale.1) as i32, size.0, size.1);
+        let mut copied = Ok(());
+        ctx.canvas().with_texture_canvas(cache, |canvas| {
+            canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+            canvas.clear();
+            copied = canvas.copy(&layer, src, None);
+        })?;
+        layer.set_blend_mode(BlendMode::Blend);
+        copied?;
+        Ok(true)
+    }
+}
+
+impl Debug for CachedWidget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachedWidget")
+            .field("inner", &self.inner())
+            .field("dirty", &self.dirty.get())
+            .finish()
+    }
+}
+
+impl Drawable for CachedWidget {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let rect = self.rect();
+        let scale = ctx.canvas().scale();
+        let size = ((rect.width() as f32 * scale.0) as u32, (rect.height() as f32 * scale.1) as u32);
+        if size.0 == 0 || size.1 == 0 {
+            return Ok(());
+        }
+        let fits = self.texture.borrow().as_ref().is_some_and(|t| (t.query().width, t.query().height) == size);
+        if !fits || self.dirty.get() || self.inner().needs_redraw() {
+            if !self.refresh(ctx, rect, size)? {
+                // nested in a faded part, or no render targets
+                return self.inner().draw(ctx);
+            }
+            self.dirty.set(false);
+        }
+        if let Some(texture) = self.texture.borrow().as_ref() {
+            ctx.canvas().copy(texture, None, rect)?;
+        }
+        Ok(())
+    }
+}
+
+impl Widget for CachedWidget {
+    fn name(&self) -> &str { self.inner().name() }
+    fn rect(&self) -> Rect { self.inner().rect() }
+
+    fn set_rect(&mut self, rect: Rect) {
+        if rect != self.rect() {
+            self.inner_mut().set_rect(rect);
+        }
+    }
+
+    fn size_hint(&self) -> (u32, u32) { self.inner().size_hint() }
+    fn stretch(&self) -> u32 { self.inner().stretch() }
+    fn fills_cross(&self) -> bool { self.inner().fills_cross() }
+
+    // runs every frame, so only a change of place counts
+    fn layout(&mut self, area: Rect) {
+        let before = self.rect();
+        self.children[0].layout(area);
+        if self.rect() != before {
+            self.dirty.set(true);
+        }
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] { &self.children }
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] {
+        self.dirty.set(true);
+        &mut self.children
+    }
+
+    fn overlay_rect(&self) -> Option<Rect> { self.inner().overlay_rect() }
+
+    fn set_font(&mut self, text: &TextRenderer, font: FontId) -> Result<(), ToolkitError> {
+        self.inner_mut().set_font(text, font)
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.inner_mut().apply_theme(theme);
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        self.inner_mut().handle_event(event)
+    }
+
+    fn needs_redraw(&self) -> bool { self.inner().needs_redraw() }
+
+    fn scroll_pixels(&mut self, dx: i32, dy: i32) -> bool {
+        self.inner_mut().scroll_pixels(dx, dy)
+    }
+
+    fn drag_data(&self, x: i32, y: i32) -> Option<DragData> { self.inner().drag_data(x, y) }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
@@ -0,0 +1,182 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::Texture;
+
+use crate::{DrawCtx, Drawable, Role, TextRenderer, ToolkitError, Widget};
+
+pub type PaintFn = Box<dyn FnMut(&mut Painter) -> Result<(), ToolkitError>>;
+
+// Drawing handle given to a CanvasWidget's paint function. Coordinates are
+// relative to the widget's top left corner and nothing gets drawn outside it.
+pub struct Painter<'c, 'a> {
+    ctx: &'c mut DrawCtx<'a>,
+    rect: Rect,
+    color: Color,
+}
+
+impl<'c, 'a> Painter<'c, 'a> {
+    pub fn width(&self) -> u32 { self.rect.width() }
+    pub fn height(&self) -> u32 { self.rect.height() }
+    pub fn color(&self) -> Color { self.color }
+
+    // Renderer for making textures of text or surfaces to draw later
+    pub fn text_renderer(&self) -> &'a TextRenderer { self.ctx.text() }
+
+    // Color for everything drawn after this
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+
+    pub fn clear(&mut self, color: Color) -> Result<(), ToolkitError> {
+        self.ctx.canvas().set_draw_color(color);
+        self.ctx.canvas().fill_rect(self.rect)?;
+        Ok(())
+    }
+
+    fn at(&self, x: i32, y: i32) -> Point {
+        Point::new(self.rect.x() + x, self.rect.y() + y)
+    }
+
+    fn offset(&self, rect: Rect) -> Rect {
+        Rect::new(self.rect.x() + rect.x(), self.rect.y() + rect.y(), rect.width(), rect.height())
+    }
+
+    fn points(&self, points: &[(i32, i32)]) -> Vec<Point> {
+        points.iter().map(|(x, y)| self.at(*x, *y)).collect()
+    }
+
+    pub fn point(&mut self, x: i32, y: i32) -> Result<(), ToolkitError> {
+        let p = self.at(x, y);
+        self.ctx.canvas().set_draw_color(self.color);
+        self.ctx.canvas().draw_point(p)?;
+        Ok(())
+    }
+
+    pub fn line(&mut self, x1: i32, y1: i32, x2: i32, y2: i32) -> Result<(), ToolkitError> {
+        let (a, b) = (self.at(x1, y1), self.at(x2, y2));
+        self.ctx.draw_line(a, b, self.color)
+    }
+
+    pub fn rect(&mut self, rect: Rect) -> Result<(), ToolkitError> {
+        let rect = self.offset(rect);
+        self.ctx.draw_rect(rect, self.color)
+    }
+
+    pub fn fill_rect(&mut self, rect: Rect) -> Result<(), ToolkitError> {
+        let rect = self.offset(rect);
+        self.ctx.fill_rect(rect, self.color)
+    }
+
+    pub fn circle(&mut self, cx: i32, cy: i32, radius: i32) -> Result<(), ToolkitError> {
+        let c = self.at(cx, cy);
+        self.ctx.draw_circle(c, radius, self.color)
+    }
+
+    pub fn fill_circle(&mut self, cx: i32, cy: i32, radius: i32) -> Result<(), ToolkitError> {
+        let c = self.at(cx, cy);
+        self.ctx.fill_circle(c, radius, self.color)
+    }
+
+    pub fn polygon(&mut self, points: &[(i32, i32)]) -> Result<(), ToolkitError> {
+        let points = self.points(points);
+        self.ctx.draw_polygon(&points, self.color)
+    }
+
+    pub fn fill_polygon(&mut self, points: &[(i32, i32)]) -> Result<(), ToolkitError> {
+        let points = self.points(points);
+        self.ctx.fill_polygon(&points, self.color)
+    }
+
+    // Copies (part of) a texture into `dst`, stretching it to fit
+    pub fn texture<R: Into<Option<Rect>>>(&mut self, texture: &Texture, src: R, dst: Rect) -> Result<(), ToolkitError> {
+        let dst = self.offset(dst);
+        self.ctx.canvas().copy(texture, src, dst)?;
+        Ok(())
+    }
+
+    // Text in the current color with its top left corner at (x, y)
+    pub fn text(&mut self, x: i32, y: i32, text: &str) -> Result<(), ToolkitError> {
+        let p = self.at(x, y);
+        self.ctx.draw_text_at(p.x(), p.y(), text, self.color)
+    }
+}
+
+// Area the application paints itself through a Painter, called every
+// time the window is drawn
+pub struct CanvasWidget {
+    name: String,
+    rect: Rect,
+    pref: (u32, u32),
+    // keeps redrawing in RedrawMode::OnDemand, for animations
+    animated: bool,
+    paint: RefCell<PaintFn>,
+}
+
+impl CanvasWidget {
+    pub fn new<F: FnMut(&mut Painter) -> Result<(), ToolkitError> + 'static>(name: &str, x: i32, y: i32, w: u32, h: u32, paint: F) -> CanvasWidget {
+        CanvasWidget {
+            name: name.to_string(),
+            rect: Rect::new(x, y, w, h),
+            pref: (w, h),
+            animated: false,
+            paint: RefCell::new(Box::new(paint)),
+        }
+    }
+
+    pub fn animated(mut self, animated: bool) -> CanvasWidget {
+        self.animated = animated;
+        self
+    }
+
+    pub fn set_animated(&mut self, animated: bool) {
+        self.animated = animated;
+    }
+
+    pub fn set_paint<F: FnMut(&mut Painter) -> Result<(), ToolkitError> + 'static>(&mut self, paint: F) {
+        self.paint = RefCell::new(Box::new(paint));
+    }
+}
+
+impl Debug for CanvasWidget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CanvasWidget")
+            .field("name", &self.name)
+            .field("rect", &self.rect)
+            .field("animated", &self.animated)
+            .finish()
+    }
+}
+
+impl Drawable for CanvasWidget {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        // a paint function that draws itself again somehow is skipped
+        let Ok(mut paint) = self.paint.try_borrow_mut() else {
+            return Ok(());
+        };
+        ctx.clipped(self.rect, |ctx| {
+            let mut painter = Painter {
+                ctx,
+                rect: self.rect,
+                color: Color::RGB(255, 255, 255),
+            };
+            paint(&mut painter)
+        })
+    }
+}
+
+impl Widget for CanvasWidget {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { self.rect }
+    fn role(&self) -> Role { Role::Canvas }
+    fn set_rect(&mut self, rect: Rect) { self.rect = rect; }
+    fn size_hint(&self) -> (u32, u32) { self.pref }
+    fn needs_redraw(&self) -> bool { self.animated }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
@@ -0,0 +1,90 @@
+use std::path::Path;
+
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::surface::Surface;
+
+use crate::{ErrorContext, Toolkit, ToolkitError};
+
+// Pixels read back from a window, RGBA with 4 bytes each and no padding
+// between rows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageBuffer {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl ImageBuffer {
+    pub fn width(&self) -> u32 { self.width }
+    pub fn height(&self) -> u32 { self.height }
+    pub fn pixels(&self) -> &[u8] { &self.pixels }
+
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    // None outside the image
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = (y * self.width + x) as usize * 4;
+        let p = &self.pixels[i..i + 4];
+        Some(Color::RGBA(p[0], p[1], p[2], p[3]))
+    }
+
+    pub fn to_surface(&self) -> Result<Surface<'static>, ToolkitError> {
+        let mut surface = Surface::new(self.width, self.height, PixelFormatEnum::RGBA32)?;
+        let row = self.width as usize * 4;
+        let pitch = surface.pitch() as usize;
+        surface.with_lock_mut(|data| {
+            for (dst, src) in data.chunks_mut(pitch).zip(self.pixels.chunks(row)) {
+                dst[..row].copy_from_slice(src);
+            }
+        });
+        Ok(surface)
+    }
+
+    // BMP always works, PNG needs the `image` feature. The extension of
+    // `path` decides which.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ToolkitError> {
+        let path = path.as_ref();
+        let ext = path.extension().and_then(|ext| ext.to_str()).map(|ext| ext.to_ascii_lowercase());
+        let saving = || format!("saving {}", path.display());
+        match ext.as_deref() {
+            Some("bmp") => self.to_surface()?.save_bmp(path).with_context(saving),
+            #[cfg(feature = "image")]
+            Some("png") => {
+                use sdl2::image::SaveSurface;
+                self.to_surface()?.save(path).with_context(saving)
+            },
+            _ => Err(ToolkitError::UnsupportedImage(path.display().to_string())),
+        }
+    }
+}
+
+impl Toolkit {
+    // Draws the current window afresh and reads it back, in physical pixels.
+    // Works the same in headless mode, where there is nothing to look at.
+    pub fn screenshot(&mut self) -> Result<ImageBuffer, ToolkitError> {
+        self.update_scale();
+        self.relayout();
+        self.paint()?;
+        let (width, height) = self.canvas.output_size()?;
+        let pixels = self.canvas.read_pixels(None, PixelFormatEnum::RGBA32).context("reading back the window")?;
+        self.canvas.present();
+        self.dirty = false;
+        Ok(ImageBuffer {width, height, pixels})
+    }
+
+    // The current window as RGBA bytes, row after row
+    pub fn capture_frame(&mut self) -> Result<Vec<u8>, ToolkitError> {
+        Ok(self.screenshot()?.into_pixels())
+    }
+
+    // Writes the current window to `path` as BMP, or PNG with the `image`
+    // feature
+    pub fn save_screenshot<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ToolkitError> {
+        self.screenshot()?.save(path)
+    }
+}
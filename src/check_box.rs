@@ -0,0 +1,150 @@
+use std::any::Any;
+use std::fmt;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use sdl2::rect::{Point, Rect};
+use sdl2::render::Texture;
+
+use crate::{DrawCtx, Drawable, Role, TextRenderer, Theme, ToolkitError, Widget, WidgetState};
+
+// A box with a label that is ticked and unticked by clicking it
+pub struct CheckBox {
+    name: String,
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    checked: bool,
+    changed: bool,
+    state: WidgetState,
+    label: Rc<Texture<'static>>,
+    text: String,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl CheckBox {
+    pub fn new(text: &TextRenderer, name: &str, label: &str, x: i32, y: i32) -> Result<CheckBox, ToolkitError> {
+        let mut check = CheckBox {
+            name: name.to_string(),
+            x,
+            y,
+            w: 0,
+            h: 0,
+            checked: false,
+            changed: false,
+            state: WidgetState::default(),
+            label: text.render(label).map_err(ToolkitError::texture_for(name))?,
+            text: label.to_string(),
+            theme: Theme::default(),
+            own_theme: false,
+        };
+        (check.w, check.h) = check.natural_size();
+        Ok(check)
+    }
+
+    pub fn checked(mut self, checked: bool) -> CheckBox {
+        self.checked = checked;
+        self
+    }
+
+    pub fn is_checked(&self) -> bool { self.checked }
+
+    pub fn set_checked(&mut self, checked: bool) {
+        self.checked = checked;
+    }
+
+    // What a click does, reported as a change
+    pub(crate) fn toggle(&mut self) {
+        self.checked = !self.checked;
+        self.changed = true;
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+    }
+
+    fn natural_size(&self) -> (i32, i32) {
+        let attr = self.label.query();
+        let h = attr.height as i32;
+        (h + self.theme.padding + attr.width as i32, h)
+    }
+}
+
+impl Debug for CheckBox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CheckBox")
+            .field("name", &self.name)
+            .field("label", &self.text)
+            .field("rect", &self.rect())
+            .field("checked", &self.checked)
+            .finish()
+    }
+}
+
+impl Drawable for CheckBox {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let theme = &self.theme;
+        let d = self.h * 2 / 3;
+        let square = Rect::new(self.x + (self.h - d) / 2, self.y + (self.h - d) / 2, d as u32, d as u32);
+        ctx.fill_rounded_rect(square, theme.corner_radius / 2, theme.field)?;
+        let border = if self.state.focused { theme.active } else { theme.border };
+        ctx.draw_rounded_rect(square, theme.corner_radius / 2, border)?;
+        if self.checked {
+            // a tick, drawn twice over for some weight
+            let color = if self.state.enabled { theme.active } else { theme.disabled };
+            let (x, y) = (square.x(), square.y());
+            for dy in 0..2 {
+                let points = [
+                    Point::new(x + d / 5, y + d / 2 + dy),
+                    Point::new(x + d * 2 / 5, y + d * 3 / 4 + dy),
+                    Point::new(x + d * 4 / 5, y + d / 4 + dy),
+                ];
+                ctx.draw_line(points[0], points[1], color)?;
+                ctx.draw_line(points[1], points[2], color)?;
+            }
+        }
+
+        let attr = self.label.query();
+        let label = Rect::new(self.x + self.h + theme.padding, self.y, attr.width, attr.height);
+        ctx.tint(&self.label, if self.state.enabled { theme.text } else { theme.disabled });
+        ctx.canvas().copy(&self.label, None, label)?;
+        Ok(())
+    }
+}
+
+impl Widget for CheckBox {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { Rect::new(self.x, self.y, self.w as u32, self.h as u32) }
+    fn role(&self) -> Role { Role::CheckBox }
+    fn access_label(&self) -> Option<String> { Some(self.text.clone()).filter(|l| !l.is_empty()) }
+    fn access_checked(&self) -> Option<bool> { Some(self.checked) }
+    fn clickable(&self) -> bool { true }
+    fn focusable(&self) -> bool { true }
+    fn take_changed(&mut self) -> bool { std::mem::take(&mut self.changed) }
+    fn widget_state(&self) -> Option<&WidgetState> { Some(&self.state) }
+    fn widget_state_mut(&mut self) -> Option<&mut WidgetState> { Some(&mut self.state) }
+
+    fn size_hint(&self) -> (u32, u32) {
+        let (w, h) = self.natural_size();
+        (w as u32, h as u32)
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.x = rect.x();
+        self.y = rect.y();
+        self.w = rect.width() as i32;
+        self.h = rect.height() as i32;
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
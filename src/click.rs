@@ -0,0 +1,150 @@
+use std::time::{Duration, Instant};
+
+use sdl2::event::Event;
+use sdl2::mouse::MouseButton;
+
+use crate::{Toolkit, UiEvent};
+
+// Pixels the pointer may move between the presses of a double click
+const CLICK_SLOP: i32 = 4;
+const DEFAULT_INTERVAL: Duration = Duration::from_millis(500);
+
+// Presses of the same button in quick succession and about the same place.
+// Counts up to three, after that a triple click starts over.
+#[derive(Debug)]
+pub(crate) struct ClickCounter {
+    interval: Duration,
+    last: Option<(Instant, MouseButton, (i32, i32))>,
+    count: u8,
+}
+
+impl Default for ClickCounter {
+    fn default() -> ClickCounter {
+        ClickCounter {
+            interval: DEFAULT_INTERVAL,
+            last: None,
+            count: 0,
+        }
+    }
+}
+
+impl ClickCounter {
+    fn press(&mut self, button: MouseButton, (x, y): (i32, i32), now: Instant) -> u8 {
+        let repeat = self.last.is_some_and(|(at, b, (lx, ly))| {
+            b == button
+                && now.saturating_duration_since(at) <= self.interval
+                && (x - lx).abs() <= CLICK_SLOP
+                && (y - ly).abs() <= CLICK_SLOP
+        });
+        self.count = if repeat && self.count < 3 { self.count + 1 } else { 1 };
+        self.last = Some((now, button, (x, y)));
+        self.count
+    }
+}
+
+impl Toolkit {
+    // Longest time between the presses of a double or triple click. Half a
+    // second unless set.
+    pub fn set_double_click_interval(&mut self, interval: Duration) {
+        self.click_counter.interval = interval;
+    }
+
+    pub fn double_click_interval(&self) -> Duration {
+        self.click_counter.interval
+    }
+
+    // Called on the second press of a double click on widget `name`, which
+    // doesn't need to be clickable
+    pub fn on_double_click<F: FnMut(&mut Toolkit) + 'static>(&mut self, name: &str, f: F) {
+        self.double_click_callbacks.entry(name.to_string()).or_default().push(Box::new(f));
+    }
+
+    // Puts our own count in `clicks` of button events, so widgets see 2 for
+    // a double click and 3 for a triple one whatever the platform does
+    pub(crate) fn count_clicks(&mut self, event: Event) -> Event {
+        match event {
+            Event::MouseButtonDown {timestamp, window_id, which, mouse_btn, x, y, ..} => {
+                let clicks = self.click_counter.press(mouse_btn, (x, y), Instant::now());
+                Event::MouseButtonDown {timestamp, window_id, which, mouse_btn, clicks, x, y}
+            },
+            Event::MouseButtonUp {timestamp, window_id, which, mouse_btn, x, y, ..} => {
+                let clicks = self.click_counter.count.max(1);
+                Event::MouseButtonUp {timestamp, window_id, which, mouse_btn, clicks, x, y}
+            },
+            event => event,
+        }
+    }
+
+    // The second press of a double click landed on the widget at `path`
+    pub(crate) fn double_clicked(&mut self, path: &[usize]) {
+        let Some(w) = self.tabs.get(self.tab_pos).and_then(|tab| tab.get(path)) else {
+            return;
+        };
+        let name = w.name().to_string();
+        self.fire_in(|tk| &mut tk.double_click_callbacks, &name);
+        if let Some(id) = self.item_id(path) {
+            self.ui_events.push(UiEvent::DoubleClicked {id, name});
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOON: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn counts_double_and_triple_clicks() {
+        let mut counter = ClickCounter::default();
+        let t = Instant::now();
+        assert_eq!(counter.press(MouseButton::Left, (10, 10), t), 1);
+        assert_eq!(counter.press(MouseButton::Left, (10, 10), t + SOON), 2);
+        assert_eq!(counter.press(MouseButton::Left, (11, 9), t + SOON * 2), 3);
+    }
+
+    #[test]
+    fn starts_over_after_three() {
+        let mut counter = ClickCounter::default();
+        let t = Instant::now();
+        let counts: Vec<u8> = (0..5).map(|i| counter.press(MouseButton::Left, (0, 0), t + SOON * i)).collect();
+        assert_eq!(counts, vec![1, 2, 3, 1, 2]);
+    }
+
+    #[test]
+    fn too_slow_is_a_new_click() {
+        let mut counter = ClickCounter::default();
+        let t = Instant::now();
+        counter.press(MouseButton::Left, (0, 0), t);
+        assert_eq!(counter.press(MouseButton::Left, (0, 0), t + DEFAULT_INTERVAL), 2);
+        assert_eq!(counter.press(MouseButton::Left, (0, 0), t + DEFAULT_INTERVAL * 2 + SOON), 1);
+    }
+
+    #[test]
+    fn moving_too_far_is_a_new_click() {
+        let mut counter = ClickCounter::default();
+        let t = Instant::now();
+        counter.press(MouseButton::Left, (0, 0), t);
+        assert_eq!(counter.press(MouseButton::Left, (CLICK_SLOP, -CLICK_SLOP), t + SOON), 2);
+        assert_eq!(counter.press(MouseButton::Left, (CLICK_SLOP * 2 + 1, 0), t + SOON * 2), 1);
+        assert_eq!(counter.press(MouseButton::Left, (CLICK_SLOP * 2 + 1, CLICK_SLOP + 1), t + SOON * 3), 1);
+    }
+
+    #[test]
+    fn another_button_is_a_new_click() {
+        let mut counter = ClickCounter::default();
+        let t = Instant::now();
+        counter.press(MouseButton::Left, (0, 0), t);
+        assert_eq!(counter.press(MouseButton::Right, (0, 0), t + SOON), 1);
+        assert_eq!(counter.press(MouseButton::Left, (0, 0), t + SOON * 2), 1);
+        assert_eq!(counter.press(MouseButton::Left, (0, 0), t + SOON * 3), 2);
+    }
+
+    #[test]
+    fn interval_can_be_changed() {
+        let mut counter = ClickCounter {interval: Duration::from_millis(50), ..ClickCounter::default()};
+        let t = Instant::now();
+        counter.press(MouseButton::Left, (0, 0), t);
+        assert_eq!(counter.press(MouseButton::Left, (0, 0), t + SOON), 1);
+    }
+}
@@ -0,0 +1,359 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::Texture;
+use sdl2::surface::Surface;
+
+use crate::{DrawCtx, Drawable, Role, TextRenderer, Theme, ToolkitError, Widget, WidgetState};
+
+const HUE_WIDTH: i32 = 20;
+const MARKER_SIZE: u32 = 7;
+const KEY_STEP: f64 = 0.02;
+const HUE_KEY_STEP: f64 = 5.0;
+
+// Size and hue a cached gradient was made for
+type AreaKey = (u32, u32, i64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    // saturation left to right, value bottom to top
+    Area,
+    Hue,
+}
+
+// Hue in degrees, saturation and value from 0 to 1
+fn hsv_to_color(h: f64, s: f64, v: f64) -> Color {
+    let c = v * s;
+    let hp = (h.rem_euclid(360.0)) / 60.0;
+    let x = c * (1.0 - (hp % 2.0 - 1.0).abs());
+    let (r, g, b) = match hp as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = v - c;
+    let byte = |f: f64| ((f + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    Color::RGB(byte(r), byte(g), byte(b))
+}
+
+fn color_to_hsv(color: Color) -> (f64, f64, f64) {
+    let (r, g, b) = (color.r as f64 / 255.0, color.g as f64 / 255.0, color.b as f64 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let d = max - min;
+    let h = if d == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / d + 2.0)
+    } else {
+        60.0 * ((r - g) / d + 4.0)
+    };
+    let s = if max == 0.0 { 0.0 } else { d / max };
+    (h, s, max)
+}
+
+// Fills a surface pixel by pixel
+fn gradient(w: u32, h: u32, f: impl Fn(u32, u32) -> Color) -> Result<Surface<'static>, ToolkitError> {
+    let mut surface = Surface::new(w.max(1), h.max(1), PixelFormatEnum::RGB24)?;
+    let pitch = surface.pitch() as usize;
+    let (w, h) = (surface.width(), surface.height());
+    surface.with_lock_mut(|pixels| {
+        for y in 0..h {
+            for x in 0..w {
+                let c = f(x, y);
+                let i = y as usize * pitch + x as usize * 3;
+                pixels[i..i + 3].copy_from_slice(&[c.r, c.g, c.b]);
+            }
+        }
+    });
+    Ok(surface)
+}
+
+// Picks a color from a saturation/value square and a hue strip, with a
+// swatch and the hex code of the result below
+pub struct ColorPicker {
+    name: String,
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    pref: (i32, i32),
+    hue: f64,
+    sat: f64,
+    val: f64,
+    drag: Option<Part>,
+    text_height: i32,
+    // gradients are only rendered again when hue or size change
+    area_texture: RefCell<Option<(AreaKey, Texture<'static>)>>,
+    hue_texture: RefCell<Option<(u32, Texture<'static>)>>,
+    state: WidgetState,
+    changed: bool,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl ColorPicker {
+    pub fn new(text: &TextRenderer, name: &str, x: i32, y: i32, w: i32, h: i32) -> ColorPicker {
+        ColorPicker {
+            name: name.to_string(),
+            x,
+            y,
+            w,
+            h,
+            pref: (w, h),
+            hue: 0.0,
+            sat: 1.0,
+            val: 1.0,
+            drag: None,
+            text_height: text.height(),
+            area_texture: RefCell::new(None),
+            hue_texture: RefCell::new(None),
+            state: WidgetState::default(),
+            changed: false,
+            theme: Theme::default(),
+            own_theme: false,
+        }
+    }
+
+    pub fn with_color(mut self, color: Color) -> ColorPicker {
+        self.set_color(color);
+        self
+    }
+
+    pub fn color(&self) -> Color {
+        hsv_to_color(self.hue, self.sat, self.val)
+    }
+
+    pub fn hex(&self) -> String {
+        let c = self.color();
+        format!("#{:02X}{:02X}{:02X}", c.r, c.g, c.b)
+    }
+
+    pub fn set_color(&mut self, color: Color) {
+        let (h, s, v) = color_to_hsv(color);
+        // greys have no hue of their own, keep the one there was
+        if s > 0.0 && v > 0.0 {
+            self.hue = h;
+        }
+        self.sat = s;
+        self.val = v;
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+    }
+
+    fn bottom_height(&self) -> i32 {
+        self.text_height + self.theme.padding
+    }
+
+    fn area_rect(&self) -> Rect {
+        let pad = self.theme.padding;
+        let w = (self.w - HUE_WIDTH - pad).max(1);
+        let h = (self.h - self.bottom_height() - pad).max(1);
+        Rect::new(self.x, self.y, w as u32, h as u32)
+    }
+
+    fn hue_rect(&self) -> Rect {
+        let area = self.area_rect();
+        Rect::new(self.x + self.w - HUE_WIDTH, self.y, HUE_WIDTH as u32, area.height())
+    }
+
+    fn bottom_rect(&self) -> Rect {
+        let h = self.bottom_height();
+        Rect::new(self.x, self.y + self.h - h, self.w.max(0) as u32, h as u32)
+    }
+
+    fn set_hsv(&mut self, hue: f64, sat: f64, val: f64) {
+        let (hue, sat, val) = (hue.rem_euclid(360.0), sat.clamp(0.0, 1.0), val.clamp(0.0, 1.0));
+        if (hue, sat, val) != (self.hue, self.sat, self.val) {
+            self.hue = hue;
+            self.sat = sat;
+            self.val = val;
+            self.changed = true;
+        }
+    }
+
+    fn drag_to(&mut self, part: Part, x: i32, y: i32) {
+        match part {
+            Part::Area => {
+                let r = self.area_rect();
+                let sat = (x - r.x()) as f64 / (r.width() as f64 - 1.0).max(1.0);
+                let val = 1.0 - (y - r.y()) as f64 / (r.height() as f64 - 1.0).max(1.0);
+                self.set_hsv(self.hue, sat, val);
+            },
+            Part::Hue => {
+                let r = self.hue_rect();
+                let t = ((y - r.y()) as f64 / r.height() as f64).clamp(0.0, 0.9999);
+                self.set_hsv(t * 360.0, self.sat, self.val);
+            },
+        }
+    }
+
+    fn draw_area(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let rect = self.area_rect();
+        let key = (rect.width(), rect.height(), (self.hue * 10.0) as i64);
+        let mut cache = self.area_texture.borrow_mut();
+        if cache.as_ref().is_none_or(|(k, _)| *k != key) {
+            let (w, h, hue) = (rect.width(), rect.height(), self.hue);
+            let surface = gradient(w, h, |x, y| {
+                let s = x as f64 / (w as f64 - 1.0).max(1.0);
+                let v = 1.0 - y as f64 / (h as f64 - 1.0).max(1.0);
+                hsv_to_color(hue, s, v)
+            })?;
+            *cache = Some((key, ctx.text().texture_from_surface(&surface)?));
+        }
+        let (_, texture) = cache.as_ref().unwrap();
+        ctx.canvas().copy(texture, None, rect)?;
+        Ok(())
+    }
+
+    fn draw_hue(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let rect = self.hue_rect();
+        let mut cache = self.hue_texture.borrow_mut();
+        if cache.as_ref().is_none_or(|(h, _)| *h != rect.height()) {
+            let h = rect.height();
+            // one column is enough, it gets stretched sideways
+            let surface = gradient(1, h, |_, y| hsv_to_color(y as f64 / h as f64 * 360.0, 1.0, 1.0))?;
+            *cache = Some((h, ctx.text().texture_from_surface(&surface)?));
+        }
+        let (_, texture) = cache.as_ref().unwrap();
+        ctx.canvas().copy(texture, None, rect)?;
+        Ok(())
+    }
+}
+
+impl Debug for ColorPicker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ColorPicker")
+            .field("name", &self.name)
+            .field("rect", &self.rect())
+            .field("color", &self.hex())
+            .finish()
+    }
+}
+
+impl Drawable for ColorPicker {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let theme = &self.theme;
+        let pad = theme.padding;
+        self.draw_area(ctx)?;
+        self.draw_hue(ctx)?;
+
+        let area = self.area_rect();
+        let border = if self.state.focused { theme.active } else { theme.border };
+        ctx.canvas().set_draw_color(border);
+        ctx.canvas().draw_rect(area)?;
+        ctx.canvas().draw_rect(self.hue_rect())?;
+
+        // markers in black or white, whichever shows up on the color below
+        let marker = if self.val > 0.5 { Color::RGB(0, 0, 0) } else { Color::RGB(255, 255, 255) };
+        let mx = area.x() + (self.sat * (area.width() as f64 - 1.0)).round() as i32;
+        let my = area.y() + ((1.0 - self.val) * (area.height() as f64 - 1.0)).round() as i32;
+        ctx.canvas().set_draw_color(marker);
+        ctx.canvas().draw_rect(Rect::from_center((mx, my), MARKER_SIZE, MARKER_SIZE))?;
+        let hue = self.hue_rect();
+        let hy = hue.y() + (self.hue / 360.0 * hue.height() as f64).round() as i32;
+        ctx.canvas().set_draw_color(theme.text);
+        ctx.canvas().draw_rect(Rect::new(hue.x() - 1, hy - 1, hue.width() + 2, 3))?;
+
+        let bottom = self.bottom_rect();
+        let swatch = Rect::new(bottom.x(), bottom.y(), 2 * bottom.height(), bottom.height());
+        ctx.fill_rounded_rect(swatch, theme.corner_radius, self.color())?;
+        ctx.draw_rounded_rect(swatch, theme.corner_radius, theme.border)?;
+
+        let texture = ctx.text().render(&self.hex())?;
+        let attr = texture.query();
+        ctx.tint(&texture, if self.state.enabled { theme.text } else { theme.disabled });
+        let dst = Rect::new(swatch.right() + pad, bottom.y() + (bottom.height() as i32 - attr.height as i32) / 2, attr.width, attr.height);
+        ctx.canvas().copy(&texture, None, dst)?;
+        Ok(())
+    }
+}
+
+impl Widget for ColorPicker {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { Rect::new(self.x, self.y, self.w as u32, self.h as u32) }
+    fn role(&self) -> Role { Role::ColorPicker }
+    fn access_value(&self) -> Option<String> { Some(self.hex()) }
+    fn focusable(&self) -> bool { true }
+    fn widget_state(&self) -> Option<&WidgetState> { Some(&self.state) }
+    fn widget_state_mut(&mut self) -> Option<&mut WidgetState> { Some(&mut self.state) }
+
+    fn size_hint(&self) -> (u32, u32) {
+        (self.pref.0 as u32, self.pref.1 as u32)
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.x = rect.x();
+        self.y = rect.y();
+        self.w = rect.width() as i32;
+        self.h = rect.height() as i32;
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::MouseButtonDown {mouse_btn: MouseButton::Left, x, y, ..} => {
+                self.drag = if self.area_rect().contains_point((*x, *y)) {
+                    Some(Part::Area)
+                } else if self.hue_rect().contains_point((*x, *y)) {
+                    Some(Part::Hue)
+                } else {
+                    None
+                };
+                if let Some(part) = self.drag {
+                    self.drag_to(part, *x, *y);
+                }
+            },
+            Event::MouseMotion {x, y, ..} => {
+                let Some(part) = self.drag else {
+                    return false;
+                };
+                self.drag_to(part, *x, *y);
+            },
+            Event::MouseButtonUp {mouse_btn: MouseButton::Left, ..} => {
+                self.drag = None;
+            },
+            Event::KeyDown {keycode: Some(key), ..} => {
+                let (h, s, v) = (self.hue, self.sat, self.val);
+                match key {
+                    Keycode::Left => self.set_hsv(h, s - KEY_STEP, v),
+                    Keycode::Right => self.set_hsv(h, s + KEY_STEP, v),
+                    Keycode::Up => self.set_hsv(h, s, v + KEY_STEP),
+                    Keycode::Down => self.set_hsv(h, s, v - KEY_STEP),
+                    Keycode::PageUp => self.set_hsv(h - HUE_KEY_STEP, s, v),
+                    Keycode::PageDown => self.set_hsv(h + HUE_KEY_STEP, s, v),
+                    _ => return false,
+                }
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
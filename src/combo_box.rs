@@ -0,0 +1,276 @@
+use std::any::Any;
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::rect::Rect;
+
+use crate::{DrawCtx, Drawable, FontId, Role, TextRenderer, Theme, ToolkitError, Widget, WidgetState};
+
+const MAX_POPUP_ROWS: usize = 8;
+
+// Shows the current choice and drops down a list of the others when clicked
+pub struct ComboBox {
+    name: String,
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    pref_w: i32,
+    items: Vec<String>,
+    selected: Option<usize>,
+    open: bool,
+    // first row shown in the popup
+    scroll: usize,
+    font: FontId,
+    text_height: i32,
+    state: WidgetState,
+    changed: bool,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl ComboBox {
+    pub fn new(text: &TextRenderer, name: &str, x: i32, y: i32, w: i32) -> ComboBox {
+        let theme = Theme::default();
+        ComboBox {
+            name: name.to_string(),
+            x,
+            y,
+            w,
+            h: text.height() + 2 * theme.padding,
+            pref_w: w,
+            items: Vec::new(),
+            selected: None,
+            open: false,
+            scroll: 0,
+            font: FontId::DEFAULT,
+            text_height: text.height(),
+            state: WidgetState::default(),
+            changed: false,
+            theme,
+            own_theme: false,
+        }
+    }
+
+    pub fn with_items<S: Into<String>, I: IntoIterator<Item = S>>(mut self, items: I) -> ComboBox {
+        self.items = items.into_iter().map(Into::into).collect();
+        if !self.items.is_empty() {
+            self.selected = Some(0);
+        }
+        self
+    }
+
+    pub fn items(&self) -> &[String] { &self.items }
+    pub fn selected(&self) -> Option<usize> { self.selected }
+    pub fn selected_item(&self) -> Option<&str> {
+        self.selected.and_then(|idx| self.items.get(idx)).map(|s| s.as_str())
+    }
+    pub fn is_open(&self) -> bool { self.open }
+
+    pub fn set_selected(&mut self, selected: Option<usize>) {
+        self.selected = selected.filter(|idx| *idx < self.items.len());
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.set_theme_inner(theme);
+    }
+
+    fn set_theme_inner(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.h = self.text_height + 2 * theme.padding;
+    }
+
+    fn row_height(&self) -> i32 {
+        self.text_height + self.theme.padding
+    }
+
+    fn popup_rows(&self) -> usize {
+        self.items.len().min(MAX_POPUP_ROWS)
+    }
+
+    fn popup_rect(&self) -> Rect {
+        let h = self.popup_rows() as i32 * self.row_height();
+        Rect::new(self.x, self.y + self.h, self.w as u32, h as u32)
+    }
+
+    fn set_open(&mut self, open: bool) {
+        self.open = open && !self.items.is_empty();
+        if self.open {
+            // start with the current choice in view
+            let sel = self.selected.unwrap_or(0);
+            let rows = self.popup_rows();
+            self.scroll = sel.saturating_sub(rows - 1).min(self.items.len() - rows);
+        }
+    }
+
+    fn select(&mut self, idx: usize) {
+        if idx < self.items.len() && self.selected != Some(idx) {
+            self.selected = Some(idx);
+            self.changed = true;
+        }
+    }
+
+    fn draw_text(&self, ctx: &mut DrawCtx, text: &str, rect: Rect) -> Result<(), ToolkitError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        let texture = ctx.text().render_font(self.font, text)?;
+        let attr = texture.query();
+        let w = attr.width.min((rect.width() as i32 - self.theme.padding).max(0) as u32);
+        let src = Rect::new(0, 0, w, attr.height);
+        let dst = Rect::new(rect.x() + self.theme.padding, rect.y() + (rect.height() as i32 - attr.height as i32) / 2, w, attr.height);
+        let theme = &self.theme;
+        ctx.tint(&texture, if self.state.enabled { theme.text } else { theme.disabled });
+        ctx.canvas().copy(&texture, src, dst)?;
+        Ok(())
+    }
+}
+
+impl Debug for ComboBox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ComboBox")
+            .field("name", &self.name)
+            .field("rect", &self.rect())
+            .field("items", &self.items)
+            .field("selected", &self.selected)
+            .field("open", &self.open)
+            .finish()
+    }
+}
+
+impl Drawable for ComboBox {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let rect = self.rect();
+        let theme = &self.theme;
+        ctx.fill_rounded_rect(rect, theme.corner_radius, theme.fill)?;
+        let border = if self.state.focused { theme.active } else { theme.border };
+        ctx.draw_rounded_rect(rect, theme.corner_radius, border)?;
+
+        // leave room for the arrow on the right
+        let arrow = self.h / 3;
+        let text_rect = Rect::new(self.x, self.y, (self.w - arrow * 2 - theme.padding).max(0) as u32, self.h as u32);
+        if let Some(item) = self.selected_item() {
+            self.draw_text(ctx, item, text_rect)?;
+        }
+
+        let cx = self.x + self.w - theme.padding - arrow;
+        let cy = self.y + self.h / 2 - arrow / 2;
+        ctx.canvas().set_draw_color(if self.state.enabled { theme.text } else { theme.disabled });
+        for i in 0..arrow {
+            ctx.canvas().draw_line((cx - arrow + i, cy + i), (cx + arrow - i, cy + i))?;
+        }
+        Ok(())
+    }
+}
+
+impl Widget for ComboBox {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { Rect::new(self.x, self.y, self.w as u32, self.h as u32) }
+    fn role(&self) -> Role { Role::ComboBox }
+    fn access_value(&self) -> Option<String> { self.selected_item().map(str::to_string) }
+    fn focusable(&self) -> bool { true }
+
+    fn widget_state(&self) -> Option<&WidgetState> { Some(&self.state) }
+    fn widget_state_mut(&mut self) -> Option<&mut WidgetState> { Some(&mut self.state) }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.state.focused = focused;
+        if !focused {
+            self.set_open(false);
+        }
+    }
+
+    fn size_hint(&self) -> (u32, u32) {
+        (self.pref_w as u32, (self.text_height + 2 * self.theme.padding) as u32)
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.x = rect.x();
+        self.y = rect.y();
+        self.w = rect.width() as i32;
+        self.h = rect.height() as i32;
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.set_theme_inner(*theme);
+        }
+    }
+
+    fn set_font(&mut self, text: &TextRenderer, font: FontId) -> Result<(), ToolkitError> {
+        self.font = font;
+        self.text_height = text.font_height(font);
+        self.set_theme_inner(self.theme);
+        Ok(())
+    }
+
+    fn overlay_rect(&self) -> Option<Rect> {
+        self.open.then(|| self.popup_rect())
+    }
+
+    fn draw_overlay(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        if !self.open {
+            return Ok(());
+        }
+        let theme = &self.theme;
+        let popup = self.popup_rect();
+        ctx.canvas().set_draw_color(theme.field);
+        ctx.canvas().fill_rect(popup)?;
+
+        let row_h = self.row_height();
+        for (i, idx) in (self.scroll..self.scroll + self.popup_rows()).enumerate() {
+            let row = Rect::new(popup.x(), popup.y() + i as i32 * row_h, popup.width(), row_h as u32);
+            if Some(idx) == self.selected {
+                ctx.canvas().set_draw_color(theme.active);
+                ctx.canvas().fill_rect(row)?;
+            }
+            self.draw_text(ctx, &self.items[idx], row)?;
+        }
+
+        ctx.canvas().set_draw_color(theme.border);
+        ctx.canvas().draw_rect(popup)?;
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::MouseButtonDown {mouse_btn: MouseButton::Left, x, y, ..} => {
+                let popup = self.popup_rect();
+                if self.open && popup.contains_point((*x, *y)) {
+                    let row = ((y - popup.y()) / self.row_height()) as usize;
+                    self.select(self.scroll + row);
+                    self.set_open(false);
+                } else {
+                    self.set_open(!self.open);
+                }
+            },
+            Event::MouseWheel {y, ..} if self.open => {
+                let max = self.items.len() - self.popup_rows();
+                self.scroll = (self.scroll as i32 - y).clamp(0, max as i32) as usize;
+            },
+            Event::KeyDown {keycode: Some(key), ..} => {
+                let cur = self.selected.unwrap_or(0);
+                match key {
+                    Keycode::Up => self.select(cur.saturating_sub(1)),
+                    Keycode::Down => self.select(cur + 1),
+                    Keycode::Return | Keycode::Space => self.set_open(!self.open),
+                    Keycode::Escape if self.open => self.set_open(false),
+                    _ => return false,
+                }
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
@@ -0,0 +1,100 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::BlendMode;
+
+use crate::{DrawCtx, Tab, Toolkit, ToolkitError, Widget};
+
+// Frames the FPS is averaged over
+const FRAMES: usize = 30;
+const CONTAINER: Color = Color::RGB(80, 160, 255);
+const LEAF: Color = Color::RGB(255, 80, 160);
+const PANEL: Color = Color::RGBA(0, 0, 0, 200);
+const INFO: Color = Color::RGB(255, 255, 255);
+
+// Widget bounds and frame stats drawn over everything
+#[derive(Debug, Default)]
+pub(crate) struct DebugOverlay {
+    pub(crate) enabled: bool,
+    // toggles it, if the app asked for a key
+    pub(crate) key: Option<Keycode>,
+    frames: VecDeque<Duration>,
+}
+
+impl DebugOverlay {
+    pub(crate) fn record(&mut self, delta: Duration) {
+        if self.frames.len() == FRAMES {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(delta);
+    }
+
+    fn frame_time(&self) -> Duration {
+        match self.frames.len() {
+            0 => Duration::ZERO,
+            n => self.frames.iter().sum::<Duration>() / n as u32,
+        }
+    }
+
+    // `width` is that of the window, the stats go in its top right corner
+    pub(crate) fn draw(&self, ctx: &mut DrawCtx, tab: Option<&Tab>, width: u32) -> Result<(), ToolkitError> {
+        if let Some(tab) = tab {
+            for (item, id) in tab.items.iter().zip(tab.ids.iter()) {
+                outline(ctx, item.as_ref())?;
+                let rect = item.rect();
+                ctx.draw_text_at(rect.x() + 2, rect.y() + 1, &format!("#{}", id.0), LEAF)?;
+            }
+        }
+
+        let frame = self.frame_time();
+        let fps = if frame.is_zero() { 0.0 } else { 1.0 / frame.as_secs_f32() };
+        let text = ctx.text();
+        let lines = [
+            format!("{fps:.1} fps"),
+            format!("{:.2} ms", frame.as_secs_f32() * 1000.0),
+            format!("{}/{} textures", text.cached(), text.cache_size()),
+        ];
+        let line_h = text.height();
+        let text_w = lines.iter().filter_map(|l| text.size_of(l).ok()).map(|(w, _)| w).max().unwrap_or(0);
+        let panel = Rect::new(width as i32 - text_w as i32 - 12, 4, text_w + 8, (line_h * lines.len() as i32 + 4) as u32);
+        ctx.canvas().set_blend_mode(BlendMode::Blend);
+        ctx.fill_rect(panel, PANEL)?;
+        ctx.canvas().set_blend_mode(BlendMode::None);
+        for (i, line) in lines.iter().enumerate() {
+            ctx.draw_text_at(panel.x() + 4, panel.y() + 2 + i as i32 * line_h, line, INFO)?;
+        }
+        Ok(())
+    }
+}
+
+// Containers in one colour, everything else in another
+fn outline(ctx: &mut DrawCtx, w: &dyn Widget) -> Result<(), ToolkitError> {
+    let children = w.children();
+    ctx.draw_rect(w.rect(), if children.is_empty() { LEAF } else { CONTAINER })?;
+    for child in children {
+        outline(ctx, child.as_ref())?;
+    }
+    Ok(())
+}
+
+impl Toolkit {
+    // Draws the bounds of every widget of the current tab, the ids of tab
+    // items, and the frame rate
+    pub fn set_debug_overlay(&mut self, enabled: bool) {
+        self.debug.enabled = enabled;
+        self.dirty = true;
+    }
+
+    // Lets the user toggle the debug overlay with `key` (F12 say) when it
+    // isn't bound to anything else. No key does it unless asked for.
+    pub fn set_debug_key(&mut self, key: Option<Keycode>) {
+        self.debug.key = key;
+    }
+
+    pub fn debug_overlay(&self) -> bool {
+        self.debug.enabled
+    }
+}
@@ -0,0 +1,278 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::BlendMode;
+
+use crate::text_area;
+use crate::widget::{self, WidgetPath};
+use crate::{Button, DrawCtx, Drawable, FontId, TextInput, TextRenderer, Theme, Toolkit, ToolkitError, Widget};
+
+pub type DialogCallback = Box<dyn FnMut(&mut Toolkit, &DialogResult)>;
+
+const OK: &str = "OK";
+const CANCEL: &str = "Cancel";
+const MAX_WIDTH: i32 = 420;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DialogResult {
+    Ok,
+    Cancel,
+    // what was entered into a prompt
+    Text(String),
+}
+
+// Lets the code that opened a dialog check on it later. The result shows up
+// here once the dialog is closed.
+#[derive(Debug, Clone, Default)]
+pub struct DialogHandle(Rc<RefCell<Option<DialogResult>>>);
+
+impl DialogHandle {
+    pub fn result(&self) -> Option<DialogResult> {
+        self.0.borrow().clone()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.0.borrow().is_some()
+    }
+
+    pub(crate) fn resolve(&self, result: DialogResult) {
+        *self.0.borrow_mut() = Some(result);
+    }
+
+    pub(crate) fn same(&self, other: &DialogHandle) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+// A modal window on top of the tabs. While one is open it gets all input.
+pub(crate) struct Dialog {
+    pub(crate) handle: DialogHandle,
+    pub(crate) callbacks: Vec<DialogCallback>,
+    title: String,
+    message: String,
+    lines: Vec<String>,
+    // window area the dialog was last laid out for
+    area: Option<Rect>,
+    rect: Rect,
+    // the prompt's input, if any, comes first, then the buttons
+    items: Vec<Box<dyn Widget>>,
+    has_input: bool,
+    has_cancel: bool,
+    pressed: Option<WidgetPath>,
+    hover: Option<WidgetPath>,
+    text_height: i32,
+    theme: Theme,
+}
+
+impl Dialog {
+    pub(crate) fn new(text: &TextRenderer, theme: Theme, title: &str, message: &str, input: Option<&str>, cancel: bool) -> Result<Dialog, ToolkitError> {
+        let mut items: Vec<Box<dyn Widget>> = Vec::new();
+        if let Some(value) = input {
+            let mut field = TextInput::new(text, "dialog-input", 0, 0, 0);
+            field.set_value(value);
+            field.set_focused(true);
+            items.push(Box::new(field));
+        }
+        items.push(Box::new(Button::new(text, OK, 0, 0)?));
+        if cancel {
+            items.push(Box::new(Button::new(text, CANCEL, 0, 0)?));
+        }
+        for item in items.iter_mut() {
+            item.apply_theme(&theme);
+        }
+
+        Ok(Dialog {
+            handle: DialogHandle::default(),
+            callbacks: Vec::new(),
+            title: title.to_string(),
+            message: message.to_string(),
+            lines: Vec::new(),
+            area: None,
+            rect: Rect::new(0, 0, 0, 0),
+            items,
+            has_input: input.is_some(),
+            has_cancel: cancel,
+            pressed: None,
+            hover: None,
+            text_height: text.height(),
+            theme,
+        })
+    }
+
+    pub(crate) fn wants_text(&self) -> bool { self.has_input }
+
+    pub(crate) fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        for item in self.items.iter_mut() {
+            item.apply_theme(&theme);
+        }
+        self.area = None;
+    }
+
+    fn accept(&self) -> DialogResult {
+        match self.items.first().and_then(|w| w.as_any().downcast_ref::<TextInput>()) {
+            Some(input) => DialogResult::Text(input.plaintext().to_string()),
+            None => DialogResult::Ok,
+        }
+    }
+
+    fn dismiss(&self) -> DialogResult {
+        if self.has_cancel { DialogResult::Cancel } else { DialogResult::Ok }
+    }
+
+    fn title_height(&self) -> i32 {
+        self.text_height + 2 * self.theme.padding
+    }
+
+    // Centers the dialog in `area`, wrapping the message to fit
+    pub(crate) fn layout(&mut self, area: Rect, text: &TextRenderer) {
+        if self.area == Some(area) {
+            return;
+        }
+        self.area = Some(area);
+
+        let pad = self.theme.padding;
+        let w = (area.width() as i32 - 4 * pad).clamp(0, MAX_WIDTH);
+        let inner_w = (w - 2 * pad).max(1);
+        self.lines = match text_area::wrap(text, FontId::DEFAULT, &self.message, inner_w) {
+            Ok(lines) => lines.into_iter().map(|l| self.message[l].trim_end().to_string()).collect(),
+            Err(_) => self.message.lines().map(str::to_string).collect(),
+        };
+
+        let btn_h = self.items.iter().rev().map(|b| b.size_hint().1 as i32).max().unwrap_or(0);
+        let input_h = if self.has_input { self.items[0].size_hint().1 as i32 + pad } else { 0 };
+        let h = self.title_height() + pad + self.lines.len() as i32 * self.text_height + input_h + 2 * pad + btn_h;
+        self.rect = Rect::from_center(area.center(), w as u32, h.max(0) as u32);
+
+        let x = self.rect.x() + pad;
+        let mut y = self.rect.y() + self.title_height() + pad + self.lines.len() as i32 * self.text_height;
+        let mut buttons = &mut self.items[..];
+        if self.has_input {
+            let (input, rest) = buttons.split_first_mut().unwrap();
+            let (_, ih) = input.size_hint();
+            input.set_rect(Rect::new(x, y + pad, inner_w as u32, ih));
+            y += input_h;
+            buttons = rest;
+        }
+
+        // buttons line up along the bottom right, OK first
+        let mut right = self.rect.right() - pad;
+        for btn in buttons.iter_mut().rev() {
+            let (bw, bh) = btn.size_hint();
+            right -= bw as i32;
+            btn.set_rect(Rect::new(right, y + pad, bw, bh));
+            right -= pad;
+        }
+    }
+
+    // Takes every event while the dialog is open, returns a result once the
+    // user closed it
+    pub(crate) fn handle_event(&mut self, event: &Event) -> Option<DialogResult> {
+        match event {
+            Event::KeyDown {keycode: Some(Keycode::Escape), ..} => return Some(self.dismiss()),
+            Event::KeyDown {keycode: Some(Keycode::Return | Keycode::KpEnter), ..} => return Some(self.accept()),
+            Event::KeyDown {..} | Event::TextInput {..} if self.has_input => {
+                self.items[0].handle_event(event);
+            },
+            Event::MouseButtonDown {mouse_btn: MouseButton::Left, x, y, ..} => {
+                self.pressed = widget::hit_path(&self.items, *x, *y);
+                if let Some(w) = self.pressed.as_ref().and_then(|path| widget::get_mut(&mut self.items, path)) {
+                    w.set_pressed(true);
+                    w.handle_event(event);
+                }
+            },
+            Event::MouseMotion {x, y, ..} => {
+                let hover = widget::hit_path(&self.items, *x, *y);
+                if hover != self.hover {
+                    if let Some(w) = self.hover.as_ref().and_then(|path| widget::get_mut(&mut self.items, path)) {
+                        w.set_hovered(false);
+                    }
+                    if let Some(w) = hover.as_ref().and_then(|path| widget::get_mut(&mut self.items, path)) {
+                        w.set_hovered(true);
+                    }
+                    self.hover = hover;
+                }
+                if let Some(w) = self.pressed.as_ref().and_then(|path| widget::get_mut(&mut self.items, path)) {
+                    w.handle_event(event);
+                }
+            },
+            Event::MouseButtonUp {mouse_btn: MouseButton::Left, x, y, ..} => {
+                let pressed = self.pressed.take()?;
+                if let Some(w) = widget::get_mut(&mut self.items, &pressed) {
+                    w.set_pressed(false);
+                    w.handle_event(event);
+                }
+                if widget::hit_path(&self.items, *x, *y).as_ref() != Some(&pressed) {
+                    return None;
+                }
+                return match widget::get(&self.items, &pressed).map(|w| w.name()) {
+                    Some(OK) => Some(self.accept()),
+                    Some(CANCEL) => Some(DialogResult::Cancel),
+                    _ => None,
+                };
+            },
+            _ => { },
+        }
+        None
+    }
+}
+
+impl Debug for Dialog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dialog")
+            .field("title", &self.title)
+            .field("message", &self.message)
+            .field("rect", &self.rect)
+            .field("items", &self.items)
+            .finish()
+    }
+}
+
+impl Drawable for Dialog {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let theme = &self.theme;
+        let pad = theme.padding;
+
+        // dim whatever is behind the dialog
+        ctx.canvas().set_blend_mode(BlendMode::Blend);
+        ctx.canvas().set_draw_color(Color::RGBA(0, 0, 0, 128));
+        ctx.canvas().fill_rect(None)?;
+        ctx.canvas().set_blend_mode(BlendMode::None);
+
+        ctx.fill_rounded_rect(self.rect, theme.corner_radius, theme.background)?;
+        let title_rect = Rect::new(self.rect.x(), self.rect.y(), self.rect.width(), self.title_height() as u32);
+        ctx.fill_rounded_rect(title_rect, theme.corner_radius, theme.active)?;
+        ctx.draw_rounded_rect(self.rect, theme.corner_radius, theme.border)?;
+
+        ctx.push_clip(self.rect);
+        if !self.title.is_empty() {
+            let texture = ctx.text().render(&self.title)?;
+            let attr = texture.query();
+            ctx.tint(&texture, theme.text);
+            ctx.canvas().copy(&texture, None, Rect::new(self.rect.x() + pad, self.rect.y() + pad, attr.width, attr.height))?;
+        }
+        let mut y = self.rect.y() + self.title_height() + pad;
+        for line in self.lines.iter() {
+            if !line.is_empty() {
+                let texture = ctx.text().render(line)?;
+                let attr = texture.query();
+                ctx.tint(&texture, theme.text);
+                ctx.canvas().copy(&texture, None, Rect::new(self.rect.x() + pad, y, attr.width, attr.height))?;
+            }
+            y += self.text_height;
+        }
+        ctx.pop_clip();
+
+        for item in self.items.iter() {
+            item.draw(ctx)?;
+        }
+        Ok(())
+    }
+}
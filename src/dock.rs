@@ -0,0 +1,567 @@
+use std::any::Any;
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::event::Event;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::BlendMode;
+
+use crate::{DrawCtx, Drawable, Role, TextRenderer, Theme, ToolkitError, Widget};
+
+// Dropping a panel this close to an edge of the dock area docks it there
+const SNAP: i32 = 24;
+// How far the pointer has to move on a title bar before it drags the panel
+const DRAG_SLOP: i32 = 4;
+const DEFAULT_SIZE: u32 = 200;
+
+// Where a panel sits in its DockArea
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "state", derive(serde::Serialize, serde::Deserialize))]
+pub enum DockSide {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    // above the center of the area, wherever it was dropped
+    Floating,
+}
+
+// What can be saved of a panel to put it back in the same place later,
+// see DockArea::panel_layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "state", derive(serde::Serialize, serde::Deserialize))]
+pub struct PanelLayout {
+    pub name: String,
+    pub side: DockSide,
+    // width of a panel docked left or right, height of one at the top or bottom
+    pub size: u32,
+    // x, y, width and height while floating, from the top left of the area
+    pub float: (i32, i32, u32, u32),
+    pub collapsed: bool,
+}
+
+// Title bar being held down, with where on the panel it was grabbed
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    grab: (i32, i32),
+    start: (i32, i32),
+    moved: bool,
+}
+
+// A tool window with a title bar, living in a DockArea. Dragging the title
+// bar takes it out to float, and dropping it against an edge of the area
+// docks it there. The button at the end of the bar collapses it down to
+// the bar.
+pub struct DockPanel {
+    name: String,
+    title: String,
+    rect: Rect,
+    side: DockSide,
+    size: u32,
+    float: Rect,
+    collapsed: bool,
+    // the whole DockArea, and the part of it left for floating panels
+    area: Rect,
+    bounds: Rect,
+    drag: Option<Drag>,
+    // side it would dock to if dropped now
+    snap: Option<DockSide>,
+    changed: bool,
+    text_height: i32,
+    children: Vec<Box<dyn Widget>>,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl DockPanel {
+    pub fn new<W: Widget>(text: &TextRenderer, name: &str, title: &str, content: W) -> DockPanel {
+        let (w, h) = content.size_hint();
+        let bar = text.height() + Theme::default().padding;
+        DockPanel {
+            name: name.to_string(),
+            title: title.to_string(),
+            rect: Rect::new(0, 0, 0, 0),
+            side: DockSide::Left,
+            size: DEFAULT_SIZE,
+            float: Rect::new(SNAP, SNAP, w.max(DEFAULT_SIZE), h.max(DEFAULT_SIZE) + bar as u32),
+            collapsed: false,
+            area: Rect::new(0, 0, 0, 0),
+            bounds: Rect::new(0, 0, 0, 0),
+            drag: None,
+            snap: None,
+            changed: false,
+            text_height: text.height(),
+            children: vec![Box::new(content)],
+            theme: Theme::default(),
+            own_theme: false,
+        }
+    }
+
+    pub fn side(mut self, side: DockSide) -> DockPanel {
+        self.side = side;
+        self
+    }
+
+    pub fn size(mut self, size: u32) -> DockPanel {
+        self.size = size;
+        self
+    }
+
+    pub fn collapsed(mut self, collapsed: bool) -> DockPanel {
+        self.collapsed = collapsed;
+        self
+    }
+
+    pub fn title_text(&self) -> &str { &self.title }
+    pub fn dock_side(&self) -> DockSide { self.side }
+    pub fn dock_size(&self) -> u32 { self.size }
+    pub fn is_collapsed(&self) -> bool { self.collapsed }
+
+    pub fn content(&self) -> &dyn Widget { self.children[0].as_ref() }
+    pub fn content_mut(&mut self) -> &mut dyn Widget { self.children[0].as_mut() }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+    }
+
+    pub fn set_side(&mut self, side: DockSide) {
+        self.side = side;
+    }
+
+    pub fn set_size(&mut self, size: u32) {
+        self.size = size;
+    }
+
+    pub fn set_collapsed(&mut self, collapsed: bool) {
+        self.collapsed = collapsed;
+    }
+
+    // Where it goes while floating, from the top left of the dock area
+    pub fn set_float_rect(&mut self, rect: Rect) {
+        self.float = rect;
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+    }
+
+    pub fn layout_state(&self) -> PanelLayout {
+        PanelLayout {
+            name: self.name.clone(),
+            side: self.side,
+            size: self.size,
+            float: (self.float.x(), self.float.y(), self.float.width(), self.float.height()),
+            collapsed: self.collapsed,
+        }
+    }
+
+    pub fn restore_layout(&mut self, layout: &PanelLayout) {
+        self.side = layout.side;
+        self.size = layout.size;
+        let (x, y, w, h) = layout.float;
+        self.float = Rect::new(x, y, w, h);
+        self.collapsed = layout.collapsed;
+    }
+
+    fn bar_height(&self) -> i32 {
+        self.text_height + self.theme.padding
+    }
+
+    // Docked at the side, collapsed down to a strip with only the button
+    fn is_strip(&self) -> bool {
+        self.collapsed && matches!(self.side, DockSide::Left | DockSide::Right)
+    }
+
+    // How much room it takes up from the side it is docked to
+    fn thickness(&self) -> u32 {
+        match self.side {
+            _ if self.collapsed => self.bar_height() as u32,
+            DockSide::Floating => 0,
+            _ => self.size,
+        }
+    }
+
+    fn bar_rect(&self) -> Rect {
+        if self.is_strip() {
+            return self.rect;
+        }
+        Rect::new(self.rect.x(), self.rect.y(), self.rect.width(), self.bar_height() as u32)
+    }
+
+    fn button_rect(&self) -> Rect {
+        let bar = self.bar_height();
+        let x = if self.is_strip() { self.rect.x() } else { self.rect.right() - bar };
+        Rect::new(x, self.rect.y(), bar as u32, bar as u32)
+    }
+
+    // A collapsed panel's content goes just below it, where it can't be hit
+    fn content_rect(&self) -> Rect {
+        let bar = self.bar_height();
+        if self.collapsed {
+            return Rect::new(self.rect.x(), self.rect.bottom(), self.rect.width(), 1);
+        }
+        Rect::new(self.rect.x(), self.rect.y() + bar, self.rect.width(), (self.rect.height() as i32 - bar).max(0) as u32)
+    }
+
+    fn snap_side(&self, x: i32, y: i32) -> Option<DockSide> {
+        let a = self.area;
+        if x - a.left() < SNAP {
+            Some(DockSide::Left)
+        } else if a.right() - x < SNAP {
+            Some(DockSide::Right)
+        } else if y - a.top() < SNAP {
+            Some(DockSide::Top)
+        } else if a.bottom() - y < SNAP {
+            Some(DockSide::Bottom)
+        } else {
+            None
+        }
+    }
+
+    // The part of the area it would take if docked to `side` now
+    fn preview_rect(&self, side: DockSide) -> Option<Rect> {
+        let a = self.area;
+        let size = self.size.min(a.width()).min(a.height());
+        Some(match side {
+            DockSide::Left => Rect::new(a.x(), a.y(), size, a.height()),
+            DockSide::Right => Rect::new(a.right() - size as i32, a.y(), size, a.height()),
+            DockSide::Top => Rect::new(a.x(), a.y(), a.width(), size),
+            DockSide::Bottom => Rect::new(a.x(), a.bottom() - size as i32, a.width(), size),
+            DockSide::Floating => return None,
+        })
+    }
+
+    // Floating rect in window coordinates, kept inside the room left by the
+    // docked panels
+    fn float_in(&self, bounds: Rect) -> Rect {
+        let h = if self.collapsed { self.bar_height() as u32 } else { self.float.height() };
+        let (w, h) = (self.float.width().min(bounds.width()), h.min(bounds.height()));
+        let x = (self.area.x() + self.float.x()).clamp(bounds.x(), bounds.right() - w as i32);
+        let y = (self.area.y() + self.float.y()).clamp(bounds.y(), bounds.bottom() - h as i32);
+        Rect::new(x, y, w, h)
+    }
+}
+
+impl Debug for DockPanel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DockPanel")
+            .field("name", &self.name)
+            .field("title", &self.title)
+            .field("rect", &self.rect)
+            .field("side", &self.side)
+            .field("collapsed", &self.collapsed)
+            .field("content", &self.children[0])
+            .finish()
+    }
+}
+
+impl Drawable for DockPanel {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let theme = &self.theme;
+        if !self.collapsed {
+            ctx.fill_rect(self.rect, theme.background)?;
+            ctx.clipped(self.content_rect(), |ctx| self.children[0].draw(ctx))?;
+        }
+
+        let bar = self.bar_rect();
+        let color = if self.drag.is_some_and(|d| d.moved) { theme.active } else { theme.fill };
+        ctx.fill_rect(bar, color)?;
+        ctx.draw_rect(self.rect, theme.border)?;
+        if !self.is_strip() && !self.title.is_empty() {
+            let clip = Rect::new(bar.x(), bar.y(), (bar.width() as i32 - bar.height() as i32).max(0) as u32, bar.height());
+            ctx.clipped(clip, |ctx| {
+                let y = bar.y() + (bar.height() as i32 - self.text_height) / 2;
+                ctx.draw_text_at(bar.x() + theme.padding / 2, y, &self.title, theme.text)
+            })?;
+        }
+
+        // a minus to collapse, a plus to expand
+        let button = self.button_rect();
+        let c = button.center();
+        let arm = button.height() as i32 / 4;
+        ctx.draw_line(Point::new(c.x() - arm, c.y()), Point::new(c.x() + arm, c.y()), theme.text)?;
+        if self.collapsed {
+            ctx.draw_line(Point::new(c.x(), c.y() - arm), Point::new(c.x(), c.y() + arm), theme.text)?;
+        }
+        Ok(())
+    }
+}
+
+impl Widget for DockPanel {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { self.rect }
+    fn role(&self) -> Role { Role::Group }
+    fn access_label(&self) -> Option<String> { Some(self.title.clone()) }
+    fn viewport(&self) -> Option<Rect> { Some(self.content_rect()) }
+    fn take_changed(&mut self) -> bool { std::mem::take(&mut self.changed) }
+
+    // The DockArea decides where it goes
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn size_hint(&self) -> (u32, u32) {
+        let (w, h) = self.children[0].size_hint();
+        (w, h + self.bar_height() as u32)
+    }
+
+    fn layout(&mut self, _area: Rect) {
+        let inner = self.content_rect();
+        self.children[0].set_rect(inner);
+        self.children[0].layout(inner);
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+        }
+        for child in self.children.iter_mut() {
+            child.apply_theme(theme);
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::MouseButtonDown {mouse_btn: MouseButton::Left, x, y, ..} => {
+                if self.button_rect().contains_point((*x, *y)) {
+                    self.collapsed = !self.collapsed;
+                    self.changed = true;
+                    return true;
+                }
+                if !self.bar_rect().contains_point((*x, *y)) {
+                    return false;
+                }
+                self.drag = Some(Drag {
+                    grab: (x - self.rect.x(), y - self.rect.y()),
+                    start: (*x, *y),
+                    moved: false,
+                });
+            },
+            Event::MouseMotion {x, y, ..} => {
+                let Some(mut drag) = self.drag else {
+                    return false;
+                };
+                if !drag.moved {
+                    if (x - drag.start.0).abs().max((y - drag.start.1).abs()) < DRAG_SLOP {
+                        return true;
+                    }
+                    drag.moved = true;
+                    if self.side != DockSide::Floating {
+                        self.side = DockSide::Floating;
+                        self.changed = true;
+                    }
+                    // the bar may have been wider docked than it is floating
+                    drag.grab.0 = drag.grab.0.min(self.float.width() as i32 - self.bar_height());
+                    self.drag = Some(drag);
+                }
+                let (w, h) = (self.float.width(), self.float.height());
+                self.float = Rect::new(x - drag.grab.0 - self.area.x(), y - drag.grab.1 - self.area.y(), w, h);
+                self.snap = self.snap_side(*x, *y);
+            },
+            Event::MouseButtonUp {mouse_btn: MouseButton::Left, ..} => {
+                let Some(drag) = self.drag.take() else {
+                    return false;
+                };
+                if let Some(side) = self.snap.take() {
+                    self.side = side;
+                }
+                // where it was let go of, kept in reach
+                let float = self.float_in(self.bounds);
+                self.float.reposition((float.x() - self.area.x(), float.y() - self.area.y()));
+                self.changed |= drag.moved;
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] { &self.children }
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] { &mut self.children }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+// A main widget in the center with DockPanels around it. Docked panels
+// take room from the edges in the order they were added; floating ones go
+// over the center, and over each other in the same order.
+pub struct DockArea {
+    name: String,
+    rect: Rect,
+    fixed: bool,
+    // the center, then the panels
+    children: Vec<Box<dyn Widget>>,
+}
+
+impl DockArea {
+    pub fn new<W: Widget>(name: &str, center: W) -> DockArea {
+        DockArea {
+            name: name.to_string(),
+            rect: Rect::new(0, 0, 0, 0),
+            fixed: false,
+            children: vec![Box::new(center)],
+        }
+    }
+
+    pub fn panel(mut self, panel: DockPanel) -> DockArea {
+        self.add_panel(panel);
+        self
+    }
+
+    pub fn add_panel(&mut self, panel: DockPanel) {
+        self.children.push(Box::new(panel));
+    }
+
+    pub fn center(&self) -> &dyn Widget { self.children[0].as_ref() }
+    pub fn center_mut(&mut self) -> &mut dyn Widget { self.children[0].as_mut() }
+
+    pub fn panels(&self) -> impl Iterator<Item = &DockPanel> {
+        self.children[1..].iter().filter_map(|c| c.as_any().downcast_ref::<DockPanel>())
+    }
+
+    pub fn panels_mut(&mut self) -> impl Iterator<Item = &mut DockPanel> {
+        self.children[1..].iter_mut().filter_map(|c| c.as_any_mut().downcast_mut::<DockPanel>())
+    }
+
+    pub fn find_panel(&self, name: &str) -> Option<&DockPanel> {
+        self.panels().find(|p| p.name == name)
+    }
+
+    pub fn find_panel_mut(&mut self, name: &str) -> Option<&mut DockPanel> {
+        self.panels_mut().find(|p| p.name == name)
+    }
+
+    // Where every panel is, to be saved and handed back to
+    // restore_panel_layout, e.g. the next time the application starts
+    pub fn panel_layout(&self) -> Vec<PanelLayout> {
+        self.panels().map(DockPanel::layout_state).collect()
+    }
+
+    // Panels are matched up by name, ones that aren't in `layout` stay as
+    // they are
+    pub fn restore_panel_layout(&mut self, layout: &[PanelLayout]) {
+        for panel in self.panels_mut() {
+            if let Some(state) = layout.iter().find(|s| s.name == panel.name) {
+                panel.restore_layout(state);
+            }
+        }
+    }
+}
+
+impl Debug for DockArea {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DockArea")
+            .field("name", &self.name)
+            .field("rect", &self.rect)
+            .field("center", &self.children[0])
+            .field("panels", &self.panels().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Drawable for DockArea {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        ctx.clipped(self.rect, |ctx| {
+            for child in self.children.iter() {
+                child.draw(ctx)?;
+            }
+            // where a panel being dragged would dock
+            for panel in self.panels() {
+                if let Some(rect) = panel.snap.and_then(|side| panel.preview_rect(side)) {
+                    let c = panel.theme.active;
+                    ctx.canvas().set_blend_mode(BlendMode::Blend);
+                    ctx.fill_rect(rect, Color::RGBA(c.r, c.g, c.b, 96))?;
+                    ctx.canvas().set_blend_mode(BlendMode::None);
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Widget for DockArea {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { self.rect }
+    fn role(&self) -> Role { Role::Group }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        self.fixed = true;
+    }
+
+    fn size_hint(&self) -> (u32, u32) {
+        let (mut w, mut h) = self.children[0].size_hint();
+        for panel in self.panels() {
+            match panel.side {
+                DockSide::Left | DockSide::Right => w += panel.thickness(),
+                DockSide::Top | DockSide::Bottom => h += panel.thickness(),
+                DockSide::Floating => { },
+            }
+        }
+        (w, h)
+    }
+
+    fn layout(&mut self, area: Rect) {
+        if !self.fixed {
+            self.rect = area;
+        }
+        let whole = self.rect;
+        // docked panels first, each taking its side of what is left
+        let mut free = whole;
+        for panel in self.panels_mut() {
+            let t = panel.thickness();
+            let rect = match panel.side {
+                DockSide::Left => {
+                    let t = t.min(free.width());
+                    free = Rect::new(free.x() + t as i32, free.y(), free.width() - t, free.height());
+                    Rect::new(free.x() - t as i32, free.y(), t, free.height())
+                },
+                DockSide::Right => {
+                    let t = t.min(free.width());
+                    free = Rect::new(free.x(), free.y(), free.width() - t, free.height());
+                    Rect::new(free.right(), free.y(), t, free.height())
+                },
+                DockSide::Top => {
+                    let t = t.min(free.height());
+                    free = Rect::new(free.x(), free.y() + t as i32, free.width(), free.height() - t);
+                    Rect::new(free.x(), free.y() - t as i32, free.width(), t)
+                },
+                DockSide::Bottom => {
+                    let t = t.min(free.height());
+                    free = Rect::new(free.x(), free.y(), free.width(), free.height() - t);
+                    Rect::new(free.x(), free.bottom(), free.width(), t)
+                },
+                DockSide::Floating => continue,
+            };
+            panel.area = whole;
+            panel.set_rect(rect);
+            panel.layout(rect);
+        }
+        self.children[0].set_rect(free);
+        self.children[0].layout(free);
+        for panel in self.panels_mut() {
+            panel.bounds = free;
+        }
+        for panel in self.panels_mut().filter(|p| p.side == DockSide::Floating) {
+            panel.area = whole;
+            let rect = panel.float_in(free);
+            panel.set_rect(rect);
+            panel.layout(rect);
+        }
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        for child in self.children.iter_mut() {
+            child.apply_theme(theme);
+        }
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] { &self.children }
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] { &mut self.children }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
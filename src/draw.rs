@@ -0,0 +1,369 @@
+use log::warn;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{Canvas, Texture};
+use sdl2::surface::Surface;
+use sdl2::sys::SDL_RendererFlags;
+use sdl2::video::Window;
+
+use crate::{TextRenderer, ToolkitError};
+
+// Everything a Drawable gets to touch while the frame is being painted
+pub struct DrawCtx<'a> {
+    canvas: &'a mut Canvas<Window>,
+    text: &'a TextRenderer,
+    // clip rects to go back to, innermost last
+    clips: Vec<Option<Rect>>,
+}
+
+// `t` of the way from `a` to `b`, alpha left out
+pub(crate) fn mix(a: Color, b: Color, t: f32) -> Color {
+    let m = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t).round() as u8;
+    Color::RGB(m(a.r, b.r), m(a.g, b.g), m(a.b, b.b))
+}
+
+// Subsamples per pixel side when working out how much of a corner pixel
+// the curve covers
+const CORNER_SAMPLES: u32 = 4;
+
+// Top left quarter of a rounded rect as white with the coverage in the
+// alpha channel, either solid or just the one pixel wide outline. The other
+// corners are the same texture flipped.
+pub(crate) fn corner_surface(radius: i32, filled: bool) -> Result<Surface<'static>, ToolkitError> {
+    let size = radius.max(1) as u32;
+    let mut surface = Surface::new(size, size, PixelFormatEnum::RGBA32)?;
+    let pitch = surface.pitch() as usize;
+    let r = radius as f32;
+    let n = CORNER_SAMPLES;
+    surface.with_lock_mut(|pixels| {
+        for py in 0..size {
+            for px in 0..size {
+                let mut hits = 0;
+                for sy in 0..n {
+                    for sx in 0..n {
+                        let x = px as f32 + (sx as f32 + 0.5) / n as f32 - r;
+                        let y = py as f32 + (sy as f32 + 0.5) / n as f32 - r;
+                        let d = (x * x + y * y).sqrt();
+                        if d <= r && (filled || d >= r - 1.0) {
+                            hits += 1;
+                        }
+                    }
+                }
+                let i = py as usize * pitch + px as usize * 4;
+                let alpha = (hits * 255 / (n * n)) as u8;
+                pixels[i..i + 4].copy_from_slice(&[255, 255, 255, alpha]);
+            }
+        }
+    });
+    Ok(surface)
+}
+
+fn clamp_radius(rect: Rect, radius: i32) -> i32 {
+    radius.min(rect.width() as i32 / 2).min(rect.height() as i32 / 2).max(0)
+}
+
+impl<'a> DrawCtx<'a> {
+    pub(crate) fn new(canvas: &'a mut Canvas<Window>, text: &'a TextRenderer) -> DrawCtx<'a> {
+        // whatever a frame that failed halfway left behind
+        canvas.set_clip_rect(None);
+        DrawCtx {
+            canvas,
+            text,
+            clips: Vec::new(),
+        }
+    }
+
+    // Narrows drawing down to the part of `rect` inside the current clip,
+    // until the matching pop_clip. False if none of `rect` is left.
+    pub fn push_clip(&mut self, rect: Rect) -> bool {
+        let old = self.canvas.clip_rect();
+        self.clips.push(old);
+        let clip = match old {
+            Some(old) => old.intersection(rect),
+            None => Some(rect),
+        };
+        // SDL takes an empty clip rect as no clipping at all, so nothing
+        // visible is a pixel off the top left instead
+        self.canvas.set_clip_rect(clip.unwrap_or(Rect::new(-1, -1, 1, 1)));
+        clip.is_some()
+    }
+
+    pub fn pop_clip(&mut self) {
+        if let Some(old) = self.clips.pop() {
+            self.canvas.set_clip_rect(old);
+        }
+    }
+
+    pub fn clip_rect(&self) -> Option<Rect> {
+        self.canvas.clip_rect()
+    }
+
+    // Runs `f` with drawing clipped to `rect`, skipping it when none of
+    // `rect` can be seen
+    pub fn clipped<F: FnOnce(&mut DrawCtx<'a>) -> Result<(), ToolkitError>>(&mut self, rect: Rect, f: F) -> Result<(), ToolkitError> {
+        let result = if self.push_clip(rect) { f(self) } else { Ok(()) };
+        self.pop_clip();
+        result
+    }
+
+    pub fn canvas(&mut self) -> &mut Canvas<Window> {
+        self.canvas
+    }
+
+    pub fn text(&self) -> &'a TextRenderer {
+        self.text
+    }
+
+    // Whether the renderer can draw into textures
+    pub(crate) fn has_targets(&self) -> bool {
+        self.canvas.info().flags & SDL_RendererFlags::SDL_RENDERER_TARGETTEXTURE as u32 != 0
+    }
+
+    // Whatever `f` draws comes out with `alpha` applied as a whole, rather
+    // than each overlapping part on its own. Drawn straight when already
+    // inside another faded part.
+    pub fn draw_faded<F: FnOnce(&mut DrawCtx) -> Result<(), ToolkitError>>(&mut self, alpha: u8, f: F) -> Result<(), ToolkitError> {
+        self.draw_layer(alpha, (0, 0), f)
+    }
+
+    // Same as draw_faded, with everything moved by `offset` as well. On a
+    // renderer without render targets `f` draws straight to the window.
+    pub fn draw_layer<F: FnOnce(&mut DrawCtx) -> Result<(), ToolkitError>>(&mut self, alpha: u8, offset: (i32, i32), f: F) -> Result<(), ToolkitError> {
+        // not every renderer can draw offscreen, those go without the fade
+        // and move things with the viewport instead
+        if !self.has_targets() {
+            if offset == (0, 0) {
+                return f(self);
+            }
+            let (w, h) = self.canvas.output_size()?;
+            let scale = self.canvas.scale();
+            let view = Rect::new(offset.0, offset.1, (w as f32 / scale.0) as u32, (h as f32 / scale.1) as u32);
+            self.canvas.set_viewport(view);
+            let result = f(self);
+            self.canvas.set_viewport(None);
+            return result;
+        }
+        let (w, h) = self.canvas.output_size()?;
+        let Some(mut layer) = self.text.layer(w, h)? else {
+            return f(self);
+        };
+        let scale = self.canvas.scale();
+        let text = self.text;
+        let mut result = Ok(());
+        self.canvas.with_texture_canvas(&mut layer, |canvas| {
+            // render targets start out unscaled
+            if let Err(e) = canvas.set_scale(scale.0, scale.1) {
+                warn!("could not scale the offscreen layer: {}", e);
+            }
+            canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+            canvas.clear();
+            result = f(&mut DrawCtx::new(canvas, text));
+        })?;
+        result?;
+        self.tint(&layer, Color::RGBA(255, 255, 255, alpha));
+        let (lw, lh) = ((w as f32 / scale.0) as u32, (h as f32 / scale.1) as u32);
+        self.canvas.copy(&layer, None, Rect::new(offset.0, offset.1, lw, lh))?;
+        Ok(())
+    }
+
+    pub fn draw_line<P: Into<Point>>(&mut self, from: P, to: P, color: Color) -> Result<(), ToolkitError> {
+        self.canvas.set_draw_color(color);
+        self.canvas.draw_line(from, to)?;
+        Ok(())
+    }
+
+    pub fn draw_rect(&mut self, rect: Rect, color: Color) -> Result<(), ToolkitError> {
+        self.canvas.set_draw_color(color);
+        self.canvas.draw_rect(rect)?;
+        Ok(())
+    }
+
+    pub fn fill_rect(&mut self, rect: Rect, color: Color) -> Result<(), ToolkitError> {
+        self.canvas.set_draw_color(color);
+        self.canvas.fill_rect(rect)?;
+        Ok(())
+    }
+
+    // Outline by the midpoint algorithm, eight points per step
+    pub fn draw_circle<P: Into<Point>>(&mut self, center: P, radius: i32, color: Color) -> Result<(), ToolkitError> {
+        let c = center.into();
+        let mut points = Vec::new();
+        let (mut x, mut y, mut err) = (radius.max(0), 0, 1 - radius.max(0));
+        while x >= y {
+            for (dx, dy) in [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)] {
+                points.push(Point::new(c.x() + dx, c.y() + dy));
+            }
+            y += 1;
+            if err < 0 {
+                err += 2 * y + 1;
+            } else {
+                x -= 1;
+                err += 2 * (y - x) + 1;
+            }
+        }
+        self.canvas.set_draw_color(color);
+        self.canvas.draw_points(points.as_slice())?;
+        Ok(())
+    }
+
+    // Filled a row at a time
+    pub fn fill_circle<P: Into<Point>>(&mut self, center: P, radius: i32, color: Color) -> Result<(), ToolkitError> {
+        let c = center.into();
+        let r = radius.max(0);
+        self.canvas.set_draw_color(color);
+        for dy in -r..=r {
+            let dx = ((r * r - dy * dy) as f32).sqrt().round() as i32;
+            self.canvas.draw_line((c.x() - dx, c.y() + dy), (c.x() + dx, c.y() + dy))?;
+        }
+        Ok(())
+    }
+
+    // Closed outline through all the points
+    pub fn draw_polygon(&mut self, points: &[Point], color: Color) -> Result<(), ToolkitError> {
+        let Some(first) = points.first() else {
+            return Ok(());
+        };
+        let mut closed = points.to_vec();
+        closed.push(*first);
+        self.canvas.set_draw_color(color);
+        self.canvas.draw_lines(closed.as_slice())?;
+        Ok(())
+    }
+
+    // Scanline fill with the even-odd rule, so it copes with concave and
+    // self-intersecting shapes too
+    pub fn fill_polygon(&mut self, points: &[Point], color: Color) -> Result<(), ToolkitError> {
+        if points.len() < 3 {
+            return self.draw_polygon(points, color);
+        }
+        let top = points.iter().map(|p| p.y()).min().unwrap_or(0);
+        let bottom = points.iter().map(|p| p.y()).max().unwrap_or(0);
+        let edges: Vec<(Point, Point)> = points.iter().copied()
+            .zip(points.iter().copied().cycle().skip(1))
+            .collect();
+
+        self.canvas.set_draw_color(color);
+        let mut xs = Vec::new();
+        for y in top..=bottom {
+            // sample through the middle of the row
+            let sy = y as f32 + 0.5;
+            xs.clear();
+            for (a, b) in edges.iter() {
+                let (ay, by) = (a.y() as f32, b.y() as f32);
+                if (ay <= sy) != (by <= sy) {
+                    let t = (sy - ay) / (by - ay);
+                    xs.push(a.x() as f32 + t * (b.x() - a.x()) as f32);
+                }
+            }
+            xs.sort_by(|a, b| a.total_cmp(b));
+            for pair in xs.chunks_exact(2) {
+                let (x1, x2) = (pair[0].round() as i32, pair[1].round() as i32 - 1);
+                if x2 >= x1 {
+                    self.canvas.draw_line((x1, y), (x2, y))?;
+                }
+            }
+        }
+        self.draw_polygon(points, color)
+    }
+
+    // Text with its top left corner at (x, y)
+    pub fn draw_text_at(&mut self, x: i32, y: i32, text: &str, color: Color) -> Result<(), ToolkitError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        let texture = self.text.render(text)?;
+        let attr = texture.query();
+        self.tint(&texture, color);
+        self.canvas.copy(&texture, None, Rect::new(x, y, attr.width, attr.height))?;
+        Ok(())
+    }
+
+    // Straight parts are plain rects, the corners come from cached
+    // anti-aliased textures
+    pub fn fill_rounded_rect(&mut self, rect: Rect, radius: i32, color: Color) -> Result<(), ToolkitError> {
+        let r = clamp_radius(rect, radius);
+        if r < 2 {
+            return self.fill_rect(rect, color);
+        }
+        let (w, h) = (rect.width() as i32, rect.height() as i32);
+        self.canvas.set_draw_color(color);
+        self.canvas.fill_rect(Rect::new(rect.x() + r, rect.y(), (w - 2 * r) as u32, h as u32))?;
+        if h > 2 * r {
+            self.canvas.fill_rect(Rect::new(rect.x(), rect.y() + r, r as u32, (h - 2 * r) as u32))?;
+            self.canvas.fill_rect(Rect::new(rect.right() - r, rect.y() + r, r as u32, (h - 2 * r) as u32))?;
+        }
+        self.draw_corners(rect, r, color, true)
+    }
+
+    pub fn draw_rounded_rect(&mut self, rect: Rect, radius: i32, color: Color) -> Result<(), ToolkitError> {
+        self.draw_rounded_outline(rect, radius, color, None)
+    }
+
+    // Outline that leaves the stretch from gap.0 to gap.1 of the top edge
+    // open, for a title to sit in
+    pub(crate) fn draw_rounded_outline(&mut self, rect: Rect, radius: i32, color: Color, gap: Option<(i32, i32)>) -> Result<(), ToolkitError> {
+        let r = clamp_radius(rect, radius);
+        let (left, top) = (rect.x(), rect.y());
+        let (right, bottom) = (rect.right() - 1, rect.bottom() - 1);
+        // corners of 0 or 1 pixel are just the end of the edges
+        let c = if r < 2 { 0 } else { r };
+
+        self.canvas.set_draw_color(color);
+        let (from, to) = (left + c, right - c);
+        match gap {
+            Some((a, b)) => {
+                if a > from {
+                    self.canvas.draw_line((from, top), (a.min(to), top))?;
+                }
+                if b < to {
+                    self.canvas.draw_line((b.max(from), top), (to, top))?;
+                }
+            },
+            None => self.canvas.draw_line((from, top), (to, top))?,
+        }
+        self.canvas.draw_line((from, bottom), (to, bottom))?;
+        self.canvas.draw_line((left, top + c), (left, bottom - c))?;
+        self.canvas.draw_line((right, top + c), (right, bottom - c))?;
+        if c > 0 {
+            self.draw_corners(rect, c, color, false)?;
+        }
+        Ok(())
+    }
+
+    fn draw_corners(&mut self, rect: Rect, r: i32, color: Color, filled: bool) -> Result<(), ToolkitError> {
+        let texture = self.text.corner(r, filled)?;
+        self.tint(&texture, color);
+        let size = r as u32;
+        let (x2, y2) = (rect.right() - r, rect.bottom() - r);
+        for (x, y, flip_h, flip_v) in [
+            (rect.x(), rect.y(), false, false),
+            (x2, rect.y(), true, false),
+            (rect.x(), y2, false, true),
+            (x2, y2, true, true),
+        ] {
+            self.canvas.copy_ex(&texture, None, Rect::new(x, y, size, size), 0.0, None, flip_h, flip_v)?;
+        }
+        Ok(())
+    }
+
+    // Recolors white text (or any texture) at copy time. Color mod is just
+    // render state, so this doesn't need the texture mutably.
+    pub fn tint(&self, texture: &Texture, color: Color) {
+        unsafe {
+            sdl2::sys::SDL_SetTextureColorMod(texture.raw(), color.r, color.g, color.b);
+            sdl2::sys::SDL_SetTextureAlphaMod(texture.raw(), color.a);
+        }
+    }
+
+    // Copies a texture centered inside `rect`, cropping whatever doesn't fit
+    pub fn copy_centered(&mut self, texture: &Texture, rect: Rect) -> Result<(), ToolkitError> {
+        let attr = texture.query();
+        let w = attr.width.min(rect.width());
+        let h = attr.height.min(rect.height());
+        let src = Rect::new(((attr.width - w) / 2) as i32, ((attr.height - h) / 2) as i32, w, h);
+        let dst = Rect::from_center(rect.center(), w, h);
+
+        self.canvas.copy(texture, src, dst)?;
+        Ok(())
+    }
+}
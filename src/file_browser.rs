@@ -0,0 +1,364 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::Debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::rect::Rect;
+
+use crate::{DrawCtx, Drawable, FileFilter, FontId, Role, Table, TableModel, TextRenderer, Theme, ToolkitError, Widget, WidgetState};
+
+// Widths of the size and modified columns, the name gets the rest
+const SIZE_COLUMN: i32 = 80;
+const MODIFIED_COLUMN: i32 = 140;
+
+#[derive(Debug, Clone)]
+struct Entry {
+    name: String,
+    path: PathBuf,
+    dir: bool,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+// The entries of the directory being shown, shared with the browser so it
+// can swap them without the font the Table wants for a new model
+struct DirModel(Rc<RefCell<Vec<Entry>>>);
+
+impl TableModel for DirModel {
+    fn columns(&self) -> usize { 3 }
+    fn rows(&self) -> usize { self.0.borrow().len() }
+
+    fn header(&self, col: usize) -> String {
+        ["Name", "Size", "Modified"].get(col).map(|s| s.to_string()).unwrap_or_default()
+    }
+
+    fn cell(&self, row: usize, col: usize) -> String {
+        let entries = self.0.borrow();
+        let Some(entry) = entries.get(row) else {
+            return String::new();
+        };
+        match col {
+            0 if entry.dir => format!("{}/", entry.name),
+            0 => entry.name.clone(),
+            1 if entry.dir => String::new(),
+            1 => format_size(entry.size),
+            2 => entry.modified.map(format_time).unwrap_or_default(),
+            _ => String::new(),
+        }
+    }
+
+    // Directories before files, then by the column's actual value
+    fn compare(&self, a: usize, b: usize, col: usize) -> Ordering {
+        let entries = self.0.borrow();
+        let (a, b) = (&entries[a], &entries[b]);
+        b.dir.cmp(&a.dir).then_with(|| match col {
+            1 => a.size.cmp(&b.size),
+            2 => a.modified.cmp(&b.modified),
+            _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        })
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+// As "YYYY-MM-DD HH:MM" in UTC
+fn format_time(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()) as i64;
+    let (days, rest) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+    // days to a civil date, after Howard Hinnant's algorithm
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, rest / 3600, rest % 3600 / 60)
+}
+
+// Directory listing for picking a file without a native dialog: a line
+// with the current directory above a table of its entries with their size
+// and modification time. Double click or Enter opens a directory or
+// chooses a file, Backspace goes up a level.
+pub struct FileBrowser {
+    name: String,
+    rect: Rect,
+    pref: (u32, u32),
+    dir: PathBuf,
+    entries: Rc<RefCell<Vec<Entry>>>,
+    table: Table,
+    filter: Option<FileFilter>,
+    show_hidden: bool,
+    // why the directory couldn't be read, shown instead of its path
+    error: Option<String>,
+    chosen: Option<PathBuf>,
+    changed: bool,
+    font: FontId,
+    text_height: i32,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl FileBrowser {
+    pub fn new(text: &TextRenderer, name: &str, x: i32, y: i32, w: u32, h: u32, dir: &Path) -> FileBrowser {
+        let entries = Rc::new(RefCell::new(Vec::new()));
+        let mut table = Table::new(text, name, x, y, w as i32, h as i32, DirModel(entries.clone()));
+        table.sort_by(Some((0, true)));
+        let mut browser = FileBrowser {
+            name: name.to_string(),
+            rect: Rect::new(x, y, w, h),
+            pref: (w, h),
+            dir: dir.to_path_buf(),
+            entries,
+            table,
+            filter: None,
+            show_hidden: false,
+            error: None,
+            chosen: None,
+            changed: false,
+            font: FontId::DEFAULT,
+            text_height: text.height(),
+            theme: Theme::default(),
+            own_theme: false,
+        };
+        browser.set_rect(browser.rect);
+        browser.refresh();
+        browser
+    }
+
+    pub fn filter(mut self, filter: FileFilter) -> FileBrowser {
+        self.set_filter(Some(filter));
+        self
+    }
+
+    pub fn show_hidden(mut self, show: bool) -> FileBrowser {
+        self.set_show_hidden(show);
+        self
+    }
+
+    pub fn dir(&self) -> &Path { &self.dir }
+    pub fn error(&self) -> Option<&str> { self.error.as_deref() }
+
+    // The entry selected in the table, file or directory
+    pub fn selected_path(&self) -> Option<PathBuf> {
+        let row = self.table.selected()?;
+        self.entries.borrow().get(row).map(|e| e.path.clone())
+    }
+
+    // The file last opened with a double click or Enter
+    pub fn chosen(&self) -> Option<&Path> { self.chosen.as_deref() }
+
+    // Only files matching it are listed, directories always are
+    pub fn set_filter(&mut self, filter: Option<FileFilter>) {
+        self.filter = filter;
+        self.refresh();
+    }
+
+    pub fn set_show_hidden(&mut self, show: bool) {
+        self.show_hidden = show;
+        self.refresh();
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+        self.table.set_theme(theme);
+    }
+
+    // Shows another directory. On failure the old one stays and the error
+    // is shown in the path line.
+    pub fn set_dir(&mut self, dir: &Path) {
+        match self.read(dir) {
+            Ok(entries) => {
+                self.dir = dir.to_path_buf();
+                self.error = None;
+                *self.entries.borrow_mut() = entries;
+                self.table.update_model(|_| { });
+                self.table.set_selected(None);
+                self.table.scroll_pixels(0, i32::MAX);
+            },
+            Err(e) => self.error = Some(format!("{}: {}", dir.display(), e)),
+        }
+    }
+
+    // Reads the current directory again
+    pub fn refresh(&mut self) {
+        let dir = self.dir.clone();
+        self.set_dir(&dir);
+    }
+
+    pub fn go_up(&mut self) -> bool {
+        let Some(parent) = self.dir.parent().map(Path::to_path_buf) else {
+            return false;
+        };
+        self.set_dir(&parent);
+        self.changed = true;
+        true
+    }
+
+    fn read(&self, dir: &Path) -> std::io::Result<Vec<Entry>> {
+        let mut entries = Vec::new();
+        for item in fs::read_dir(dir)? {
+            let Ok(item) = item else {
+                continue;
+            };
+            let name = item.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') && !self.show_hidden {
+                continue;
+            }
+            let path = item.path();
+            // follows links, so a link to a directory can be opened
+            let meta = fs::metadata(&path).or_else(|_| item.metadata());
+            let dir = meta.as_ref().is_ok_and(|m| m.is_dir());
+            if !dir && self.filter.as_ref().is_some_and(|f| !f.matches(&path)) {
+                continue;
+            }
+            entries.push(Entry {
+                name,
+                path,
+                dir,
+                size: meta.as_ref().map_or(0, |m| m.len()),
+                modified: meta.ok().and_then(|m| m.modified().ok()),
+            });
+        }
+        Ok(entries)
+    }
+
+    // Goes into the selected directory or chooses the selected file
+    fn open_selected(&mut self) -> bool {
+        let Some(row) = self.table.selected() else {
+            return false;
+        };
+        let Some(entry) = self.entries.borrow().get(row).cloned() else {
+            return false;
+        };
+        if entry.dir {
+            self.set_dir(&entry.path);
+        } else {
+            self.chosen = Some(entry.path);
+        }
+        self.changed = true;
+        true
+    }
+
+    fn path_bar(&self) -> Rect {
+        Rect::new(self.rect.x(), self.rect.y(), self.rect.width(), (self.text_height + self.theme.padding) as u32)
+    }
+}
+
+impl Debug for FileBrowser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileBrowser")
+            .field("name", &self.name)
+            .field("rect", &self.rect)
+            .field("dir", &self.dir)
+            .field("entries", &self.entries.borrow().len())
+            .field("filter", &self.filter)
+            .field("chosen", &self.chosen)
+            .finish()
+    }
+}
+
+impl Drawable for FileBrowser {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let bar = self.path_bar();
+        let theme = &self.theme;
+        let (line, color) = match &self.error {
+            Some(e) => (e.clone(), theme.error),
+            None => (self.dir.display().to_string(), if self.table.is_enabled() { theme.text } else { theme.disabled }),
+        };
+        ctx.push_clip(bar);
+        if !line.is_empty() {
+            let texture = ctx.text().render_font(self.font, &line)?;
+            let attr = texture.query();
+            ctx.tint(&texture, color);
+            let y = bar.y() + (bar.height() as i32 - attr.height as i32) / 2;
+            ctx.canvas().copy(&texture, None, Rect::new(bar.x() + theme.padding / 2, y, attr.width, attr.height))?;
+        }
+        ctx.pop_clip();
+        self.table.draw(ctx)
+    }
+}
+
+impl Widget for FileBrowser {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { self.rect }
+    fn role(&self) -> Role { Role::Table }
+    fn access_value(&self) -> Option<String> { Some(self.dir.display().to_string()) }
+    fn focusable(&self) -> bool { true }
+    fn widget_state(&self) -> Option<&WidgetState> { self.table.widget_state() }
+    fn widget_state_mut(&mut self) -> Option<&mut WidgetState> { self.table.widget_state_mut() }
+    fn size_hint(&self) -> (u32, u32) { self.pref }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        let bar = self.path_bar().height();
+        self.table.set_rect(Rect::new(rect.x(), rect.y() + bar as i32, rect.width(), rect.height().saturating_sub(bar)));
+        self.table.set_column_width(0, rect.width() as i32 - SIZE_COLUMN - MODIFIED_COLUMN);
+        self.table.set_column_width(1, SIZE_COLUMN);
+        self.table.set_column_width(2, MODIFIED_COLUMN);
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+            self.table.apply_theme(theme);
+            self.set_rect(self.rect);
+        }
+    }
+
+    fn set_font(&mut self, text: &TextRenderer, font: FontId) -> Result<(), ToolkitError> {
+        self.font = font;
+        self.text_height = text.font_height(font);
+        self.table.set_font(text, font)?;
+        self.set_rect(self.rect);
+        Ok(())
+    }
+
+    fn scroll_pixels(&mut self, dx: i32, dy: i32) -> bool {
+        self.table.scroll_pixels(dx, dy)
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        let consumed = match event {
+            Event::KeyDown {keycode: Some(Keycode::Return | Keycode::KpEnter), ..} => self.open_selected(),
+            Event::KeyDown {keycode: Some(Keycode::Backspace), ..} => self.go_up(),
+            Event::MouseButtonDown {mouse_btn: MouseButton::Left, clicks: 2, y, ..} if *y >= self.table.body_rect().y() => {
+                self.table.handle_event(event);
+                self.open_selected()
+            },
+            _ => self.table.handle_event(event),
+        };
+        self.changed |= self.table.take_changed();
+        consumed
+    }
+
+    fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
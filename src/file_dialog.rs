@@ -0,0 +1,87 @@
+use std::path::{Path, PathBuf};
+
+use crate::{Toolkit, ToolkitError};
+#[cfg(not(feature = "file-dialog"))]
+use crate::DialogResult;
+
+// One entry of a dialog's list of file types,
+// e.g. FileFilter::new("Images", &["png", "jpg"])
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileFilter {
+    pub name: String,
+    // without the dot
+    pub extensions: Vec<String>,
+}
+
+impl FileFilter {
+    pub fn new(name: &str, extensions: &[&str]) -> FileFilter {
+        FileFilter {
+            name: name.to_string(),
+            extensions: extensions.iter().map(|e| e.trim_start_matches('.').to_string()).collect(),
+        }
+    }
+
+    // Whether `path` ends in one of the extensions, in any case
+    pub fn matches(&self, path: &Path) -> bool {
+        let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
+            return false;
+        };
+        self.extensions.iter().any(|e| e == "*" || e.eq_ignore_ascii_case(ext))
+    }
+}
+
+pub type FileCallback = Box<dyn FnOnce(&mut Toolkit, Option<PathBuf>)>;
+
+impl Toolkit {
+    // Asks for a file to open and hands it to `f`, None if the user
+    // cancelled. With the file-dialog feature this is the platform's own
+    // dialog, which blocks until it is closed; without it, a prompt for the
+    // path drawn like any other dialog.
+    pub fn file_open_dialog<F: FnOnce(&mut Toolkit, Option<PathBuf>) + 'static>(&mut self, filters: &[FileFilter], f: F) -> Result<(), ToolkitError> {
+        self.file_dialog(false, filters, Box::new(f))
+    }
+
+    // The same for a file to save to, which may not exist yet
+    pub fn file_save_dialog<F: FnOnce(&mut Toolkit, Option<PathBuf>) + 'static>(&mut self, filters: &[FileFilter], f: F) -> Result<(), ToolkitError> {
+        self.file_dialog(true, filters, Box::new(f))
+    }
+
+    #[cfg(feature = "file-dialog")]
+    fn file_dialog(&mut self, save: bool, filters: &[FileFilter], f: FileCallback) -> Result<(), ToolkitError> {
+        let mut dialog = rfd::FileDialog::new();
+        if let Ok(dir) = std::env::current_dir() {
+            dialog = dialog.set_directory(dir);
+        }
+        for filter in filters {
+            dialog = dialog.add_filter(&filter.name, &filter.extensions);
+        }
+        let path = if save { dialog.save_file() } else { dialog.pick_file() };
+        // nothing got drawn while it was up
+        self.dirty = true;
+        f(self, path);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "file-dialog"))]
+    fn file_dialog(&mut self, save: bool, filters: &[FileFilter], f: FileCallback) -> Result<(), ToolkitError> {
+        let (title, verb) = if save { ("Save file", "save to") } else { ("Open file", "open") };
+        let mut text = format!("Path of the file to {}", verb);
+        if !filters.is_empty() {
+            let exts: Vec<String> = filters.iter().flat_map(|f| f.extensions.iter().map(|e| format!("*.{}", e))).collect();
+            text = format!("{} ({})", text, exts.join(", "));
+        }
+        let dir = std::env::current_dir().map(|d| format!("{}{}", d.display(), std::path::MAIN_SEPARATOR)).unwrap_or_default();
+        let handle = self.prompt(title, &text, &dir)?;
+        let mut f = Some(f);
+        self.on_dialog_close(&handle, move |tk, result| {
+            let path = match result {
+                DialogResult::Text(path) if !path.trim().is_empty() => Some(PathBuf::from(path.trim())),
+                _ => None,
+            };
+            if let Some(f) = f.take() {
+                f(tk, path);
+            }
+        });
+        Ok(())
+    }
+}
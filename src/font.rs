@@ -0,0 +1,418 @@
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use log::warn;
+use sdl2::rwops::RWops;
+use sdl2::ttf::{Font, FontStyle, Sdl2TtfContext};
+
+use crate::{ErrorContext, ToolkitError};
+
+// DejaVu Sans, see assets/DejaVuSans-LICENSE.txt
+static EMBEDDED_FONT: &[u8] = include_bytes!("../assets/DejaVuSans.ttf");
+
+// Where the toolkit gets its font from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontSource {
+    // the copy compiled into the crate
+    Embedded,
+    File(PathBuf),
+    // whatever find_system_font() turns up
+    System,
+    // a fixed width font from the system, for code and tables of numbers
+    Monospace,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextStyle {
+    Regular,
+    Bold,
+    Italic,
+    BoldItalic,
+}
+
+impl TextStyle {
+    pub(crate) fn sdl(self) -> FontStyle {
+        match self {
+            TextStyle::Regular => FontStyle::NORMAL,
+            TextStyle::Bold => FontStyle::BOLD,
+            TextStyle::Italic => FontStyle::ITALIC,
+            TextStyle::BoldItalic => FontStyle::BOLD | FontStyle::ITALIC,
+        }
+    }
+}
+
+// A font loaded into a FontManager. The one the Toolkit was built with is
+// FontId::DEFAULT, and it's what everything uses unless told otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FontId(u32);
+
+impl FontId {
+    pub const DEFAULT: FontId = FontId(0);
+}
+
+impl Default for FontId {
+    fn default() -> FontId {
+        FontId::DEFAULT
+    }
+}
+
+// Fonts that are likely to be around on a stock install
+const KNOWN_FONTS: &[&str] = &[
+    "/usr/share/fonts/liberation/LiberationSans.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationSans-Regular.ttf",
+    "/usr/share/fonts/liberation-sans/LiberationSans-Regular.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/truetype/DejaVuSans.ttf",
+    "/usr/share/fonts/noto/NotoSans-Regular.ttf",
+    "/usr/share/fonts/truetype/noto/NotoSans-Regular.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/Library/Fonts/Arial.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+];
+
+const KNOWN_MONO_FONTS: &[&str] = &[
+    "/usr/share/fonts/TTF/DejaVuSansMono.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSansMono.ttf",
+    "/usr/share/fonts/liberation/LiberationMono-Regular.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationMono-Regular.ttf",
+    "/usr/share/fonts/liberation-mono/LiberationMono-Regular.ttf",
+    "/usr/share/fonts/truetype/noto/NotoSansMono-Regular.ttf",
+    "/System/Library/Fonts/Menlo.ttc",
+    "/System/Library/Fonts/Monaco.ttf",
+    "C:\\Windows\\Fonts\\consola.ttf",
+    "C:\\Windows\\Fonts\\cour.ttf",
+];
+
+// Fonts tried in turn for characters the main font lacks: CJK first, then
+// symbols and emoji
+const KNOWN_FALLBACKS: &[&str] = &[
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+    "/usr/share/fonts/google-droid/DroidSansFallbackFull.ttf",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/wenquanyi/wqy-microhei/wqy-microhei.ttc",
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\msgothic.ttc",
+    "/usr/share/fonts/truetype/ancient-scripts/Symbola_hint.ttf",
+    "/usr/share/fonts/gdouros-symbola/Symbola.ttf",
+    "/usr/share/fonts/TTF/Symbola.ttf",
+    "/usr/share/fonts/truetype/noto/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/noto/NotoEmoji-Regular.ttf",
+    "C:\\Windows\\Fonts\\seguiemj.ttf",
+    "C:\\Windows\\Fonts\\seguisym.ttf",
+];
+
+// Directories scanned as a last resort, any TrueType font will do
+const FONT_DIRS: &[&str] = &[
+    "/usr/share/fonts",
+    "/usr/local/share/fonts",
+    "/System/Library/Fonts",
+    "/Library/Fonts",
+    "C:\\Windows\\Fonts",
+];
+
+// Asks fontconfig for its idea of a font of the given family
+fn fontconfig(family: &str) -> Option<PathBuf> {
+    let out = Command::new("fc-match")
+        .args(["--format=%{file}", &format!("{}:fontformat=TrueType", family)])
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    let path = PathBuf::from(String::from_utf8(out.stdout).ok()?.trim());
+    path.is_file().then_some(path)
+}
+
+fn is_ttf(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ttf"))
+}
+
+fn scan_dir(dir: &Path, depth: u32) -> Option<PathBuf> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir).ok()?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .collect();
+    // keep the pick stable between runs
+    entries.sort();
+
+    if let Some(font) = entries.iter().find(|p| p.is_file() && is_ttf(p)) {
+        return Some(font.clone());
+    }
+    if depth == 0 {
+        return None;
+    }
+    entries.iter()
+        .filter(|p| p.is_dir())
+        .find_map(|p| scan_dir(p, depth - 1))
+}
+
+// Best guess at a usable font on this machine
+pub(crate) fn find_system_font() -> Option<PathBuf> {
+    KNOWN_FONTS.iter()
+        .map(PathBuf::from)
+        .find(|p| p.is_file())
+        .or_else(|| fontconfig("sans-serif"))
+        .or_else(|| FONT_DIRS.iter().find_map(|dir| scan_dir(Path::new(dir), 3)))
+}
+
+pub(crate) fn find_mono_font() -> Option<PathBuf> {
+    KNOWN_MONO_FONTS.iter()
+        .map(PathBuf::from)
+        .find(|p| p.is_file())
+        .or_else(|| fontconfig("monospace"))
+}
+
+// The fallback fonts that exist here, after the ones asked for by name
+pub(crate) fn fallback_fonts(extra: &[PathBuf], system: bool) -> Vec<PathBuf> {
+    let mut fonts: Vec<PathBuf> = extra.to_vec();
+    if system {
+        fonts.extend(KNOWN_FALLBACKS.iter().map(PathBuf::from).filter(|p| p.is_file()));
+    }
+    fonts
+}
+
+// Whether the font has a glyph for `c`. SDL_ttf can only be asked about
+// the basic multilingual plane, beyond that (mostly emoji) only fonts that
+// call themselves emoji or symbol fonts are trusted with it.
+pub(crate) fn covers(font: &Font, c: char) -> bool {
+    match u16::try_from(c as u32) {
+        Ok(_) => font.find_glyph(c).is_some(),
+        Err(_) => font.face_family_name().is_some_and(|name| {
+            let name = name.to_lowercase();
+            name.contains("emoji") || name.contains("symbola")
+        }),
+    }
+}
+
+// Splits `text` into runs of the font `font_for` picks for each character,
+// the first one for those no font has
+fn split_runs<F: Fn(char) -> Option<usize>>(text: &str, font_for: F) -> Vec<(usize, String)> {
+    let mut runs: Vec<(usize, String)> = Vec::new();
+    for c in text.chars() {
+        let current = runs.last().map_or(0, |(i, _)| *i);
+        // spaces and the like go with whatever is around them
+        let i = if c.is_whitespace() || c.is_control() {
+            current
+        } else {
+            font_for(c).unwrap_or(0)
+        };
+        match runs.last_mut() {
+            Some((last, run)) if *last == i => run.push(c),
+            _ => runs.push((i, c.to_string())),
+        }
+    }
+    runs
+}
+
+pub(crate) fn load_font(ttf: &'static Sdl2TtfContext, source: &FontSource, size: u16) -> Result<Font<'static, 'static>, ToolkitError> {
+    let path = match source {
+        FontSource::Embedded => {
+            let rwops = RWops::from_bytes(EMBEDDED_FONT)?;
+            return ttf.load_font_from_rwops(rwops, size).context("loading the embedded font");
+        },
+        FontSource::File(path) => path.clone(),
+        FontSource::System => find_system_font()
+            .ok_or_else(|| ToolkitError::FontNotFound("no usable system font".to_string()))?,
+        FontSource::Monospace => find_mono_font()
+            .ok_or_else(|| ToolkitError::FontNotFound("no usable monospace font".to_string()))?,
+    };
+
+    if !path.is_file() {
+        return Err(ToolkitError::FontNotFound(path.display().to_string()));
+    }
+    ttf.load_font(&path, size).map_err(|e| ToolkitError::FontLoadError {path, source: Box::new(e.into())})
+}
+
+struct FontEntry {
+    source: FontSource,
+    size: u16,
+    style: TextStyle,
+    // the font itself first, then its fallbacks once something needed them
+    fonts: Vec<Font<'static, 'static>>,
+    fallbacks_loaded: bool,
+}
+
+// Every font the application uses, each in one size and style. Loading the
+// same font again hands back the id it already has. Shared by the windows,
+// since fonts (unlike textures) don't belong to a renderer.
+pub struct FontManager {
+    ttf: &'static Sdl2TtfContext,
+    fallbacks: Vec<PathBuf>,
+    // style is switched per span while rendering, which needs the fonts mutably
+    entries: RefCell<Vec<FontEntry>>,
+}
+
+impl FontManager {
+    pub(crate) fn new(ttf: &'static Sdl2TtfContext, source: &FontSource, size: u16, fallbacks: Vec<PathBuf>) -> Result<FontManager, ToolkitError> {
+        let fonts = FontManager {
+            ttf,
+            fallbacks,
+            entries: RefCell::new(Vec::new()),
+        };
+        fonts.load(source, size, TextStyle::Regular)?;
+        Ok(fonts)
+    }
+
+    pub fn load(&self, source: &FontSource, size: u16, style: TextStyle) -> Result<FontId, ToolkitError> {
+        if let Some(id) = self.find(source, size, style) {
+            return Ok(id);
+        }
+        let mut font = load_font(self.ttf, source, size)?;
+        font.set_style(style.sdl());
+        let mut entries = self.entries.borrow_mut();
+        entries.push(FontEntry {
+            source: source.clone(),
+            size,
+            style,
+            fonts: vec![font],
+            fallbacks_loaded: false,
+        });
+        Ok(FontId(entries.len() as u32 - 1))
+    }
+
+    pub fn find(&self, source: &FontSource, size: u16, style: TextStyle) -> Option<FontId> {
+        self.entries.borrow().iter()
+            .position(|e| e.source == *source && e.size == size && e.style == style)
+            .map(|idx| FontId(idx as u32))
+    }
+
+    pub fn len(&self) -> usize { self.entries.borrow().len() }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    pub fn size(&self, id: FontId) -> Option<u16> {
+        self.entries.borrow().get(id.0 as usize).map(|e| e.size)
+    }
+
+    pub fn source(&self, id: FontId) -> Option<FontSource> {
+        self.entries.borrow().get(id.0 as usize).map(|e| e.source.clone())
+    }
+
+    pub fn style(&self, id: FontId) -> Option<TextStyle> {
+        self.entries.borrow().get(id.0 as usize).map(|e| e.style)
+    }
+
+    // Unknown ids fall back to the default font rather than failing
+    fn index(&self, id: FontId) -> usize {
+        let idx = id.0 as usize;
+        if idx < self.entries.borrow().len() { idx } else { 0 }
+    }
+
+    pub(crate) fn height(&self, id: FontId) -> i32 {
+        let idx = self.index(id);
+        self.entries.borrow()[idx].fonts[0].height()
+    }
+
+    // Fallback fonts in the size and style of the entry, loaded the first
+    // time a string needs a glyph it lacks. Ones that fail to load are skipped.
+    fn load_fallbacks(&self, idx: usize) {
+        let mut entries = self.entries.borrow_mut();
+        let entry = &mut entries[idx];
+        if entry.fallbacks_loaded {
+            return;
+        }
+        entry.fallbacks_loaded = true;
+        for path in self.fallbacks.iter() {
+            match self.ttf.load_font(path, entry.size) {
+                Ok(mut font) => {
+                    font.set_style(entry.style.sdl());
+                    entry.fonts.push(font);
+                },
+                Err(e) => warn!("skipping fallback font {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    // Splits a string into runs that one font of the entry can draw each.
+    // Characters no font has stay with the main one and show up as its
+    // missing glyph box.
+    pub(crate) fn runs(&self, id: FontId, text: &str) -> Vec<(usize, String)> {
+        let idx = self.index(id);
+        // SDL_ttf takes C strings, a NUL would cut the text short
+        let text = text.replace('\0', "");
+        if !text.chars().all(|c| covers(&self.entries.borrow()[idx].fonts[0], c)) {
+            self.load_fallbacks(idx);
+        }
+
+        let entries = self.entries.borrow();
+        let fonts = &entries[idx].fonts;
+        split_runs(&text, |c| fonts.iter().position(|f| covers(f, c)))
+    }
+
+    // Runs `f` with one font of an entry, 0 being the entry's own and the
+    // rest its fallbacks. `extra` styles are added for the duration.
+    pub(crate) fn with_font<R, F: FnOnce(&Font) -> R>(&self, id: FontId, font: usize, extra: FontStyle, f: F) -> R {
+        let idx = self.index(id);
+        let mut entries = self.entries.borrow_mut();
+        let entry = &mut entries[idx];
+        let style = entry.style.sdl();
+        let font = &mut entry.fonts[font];
+        if !extra.is_empty() {
+            font.set_style(style | extra);
+        }
+        let result = f(font);
+        if !extra.is_empty() {
+            font.set_style(style);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A Latin font, a CJK fallback and an emoji fallback
+    fn font_for(c: char) -> Option<usize> {
+        match c as u32 {
+            0x0000..=0x024f => Some(0),
+            0x3040..=0x30ff | 0x4e00..=0x9fff => Some(1),
+            0x1f300..=0x1faff => Some(2),
+            _ => None,
+        }
+    }
+
+    fn runs(text: &str) -> Vec<(usize, String)> {
+        split_runs(text, font_for)
+    }
+
+    fn expect(runs: &[(usize, &str)]) -> Vec<(usize, String)> {
+        runs.iter().map(|&(i, run)| (i, run.to_string())).collect()
+    }
+
+    #[test]
+    fn one_script_is_one_run() {
+        assert_eq!(runs("Hello, world"), expect(&[(0, "Hello, world")]));
+        assert_eq!(runs("日本語"), expect(&[(1, "日本語")]));
+        assert!(runs("").is_empty());
+    }
+
+    #[test]
+    fn mixed_scripts_split_at_the_boundaries() {
+        assert_eq!(runs("abc漢字def"), expect(&[(0, "abc"), (1, "漢字"), (0, "def")]));
+        assert_eq!(runs("ok👍🎉!"), expect(&[(0, "ok"), (2, "👍🎉"), (0, "!")]));
+        assert_eq!(runs("東京🗼Tower"), expect(&[(1, "東京"), (2, "🗼"), (0, "Tower")]));
+    }
+
+    #[test]
+    fn spaces_stay_with_the_run_before() {
+        assert_eq!(runs("hi 你好 😀 yo"), expect(&[(0, "hi "), (1, "你好 "), (2, "😀 "), (0, "yo")]));
+        assert_eq!(runs(" \t漢"), expect(&[(0, " \t"), (1, "漢")]));
+    }
+
+    #[test]
+    fn uncovered_characters_go_to_the_main_font() {
+        assert_eq!(runs("a\u{10000}b"), expect(&[(0, "a\u{10000}b")]));
+        assert_eq!(runs("字\u{10000}"), expect(&[(1, "字"), (0, "\u{10000}")]));
+    }
+}
@@ -0,0 +1,172 @@
+use std::any::Any;
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::rect::Rect;
+
+use crate::{DrawCtx, Drawable, FontId, Role, TextRenderer, Theme, ToolkitError, Widget};
+
+// Room left between the border and the start of the title
+const TITLE_INDENT: i32 = 10;
+
+// Draws a border with an optional title around a single child, usually a
+// layout holding a group of related controls, and lays it out inside
+pub struct Frame {
+    name: String,
+    title: Option<String>,
+    rect: Rect,
+    fixed: bool,
+    font: FontId,
+    text_height: i32,
+    children: Vec<Box<dyn Widget>>,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl Frame {
+    pub fn new<W: Widget>(text: &TextRenderer, name: &str, content: W) -> Frame {
+        Frame {
+            name: name.to_string(),
+            title: None,
+            rect: Rect::new(0, 0, 0, 0),
+            fixed: false,
+            font: FontId::DEFAULT,
+            text_height: text.height(),
+            children: vec![Box::new(content)],
+            theme: Theme::default(),
+            own_theme: false,
+        }
+    }
+
+    pub fn title(mut self, title: &str) -> Frame {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn set_title(&mut self, title: Option<&str>) {
+        self.title = title.map(|t| t.to_string());
+    }
+
+    pub fn title_text(&self) -> Option<&str> { self.title.as_deref() }
+
+    pub fn content(&self) -> &dyn Widget { self.children[0].as_ref() }
+    pub fn content_mut(&mut self) -> &mut dyn Widget { self.children[0].as_mut() }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+    }
+
+    // The border runs through the middle of the title
+    fn top_inset(&self) -> i32 {
+        if self.title.is_some() { self.text_height } else { self.theme.padding }
+    }
+
+    fn border_top(&self) -> i32 {
+        if self.title.is_some() { self.rect.y() + self.text_height / 2 } else { self.rect.y() }
+    }
+
+    fn inner_rect(&self) -> Rect {
+        let pad = self.theme.padding;
+        let top = self.top_inset();
+        Rect::new(
+            self.rect.x() + pad,
+            self.rect.y() + top,
+            (self.rect.width() as i32 - 2 * pad).max(0) as u32,
+            (self.rect.height() as i32 - top - pad).max(0) as u32,
+        )
+    }
+}
+
+impl Debug for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Frame")
+            .field("name", &self.name)
+            .field("title", &self.title)
+            .field("rect", &self.rect)
+            .field("content", &self.children[0])
+            .finish()
+    }
+}
+
+impl Drawable for Frame {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let theme = &self.theme;
+        let top = self.border_top();
+        let border = Rect::new(self.rect.x(), top, self.rect.width(), (self.rect.bottom() - top).max(0) as u32);
+
+        // the top edge leaves a gap for the title
+        let mut gap = None;
+        let title = match &self.title {
+            Some(title) if !title.is_empty() => Some(ctx.text().render_font(self.font, title)?),
+            _ => None,
+        };
+        if let Some(texture) = &title {
+            let attr = texture.query();
+            let x = self.rect.x() + TITLE_INDENT;
+            gap = Some((x - 2, x + attr.width as i32 + 2));
+            ctx.tint(texture, theme.text);
+            ctx.canvas().copy(texture, None, Rect::new(x, self.rect.y(), attr.width, attr.height))?;
+        }
+        ctx.draw_rounded_outline(border, theme.corner_radius, theme.border, gap)?;
+
+        ctx.clipped(self.rect, |ctx| {
+            for child in self.children.iter() {
+                child.draw(ctx)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Widget for Frame {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { self.rect }
+    fn role(&self) -> Role { Role::Group }
+    fn access_label(&self) -> Option<String> { self.title.clone() }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        self.fixed = true;
+    }
+
+    fn size_hint(&self) -> (u32, u32) {
+        let (w, h) = self.children[0].size_hint();
+        let pad = self.theme.padding;
+        (
+            (w as i32 + 2 * pad) as u32,
+            (h as i32 + self.top_inset() + pad) as u32,
+        )
+    }
+
+    fn layout(&mut self, area: Rect) {
+        if !self.fixed {
+            self.rect = area;
+        }
+        let inner = self.inner_rect();
+        self.children[0].set_rect(inner);
+        self.children[0].layout(inner);
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+        }
+        for child in self.children.iter_mut() {
+            child.apply_theme(theme);
+        }
+    }
+
+    // Only the title, the content keeps its own fonts
+    fn set_font(&mut self, text: &TextRenderer, font: FontId) -> Result<(), ToolkitError> {
+        self.font = font;
+        self.text_height = text.font_height(font);
+        Ok(())
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] { &self.children }
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] { &mut self.children }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
@@ -0,0 +1,149 @@
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::controller::{Axis, Button, GameController};
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod, Scancode};
+use sdl2::GameControllerSubsystem;
+
+use crate::{Toolkit, ToolkitError, UiEvent};
+
+// How far a stick has to be pushed to count as a D-pad press, and how far
+// back it has to come before it can press again
+const STICK_PRESS: i16 = 16000;
+const STICK_RELEASE: i16 = 8000;
+
+// Game controllers plugged in, opened as SDL reports them. SDL only sends
+// their events while they are held open.
+pub(crate) struct Gamepads {
+    subsystem: GameControllerSubsystem,
+    open: Vec<GameController>,
+    // direction the left stick is pushed in, -1, 0 or 1 per axis
+    stick: (i32, i32),
+}
+
+impl Gamepads {
+    pub(crate) fn new(sdl: &sdl2::Sdl) -> Result<Gamepads, ToolkitError> {
+        Ok(Gamepads {
+            subsystem: sdl.game_controller()?,
+            open: Vec::new(),
+            stick: (0, 0),
+        })
+    }
+}
+
+impl Debug for Gamepads {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Gamepads")
+            .field("open", &self.open.iter().map(|c| c.name()).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+// Where the stick points along one axis, with some slack so it doesn't
+// flicker around the threshold
+fn stick_direction(current: i32, value: i16) -> i32 {
+    let press = if current == 0 { STICK_PRESS } else { STICK_RELEASE };
+    match value {
+        v if v >= press => 1,
+        v if v <= -press => -1,
+        _ if value.unsigned_abs() >= STICK_RELEASE as u16 => current,
+        _ => 0,
+    }
+}
+
+impl Toolkit {
+    // Controllers currently plugged in, e.g. for rumble
+    pub fn controllers(&self) -> &[GameController] {
+        &self.gamepads.open
+    }
+
+    // True if the event came from a controller and has been dealt with
+    pub(crate) fn controller_event(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::ControllerDeviceAdded {which, ..} => {
+                if let Ok(controller) = self.gamepads.subsystem.open(which) {
+                    self.gamepads.open.push(controller);
+                }
+            },
+            Event::ControllerDeviceRemoved {which, ..} => {
+                self.gamepads.open.retain(|c| c.instance_id() != which);
+            },
+            Event::ControllerButtonDown {button, ..} => {
+                self.ui_events.push(UiEvent::ControllerButton {button, pressed: true});
+                self.controller_button(button);
+            },
+            Event::ControllerButtonUp {button, ..} => {
+                self.ui_events.push(UiEvent::ControllerButton {button, pressed: false});
+            },
+            Event::ControllerAxisMotion {axis, value, ..} => {
+                self.ui_events.push(UiEvent::ControllerAxis {axis, value});
+                let (x, y) = self.gamepads.stick;
+                let button = match axis {
+                    Axis::LeftX => {
+                        let dir = stick_direction(x, value);
+                        self.gamepads.stick.0 = dir;
+                        (dir != x && dir != 0).then_some(if dir < 0 { Button::DPadLeft } else { Button::DPadRight })
+                    },
+                    Axis::LeftY => {
+                        let dir = stick_direction(y, value);
+                        self.gamepads.stick.1 = dir;
+                        (dir != y && dir != 0).then_some(if dir < 0 { Button::DPadUp } else { Button::DPadDown })
+                    },
+                    _ => None,
+                };
+                if let Some(button) = button {
+                    self.controller_button(button);
+                }
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    // The D-pad works like the arrow keys and moves the focus when the
+    // focused widget has no use for it, A is Enter and B is Escape. B never
+    // quits the app, it only backs out of menus, dialogs and popups.
+    fn controller_button(&mut self, button: Button) {
+        let key = match button {
+            Button::DPadUp => Keycode::Up,
+            Button::DPadDown => Keycode::Down,
+            Button::DPadLeft => Keycode::Left,
+            Button::DPadRight => Keycode::Right,
+            Button::A => Keycode::Return,
+            Button::B => Keycode::Escape,
+            _ => return,
+        };
+        let event = Event::KeyDown {
+            timestamp: 0,
+            window_id: self.window.0,
+            keycode: Some(key),
+            scancode: Scancode::from_keycode(key),
+            keymod: Mod::NOMOD,
+            repeat: false,
+        };
+        if self.menu.is_some() || !self.dialogs.is_empty() {
+            self.handle_event(event);
+            return;
+        }
+
+        self.dirty = true;
+        let consumed = match self.focus.clone() {
+            Some(path) => self.dispatch(&path, &event),
+            None => false,
+        };
+        if consumed {
+            return;
+        }
+        match key {
+            Keycode::Up | Keycode::Left => self.cycle_focus(false),
+            Keycode::Down | Keycode::Right => self.cycle_focus(true),
+            Keycode::Return => {
+                if let Some(path) = self.focus.clone() {
+                    self.activate(&path);
+                }
+            },
+            _ => { },
+        }
+    }
+}
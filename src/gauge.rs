@@ -0,0 +1,239 @@
+use std::any::Any;
+use std::f64::consts::PI;
+use std::fmt;
+use std::fmt::Debug;
+use std::time::Duration;
+
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+
+use crate::{AnimationId, DrawCtx, Drawable, Easing, Property, Role, Theme, Toolkit, ToolkitError, Widget, WidgetId};
+
+// The scale runs clockwise from bottom left to bottom right, leaving a gap
+// at the bottom. Angles are in degrees, clockwise from 3 o'clock.
+const START_ANGLE: f64 = 135.0;
+const SWEEP: f64 = 270.0;
+// Width of the scale's band, as a share of the radius
+const BAND: f64 = 0.12;
+// Arcs are drawn as quads this many degrees wide
+const ARC_STEP: f64 = 3.0;
+
+// A needle over an arc from min to max, for instruments. Parts of the arc
+// can be colored to mark zones, and the value is also shown as a number.
+pub struct Gauge {
+    name: String,
+    rect: Rect,
+    min: f64,
+    max: f64,
+    value: f64,
+    // where the needle is, which trails value while animating
+    shown: f64,
+    zones: Vec<(f64, f64, Color)>,
+    // major ticks, each with a label
+    divisions: u32,
+    decimals: usize,
+    unit: String,
+    label: String,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl Gauge {
+    pub fn new(name: &str, x: i32, y: i32, size: u32, min: f64, max: f64) -> Gauge {
+        Gauge {
+            name: name.to_string(),
+            rect: Rect::new(x, y, size, size),
+            min,
+            max: if max > min { max } else { min + 1.0 },
+            value: min,
+            shown: min,
+            zones: Vec::new(),
+            divisions: 10,
+            decimals: 0,
+            unit: String::new(),
+            label: String::new(),
+            theme: Theme::default(),
+            own_theme: false,
+        }
+    }
+
+    // Colors the arc from `from` to `to`, e.g. red towards the end
+    pub fn zone(mut self, from: f64, to: f64, color: Color) -> Gauge {
+        self.zones.push((from.min(to), from.max(to), color));
+        self
+    }
+
+    pub fn divisions(mut self, divisions: u32) -> Gauge {
+        self.divisions = divisions.max(1);
+        self
+    }
+
+    // Of the readout
+    pub fn decimals(mut self, decimals: usize) -> Gauge {
+        self.decimals = decimals;
+        self
+    }
+
+    // Goes after the readout, e.g. "rpm"
+    pub fn unit(mut self, unit: &str) -> Gauge {
+        self.unit = unit.to_string();
+        self
+    }
+
+    // Under the readout, e.g. what is measured
+    pub fn label(mut self, label: &str) -> Gauge {
+        self.label = label.to_string();
+        self
+    }
+
+    pub fn value(&self) -> f64 { self.value }
+    // Where the needle points right now
+    pub fn shown_value(&self) -> f64 { self.shown }
+    pub fn range(&self) -> (f64, f64) { (self.min, self.max) }
+
+    // Moves the needle straight there, see Toolkit::animate_gauge for
+    // getting there smoothly
+    pub fn set_value(&mut self, value: f64) {
+        self.value = value.clamp(self.min, self.max);
+        self.shown = self.value;
+    }
+
+    pub fn clear_zones(&mut self) {
+        self.zones.clear();
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+    }
+
+    fn angle_of(&self, value: f64) -> f64 {
+        let t = ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0);
+        START_ANGLE + SWEEP * t
+    }
+
+    fn readout(&self) -> String {
+        format!("{:.*}{}", self.decimals, self.shown, self.unit)
+    }
+
+    fn tick_label(&self, value: f64) -> String {
+        let step = (self.max - self.min) / self.divisions as f64;
+        let decimals = (-step.log10().floor()).max(0.0) as usize;
+        format!("{:.*}", decimals, value)
+    }
+}
+
+// Point `radius` away from `center` at `angle` degrees
+fn polar(center: Point, radius: f64, angle: f64) -> Point {
+    let a = angle * PI / 180.0;
+    Point::new(center.x() + (radius * a.cos()).round() as i32, center.y() + (radius * a.sin()).round() as i32)
+}
+
+// Band between radius `inner` and `outer` from angle `from` to `to`
+fn fill_arc(ctx: &mut DrawCtx, center: Point, inner: f64, outer: f64, from: f64, to: f64, color: Color) -> Result<(), ToolkitError> {
+    let mut a = from;
+    while a < to {
+        let b = (a + ARC_STEP).min(to);
+        let quad = [polar(center, outer, a), polar(center, outer, b), polar(center, inner, b), polar(center, inner, a)];
+        ctx.fill_polygon(&quad, color)?;
+        a = b;
+    }
+    Ok(())
+}
+
+impl Debug for Gauge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Gauge")
+            .field("name", &self.name)
+            .field("rect", &self.rect)
+            .field("range", &(self.min, self.max))
+            .field("value", &self.value)
+            .field("shown", &self.shown)
+            .field("zones", &self.zones.len())
+            .finish()
+    }
+}
+
+impl Drawable for Gauge {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let theme = &self.theme;
+        let center = self.rect.center();
+        let radius = (self.rect.width().min(self.rect.height()) as i32 / 2 - theme.padding) as f64;
+        if radius < 8.0 {
+            return Ok(());
+        }
+        let band = (radius * BAND).max(2.0);
+        let end = START_ANGLE + SWEEP;
+        fill_arc(ctx, center, radius - band, radius, START_ANGLE, end, theme.fill)?;
+        for &(from, to, color) in self.zones.iter() {
+            fill_arc(ctx, center, radius - band, radius, self.angle_of(from), self.angle_of(to), color)?;
+        }
+
+        let text_h = ctx.text().height();
+        for i in 0..=self.divisions {
+            let value = self.min + (self.max - self.min) * i as f64 / self.divisions as f64;
+            let angle = self.angle_of(value);
+            ctx.draw_line(polar(center, radius - band * 1.8, angle), polar(center, radius, angle), theme.border)?;
+            let label = self.tick_label(value);
+            let w = ctx.text().size_of(&label)?.0 as i32;
+            let at = polar(center, radius - band * 1.8 - text_h as f64, angle);
+            ctx.draw_text_at(at.x() - w / 2, at.y() - text_h / 2, &label, theme.text)?;
+        }
+
+        let readout = self.readout();
+        let w = ctx.text().size_of(&readout)?.0 as i32;
+        let y = center.y() + (radius * 0.35) as i32;
+        ctx.draw_text_at(center.x() - w / 2, y, &readout, theme.text)?;
+        if !self.label.is_empty() {
+            let w = ctx.text().size_of(&self.label)?.0 as i32;
+            ctx.draw_text_at(center.x() - w / 2, y + text_h, &self.label, theme.disabled)?;
+        }
+
+        // a thin triangle from the hub out to just short of the band
+        let angle = self.angle_of(self.shown);
+        let hub = (radius * 0.06).max(3.0);
+        let needle = [
+            polar(center, hub, angle - 90.0),
+            polar(center, radius - band * 1.2, angle),
+            polar(center, hub, angle + 90.0),
+        ];
+        ctx.fill_polygon(&needle, theme.active)?;
+        ctx.fill_circle(center, hub as i32, theme.active)?;
+        Ok(())
+    }
+}
+
+impl Widget for Gauge {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { self.rect }
+    fn role(&self) -> Role { Role::Meter }
+    fn set_rect(&mut self, rect: Rect) { self.rect = rect; }
+    fn size_hint(&self) -> (u32, u32) { (self.rect.width(), self.rect.height()) }
+    fn access_label(&self) -> Option<String> { Some(self.label.clone()).filter(|l| !l.is_empty()) }
+    fn access_value(&self) -> Option<String> { Some(format!("{:.*}{}", self.decimals, self.value, self.unit)) }
+
+    fn animate_value(&mut self, value: f64) {
+        self.shown = value.clamp(self.min, self.max);
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+impl Toolkit {
+    // Swings the needle of gauge `id` from where it is to `value`. None if
+    // `id` is no gauge.
+    pub fn animate_gauge(&mut self, id: WidgetId, value: f64, duration: Duration, easing: Easing) -> Option<AnimationId> {
+        let gauge = self.widget_mut::<Gauge>(id)?;
+        let from = gauge.shown;
+        gauge.value = value.clamp(gauge.min, gauge.max);
+        let to = gauge.value;
+        Some(self.animate(id, Property::Value, from as f32, to as f32, duration, easing))
+    }
+}
@@ -0,0 +1,199 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::fmt;
+use std::fmt::Debug;
+use std::mem;
+
+use log::warn;
+use sdl2::rect::Rect;
+
+use crate::{DrawCtx, Drawable, Role, ToolkitError, Widget};
+
+const GL_VIEWPORT: u32 = 0x0BA2;
+const GL_SCISSOR_BOX: u32 = 0x0C10;
+const GL_SCISSOR_TEST: u32 = 0x0C11;
+
+type GlRect = extern "system" fn(i32, i32, i32, i32);
+type GlGetIntegerv = extern "system" fn(u32, *mut i32);
+type GlCap = extern "system" fn(u32);
+type GlIsEnabled = extern "system" fn(u32) -> u8;
+
+pub type GlPaintFn = Box<dyn FnMut(&GlFrame)>;
+
+// Looks up an OpenGL function of the current context, null if it has none
+// by that name
+pub fn gl_proc_address(name: &str) -> *const () {
+    let Ok(name) = CString::new(name) else {
+        return std::ptr::null();
+    };
+    unsafe { sdl2::sys::SDL_GL_GetProcAddress(name.as_ptr()) as *const () }
+}
+
+// The few GL calls needed to fence the scene into the widget and restore
+// the renderer's view afterwards
+struct Gl {
+    viewport: GlRect,
+    scissor: GlRect,
+    get_integerv: GlGetIntegerv,
+    enable: GlCap,
+    disable: GlCap,
+    is_enabled: GlIsEnabled,
+}
+
+impl Gl {
+    fn load() -> Option<Gl> {
+        // the pointers come straight from the driver and have the signature
+        // the GL spec gives them
+        unsafe fn get<T: Copy>(name: &str) -> Option<T> {
+            let p = gl_proc_address(name);
+            (!p.is_null()).then(|| mem::transmute_copy::<*const (), T>(&p))
+        }
+        unsafe {
+            Some(Gl {
+                viewport: get("glViewport")?,
+                scissor: get("glScissor")?,
+                get_integerv: get("glGetIntegerv")?,
+                enable: get("glEnable")?,
+                disable: get("glDisable")?,
+                is_enabled: get("glIsEnabled")?,
+            })
+        }
+    }
+}
+
+// What the scene gets to know while it draws. GL's viewport and scissor
+// box are set to the widget, in physical pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct GlFrame {
+    width: u32,
+    height: u32,
+}
+
+impl GlFrame {
+    pub fn width(&self) -> u32 { self.width }
+    pub fn height(&self) -> u32 { self.height }
+
+    pub fn proc_address(&self, name: &str) -> *const () {
+        gl_proc_address(name)
+    }
+}
+
+// Area drawn with raw OpenGL calls in between the toolkit's own drawing,
+// for a game or visualization with the toolkit on top as its UI. Needs the
+// OpenGL renderer, see ToolkitBuilder::opengl, and draws nothing on others.
+// The scene runs in the renderer's GL context and has to leave bound
+// textures, programs and blending the way it found them.
+pub struct GlViewport {
+    name: String,
+    rect: Rect,
+    pref: (u32, u32),
+    // keeps redrawing in RedrawMode::OnDemand, which a live scene wants
+    animated: bool,
+    paint: RefCell<GlPaintFn>,
+    gl: RefCell<Option<Gl>>,
+}
+
+impl GlViewport {
+    pub fn new<F: FnMut(&GlFrame) + 'static>(name: &str, x: i32, y: i32, w: u32, h: u32, paint: F) -> GlViewport {
+        GlViewport {
+            name: name.to_string(),
+            rect: Rect::new(x, y, w, h),
+            pref: (w, h),
+            animated: true,
+            paint: RefCell::new(Box::new(paint)),
+            gl: RefCell::new(None),
+        }
+    }
+
+    pub fn animated(mut self, animated: bool) -> GlViewport {
+        self.animated = animated;
+        self
+    }
+
+    pub fn set_animated(&mut self, animated: bool) {
+        self.animated = animated;
+    }
+
+    pub fn set_paint<F: FnMut(&GlFrame) + 'static>(&mut self, paint: F) {
+        self.paint = RefCell::new(Box::new(paint));
+    }
+}
+
+impl Debug for GlViewport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GlViewport")
+            .field("name", &self.name)
+            .field("rect", &self.rect)
+            .field("animated", &self.animated)
+            .finish()
+    }
+}
+
+impl Drawable for GlViewport {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        if !ctx.canvas().info().name.starts_with("opengl") {
+            return Ok(());
+        }
+        let Ok(mut paint) = self.paint.try_borrow_mut() else {
+            return Ok(());
+        };
+        let mut gl = self.gl.borrow_mut();
+        if gl.is_none() {
+            *gl = Gl::load();
+        }
+        let Some(gl) = gl.as_ref() else {
+            warn!("{}: OpenGL functions not found", self.name);
+            return Ok(());
+        };
+        let visible = match ctx.clip_rect() {
+            Some(clip) => match self.rect.intersection(clip) {
+                Some(r) => r,
+                None => return Ok(()),
+            },
+            None => self.rect,
+        };
+
+        // SDL batches its drawing, what it has so far goes below the scene
+        unsafe { sdl2::sys::SDL_RenderFlush(ctx.canvas().raw()) };
+        let (_, out_h) = ctx.canvas().output_size()?;
+        let (sx, sy) = ctx.canvas().scale();
+        // GL counts pixels from the bottom left
+        let to_gl = |r: Rect| {
+            let (x, y) = ((r.x() as f32 * sx) as i32, (r.y() as f32 * sy) as i32);
+            let (w, h) = ((r.width() as f32 * sx) as i32, (r.height() as f32 * sy) as i32);
+            (x, out_h as i32 - y - h, w, h)
+        };
+        let view = to_gl(self.rect);
+        let scissor = to_gl(visible);
+
+        let (mut old_view, mut old_scissor) = ([0; 4], [0; 4]);
+        (gl.get_integerv)(GL_VIEWPORT, old_view.as_mut_ptr());
+        (gl.get_integerv)(GL_SCISSOR_BOX, old_scissor.as_mut_ptr());
+        let scissoring = (gl.is_enabled)(GL_SCISSOR_TEST) != 0;
+
+        (gl.viewport)(view.0, view.1, view.2, view.3);
+        (gl.enable)(GL_SCISSOR_TEST);
+        (gl.scissor)(scissor.0, scissor.1, scissor.2, scissor.3);
+        paint(&GlFrame {width: view.2 as u32, height: view.3 as u32});
+
+        (gl.viewport)(old_view[0], old_view[1], old_view[2], old_view[3]);
+        (gl.scissor)(old_scissor[0], old_scissor[1], old_scissor[2], old_scissor[3]);
+        if !scissoring {
+            (gl.disable)(GL_SCISSOR_TEST);
+        }
+        Ok(())
+    }
+}
+
+impl Widget for GlViewport {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { self.rect }
+    fn role(&self) -> Role { Role::Canvas }
+    fn set_rect(&mut self, rect: Rect) { self.rect = rect; }
+    fn size_hint(&self) -> (u32, u32) { self.pref }
+    fn needs_redraw(&self) -> bool { self.animated }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
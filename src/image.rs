@@ -0,0 +1,173 @@
+use std::any::Any;
+use std::fmt;
+use std::fmt::Debug;
+use std::path::Path;
+
+use sdl2::rect::Rect;
+use sdl2::render::Texture;
+use sdl2::surface::Surface;
+
+use crate::{DrawCtx, Drawable, Role, TextRenderer, ToolkitError, Widget};
+
+// How a picture is fitted into the widget's rect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleMode {
+    // as large as fits with the aspect ratio kept, centered
+    Fit,
+    // covers the whole rect with the aspect ratio kept, cropping the rest
+    Fill,
+    // squeezed into the rect regardless of aspect ratio
+    Stretch,
+    // original size, centered and cropped
+    None,
+}
+
+// BMP is built into SDL, everything else needs the `image` feature
+pub fn load_surface(path: &Path) -> Result<Surface<'static>, ToolkitError> {
+    let failed = |e: String| ToolkitError::ImageLoadError {path: path.to_path_buf(), source: Box::new(e.into())};
+    let is_bmp = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("bmp"));
+    if is_bmp {
+        return Surface::load_bmp(path).map_err(failed);
+    }
+
+    #[cfg(feature = "image")]
+    {
+        use sdl2::image::LoadSurface;
+        Surface::from_file(path).map_err(failed)
+    }
+    #[cfg(not(feature = "image"))]
+    Err(ToolkitError::UnsupportedImage(path.display().to_string()))
+}
+
+// Shows a picture loaded from disk, or any texture handed to it
+pub struct Image {
+    name: String,
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    pref: (i32, i32),
+    texture: Option<Texture<'static>>,
+    mode: ScaleMode,
+}
+
+impl Image {
+    pub fn new(name: &str, x: i32, y: i32, w: i32, h: i32) -> Image {
+        Image {
+            name: name.to_string(),
+            x,
+            y,
+            w,
+            h,
+            pref: (w, h),
+            texture: None,
+            mode: ScaleMode::Fit,
+        }
+    }
+
+    // An image as large as the picture in `path`
+    pub fn from_file<P: AsRef<Path>>(text: &TextRenderer, name: &str, path: P, x: i32, y: i32) -> Result<Image, ToolkitError> {
+        let mut img = Image::new(name, x, y, 0, 0);
+        img.load(text, path)?;
+        let attr = img.texture.as_ref().unwrap().query();
+        (img.w, img.h) = (attr.width as i32, attr.height as i32);
+        img.pref = (img.w, img.h);
+        Ok(img)
+    }
+
+    pub fn scale(mut self, mode: ScaleMode) -> Image {
+        self.mode = mode;
+        self
+    }
+
+    pub fn scale_mode(&self) -> ScaleMode { self.mode }
+    pub fn texture(&self) -> Option<&Texture<'static>> { self.texture.as_ref() }
+
+    pub fn set_scale_mode(&mut self, mode: ScaleMode) {
+        self.mode = mode;
+    }
+
+    // Swaps the picture, the widget keeps its size
+    pub fn load<P: AsRef<Path>>(&mut self, text: &TextRenderer, path: P) -> Result<(), ToolkitError> {
+        let surface = load_surface(path.as_ref())?;
+        self.texture = Some(text.texture_from_surface(&surface)?);
+        Ok(())
+    }
+
+    pub fn set_texture(&mut self, texture: Option<Texture<'static>>) {
+        self.texture = texture;
+    }
+
+    // Where the texture goes on screen and which part of it is shown
+    fn placement(&self, tw: u32, th: u32) -> (Rect, Rect) {
+        let rect = self.rect();
+        let full = Rect::new(0, 0, tw, th);
+        let (rw, rh) = (rect.width() as f32, rect.height() as f32);
+        let (fw, fh) = (tw as f32, th as f32);
+        match self.mode {
+            ScaleMode::Stretch => (full, rect),
+            ScaleMode::Fit => {
+                let s = (rw / fw).min(rh / fh);
+                (full, Rect::from_center(rect.center(), (fw * s) as u32, (fh * s) as u32))
+            },
+            ScaleMode::Fill => {
+                // crop the texture to the rect's aspect ratio
+                let s = (rw / fw).max(rh / fh);
+                let (cw, ch) = ((rw / s) as u32, (rh / s) as u32);
+                let src = Rect::new(((tw - cw.min(tw)) / 2) as i32, ((th - ch.min(th)) / 2) as i32, cw.min(tw), ch.min(th));
+                (src, rect)
+            },
+            ScaleMode::None => {
+                let (w, h) = (tw.min(rect.width()), th.min(rect.height()));
+                let src = Rect::new(((tw - w) / 2) as i32, ((th - h) / 2) as i32, w, h);
+                (src, Rect::from_center(rect.center(), w, h))
+            },
+        }
+    }
+}
+
+impl Debug for Image {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Image")
+            .field("name", &self.name)
+            .field("rect", &self.rect())
+            .field("loaded", &self.texture.is_some())
+            .field("mode", &self.mode)
+            .finish()
+    }
+}
+
+impl Drawable for Image {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let Some(texture) = self.texture.as_ref() else {
+            return Ok(());
+        };
+        let attr = texture.query();
+        if attr.width == 0 || attr.height == 0 || self.w <= 0 || self.h <= 0 {
+            return Ok(());
+        }
+        let (src, dst) = self.placement(attr.width, attr.height);
+        ctx.canvas().copy(texture, src, dst)?;
+        Ok(())
+    }
+}
+
+impl Widget for Image {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { Rect::new(self.x, self.y, self.w as u32, self.h as u32) }
+    fn role(&self) -> Role { Role::Image }
+
+    fn size_hint(&self) -> (u32, u32) {
+        (self.pref.0 as u32, self.pref.1 as u32)
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.x = rect.x();
+        self.y = rect.y();
+        self.w = rect.width() as i32;
+        self.h = rect.height() as i32;
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
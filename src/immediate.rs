@@ -0,0 +1,255 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fmt::Debug;
+
+use crate::widget;
+use crate::{BoxLayout, Button, CheckBox, RichText, Slider, TextInput, TextSpan, Toolkit, ToolkitError, Widget};
+
+const FIELD_WIDTH: i32 = 200;
+
+// What one call asked for
+enum Item {
+    Label(String),
+    Button(String),
+    Slider {value: f64, min: f64, max: f64},
+    CheckBox {label: String, checked: bool},
+    TextInput(String),
+}
+
+// What can't change about an item without making its widget again
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    Label,
+    Button,
+    Slider(f64, f64),
+    CheckBox(String),
+    TextInput,
+}
+
+impl Item {
+    fn shape(&self) -> Shape {
+        match self {
+            Item::Label(_) => Shape::Label,
+            Item::Button(_) => Shape::Button,
+            Item::Slider {min, max, ..} => Shape::Slider(*min, *max),
+            Item::CheckBox {label, ..} => Shape::CheckBox(label.clone()),
+            Item::TextInput(_) => Shape::TextInput,
+        }
+    }
+}
+
+// Kept between frames for every immediate panel
+#[derive(Debug, Default)]
+pub(crate) struct Panel {
+    shape: Vec<Shape>,
+    // items clicked or changed by the user since the last frame
+    fired: HashSet<String>,
+    // items whose handlers are registered
+    handled: HashSet<String>,
+}
+
+// Handed to the closure of Toolkit::immediate. Every call adds a widget
+// under the last one and tells what the user did with it since the last
+// frame. Widgets are told apart by their position, so a panel whose calls
+// come in a different order from one frame to the next starts over.
+pub struct ImmediateUi<'a> {
+    tk: &'a mut Toolkit,
+    panel: String,
+    items: Vec<Item>,
+    state: Panel,
+}
+
+impl ImmediateUi<'_> {
+    pub fn label(&mut self, text: &str) {
+        self.items.push(Item::Label(text.to_string()));
+    }
+
+    // True if the button was clicked since the last frame
+    pub fn button(&mut self, label: &str) -> bool {
+        let clicked = self.fired(Shape::Button).is_some();
+        self.items.push(Item::Button(label.to_string()));
+        clicked
+    }
+
+    // Shows `value` and puts what the user picks into it. True if it did.
+    pub fn slider(&mut self, value: &mut f64, min: f64, max: f64) -> bool {
+        let picked = self.fired(Shape::Slider(min, max)).and_then(|key| self.tk.slider_value(&key));
+        if let Some(picked) = picked {
+            *value = picked;
+        }
+        self.items.push(Item::Slider {value: *value, min, max});
+        picked.is_some()
+    }
+
+    pub fn checkbox(&mut self, label: &str, checked: &mut bool) -> bool {
+        let toggled = self.fired(Shape::CheckBox(label.to_string())).and_then(|key| self.tk.is_checked(&key));
+        if let Some(toggled) = toggled {
+            *checked = toggled;
+        }
+        self.items.push(Item::CheckBox {label: label.to_string(), checked: *checked});
+        toggled.is_some()
+    }
+
+    pub fn text_input(&mut self, value: &mut String) -> bool {
+        let typed = self.fired(Shape::TextInput).and_then(|key| self.tk.text_value(&key).map(str::to_string));
+        let changed = typed.is_some();
+        if let Some(typed) = typed {
+            *value = typed;
+        }
+        self.items.push(Item::TextInput(value.clone()));
+        changed
+    }
+
+    // The rest of the Toolkit, for retained widgets in the same frame
+    pub fn toolkit(&mut self) -> &mut Toolkit {
+        self.tk
+    }
+
+    // Name of the next item's widget if the user did something with it,
+    // which only counts if it was the same kind of item last frame
+    fn fired(&mut self, shape: Shape) -> Option<String> {
+        let idx = self.items.len();
+        let key = item_name(&self.panel, idx);
+        (self.state.shape.get(idx) == Some(&shape) && self.state.fired.remove(&key)).then_some(key)
+    }
+}
+
+impl Debug for ImmediateUi<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImmediateUi")
+            .field("panel", &self.panel)
+            .field("items", &self.items.len())
+            .finish()
+    }
+}
+
+fn item_name(panel: &str, idx: usize) -> String {
+    format!("{}#{}", panel, idx)
+}
+
+impl Toolkit {
+    // Fills the vertical box `name` with what `f` asks for, the quick way
+    // for tools and debug panels. The box is added to the current tab the
+    // first time, unless there is one by that name already, say in a dock
+    // panel. Call it every frame, e.g. between ticks: widgets are only made
+    // again when the kind of calls changes, otherwise they are brought up
+    // to date and keep focus and whatever is being typed.
+    pub fn immediate<F: FnOnce(&mut ImmediateUi)>(&mut self, name: &str, f: F) -> Result<(), ToolkitError> {
+        let mut state = self.immediate_panels.remove(name).unwrap_or_default();
+        if self.find_widget::<BoxLayout>(name).is_none() {
+            self.add_widget(BoxLayout::vertical(name))?;
+            state.shape.clear();
+        }
+
+        let mut ui = ImmediateUi {tk: self, panel: name.to_string(), items: Vec::new(), state};
+        f(&mut ui);
+        let ImmediateUi {items, mut state, ..} = ui;
+        // what wasn't asked about this frame is old news by the next
+        state.fired.clear();
+
+        let shape: Vec<Shape> = items.iter().map(Item::shape).collect();
+        let result = if shape == state.shape {
+            self.refresh_panel(name, &items)
+        } else {
+            self.rebuild_panel(name, &items, &mut state)
+        };
+        // try again next frame if that failed
+        state.shape = if result.is_ok() { shape } else { Vec::new() };
+        self.immediate_panels.insert(name.to_string(), state);
+        result
+    }
+
+    fn rebuild_panel(&mut self, name: &str, items: &[Item], state: &mut Panel) -> Result<(), ToolkitError> {
+        let mut widgets: Vec<Box<dyn Widget>> = Vec::new();
+        for (idx, item) in items.iter().enumerate() {
+            let key = item_name(name, idx);
+            widgets.push(match item {
+                Item::Label(text) => Box::new(RichText::new(&self.text, &key, 0, 0, vec![TextSpan::new(text, self.theme.text)])?),
+                Item::Button(label) => {
+                    let mut btn = Button::new(&self.text, &key, 0, 0)?;
+                    btn.set_label(&self.text, label)?;
+                    Box::new(btn)
+                },
+                Item::Slider {value, min, max} => {
+                    let mut slider = Slider::new(&key, 0, 0, FIELD_WIDTH, *min, *max);
+                    slider.set_value(*value);
+                    Box::new(slider)
+                },
+                Item::CheckBox {label, checked} => Box::new(CheckBox::new(&self.text, &key, label, 0, 0)?.checked(*checked)),
+                Item::TextInput(value) => {
+                    let mut input = TextInput::new(&self.text, &key, 0, 0, FIELD_WIDTH);
+                    input.set_value(value);
+                    Box::new(input)
+                },
+            });
+
+            if state.handled.insert(key.clone()) {
+                let (panel, item) = (name.to_string(), key.clone());
+                let fired = move |tk: &mut Toolkit| {
+                    if let Some(state) = tk.immediate_panels.get_mut(&panel) {
+                        state.fired.insert(item.clone());
+                    }
+                };
+                self.on_click(&key, fired.clone());
+                self.on_change(&key, fired);
+            }
+        }
+
+        // the old widgets may be focused, hovered or pressed
+        let prefix = format!("{}#", name);
+        let inside = [&self.focus, &self.hover, &self.pressed].into_iter().flatten().any(|path| {
+            self.tabs.get(self.tab_pos)
+                .and_then(|tab| widget::get(&tab.items, path))
+                .is_some_and(|w| w.name().starts_with(&prefix))
+        });
+        if inside {
+            self.forget_paths();
+        }
+
+        let theme = self.theme;
+        if let Some(panel) = self.find_widget_mut::<BoxLayout>(name) {
+            panel.clear();
+            for w in widgets {
+                panel.push_boxed(w);
+            }
+            panel.apply_theme(&theme);
+        }
+        Ok(())
+    }
+
+    // Same widgets as last frame, only what changed is put into them
+    fn refresh_panel(&mut self, name: &str, items: &[Item]) -> Result<(), ToolkitError> {
+        for (idx, item) in items.iter().enumerate() {
+            let key = item_name(name, idx);
+            match item {
+                Item::Label(text) => {
+                    let stale = self.find_widget::<RichText>(&key).is_some_and(|rich| rich.spans().len() != 1 || rich.spans()[0].text != *text);
+                    if stale {
+                        self.set_rich_text(&key, vec![TextSpan::new(text, self.theme.text)])?;
+                    }
+                },
+                Item::Button(label) => {
+                    if self.find_widget::<Button>(&key).is_some_and(|btn| btn.label() != label) {
+                        self.set_btn_label(&key, label)?;
+                    }
+                },
+                Item::Slider {value, min, max} => {
+                    if self.slider_value(&key).is_some_and(|v| v != value.max(*min).min(*max)) {
+                        self.set_slider_value(&key, *value);
+                    }
+                },
+                Item::CheckBox {checked, ..} => {
+                    if self.is_checked(&key).is_some_and(|c| c != *checked) {
+                        self.set_checked(&key, *checked);
+                    }
+                },
+                Item::TextInput(value) => {
+                    if self.text_value(&key).is_some_and(|v| v != value) {
+                        self.set_text_value(&key, value);
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+}
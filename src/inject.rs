@@ -0,0 +1,48 @@
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Scancode};
+use sdl2::mouse::{MouseButton, MouseState};
+
+use crate::{Modifiers, Toolkit};
+
+// Input made up by a test rather than coming from SDL. Positions are in the
+// same units as widget rects.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TestEvent {
+    // the pointer moves to (x, y) and the left button goes down and up there
+    Click {x: i32, y: i32},
+    // pressed and released
+    Key {key: Keycode, mods: Modifiers},
+    // typed into whatever has the focus
+    Text {text: String},
+}
+
+impl Toolkit {
+    // Handles `event` as if SDL had sent it to the current window, callbacks
+    // run before this returns. The layout is brought up to date first so
+    // clicks land where the widgets would be drawn.
+    pub fn inject_event(&mut self, event: TestEvent) {
+        self.update_scale();
+        self.relayout();
+        let (timestamp, window_id) = (0, self.window.0);
+        match event {
+            TestEvent::Click {x, y} => {
+                let (xrel, yrel) = (x - self.mouse.0, y - self.mouse.1);
+                let mousestate = MouseState::from_sdl_state(0);
+                self.handle_event(Event::MouseMotion {timestamp, window_id, which: 0, mousestate, x, y, xrel, yrel});
+                // hover follows the pointer between frames
+                self.update_hover();
+                let (which, mouse_btn, clicks) = (0, MouseButton::Left, 1);
+                self.handle_event(Event::MouseButtonDown {timestamp, window_id, which, mouse_btn, clicks, x, y});
+                self.handle_event(Event::MouseButtonUp {timestamp, window_id, which, mouse_btn, clicks, x, y});
+            },
+            TestEvent::Key {key, mods} => {
+                let (keycode, scancode, keymod) = (Some(key), Scancode::from_keycode(key), mods.into());
+                self.handle_event(Event::KeyDown {timestamp, window_id, keycode, scancode, keymod, repeat: false});
+                self.handle_event(Event::KeyUp {timestamp, window_id, keycode, scancode, keymod, repeat: false});
+            },
+            TestEvent::Text {text} => {
+                self.handle_event(Event::TextInput {timestamp, window_id, text});
+            },
+        }
+    }
+}
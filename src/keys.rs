@@ -0,0 +1,67 @@
+use std::ops::BitOr;
+
+use sdl2::keyboard::Mod;
+
+// Modifier keys held for a key binding, left and right counting the same.
+// Combine them with |, e.g. Modifiers::CTRL | Modifiers::SHIFT.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    // the Windows, Command or Super key
+    pub gui: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers { ctrl: false, shift: false, alt: false, gui: false };
+    pub const CTRL: Modifiers = Modifiers { ctrl: true, ..Modifiers::NONE };
+    pub const SHIFT: Modifiers = Modifiers { shift: true, ..Modifiers::NONE };
+    pub const ALT: Modifiers = Modifiers { alt: true, ..Modifiers::NONE };
+    pub const GUI: Modifiers = Modifiers { gui: true, ..Modifiers::NONE };
+}
+
+impl BitOr for Modifiers {
+    type Output = Modifiers;
+
+    fn bitor(self, other: Modifiers) -> Modifiers {
+        Modifiers {
+            ctrl: self.ctrl || other.ctrl,
+            shift: self.shift || other.shift,
+            alt: self.alt || other.alt,
+            gui: self.gui || other.gui,
+        }
+    }
+}
+
+// Num Lock, Caps Lock and the like don't matter
+impl From<Mod> for Modifiers {
+    fn from(keymod: Mod) -> Modifiers {
+        Modifiers {
+            ctrl: keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD),
+            shift: keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+            alt: keymod.intersects(Mod::LALTMOD | Mod::RALTMOD),
+            gui: keymod.intersects(Mod::LGUIMOD | Mod::RGUIMOD),
+        }
+    }
+}
+
+// The left hand keys stand in for either side
+impl From<Modifiers> for Mod {
+    fn from(mods: Modifiers) -> Mod {
+        let mut keymod = Mod::NOMOD;
+        if mods.ctrl {
+            keymod |= Mod::LCTRLMOD;
+        }
+        if mods.shift {
+            keymod |= Mod::LSHIFTMOD;
+        }
+        if mods.alt {
+            keymod |= Mod::LALTMOD;
+        }
+        if mods.gui {
+            keymod |= Mod::LGUIMOD;
+        }
+        keymod
+    }
+}
@@ -0,0 +1,747 @@
+use std::any::Any;
+use std::collections::HashMap;
+
+use sdl2::rect::Rect;
+
+use crate::{DrawCtx, Drawable, Theme, ToolkitError, Widget, WidgetId};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+// Placement of children across the box's main axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+    Stretch,
+}
+
+// How big a widget is along one axis, worked out by the layout pass from
+// the room its parent has
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Size {
+    Pixels(u32),
+    // share of the parent's inner size, 0 to 100
+    Percent(f32),
+    // all the room that is left
+    Fill,
+    // whatever the widget's size hint asks for
+    FitContent,
+}
+
+impl Size {
+    // Pixels along one axis, given the room there is and what the content
+    // would like
+    pub fn resolve(self, available: u32, content: u32) -> u32 {
+        match self {
+            Size::Pixels(px) => px,
+            Size::Percent(pct) => (available as f32 * pct.max(0.0) / 100.0).round() as u32,
+            Size::Fill => available,
+            Size::FitContent => content,
+        }
+    }
+}
+
+// Space on each side of a rect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Insets {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl Insets {
+    pub const ZERO: Insets = Insets {left: 0, top: 0, right: 0, bottom: 0};
+
+    pub fn all(n: i32) -> Insets {
+        Insets {left: n, top: n, right: n, bottom: n}
+    }
+
+    pub fn symmetric(horizontal: i32, vertical: i32) -> Insets {
+        Insets {left: horizontal, top: vertical, right: horizontal, bottom: vertical}
+    }
+
+    // What is left of `rect` inside the insets, at least a pixel
+    pub fn shrink(&self, rect: Rect) -> Rect {
+        let w = (rect.width() as i32 - self.left - self.right).max(1) as u32;
+        let h = (rect.height() as i32 - self.top - self.bottom).max(1) as u32;
+        Rect::new(rect.x() + self.left, rect.y() + self.top, w, h)
+    }
+}
+
+// Room kept free around a widget, room inside it around its content and
+// limits on its size. Layout containers honor these for their children and
+// the Toolkit for tab items, see Toolkit::set_box_model. Margin and padding
+// left unset come from the theme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BoxModel {
+    margin: Option<Insets>,
+    padding: Option<i32>,
+    min_size: (u32, u32),
+    max_size: Option<(u32, u32)>,
+}
+
+impl BoxModel {
+    pub fn new() -> BoxModel {
+        BoxModel::default()
+    }
+
+    pub fn margin(mut self, margin: Insets) -> BoxModel {
+        self.margin = Some(margin);
+        self
+    }
+
+    // Stands in for the theme's padding, so it works for every widget
+    // that pads its content by the theme
+    pub fn padding(mut self, padding: i32) -> BoxModel {
+        self.padding = Some(padding);
+        self
+    }
+
+    pub fn min_size(mut self, width: u32, height: u32) -> BoxModel {
+        self.min_size = (width, height);
+        self
+    }
+
+    pub fn max_size(mut self, width: u32, height: u32) -> BoxModel {
+        self.max_size = Some((width, height));
+        self
+    }
+
+    // (w, h) held between the min and max size, the min winning if they
+    // cross. Kept within i32 so it can go into a Rect.
+    pub fn clamp(&self, w: u32, h: u32) -> (u32, u32) {
+        let (max_w, max_h) = self.max_size.unwrap_or((u32::MAX, u32::MAX));
+        let limit = |v: u32, min: u32, max: u32| v.min(max).max(min).min(i32::MAX as u32);
+        (limit(w, self.min_size.0, max_w), limit(h, self.min_size.1, max_h))
+    }
+
+    pub(crate) fn margin_or(&self, default: i32) -> Insets {
+        self.margin.unwrap_or(Insets::all(default))
+    }
+
+    // The theme as the widget should see it
+    pub(crate) fn theme_for(&self, theme: &Theme) -> Theme {
+        match self.padding {
+            Some(padding) => Theme {padding, ..*theme},
+            None => *theme,
+        }
+    }
+}
+
+// Edge or corner of the content area a widget stays pinned to as the
+// window changes size. Stretch pins all four edges, so the widget grows and
+// shrinks with the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+    Stretch,
+}
+
+// An anchor along with the distances to the area's edges at the time it
+// was set, which are kept from then on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Anchored {
+    anchor: Anchor,
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+    // from the area's center to the widget's
+    center: (i32, i32),
+}
+
+// Where a widget goes along one axis: pinned to the start, the end, the
+// middle or both ends
+#[derive(Clone, Copy)]
+enum Pin {
+    Start,
+    Middle,
+    End,
+    Both,
+}
+
+impl Anchored {
+    pub(crate) fn new(anchor: Anchor, rect: Rect, area: Rect) -> Anchored {
+        Anchored {
+            anchor,
+            left: rect.x() - area.x(),
+            top: rect.y() - area.y(),
+            right: area.right() - rect.right(),
+            bottom: area.bottom() - rect.bottom(),
+            center: (rect.center().x() - area.center().x(), rect.center().y() - area.center().y()),
+        }
+    }
+
+    fn pins(&self) -> (Pin, Pin) {
+        match self.anchor {
+            Anchor::TopLeft => (Pin::Start, Pin::Start),
+            Anchor::Top => (Pin::Middle, Pin::Start),
+            Anchor::TopRight => (Pin::End, Pin::Start),
+            Anchor::Left => (Pin::Start, Pin::Middle),
+            Anchor::Center => (Pin::Middle, Pin::Middle),
+            Anchor::Right => (Pin::End, Pin::Middle),
+            Anchor::BottomLeft => (Pin::Start, Pin::End),
+            Anchor::Bottom => (Pin::Middle, Pin::End),
+            Anchor::BottomRight => (Pin::End, Pin::End),
+            Anchor::Stretch => (Pin::Both, Pin::Both),
+        }
+    }
+
+    // The widget's rect in `area`, keeping its size unless it stretches
+    pub(crate) fn place(&self, rect: Rect, area: Rect) -> Rect {
+        // start and length along one axis
+        let along = |pin: Pin, start: i32, end: i32, len: i32, before: i32, after: i32, center: i32| match pin {
+            Pin::Start => (start + before, len),
+            Pin::Middle => ((start + end) / 2 + center - len / 2, len),
+            Pin::End => (end - after - len, len),
+            Pin::Both => (start + before, (end - after - start - before).max(1)),
+        };
+        let (h, v) = self.pins();
+        let (x, w) = along(h, area.x(), area.right(), rect.width() as i32, self.left, self.right, self.center.0);
+        let (y, ht) = along(v, area.y(), area.bottom(), rect.height() as i32, self.top, self.bottom, self.center.1);
+        Rect::new(x, y, w as u32, ht as u32)
+    }
+}
+
+// What the Toolkit knows about placing tab items beyond where they were
+// put: sizes relative to the content area, anchors and box models
+#[derive(Debug, Default)]
+pub(crate) struct Placements {
+    pub(crate) sizes: HashMap<WidgetId, (Size, Size)>,
+    pub(crate) anchors: HashMap<WidgetId, Anchored>,
+    pub(crate) models: HashMap<WidgetId, BoxModel>,
+}
+
+impl Placements {
+    pub(crate) fn forget(&mut self, id: WidgetId) {
+        self.sizes.remove(&id);
+        self.anchors.remove(&id);
+        self.models.remove(&id);
+    }
+
+    // Sizes the item first, keeping its position, then moves it to stay
+    // anchored. The item has already laid itself out in `area`.
+    pub(crate) fn place(&self, id: WidgetId, item: &mut dyn Widget, area: Rect) {
+        let model = self.models.get(&id);
+        let resize = |item: &mut dyn Widget, rect: Rect| {
+            if rect != item.rect() {
+                item.set_rect(rect);
+                item.layout(rect);
+            }
+        };
+        let limit = |rect: Rect| match model {
+            Some(model) => {
+                let (w, h) = model.clamp(rect.width(), rect.height());
+                Rect::new(rect.x(), rect.y(), w.max(1), h.max(1))
+            },
+            None => rect,
+        };
+
+        if let Some((w, h)) = self.sizes.get(&id) {
+            let rect = item.rect();
+            let (hw, hh) = item.size_hint();
+            // Fill takes whatever is left to the right or below,
+            // percentages are of the whole area. Margins keep both off the
+            // edges.
+            let area = model.map_or(area, |m| m.margin_or(0).shrink(area));
+            let room = |size: &Size, whole: u32, left: i32| match size {
+                Size::Fill => left.max(0) as u32,
+                _ => whole,
+            };
+            let width = w.resolve(room(w, area.width(), area.right() - rect.x()), hw);
+            let height = h.resolve(room(h, area.height(), area.bottom() - rect.y()), hh);
+            resize(item, Rect::new(rect.x(), rect.y(), width.max(1), height.max(1)));
+        }
+        let limited = limit(item.rect());
+        resize(item, limited);
+        if let Some(anchored) = self.anchors.get(&id) {
+            // stretching may take it past its limits again
+            let placed = limit(anchored.place(item.rect(), area));
+            resize(item, placed);
+        }
+    }
+}
+
+// Stacks its children one after another, vertically or horizontally
+pub struct BoxLayout {
+    name: String,
+    orientation: Orientation,
+    rect: Rect,
+    // set once somebody places the box explicitly, otherwise it takes
+    // whatever area its parent offers
+    fixed: bool,
+    spacing: i32,
+    padding: i32,
+    align: Align,
+    children: Vec<Box<dyn Widget>>,
+    // width and height of each child given them, None goes by its hint
+    sizes: Vec<Option<(Size, Size)>>,
+    models: Vec<BoxModel>,
+    // the theme's, for children without a margin of their own
+    margin: i32,
+}
+
+impl BoxLayout {
+    pub fn new(name: &str, orientation: Orientation) -> BoxLayout {
+        BoxLayout {
+            name: name.to_string(),
+            orientation,
+            rect: Rect::new(0, 0, 0, 0),
+            fixed: false,
+            spacing: 4,
+            padding: 4,
+            align: Align::Start,
+            children: Vec::new(),
+            sizes: Vec::new(),
+            models: Vec::new(),
+            margin: 0,
+        }
+    }
+
+    pub fn vertical(name: &str) -> BoxLayout {
+        BoxLayout::new(name, Orientation::Vertical)
+    }
+
+    pub fn horizontal(name: &str) -> BoxLayout {
+        BoxLayout::new(name, Orientation::Horizontal)
+    }
+
+    pub fn spacing(mut self, spacing: i32) -> BoxLayout {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn padding(mut self, padding: i32) -> BoxLayout {
+        self.padding = padding;
+        self
+    }
+
+    pub fn align(mut self, align: Align) -> BoxLayout {
+        self.align = align;
+        self
+    }
+
+    pub fn push<W: Widget>(&mut self, widget: W) {
+        self.push_boxed(Box::new(widget));
+    }
+
+    pub fn push_boxed(&mut self, widget: Box<dyn Widget>) {
+        self.children.push(widget);
+        self.sizes.push(None);
+        self.models.push(BoxModel::default());
+    }
+
+    pub fn with<W: Widget>(mut self, widget: W) -> BoxLayout {
+        self.push(widget);
+        self
+    }
+
+    // Adds a widget sized relative to the box rather than by its hint.
+    // Fill along the box takes a share of the leftover space like a
+    // stretch factor of 1, across it the full breadth.
+    pub fn push_sized<W: Widget>(&mut self, widget: W, width: Size, height: Size) {
+        self.children.push(Box::new(widget));
+        self.sizes.push(Some((width, height)));
+        self.models.push(BoxModel::default());
+    }
+
+    pub fn with_sized<W: Widget>(mut self, widget: W, width: Size, height: Size) -> BoxLayout {
+        self.push_sized(widget, width, height);
+        self
+    }
+
+    // Changes the size of the child at `idx`, None goes back to its hint
+    pub fn set_child_size(&mut self, idx: usize, size: Option<(Size, Size)>) {
+        if let Some(s) = self.sizes.get_mut(idx) {
+            *s = size;
+        }
+    }
+
+    // Adds a widget with its own margin, padding and size limits
+    pub fn with_model<W: Widget>(mut self, widget: W, model: BoxModel) -> BoxLayout {
+        self.push(widget);
+        self.models[self.children.len() - 1] = model;
+        self
+    }
+
+    // Takes effect from the next apply_theme on, as does the padding
+    pub fn set_child_model(&mut self, idx: usize, model: BoxModel) {
+        if let Some(m) = self.models.get_mut(idx) {
+            *m = model;
+        }
+    }
+
+    // Takes out every child
+    pub fn clear(&mut self) {
+        self.children.clear();
+        self.sizes.clear();
+        self.models.clear();
+    }
+
+    pub fn orientation(&self) -> Orientation { self.orientation }
+
+    // Splits a size into (main axis, cross axis) for this box
+    fn axes(&self, w: u32, h: u32) -> (i32, i32) {
+        match self.orientation {
+            Orientation::Vertical => (h as i32, w as i32),
+            Orientation::Horizontal => (w as i32, h as i32),
+        }
+    }
+
+    // Margins of the child at `idx` as (before, after) along the box and
+    // (before, after) across it
+    fn margins(&self, idx: usize) -> ((i32, i32), (i32, i32)) {
+        let m = self.models[idx].margin_or(self.margin);
+        match self.orientation {
+            Orientation::Vertical => ((m.top, m.bottom), (m.left, m.right)),
+            Orientation::Horizontal => ((m.left, m.right), (m.top, m.bottom)),
+        }
+    }
+
+    // Holds (main, cross) of the child at `idx` to its min and max size
+    fn clamp_axes(&self, idx: usize, main: i32, cross: i32) -> (i32, i32) {
+        let (w, h) = match self.orientation {
+            Orientation::Vertical => (cross, main),
+            Orientation::Horizontal => (main, cross),
+        };
+        let (w, h) = self.models[idx].clamp(w.max(0) as u32, h.max(0) as u32);
+        self.axes(w, h)
+    }
+
+    // Size hint of the child at `idx` as (main, cross), limits applied
+    fn child_hint(&self, idx: usize) -> (i32, i32) {
+        let (mut w, mut h) = self.children[idx].size_hint();
+        // only fixed sizes are known before there is a parent to go by
+        if let Some((sw, sh)) = self.sizes[idx] {
+            if let Size::Pixels(px) = sw {
+                w = px;
+            }
+            if let Size::Pixels(px) = sh {
+                h = px;
+            }
+        }
+        let (main, cross) = self.axes(w, h);
+        self.clamp_axes(idx, main, cross)
+    }
+
+    // Room the children ask for, spacing, padding and margins included
+    fn hint_axes(&self) -> (i32, i32) {
+        let mut main = 0;
+        let mut cross = 0;
+        for idx in 0..self.children.len() {
+            let (m, c) = self.child_hint(idx);
+            let ((mb, ma), (cb, ca)) = self.margins(idx);
+            main += m + mb + ma;
+            cross = cross.max(c + cb + ca);
+        }
+        main += self.spacing * (self.children.len() as i32 - 1).max(0) + 2 * self.padding;
+        (main, cross + 2 * self.padding)
+    }
+
+    // Main axis size, cross axis size and stretch factor of each child
+    // before the spare room is handed out, margins left out. None across
+    // means the hint.
+    fn child_sizes(&self, main_space: i32, cross_space: i32) -> Vec<(i32, Option<i32>, u32)> {
+        (0..self.children.len()).map(|idx| {
+            let stretch = self.children[idx].stretch();
+            let (hint_main, hint_cross) = self.child_hint(idx);
+            let Some((w, h)) = self.sizes[idx] else {
+                return (hint_main, None, stretch);
+            };
+            let ((mb, ma), (cb, ca)) = self.margins(idx);
+            let (main, cross) = match self.orientation {
+                Orientation::Vertical => (h, w),
+                Orientation::Horizontal => (w, h),
+            };
+            let (main, stretch) = match main {
+                Size::Fill => (0, stretch.max(1)),
+                size => (size.resolve((main_space - mb - ma).max(0) as u32, hint_main as u32) as i32, stretch),
+            };
+            let cross = cross.resolve((cross_space - cb - ca).max(0) as u32, hint_cross as u32) as i32;
+            (main, Some(cross), stretch)
+        }).collect()
+    }
+
+    fn arrange(&mut self) {
+        let inner_x = self.rect.x() + self.padding;
+        let inner_y = self.rect.y() + self.padding;
+        let (main_space, cross_space) = self.axes(self.rect.width(), self.rect.height());
+        let cross_space = (cross_space - 2 * self.padding).max(0);
+        let gaps = self.spacing * (self.children.len() as i32 - 1).max(0);
+        let sizes = self.child_sizes(main_space - 2 * self.padding - gaps, cross_space);
+
+        // whatever the children take leaves the rest to stretching ones
+        let taken: i32 = sizes.iter().enumerate().map(|(idx, (main, _, _))| {
+            let ((mb, ma), _) = self.margins(idx);
+            main + mb + ma
+        }).sum();
+        let mut spare = (main_space - 2 * self.padding - gaps - taken).max(0);
+        let mut total: u32 = sizes.iter().map(|(_, _, stretch)| stretch).sum();
+
+        let mut pos = 0;
+        for (idx, (mut main, sized_cross, stretch)) in sizes.into_iter().enumerate() {
+            let (_, hint_cross) = self.child_hint(idx);
+            let ((mb, ma), (cb, ca)) = self.margins(idx);
+            let room = (cross_space - cb - ca).max(0);
+            if stretch > 0 && total > 0 {
+                let extra = spare * stretch as i32 / total as i32;
+                main += extra;
+                spare -= extra;
+                total -= stretch;
+            }
+            // the slot keeps its length even if a max size holds the widget
+            // back from filling it
+            let slot = main;
+            let fill = sized_cross.is_none() && (self.align == Align::Stretch || self.children[idx].fills_cross());
+            let cross = match sized_cross {
+                Some(cross) => cross.min(room),
+                None if fill => room,
+                None => hint_cross,
+            };
+            let (main, cross) = self.clamp_axes(idx, main, cross);
+            let offset = cb + match self.align {
+                _ if fill => 0,
+                Align::Start | Align::Stretch => 0,
+                Align::Center => (room - cross) / 2,
+                Align::End => room - cross,
+            };
+
+            let rect = match self.orientation {
+                Orientation::Vertical => Rect::new(inner_x + offset, inner_y + pos + mb, cross.max(0) as u32, main.max(0) as u32),
+                Orientation::Horizontal => Rect::new(inner_x + pos + mb, inner_y + offset, main.max(0) as u32, cross.max(0) as u32),
+            };
+            let child = &mut self.children[idx];
+            child.set_rect(rect);
+            child.layout(rect);
+            pos += mb + slot + ma + self.spacing;
+        }
+    }
+}
+
+impl Drawable for BoxLayout {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        ctx.clipped(self.rect, |ctx| {
+            for child in self.children.iter() {
+                child.draw(ctx)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Widget for BoxLayout {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { self.rect }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        self.fixed = true;
+    }
+
+    fn size_hint(&self) -> (u32, u32) {
+        let (main, cross) = self.hint_axes();
+        match self.orientation {
+            Orientation::Vertical => (cross as u32, main as u32),
+            Orientation::Horizontal => (main as u32, cross as u32),
+        }
+    }
+
+    fn layout(&mut self, area: Rect) {
+        if !self.fixed {
+            self.rect = area;
+        }
+        self.arrange();
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.margin = theme.margin;
+        for (child, model) in self.children.iter_mut().zip(self.models.iter()) {
+            child.apply_theme(&model.theme_for(theme));
+        }
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] { &self.children }
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] { &mut self.children }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+// Places children in cells addressed by (row, col). Rows are as tall as their
+// tallest child, spare width is handed out to columns by stretch factor.
+pub struct Grid {
+    name: String,
+    rect: Rect,
+    fixed: bool,
+    cell_padding: i32,
+    stretch: Vec<u32>,
+    cells: Vec<(usize, usize)>,
+    children: Vec<Box<dyn Widget>>,
+    models: Vec<BoxModel>,
+    // the theme's, for children without a margin of their own
+    margin: i32,
+}
+
+impl Grid {
+    pub fn new(name: &str) -> Grid {
+        Grid {
+            name: name.to_string(),
+            rect: Rect::new(0, 0, 0, 0),
+            fixed: false,
+            cell_padding: 4,
+            stretch: Vec::new(),
+            cells: Vec::new(),
+            children: Vec::new(),
+            models: Vec::new(),
+            margin: 0,
+        }
+    }
+
+    pub fn cell_padding(mut self, padding: i32) -> Grid {
+        self.cell_padding = padding;
+        self
+    }
+
+    // Columns without a stretch factor keep their natural width
+    pub fn stretch(mut self, col: usize, factor: u32) -> Grid {
+        if self.stretch.len() <= col {
+            self.stretch.resize(col + 1, 0);
+        }
+        self.stretch[col] = factor;
+        self
+    }
+
+    pub fn place<W: Widget>(&mut self, row: usize, col: usize, widget: W) {
+        self.place_with_model(row, col, widget, BoxModel::default());
+    }
+
+    pub fn with<W: Widget>(mut self, row: usize, col: usize, widget: W) -> Grid {
+        self.place(row, col, widget);
+        self
+    }
+
+    // Places a widget with its own margin, padding and size limits
+    pub fn place_with_model<W: Widget>(&mut self, row: usize, col: usize, widget: W, model: BoxModel) {
+        self.cells.push((row, col));
+        self.children.push(Box::new(widget));
+        self.models.push(model);
+    }
+
+    pub fn with_model<W: Widget>(mut self, row: usize, col: usize, widget: W, model: BoxModel) -> Grid {
+        self.place_with_model(row, col, widget, model);
+        self
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        self.cells.iter().fold((0, 0), |(rows, cols), (r, c)| (rows.max(r + 1), cols.max(c + 1)))
+    }
+
+    // Natural row heights and column widths, padding and margins included
+    fn natural_sizes(&self) -> (Vec<i32>, Vec<i32>) {
+        let (rows, cols) = self.dimensions();
+        let mut heights = vec![0; rows];
+        let mut widths = vec![0; cols];
+        for (((row, col), child), model) in self.cells.iter().zip(self.children.iter()).zip(self.models.iter()) {
+            let (w, h) = child.size_hint();
+            let (w, h) = model.clamp(w, h);
+            let m = model.margin_or(self.margin);
+            widths[*col] = widths[*col].max(w as i32 + m.left + m.right + 2 * self.cell_padding);
+            heights[*row] = heights[*row].max(h as i32 + m.top + m.bottom + 2 * self.cell_padding);
+        }
+        (heights, widths)
+    }
+
+    fn arrange(&mut self) {
+        let (heights, mut widths) = self.natural_sizes();
+
+        let spare = self.rect.width() as i32 - widths.iter().sum::<i32>();
+        let total: u32 = self.stretch.iter().take(widths.len()).sum();
+        if spare > 0 && total > 0 {
+            for (col, w) in widths.iter_mut().enumerate() {
+                let factor = self.stretch.get(col).copied().unwrap_or(0);
+                *w += spare * factor as i32 / total as i32;
+            }
+        }
+
+        let offsets = |sizes: &[i32], start: i32| -> Vec<i32> {
+            sizes.iter().scan(start, |pos, size| {
+                let at = *pos;
+                *pos += size;
+                Some(at)
+            }).collect()
+        };
+        let xs = offsets(&widths, self.rect.x());
+        let ys = offsets(&heights, self.rect.y());
+
+        let pad = self.cell_padding;
+        for (((row, col), child), model) in self.cells.iter().zip(self.children.iter_mut()).zip(self.models.iter()) {
+            let (_, hint_h) = child.size_hint();
+            let m = model.margin_or(self.margin);
+            let w = (widths[*col] - 2 * pad - m.left - m.right).max(0) as u32;
+            let (w, h) = model.clamp(w, hint_h);
+            let rect = Rect::new(xs[*col] + pad + m.left, ys[*row] + pad + m.top, w, h);
+            child.set_rect(rect);
+            child.layout(rect);
+        }
+    }
+}
+
+impl Drawable for Grid {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        ctx.clipped(self.rect, |ctx| {
+            for child in self.children.iter() {
+                child.draw(ctx)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Widget for Grid {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { self.rect }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        self.fixed = true;
+    }
+
+    fn size_hint(&self) -> (u32, u32) {
+        let (heights, widths) = self.natural_sizes();
+        (widths.iter().sum::<i32>() as u32, heights.iter().sum::<i32>() as u32)
+    }
+
+    fn layout(&mut self, area: Rect) {
+        if !self.fixed {
+            self.rect = area;
+        }
+        self.arrange();
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.margin = theme.margin;
+        for (child, model) in self.children.iter_mut().zip(self.models.iter()) {
+            child.apply_theme(&model.theme_for(theme));
+        }
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] { &self.children }
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] { &mut self.children }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
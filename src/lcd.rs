@@ -0,0 +1,284 @@
+use std::any::Any;
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+
+use crate::draw::mix;
+use crate::{DrawCtx, Drawable, Role, Theme, ToolkitError, Widget};
+
+// Segments, one bit each: top, upper right, lower right, bottom, lower left,
+// upper left, middle
+const A: u8 = 0x01;
+const B: u8 = 0x02;
+const C: u8 = 0x04;
+const D: u8 = 0x08;
+const E: u8 = 0x10;
+const F: u8 = 0x20;
+const G: u8 = 0x40;
+// Width of a digit and thickness of a segment, as shares of its height
+const DIGIT_WIDTH: f32 = 0.55;
+const THICKNESS: f32 = 0.1;
+// How much of the field color shows through unlit segments
+const UNLIT_MIX: f32 = 0.85;
+
+fn segments(c: char) -> u8 {
+    match c {
+        '0' | 'O' => A | B | C | D | E | F,
+        '1' | 'I' => B | C,
+        '2' | 'Z' => A | B | D | E | G,
+        '3' => A | B | C | D | G,
+        '4' => B | C | F | G,
+        '5' | 'S' | 's' => A | C | D | F | G,
+        '6' => A | C | D | E | F | G,
+        '7' => A | B | C,
+        '8' => A | B | C | D | E | F | G,
+        '9' => A | B | C | D | F | G,
+        'A' | 'a' => A | B | C | E | F | G,
+        'B' | 'b' => C | D | E | F | G,
+        'C' => A | D | E | F,
+        'c' => D | E | G,
+        'D' | 'd' => B | C | D | E | G,
+        'E' | 'e' => A | D | E | F | G,
+        'F' | 'f' => A | E | F | G,
+        'H' => B | C | E | F | G,
+        'h' => C | E | F | G,
+        'L' | 'l' => D | E | F,
+        'o' => C | D | E | G,
+        'P' | 'p' => A | B | E | F | G,
+        'r' | 'R' => E | G,
+        'U' => B | C | D | E | F,
+        'u' => C | D | E,
+        '-' => G,
+        '_' => D,
+        _ => 0,
+    }
+}
+
+// One place on the display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    // segments and the decimal point after it
+    Digit(u8, bool),
+    Colon,
+}
+
+fn cells(text: &str) -> Vec<Cell> {
+    let mut cells = Vec::new();
+    for c in text.chars() {
+        match c {
+            // a point belongs to the digit before, if it has none yet
+            '.' | ',' => match cells.last_mut() {
+                Some(Cell::Digit(_, dot)) if !*dot => *dot = true,
+                _ => cells.push(Cell::Digit(0, true)),
+            },
+            ':' => cells.push(Cell::Colon),
+            c => cells.push(Cell::Digit(segments(c), false)),
+        }
+    }
+    cells
+}
+
+// Shows numbers (and the few letters that work) in seven segments, drawn
+// to whatever height the widget gets rather than from a font. Text is
+// right aligned over `digits` places, and what doesn't fit shows as dashes.
+pub struct LcdNumber {
+    name: String,
+    rect: Rect,
+    digits: usize,
+    text: String,
+    decimals: usize,
+    color: Option<Color>,
+    show_unlit: bool,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl LcdNumber {
+    pub fn new(name: &str, x: i32, y: i32, digits: usize, height: u32) -> LcdNumber {
+        let mut lcd = LcdNumber {
+            name: name.to_string(),
+            rect: Rect::new(x, y, 1, height.max(1)),
+            digits: digits.max(1),
+            text: String::new(),
+            decimals: 0,
+            color: None,
+            show_unlit: true,
+            theme: Theme::default(),
+            own_theme: false,
+        };
+        lcd.rect.set_width(lcd.natural_width(height as i32) as u32);
+        lcd
+    }
+
+    // Of set_value
+    pub fn decimals(mut self, decimals: usize) -> LcdNumber {
+        self.decimals = decimals;
+        self
+    }
+
+    // Lit segments, instead of the theme's active color
+    pub fn color(mut self, color: Color) -> LcdNumber {
+        self.color = Some(color);
+        self
+    }
+
+    // Unlit segments faintly drawn, as on a real display
+    pub fn show_unlit(mut self, show: bool) -> LcdNumber {
+        self.show_unlit = show;
+        self
+    }
+
+    pub fn text(&self) -> &str { &self.text }
+    pub fn digits(&self) -> usize { self.digits }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.fit();
+    }
+
+    pub fn set_value(&mut self, value: f64) {
+        self.text = format!("{:.*}", self.decimals, value);
+        self.fit();
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+    }
+
+    // Width of a digit and segment thickness at digit height `h`
+    fn metrics(h: i32) -> (i32, i32) {
+        ((h as f32 * DIGIT_WIDTH).round() as i32, ((h as f32 * THICKNESS).round() as i32).max(1))
+    }
+
+    // Colons take room besides the digits
+    fn natural_width(&self, height: i32) -> i32 {
+        let h = (height - 2 * self.theme.padding).max(0);
+        let (w, t) = LcdNumber::metrics(h);
+        let colons = self.shown().iter().filter(|c| **c == Cell::Colon).count() as i32;
+        self.digits as i32 * (w + 2 * t) + colons * 2 * t + 2 * self.theme.padding
+    }
+
+    // Grows the widget for text with more colons than it has room for
+    fn fit(&mut self) {
+        let w = self.natural_width(self.rect.height() as i32);
+        if w > self.rect.width() as i32 {
+            self.rect.set_width(w as u32);
+        }
+    }
+
+    // What is shown, dashes if the text needs more digits than there are
+    fn shown(&self) -> Vec<Cell> {
+        let cells = cells(&self.text);
+        let used = cells.iter().filter(|c| matches!(c, Cell::Digit(..))).count();
+        if used > self.digits {
+            return vec![Cell::Digit(G, false); self.digits];
+        }
+        cells
+    }
+}
+
+// A segment from `from` to `to`, pointed at both ends
+fn segment(ctx: &mut DrawCtx, from: Point, to: Point, t: i32, color: Color) -> Result<(), ToolkitError> {
+    let h = t / 2;
+    let points = if from.y() == to.y() {
+        let y = from.y();
+        [(from.x(), y), (from.x() + h, y - h), (to.x() - h, y - h), (to.x(), y), (to.x() - h, y + h), (from.x() + h, y + h)]
+    } else {
+        let x = from.x();
+        [(x, from.y()), (x + h, from.y() + h), (x + h, to.y() - h), (x, to.y()), (x - h, to.y() - h), (x - h, from.y() + h)]
+    };
+    let points: Vec<Point> = points.iter().map(|&p| Point::from(p)).collect();
+    ctx.fill_polygon(&points, color)
+}
+
+impl Debug for LcdNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LcdNumber")
+            .field("name", &self.name)
+            .field("rect", &self.rect)
+            .field("digits", &self.digits)
+            .field("text", &self.text)
+            .finish()
+    }
+}
+
+impl Drawable for LcdNumber {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let theme = &self.theme;
+        ctx.fill_rounded_rect(self.rect, theme.corner_radius, theme.field)?;
+        let pad = theme.padding;
+        let h = self.rect.height() as i32 - 2 * pad;
+        let (w, t) = LcdNumber::metrics(h);
+        if t < 2 {
+            return Ok(());
+        }
+        let lit = self.color.unwrap_or(theme.active);
+        let unlit = mix(lit, theme.field, UNLIT_MIX);
+        let gap = (t / 4).max(1);
+        let pitch = w + 2 * t;
+
+        // laid out from the right, so numbers line up
+        let mut x = self.rect.right() - pad;
+        let top = self.rect.y() + pad;
+        for cell in self.shown().iter().rev() {
+            match *cell {
+                Cell::Colon => {
+                    x -= 2 * t;
+                    for y in [top + h / 3, top + h * 2 / 3] {
+                        ctx.fill_rect(Rect::new(x + t / 2, y - t / 2, t as u32, t as u32), lit)?;
+                    }
+                },
+                Cell::Digit(mask, dot) => {
+                    x -= pitch;
+                    if dot {
+                        ctx.fill_rect(Rect::new(x + w + t / 2, top + h - t, t as u32, t as u32), lit)?;
+                    }
+                    let (l, r) = (x + t / 2, x + w - t / 2);
+                    let (tp, mid, bt) = (top + t / 2, top + h / 2, top + h - t / 2);
+                    let parts = [
+                        (A, (l + gap, tp), (r - gap, tp)),
+                        (B, (r, tp + gap), (r, mid - gap)),
+                        (C, (r, mid + gap), (r, bt - gap)),
+                        (D, (l + gap, bt), (r - gap, bt)),
+                        (E, (l, mid + gap), (l, bt - gap)),
+                        (F, (l, tp + gap), (l, mid - gap)),
+                        (G, (l + gap, mid), (r - gap, mid)),
+                    ];
+                    for (bit, from, to) in parts {
+                        let on = mask & bit != 0;
+                        if on || self.show_unlit {
+                            segment(ctx, from.into(), to.into(), t, if on { lit } else { unlit })?;
+                        }
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Widget for LcdNumber {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { self.rect }
+    fn role(&self) -> Role { Role::Meter }
+    fn access_value(&self) -> Option<String> { Some(self.text.clone()) }
+
+    fn set_rect(&mut self, rect: Rect) { self.rect = rect; }
+
+    fn size_hint(&self) -> (u32, u32) {
+        let h = self.rect.height() as i32;
+        (self.natural_width(h) as u32, h as u32)
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
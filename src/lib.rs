@@ -1,25 +1,178 @@
+use std::any::Any;
+use std::cell::{RefCell, RefMut};
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::fmt;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::str::Chars;
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
 
+use log::{debug, trace, warn};
 use thiserror::Error;
 
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use sdl2::pixels::Color;
-use sdl2::render::Texture;
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::{Cursor, MouseButton, SystemCursor};
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{BlendMode, Texture, TextureCreator};
+use sdl2::surface::Surface;
+use sdl2::sys::SDL_RendererFlags;
+use sdl2::ttf::FontStyle;
+use sdl2::video::WindowContext;
 
 use sdl2::video::WindowBuildError;
 use sdl2::IntegerOrSdlError;
 use sdl2::ttf::InitError;
-use sdl2::render::TextureValueError;
+use sdl2::render::{TargetRenderError, TextureValueError};
 use sdl2::ttf::FontError;
 
+mod builder;
+pub use builder::ToolkitBuilder;
+mod draw;
+pub use draw::DrawCtx;
+mod font;
+pub use font::{FontId, FontManager, FontSource, TextStyle};
+mod theme;
+pub use theme::Theme;
+mod widget;
+pub use widget::{DragData, Widget, WidgetState};
+use widget::WidgetPath;
+mod layout;
+pub use layout::{Align, Anchor, BoxLayout, BoxModel, Grid, Insets, Orientation, Size};
+use layout::{Anchored, Placements};
+mod text_input;
+pub use text_input::TextInput;
+mod validate;
+pub use validate::{InputMask, Validator};
+mod radio;
+pub use radio::{RadioButton, RadioGroup};
+mod check_box;
+pub use check_box::CheckBox;
+mod slider;
+pub use slider::Slider;
+mod list_box;
+pub use list_box::ListBox;
+mod table;
+pub use table::{Table, TableData, TableModel};
+mod combo_box;
+pub use combo_box::ComboBox;
+mod frame;
+pub use frame::Frame;
+mod scroll_area;
+pub use scroll_area::ScrollArea;
+mod splitter;
+pub use splitter::Splitter;
+mod dock;
+pub use dock::{DockArea, DockPanel, DockSide, PanelLayout};
+mod separator;
+pub use separator::{Separator, Spacer};
+mod spin_box;
+pub use spin_box::SpinBox;
+mod rich_text;
+pub use rich_text::{RichText, TextSpan};
+mod canvas;
+pub use canvas::{CanvasWidget, PaintFn, Painter};
+mod plot;
+pub use plot::{Plot, PlotKind, Series};
+mod gauge;
+pub use gauge::Gauge;
+mod lcd;
+pub use lcd::LcdNumber;
+mod log_view;
+pub use log_view::LogView;
+mod markdown;
+pub use markdown::MarkdownView;
+mod link;
+pub use link::Link;
+mod cached;
+pub use cached::CachedWidget;
+mod color_picker;
+pub use color_picker::ColorPicker;
+mod text_area;
+pub use text_area::TextArea;
+mod undo;
+mod dialog;
+pub use dialog::{DialogCallback, DialogHandle, DialogResult};
+use dialog::Dialog;
+mod file_dialog;
+pub use file_dialog::{FileCallback, FileFilter};
+mod file_browser;
+pub use file_browser::FileBrowser;
+mod image;
+pub use image::{Image, ScaleMode};
+mod text_cache;
+use text_cache::TextCache;
+mod timer;
+pub use timer::TimerId;
+use timer::Timers;
+mod menu;
+pub use menu::MenuItem;
+use menu::{Menu, MenuAction};
+mod window;
+pub use window::WindowId;
+use window::WindowData;
+mod app;
+pub use app::RunningApp;
+mod sender;
+pub use sender::{EventSender, Message};
+mod keys;
+pub use keys::Modifiers;
+mod anim;
+pub use anim::{AnimationId, Easing, Property, Transition};
+use anim::{Animations, TabSwitch};
+mod capture;
+pub use capture::ImageBuffer;
+mod inject;
+pub use inject::TestEvent;
+mod access;
+pub use access::{AccessAction, AccessNode, Role};
+mod debug;
+use debug::DebugOverlay;
+pub mod bind;
+use bind::Binding;
+mod immediate;
+pub use immediate::ImmediateUi;
+use immediate::Panel;
+mod busy;
+pub use busy::BusyGuard;
+use busy::BusyState;
+mod toast;
+pub use toast::{Severity, ToastId};
+use toast::Toast;
+mod click;
+use click::ClickCounter;
+mod touch;
+pub use touch::{Pinch, PinchCallback};
+use touch::Touch;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+#[cfg(feature = "gamepad")]
+use gamepad::Gamepads;
+#[cfg(feature = "opengl")]
+mod gl_view;
+#[cfg(feature = "opengl")]
+pub use gl_view::{gl_proc_address, GlFrame, GlPaintFn, GlViewport};
+#[cfg(feature = "ui")]
+pub mod ui;
+#[cfg(feature = "state")]
+mod state;
+#[cfg(feature = "state")]
+pub use state::{StateError, UiState, WindowState};
+#[cfg(feature = "settings")]
+mod settings;
+#[cfg(feature = "settings")]
+pub use settings::SettingsError;
+
 #[derive(Error, Debug)]
 pub enum ToolkitError {
     #[error("SDL Error: {0}")]
     SDLError(String),
     #[error("SDL_ttf input/output Error: {0}")]
-    TTFError(std::io::Error),
+    TTFError(#[source] std::io::Error),
     #[error("Integer overflow")]
     IntOverflow,
     #[error("TTF Context already initialized")]
@@ -31,6 +184,67 @@ pub enum ToolkitError {
 
     #[error("No tabs have been created")]
     NoTabs,
+    #[error("No tab named {0}")]
+    TabNotFound(String),
+    #[error("Font not found: {0}")]
+    FontNotFound(String),
+    #[error("Unsupported image format: {0}")]
+    UnsupportedImage(String),
+    #[error("No window with id {0}")]
+    WindowNotFound(u32),
+    #[error("The Toolkit has been dropped")]
+    Disconnected,
+
+    // The variants below wrap the error that caused them, the whole story
+    // is in source() or report()
+    #[error("Could not load font {}", path.display())]
+    FontLoadError {path: PathBuf, source: Box<ToolkitError>},
+    #[error("Could not load image {}", path.display())]
+    ImageLoadError {path: PathBuf, source: Box<ToolkitError>},
+    #[error("Could not create a texture for {widget}")]
+    TextureCreateError {widget: String, source: Box<ToolkitError>},
+    // anything else that failed while doing `context`
+    #[error("{context}")]
+    Context {context: String, source: Box<ToolkitError>},
+}
+
+impl ToolkitError {
+    pub fn context<S: Into<String>>(self, context: S) -> ToolkitError {
+        ToolkitError::Context {context: context.into(), source: Box::new(self)}
+    }
+
+    // This error and all its sources, outermost first, joined with ": "
+    pub fn report(&self) -> String {
+        let mut report = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(e) = source {
+            report.push_str(": ");
+            report.push_str(&e.to_string());
+            source = e.source();
+        }
+        report
+    }
+
+    pub(crate) fn texture_for(widget: &str) -> impl FnOnce(ToolkitError) -> ToolkitError + '_ {
+        move |e| ToolkitError::TextureCreateError {widget: widget.to_string(), source: Box::new(e)}
+    }
+}
+
+// Adds what was being done to an error on its way up, e.g.
+// `tk.set_font(f).context("switching to the large font")?`
+pub trait ErrorContext<T> {
+    fn context<S: Into<String>>(self, context: S) -> Result<T, ToolkitError>;
+    fn with_context<S: Into<String>, F: FnOnce() -> S>(self, f: F) -> Result<T, ToolkitError>;
+}
+
+impl<T, E: Into<ToolkitError>> ErrorContext<T> for Result<T, E> {
+    fn context<S: Into<String>>(self, context: S) -> Result<T, ToolkitError> {
+        self.map_err(|e| e.into().context(context))
+    }
+
+    fn with_context<S: Into<String>, F: FnOnce() -> S>(self, f: F) -> Result<T, ToolkitError> {
+        self.map_err(|e| e.into().context(f()))
+    }
 }
 
 impl From<ToolkitError> for String {
@@ -43,6 +257,13 @@ impl From<ToolkitError> for String {
             ToolkitError::NotMultOfTwo => "Input value not a multiple of two".to_string(),
             ToolkitError::InvalidText => "Invalid input text".to_string(),
             ToolkitError::NoTabs => "No tabs have been created".to_string(),
+            ToolkitError::TabNotFound(name) => format!("No tab named {}", name),
+            ToolkitError::FontNotFound(path) => format!("Font not found: {}", path),
+            ToolkitError::UnsupportedImage(path) => format!("Unsupported image format: {}", path),
+            ToolkitError::WindowNotFound(id) => format!("No window with id {}", id),
+            ToolkitError::Disconnected => "The Toolkit has been dropped".to_string(),
+            e @ (ToolkitError::FontLoadError {..} | ToolkitError::ImageLoadError {..}
+                | ToolkitError::TextureCreateError {..} | ToolkitError::Context {..}) => e.report(),
         }
     }
 }
@@ -87,6 +308,12 @@ impl From<FontError> for ToolkitError {
     }
 }
 
+impl From<TargetRenderError> for ToolkitError {
+    fn from(e: TargetRenderError) -> ToolkitError {
+        ToolkitError::SDLError(e.to_string())
+    }
+}
+
 impl From<TextureValueError> for ToolkitError {
     fn from(e: TextureValueError) -> ToolkitError {
         match e {
@@ -100,7 +327,7 @@ impl From<TextureValueError> for ToolkitError {
 
 // For almost everything we want to draw on the screen
 pub trait Drawable {
-    fn draw(&self) -> Result<(), ToolkitError>;
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError>;
 }
 
 impl Debug for dyn Drawable {
@@ -109,214 +336,3242 @@ impl Debug for dyn Drawable {
     }
 }
 
+// Where something drawn outside the tabs goes, bottom to top. Content sits
+// with the tab's widgets under the tab and menu bars, Overlay with the
+// widgets' open popups, Modal with the dialogs and Tooltip above menus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+    Background,
+    Content,
+    Overlay,
+    Modal,
+    Tooltip,
+}
+
+// A drawable added with Toolkit::add_drawable, kept sorted by layer and
+// z-index
+#[derive(Debug)]
+struct LayerItem {
+    id: WidgetId,
+    layer: Layer,
+    z: i32,
+    drawable: Box<dyn Drawable>,
+}
+
+// Where a text goes on the status bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusSegment {
+    Left,
+    Center,
+    Right,
+}
+
+// When RunningApp::tick repaints the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedrawMode {
+    // every tick, whether anything changed or not
+    EveryFrame,
+    // only after input, changes made through the Toolkit, or widgets asking
+    // for it
+    OnDemand,
+}
+
 #[derive(Debug, Clone, Copy)]
 enum ButtonType {
     Normal,
 }
 
-//#[derive(Debug)]
-struct Button<'a> {
-    name: &'static str,
+// What a button looks like right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonState {
+    Normal,
+    Hover,
+    Pressed,
+    Disabled,
+}
+
+pub struct Button {
+    name: String,
     x: i32,
     y: i32,
     w: i32,
     h: i32,
     typ: ButtonType,
-    text: Texture<'a>,
+    label: String,
+    font: FontId,
+    text: Rc<Texture<'static>>,
+    // drawn left of the text at icon_size, scaled if need be
+    icon: Option<Texture<'static>>,
+    icon_size: (u32, u32),
+    icon_spacing: i32,
+    state: WidgetState,
+    theme: Theme,
+    // keep `theme` when the global one changes
+    own_theme: bool,
 }
 
-impl Button<'_> {
-    fn name(&self) -> &'static str { self.name }
+impl Button {
     fn x(&self) -> i32 { self.x }
     fn y(&self) -> i32 { self.y }
     fn w(&self) -> i32 { self.w }
     fn h(&self) -> i32 { self.h }
     fn typ(&self) -> ButtonType { self.typ }
 
-    fn new<'a>(tk: &'a Toolkit, name: &'static str, x: i32, y: i32) -> Result<Button<'a>, ToolkitError> {
-        let texture = tk.render_text(name)?;
-        let attr = texture.query();
-        Ok(Button {
-            name: name,
-            x: x,
-            y: y,
-            w: attr.width as i32,
-            h: attr.height as i32,
+    pub fn new(text: &TextRenderer, name: &str, x: i32, y: i32) -> Result<Button, ToolkitError> {
+        let mut btn = Button {
+            name: name.to_string(),
+            x,
+            y,
+            w: 0,
+            h: 0,
             typ: ButtonType::Normal,
-            text: texture,
-        })
+            label: name.to_string(),
+            font: FontId::DEFAULT,
+            text: text.render(name).map_err(ToolkitError::texture_for(name))?,
+            icon: None,
+            icon_size: (text.height() as u32, text.height() as u32),
+            icon_spacing: 4,
+            state: WidgetState::default(),
+            theme: Theme::default(),
+            own_theme: false,
+        };
+        (btn.w, btn.h) = btn.natural_size();
+        Ok(btn)
+    }
+
+    // Width and height of icon plus text, without padding
+    fn content_size(&self) -> (i32, i32) {
+        let attr = self.text.query();
+        let (mut w, mut h) = (attr.width as i32, attr.height as i32);
+        if self.icon.is_some() {
+            let (iw, ih) = self.icon_size;
+            w += iw as i32 + self.icon_spacing;
+            h = h.max(ih as i32);
+        }
+        (w, h)
+    }
+
+    fn natural_size(&self) -> (i32, i32) {
+        let (w, h) = self.content_size();
+        let pad = self.theme.padding;
+        (w + 2 * pad, h + 2 * pad)
+    }
+
+    // Changes the text shown, the name the button is known by stays the same
+    pub fn set_label(&mut self, text: &TextRenderer, label: &str) -> Result<(), ToolkitError> {
+        self.text = text.render_font(self.font, label).map_err(ToolkitError::texture_for(&self.name))?;
+        self.label = label.to_string();
+        (self.w, self.h) = self.natural_size();
+        Ok(())
+    }
+
+    pub fn label(&self) -> &str { &self.label }
+    pub fn font(&self) -> FontId { self.font }
+
+    pub fn set_icon(&mut self, icon: Option<Texture<'static>>) {
+        self.icon = icon;
+        (self.w, self.h) = self.natural_size();
+    }
+
+    // Size the icon is drawn at, defaults to the height of a line of text
+    pub fn set_icon_size(&mut self, w: u32, h: u32) {
+        self.icon_size = (w, h);
+        (self.w, self.h) = self.natural_size();
+    }
+
+    // Gap between icon and text
+    pub fn set_icon_spacing(&mut self, spacing: i32) {
+        self.icon_spacing = spacing;
+        (self.w, self.h) = self.natural_size();
+    }
+
+    pub fn with_icon(mut self, icon: Texture<'static>) -> Button {
+        self.set_icon(Some(icon));
+        self
+    }
+
+    pub fn has_icon(&self) -> bool { self.icon.is_some() }
+
+    // Pressed only shows while the pointer is still over the button, as
+    // that's when letting go would click it
+    pub fn state(&self) -> ButtonState {
+        let state = &self.state;
+        match (state.hovered, state.pressed) {
+            _ if !state.enabled => ButtonState::Disabled,
+            (true, true) => ButtonState::Pressed,
+            (true, false) => ButtonState::Hover,
+            _ => ButtonState::Normal,
+        }
+    }
+
+    // Gives this button its own look, unaffected by Toolkit::set_theme
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.set_theme_inner(theme);
+    }
+
+    fn set_theme_inner(&mut self, theme: Theme) {
+        self.theme = theme;
+        (self.w, self.h) = self.natural_size();
     }
 }
 
-impl Debug for Button<'_> {
+impl Debug for Button {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Button")
             .field("name", &self.name())
             .field("x", &self.x())
             .field("y", &self.y())
+            .field("w", &self.w())
+            .field("h", &self.h())
             .field("type", &self.typ())
+            .field("state", &self.state())
             .finish()
     }
 }
 
-impl Drawable for Button<'_> {
-    fn draw(&self) -> Result<(), ToolkitError> {
-        println!("Drawing button {}", self.name());
+impl Drawable for Button {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let rect = self.rect();
+        let theme = &self.theme;
+        let (fill, text) = match self.state() {
+            ButtonState::Normal => (theme.fill, theme.text),
+            ButtonState::Hover => (theme.hover, theme.text),
+            ButtonState::Pressed => (theme.active, theme.text),
+            ButtonState::Disabled => (theme.fill, theme.disabled),
+        };
+        ctx.fill_rounded_rect(rect, theme.corner_radius, fill)?;
+        let border = if self.state.focused { theme.active } else { theme.border };
+        ctx.draw_rounded_rect(rect, theme.corner_radius, border)?;
+        let Some(icon) = self.icon.as_ref() else {
+            ctx.tint(&self.text, text);
+            ctx.copy_centered(&self.text, rect)?;
+            return Ok(());
+        };
+
+        // icon and text are centered together as one block
+        let (cw, _) = self.content_size();
+        let (iw, ih) = self.icon_size;
+        let x = rect.center().x() - cw / 2;
+        let icon_rect = Rect::new(x, rect.center().y() - ih as i32 / 2, iw, ih);
+        ctx.canvas().copy(icon, None, icon_rect)?;
+
+        let text_x = x + iw as i32 + self.icon_spacing;
+        let text_w = (self.text.query().width as i32).min(rect.right() - text_x).max(0);
+        let text_rect = Rect::new(text_x, rect.y(), text_w as u32, rect.height());
+        ctx.tint(&self.text, text);
+        ctx.copy_centered(&self.text, text_rect)?;
         Ok(())
     }
 }
 
-#[derive(Debug)]
-struct Tab<'a> {
-    items: Vec<Button<'a>>,
-    item_pos: usize,
-    name: &'static str,
+impl Widget for Button {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { Rect::new(self.x, self.y, self.w as u32, self.h as u32) }
+    fn role(&self) -> Role { Role::Button }
+    fn access_label(&self) -> Option<String> { Some(self.label.clone()).filter(|l| !l.is_empty()) }
+    fn clickable(&self) -> bool { true }
+    fn focusable(&self) -> bool { true }
+    fn widget_state(&self) -> Option<&WidgetState> { Some(&self.state) }
+    fn widget_state_mut(&mut self) -> Option<&mut WidgetState> { Some(&mut self.state) }
+
+    fn size_hint(&self) -> (u32, u32) {
+        let (w, h) = self.natural_size();
+        (w as u32, h as u32)
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.x = rect.x();
+        self.y = rect.y();
+        self.w = rect.width() as i32;
+        self.h = rect.height() as i32;
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.set_theme_inner(*theme);
+        }
+    }
+
+    fn set_font(&mut self, text: &TextRenderer, font: FontId) -> Result<(), ToolkitError> {
+        self.font = font;
+        let label = self.label.clone();
+        self.set_label(text, &label)
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+// How windows are drawn, picked with ToolkitBuilder::renderer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RendererKind {
+    // on the GPU, or in software if there is none to be had
+    #[default]
+    Accelerated,
+    Software,
+}
+
+// What the Toolkit ended up drawing with, see Toolkit::renderer_info
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RendererInfo {
+    // SDL's name for it, e.g. "opengl" or "software"
+    pub renderer: String,
+    pub video_driver: String,
+    pub software: bool,
+    pub vsync: bool,
+    pub max_texture_size: (u32, u32),
+    // an accelerated renderer was wanted but couldn't be had
+    pub software_fallback: bool,
+    pub font: FontSource,
+    // the font asked for didn't load and the embedded one stands in
+    pub font_fallback: bool,
+}
+
+// Handle to a widget added to a tab, stays valid for as long as the widget
+// is around
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WidgetId(u64);
+
+// What happened during a tick, for polling instead of registering callbacks.
+// Widgets nested in a container report the id of the tab item holding them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UiEvent {
+    ButtonClicked {id: WidgetId, name: String},
+    // sliders, lists and combo boxes
+    ValueChanged {id: WidgetId, name: String},
+    TextChanged {id: WidgetId, name: String, text: String},
+    TabChanged(String),
+    // name of the menu item picked
+    MenuSelected(String),
+    // dragged in from another application
+    FileDropped(PathBuf),
+    TextDropped(String),
+    // a drag between widgets that the target accepted
+    DragDropped {source: String, target: String},
+    // the second press of a double click, on any widget
+    DoubleClicked {id: WidgetId, name: String},
+    // a link in a widget like MarkdownView, with where it points
+    LinkClicked {id: WidgetId, name: String, url: String},
+    #[cfg(feature = "gamepad")]
+    ControllerButton {button: sdl2::controller::Button, pressed: bool},
+    #[cfg(feature = "gamepad")]
+    ControllerAxis {axis: sdl2::controller::Axis, value: i16},
+    WindowResized(u32, u32),
+    Quit,
+}
+
+struct Tab {
+    items: Vec<Box<dyn Widget>>,
+    // id of each entry in `items`
+    ids: Vec<WidgetId>,
+    name: String,
+    label: Rc<Texture<'static>>,
 }
 
-impl Tab<'_> {
-    fn new(name: &'static str) -> Tab {
-        Tab {
+impl Tab {
+    fn new(text: &TextRenderer, name: &str) -> Result<Tab, ToolkitError> {
+        Ok(Tab {
             items: Vec::new(),
-            item_pos: 0,
-            name: name,
+            ids: Vec::new(),
+            name: name.to_string(),
+            label: text.render(name).map_err(ToolkitError::texture_for(name))?,
+        })
+    }
+
+    fn name(&self) -> &str { &self.name }
+
+    // Open popups catch input before the widgets underneath them
+    fn hit(&self, x: i32, y: i32) -> Option<WidgetPath> {
+        widget::overlay_hit(&self.items, x, y).or_else(|| widget::hit_path(&self.items, x, y))
+    }
+
+    fn get(&self, path: &[usize]) -> Option<&dyn Widget> {
+        widget::get(&self.items, path)
+    }
+
+    fn get_mut(&mut self, path: &[usize]) -> Option<&mut dyn Widget> {
+        widget::get_mut(&mut self.items, path)
+    }
+
+    fn position(&self, id: WidgetId) -> Option<usize> {
+        self.ids.iter().position(|i| *i == id)
+    }
+
+    // Drops every widget, their textures go with them
+    fn clear(&mut self) -> Vec<Box<dyn Widget>> {
+        self.ids.clear();
+        std::mem::take(&mut self.items)
+    }
+
+    fn remove(&mut self, pos: usize) -> Box<dyn Widget> {
+        self.ids.remove(pos);
+        self.items.remove(pos)
+    }
+
+    // Scrolls every scroll area the widget sits in so that it can be seen,
+    // innermost first
+    fn reveal(&mut self, path: &[usize]) {
+        let Some(target) = self.get(path).map(|w| w.rect()) else {
+            return;
+        };
+        for depth in (1..path.len()).rev() {
+            let Some(area) = self.get_mut(&path[..depth]).and_then(|w| w.as_any_mut().downcast_mut::<ScrollArea>()) else {
+                continue;
+            };
+            area.scroll_into_view(target);
+        }
+    }
+
+    // Sorts the items by z-index, keeping the order they were added in
+    // among equals. Returns where each item went if any moved.
+    fn restack(&mut self, z_index: &HashMap<WidgetId, i32>) -> Option<Vec<usize>> {
+        let items = std::mem::take(&mut self.items);
+        let ids = std::mem::take(&mut self.ids);
+        let mut entries: Vec<_> = items.into_iter().zip(ids).enumerate().collect();
+        entries.sort_by_key(|(_, (_, id))| z_index.get(id).copied().unwrap_or(0));
+
+        let mut moved = vec![0; entries.len()];
+        for (new, (old, (item, id))) in entries.into_iter().enumerate() {
+            moved[old] = new;
+            self.items.push(item);
+            self.ids.push(id);
+        }
+        moved.iter().enumerate().any(|(old, new)| old != *new).then_some(moved)
+    }
+
+    // Leaves out the items that are fully see-through
+    fn draw_with(&self, ctx: &mut DrawCtx, opacity: &HashMap<WidgetId, u8>) -> Result<(), ToolkitError> {
+        for (item, id) in self.items.iter().zip(self.ids.iter()) {
+            let drawn = match opacity.get(id) {
+                Some(0) => continue,
+                Some(&alpha) => ctx.draw_faded(alpha, |ctx| item.draw(ctx)),
+                None => item.draw(ctx),
+            };
+            drawn.with_context(|| format!("drawing {}", item.name()))?;
         }
+        Ok(())
+    }
+
+    // Items the Toolkit has placements for get sized and moved after they
+    // laid themselves out
+    fn layout(&mut self, area: Rect, placements: &Placements) {
+        for (item, id) in self.items.iter_mut().zip(self.ids.iter()) {
+            item.layout(area);
+            placements.place(*id, item.as_mut(), area);
+        }
+    }
+}
+
+impl Debug for Tab {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tab")
+            .field("name", &self.name())
+            .field("items", &self.items)
+            .finish()
     }
-    fn name(&self) -> &'static str { self.name }
 }
 
-impl Drawable for Tab<'_> {
-    fn draw(&self) -> Result<(), ToolkitError> {
-        for button in self.items.iter() {
-            button.draw()?;
+impl Drawable for Tab {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        for widget in self.items.iter() {
+            widget.draw(ctx)?;
         }
         Ok(())
     }
 }
 
-pub struct Toolkit<'a> {
-    tabs: Vec<Tab<'a>>,
+pub type Callback = Box<dyn FnMut(&mut Toolkit)>;
+pub type DropCallback = Box<dyn FnMut(&mut Toolkit, &Dropped)>;
+// false keeps the app running
+pub type CloseCallback = Box<dyn FnMut(&mut Toolkit) -> bool>;
+// true if the target took what was dragged onto it
+pub type DragCallback = Box<dyn FnMut(&mut Toolkit, &DragData) -> bool>;
+
+// What was dropped on the window by another application
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Dropped {
+    File(PathBuf),
+    Text(String),
+}
+
+// How long an OnDemand tick with nothing to draw takes at least, about one
+// frame at 60 FPS
+const IDLE_FRAME: Duration = Duration::from_micros(16_667);
+// Longest RunningApp::run sleeps waiting for input with nothing else to do
+const MAX_WAIT: Duration = Duration::from_secs(1);
+
+// Pixels the pointer has to travel with the button down before a drag starts
+const DRAG_THRESHOLD: i32 = 4;
+
+// Owns everything needed to turn strings into textures. Kept apart from the
+// rest of the Toolkit so widgets can be built while the tabs are borrowed.
+pub struct TextRenderer {
+    fonts: Rc<FontManager>,
+    creator: &'static TextureCreator<WindowContext>,
+    // render() takes &self, so widgets can use it while drawing
+    cache: RefCell<TextCache>,
+    // rounded rect corners by radius and whether they are filled
+    corners: RefCell<HashMap<(i32, bool), Rc<Texture<'static>>>>,
+    // offscreen target the size of the window, for drawing see-through
+    layer: RefCell<Option<Texture<'static>>>,
+}
+
+impl TextRenderer {
+    fn new(fonts: Rc<FontManager>, creator: &'static TextureCreator<WindowContext>) -> TextRenderer {
+        TextRenderer {
+            fonts,
+            creator,
+            cache: RefCell::new(TextCache::new()),
+            corners: RefCell::new(HashMap::new()),
+            layer: RefCell::new(None),
+        }
+    }
+
+    pub fn fonts(&self) -> &FontManager { &self.fonts }
+
+    // Rendered white so DrawCtx::tint can recolor it to any theme
+    pub fn render(&self, input: &str) -> Result<Rc<Texture<'static>>, ToolkitError> {
+        self.render_font(FontId::DEFAULT, input)
+    }
+
+    pub fn render_font(&self, font: FontId, input: &str) -> Result<Rc<Texture<'static>>, ToolkitError> {
+        self.render_color_font(font, input, Color::RGBA(255, 255, 255, 255))
+    }
+
+    pub fn render_color(&self, input: &str, color: Color) -> Result<Rc<Texture<'static>>, ToolkitError> {
+        self.render_color_font(FontId::DEFAULT, input, color)
+    }
+
+    // Repeated strings come out of the cache instead of being rendered again
+    pub fn render_color_font(&self, font: FontId, input: &str, color: Color) -> Result<Rc<Texture<'static>>, ToolkitError> {
+        if let Some(texture) = self.cache.borrow_mut().get(input, color, font) {
+            return Ok(texture);
+        }
+        let surface = match self.render_runs(font, &[(input, color, FontStyle::NORMAL)])? {
+            Some(surface) => surface,
+            // lets SDL_ttf report empty strings the way it always did
+            None => self.fonts.with_font(font, 0, FontStyle::NORMAL, |f| f.render(input).blended(color))?,
+        };
+        let texture = Rc::new(self.creator.create_texture_from_surface(&surface)?);
+        self.cache.borrow_mut().insert(input, color, font, texture.clone());
+
+        Ok(texture)
+    }
+
+    // How many rendered strings to keep, 0 turns the cache off
+    pub fn set_cache_size(&self, size: usize) {
+        self.cache.borrow_mut().set_capacity(size);
+    }
+
+    pub fn cache_size(&self) -> usize {
+        self.cache.borrow().capacity()
+    }
+
+    pub fn cached(&self) -> usize {
+        self.cache.borrow().len()
+    }
+
+    pub fn flush_cache(&self) {
+        self.cache.borrow_mut().clear();
+    }
+
+    // Uploads any surface, e.g. a loaded image, with the same renderer
+    pub fn texture_from_surface(&self, surface: &Surface) -> Result<Texture<'static>, ToolkitError> {
+        Ok(self.creator.create_texture_from_surface(surface)?)
+    }
+
+    // Spans laid side by side on one line, in their own colors. None when
+    // there is nothing to show.
+    pub fn render_spans(&self, font: FontId, spans: &[TextSpan]) -> Result<Option<Texture<'static>>, ToolkitError> {
+        let pieces: Vec<(&str, Color, FontStyle)> = spans.iter()
+            .map(|span| (span.text.as_str(), span.color, span.style()))
+            .collect();
+        let Some(line) = self.render_runs(font, &pieces)? else {
+            return Ok(None);
+        };
+        let mut texture = self.creator.create_texture_from_surface(&line)?;
+        texture.set_blend_mode(BlendMode::Blend);
+        Ok(Some(texture))
+    }
+
+    // Renders pieces of text with their own color and style onto one line,
+    // each with whatever fonts it takes, lined up on a common baseline
+    fn render_runs(&self, font: FontId, pieces: &[(&str, Color, FontStyle)]) -> Result<Option<Surface<'static>>, ToolkitError> {
+        let mut parts = Vec::new();
+        for (text, color, style) in pieces.iter() {
+            for (idx, run) in self.fonts.runs(font, text) {
+                let (part, ascent) = self.fonts.with_font(font, idx, *style, |f| (f.render(&run).blended(*color), f.ascent()));
+                parts.push((part?, ascent));
+            }
+        }
+        if parts.len() <= 1 {
+            return Ok(parts.pop().map(|(part, _)| part));
+        }
+
+        let ascent = parts.iter().map(|(_, a)| *a).max().unwrap_or(0);
+        let w = parts.iter().map(|(p, _)| p.width()).sum();
+        let h = parts.iter().map(|(p, a)| (ascent - a) as u32 + p.height()).max().unwrap_or(0);
+        let mut line = Surface::new(w, h, PixelFormatEnum::ARGB8888)?;
+        let mut x = 0;
+        for (mut part, a) in parts {
+            // copy the alpha over instead of blending it onto nothing
+            part.set_blend_mode(BlendMode::None)?;
+            let (pw, ph) = (part.width(), part.height());
+            part.blit(None, &mut line, Rect::new(x, ascent - a, pw, ph))?;
+            x += pw as i32;
+        }
+        Ok(Some(line))
+    }
+
+    pub(crate) fn corner(&self, radius: i32, filled: bool) -> Result<Rc<Texture<'static>>, ToolkitError> {
+        if let Some(texture) = self.corners.borrow().get(&(radius, filled)) {
+            return Ok(texture.clone());
+        }
+        let surface = draw::corner_surface(radius, filled)?;
+        let mut texture = self.creator.create_texture_from_surface(&surface)?;
+        texture.set_blend_mode(BlendMode::Blend);
+        let texture = Rc::new(texture);
+        self.corners.borrow_mut().insert((radius, filled), texture.clone());
+        Ok(texture)
+    }
+
+    // The offscreen layer, remade when the window size changed. None while
+    // it is already being drawn into.
+    pub(crate) fn layer(&self, width: u32, height: u32) -> Result<Option<RefMut<'_, Texture<'static>>>, ToolkitError> {
+        let Ok(mut layer) = self.layer.try_borrow_mut() else {
+            return Ok(None);
+        };
+        let fits = layer.as_ref().is_some_and(|t| {
+            let attr = t.query();
+            attr.width == width && attr.height == height
+        });
+        if !fits {
+            let mut texture = self.creator.create_texture_target(PixelFormatEnum::ARGB8888, width, height)?;
+            texture.set_blend_mode(BlendMode::Blend);
+            *layer = Some(texture);
+        }
+        Ok(RefMut::filter_map(layer, |l| l.as_mut()).ok())
+    }
+
+    pub fn size_of(&self, input: &str) -> Result<(u32, u32), ToolkitError> {
+        self.size_of_font(FontId::DEFAULT, input)
+    }
+
+    pub fn size_of_font(&self, font: FontId, input: &str) -> Result<(u32, u32), ToolkitError> {
+        self.size_of_styled(font, input, FontStyle::NORMAL)
+    }
+
+    fn size_of_styled(&self, font: FontId, input: &str, style: FontStyle) -> Result<(u32, u32), ToolkitError> {
+        let runs = self.fonts.runs(font, input);
+        if runs.is_empty() {
+            return Ok(self.fonts.with_font(font, 0, style, |f| f.size_of(input))?);
+        }
+        let (mut w, mut h) = (0, 0);
+        for (idx, run) in runs.iter() {
+            let (rw, rh) = self.fonts.with_font(font, *idx, style, |f| f.size_of(run))?;
+            w += rw;
+            h = h.max(rh);
+        }
+        Ok((w, h))
+    }
+
+    // x of every char boundary of `input`, starting with 0. Glyph advances
+    // add up within a run and each run ends where size_of says, so kerning
+    // only shifts the chars in the middle of a run.
+    pub(crate) fn char_offsets(&self, font: FontId, input: &str, style: FontStyle) -> Result<Vec<i32>, ToolkitError> {
+        let mut xs = vec![0];
+        // runs leave NULs out, they take no room
+        let mut chars = input.chars().peekable();
+        let skip_nuls = |chars: &mut Peekable<Chars>, xs: &mut Vec<i32>| {
+            while chars.next_if_eq(&'\0').is_some() {
+                xs.push(xs[xs.len() - 1]);
+            }
+        };
+        for (idx, run) in self.fonts.runs(font, input) {
+            let start = xs[xs.len() - 1];
+            let mut x = start;
+            let end = self.fonts.with_font(font, idx, style, |f| {
+                for c in run.chars() {
+                    skip_nuls(&mut chars, &mut xs);
+                    chars.next();
+                    x += match f.find_glyph_metrics(c) {
+                        Some(metrics) => metrics.advance,
+                        None => f.size_of_char(c)?.0 as i32,
+                    };
+                    xs.push(x);
+                }
+                f.size_of(&run).map(|(w, _)| start + w as i32)
+            })?;
+            if let Some(last) = xs.last_mut() {
+                *last = end;
+            }
+        }
+        skip_nuls(&mut chars, &mut xs);
+        Ok(xs)
+    }
+
+    // x of every char boundary of spans laid out as render_spans does
+    pub(crate) fn span_offsets(&self, font: FontId, spans: &[TextSpan]) -> Result<Vec<i32>, ToolkitError> {
+        let mut xs = vec![0];
+        for span in spans {
+            let start = xs[xs.len() - 1];
+            let offsets = self.char_offsets(font, &span.text, span.style())?;
+            xs.extend(offsets[1..].iter().map(|x| start + x));
+        }
+        Ok(xs)
+    }
+
+    // Size of text that may span several lines, as laid out by RichText or a
+    // TextArea. Lines are one font height apart.
+    pub fn measure(&self, font: FontId, input: &str) -> Result<(u32, u32), ToolkitError> {
+        let mut w = 0;
+        for line in input.split('\n') {
+            w = w.max(self.size_of_font(font, line)?.0);
+        }
+        let lines = input.split('\n').count() as u32;
+        Ok((w, lines * self.font_height(font) as u32))
+    }
+
+    // Size of text word wrapped to `width`, the same way TextArea does it
+    pub fn measure_wrapped(&self, font: FontId, input: &str, width: u32) -> Result<(u32, u32), ToolkitError> {
+        let lines = text_area::wrap(self, font, input, width as i32)?;
+        let mut w = 0;
+        for line in lines.iter() {
+            w = w.max(self.size_of_font(font, input[line.clone()].trim_end_matches(' '))?.0);
+        }
+        Ok((w, lines.len() as u32 * self.font_height(font) as u32))
+    }
+
+    pub fn height(&self) -> i32 {
+        self.fonts.height(FontId::DEFAULT)
+    }
+
+    pub fn font_height(&self, font: FontId) -> i32 {
+        self.fonts.height(font)
+    }
+}
+
+pub struct Toolkit {
+    tabs: Vec<Tab>,
     tab_pos: usize,
-    items: Vec<Box<dyn Drawable>>,
+    items: Vec<LayerItem>,
+    // sizes, anchors and box models of tab items
+    placements: Placements,
+    // stacking of tab items, higher ones are drawn over and hit before the
+    // others. Missing ones are at 0.
+    z_index: HashMap<WidgetId, i32>,
     run: bool,
 
-    ctx: sdl2::Sdl,
+    mouse: (i32, i32),
+    mouse_inside: bool,
+    hover: Option<WidgetPath>,
+    pressed: Option<WidgetPath>,
+    focus: Option<WidgetPath>,
+    clicks: Vec<String>,
+    // everything that happened during the current tick
+    ui_events: Vec<UiEvent>,
+    callbacks: HashMap<String, Vec<Callback>>,
+    change_callbacks: HashMap<String, Vec<Callback>>,
+    double_click_callbacks: HashMap<String, Vec<Callback>>,
+    click_counter: ClickCounter,
+    // widgets that accept drops, by name
+    drop_callbacks: HashMap<String, Vec<DropCallback>>,
+    // highlighted while a drop is coming in
+    drop_target: Option<WidgetPath>,
+    // asked before quitting, any of them can keep the app running
+    close_callbacks: Vec<CloseCallback>,
+    touch: Touch,
+    pinch_callbacks: Vec<PinchCallback>,
+    // global shortcuts, shared between the windows. Kept in an Rc so one can
+    // unbind itself while running.
+    bindings: HashMap<(Keycode, Modifiers), Rc<RefCell<Callback>>>,
+    // widgets things can be dragged out of, and ones that take them
+    draggable: HashSet<String>,
+    drag_callbacks: HashMap<String, Vec<DragCallback>>,
+    // draggable widget pressed and where, until the pointer moves far enough
+    drag_press: Option<(WidgetPath, i32, i32)>,
+    drag: Option<DragData>,
+    radio_groups: HashMap<String, RadioGroup>,
+    next_id: u64,
+    redraw_mode: RedrawMode,
+    // minimum time per tick, from the target FPS
+    frame_budget: Option<Duration>,
+    last_frame: Instant,
+    delta: Duration,
+    // something changed since the last repaint
+    dirty: bool,
+    // pointer shape the hovered widget asked for, kept alive while it shows
+    cursor: Option<(SystemCursor, Cursor)>,
+    timers: Timers,
+    animations: Animations,
+    // see-through top level widgets, the rest are drawn opaque
+    opacity: HashMap<WidgetId, u8>,
+    transition: Transition,
+    transition_time: Duration,
+    tab_switch: Option<TabSwitch>,
+    // messages from EventSenders, and the SDL event type that announces them
+    messages: Receiver<Message>,
+    sender: EventSender,
+    // open modal dialogs, the last one is on top and gets the input
+    dialogs: Vec<Dialog>,
+    // properties bound to widgets, see bind.rs
+    property_bindings: Vec<Binding>,
+    // state of the panels filled by Toolkit::immediate, by name
+    immediate_panels: HashMap<String, Panel>,
+    // shared by all windows, which all spin and ignore input while busy
+    busy_state: BusyState,
+    // notifications of the current window, oldest first
+    toasts: Vec<Toast>,
+    toast_corner: Anchor,
+    // open popup menu, above even the dialogs
+    menu: Option<Menu>,
+    // pull-down menus along the top of the window
+    menu_bar: Vec<MenuItem>,
+    // status bar texts by StatusSegment, None while the bar is hidden
+    status: Option<[String; 3]>,
+    // menus opened by right-clicking the widget they're named after
+    context_menus: HashMap<String, Vec<MenuItem>>,
+
+    sdl: sdl2::Sdl,
     video: sdl2::VideoSubsystem,
+    #[cfg(feature = "gamepad")]
+    gamepads: Gamepads,
+    // the window the fields above and canvas/text below belong to, and
+    // every other window parked
+    window: WindowId,
+    windows: Vec<WindowData>,
+    // closed windows, hidden and waiting to be opened again
+    spare_windows: Vec<(sdl2::render::Canvas<sdl2::video::Window>, TextRenderer)>,
     canvas: sdl2::render::Canvas<sdl2::video::Window>,
     pump: sdl2::EventPump,
-    ttf: sdl2::ttf::Sdl2TtfContext,
-    font: sdl2::ttf::Font<'a, 'static>,
-    text_creator: sdl2::render::TextureCreator<sdl2::video::WindowContext>,
+    text: TextRenderer,
+    debug: DebugOverlay,
+    vsync: bool,
+    // the font asked for didn't load and the embedded one stands in
+    font_fallback: bool,
+    // windows are hidden and drawn in software
+    headless: bool,
+    renderer: RendererKind,
+    // UI scale set by the application, and the one in use
+    scale: Option<f32>,
+    ui_scale: f32,
 
-    bg_color: Color,
+    theme: Theme,
 }
 
-impl Debug for Toolkit<'_> {
+impl Debug for Toolkit {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Toolkit")
+            .field("window", &self.window)
+            .field("windows", &self.windows)
+            .field("spare_windows", &self.spare_windows.len())
             .field("tabs", &self.tabs)
             .field("tab_pos", &self.tab_pos)
             .field("items", &self.items)
             .field("run", &self.run)
-            .field("bg_color", &self.bg_color)
+            .field("mouse", &self.mouse)
+            .field("hover", &self.hover)
+            .field("pressed", &self.pressed)
+            .field("focus", &self.focus)
+            .field("clicks", &self.clicks)
+            .field("callbacks", &self.callbacks.keys())
+            .field("change_callbacks", &self.change_callbacks.keys())
+            .field("double_click_callbacks", &self.double_click_callbacks.keys())
+            .field("click_counter", &self.click_counter)
+            .field("drop_callbacks", &self.drop_callbacks.keys())
+            .field("drop_target", &self.drop_target)
+            .field("close_callbacks", &self.close_callbacks.len())
+            .field("touch", &self.touch)
+            .field("bindings", &self.bindings.keys())
+            .field("draggable", &self.draggable)
+            .field("drag_callbacks", &self.drag_callbacks.keys())
+            .field("drag", &self.drag)
+            .field("radio_groups", &self.radio_groups)
+            .field("timers", &self.timers)
+            .field("animations", &self.animations)
+            .field("redraw_mode", &self.redraw_mode)
+            .field("dirty", &self.dirty)
+            .field("cursor", &self.cursor.as_ref().map(|(shape, _)| shape))
+            .field("frame_budget", &self.frame_budget)
+            .field("dialogs", &self.dialogs)
+            .field("toasts", &self.toasts)
+            .field("busy_state", &self.busy_state)
+            .field("property_bindings", &self.property_bindings.len())
+            .field("immediate_panels", &self.immediate_panels)
+            .field("debug", &self.debug)
+            .field("theme", &self.theme)
             .finish()
     }
 }
 
-impl Toolkit<'_> {
-    pub fn tick(&mut self) -> Result<bool, ToolkitError> {
-        for event in self.pump.poll_iter() {
-            match event {
-                Event::Quit {..} => {
-                    self.run = false;
-                },
-                Event::KeyDown {keycode, ..} => {
-                    match keycode {
-                        Some(Keycode::Escape) => {
-                            self.run = false;
-                        },
-                        _ => { },
-                    }
-                },
-                _ => { },
-            }
+impl Toolkit {
+    // The main loop itself is driven through RunningApp
+    fn tick(&mut self) -> Result<bool, ToolkitError> {
+        self.ui_events.clear();
+        let events: Vec<Event> = self.pump.poll_iter().collect();
+        for event in events {
+            let event = self.to_logical(event);
+            self.handle_event(event);
         }
+        self.run_messages();
+        self.fire_timers();
 
-        self.redraw()?;
+        let presented = self.update()?;
+        // without a present to block on, vsync can't pace the loop either
+        if let Some(budget) = self.frame_budget.or((!presented).then_some(IDLE_FRAME)) {
+            let spent = self.last_frame.elapsed();
+            if spent < budget {
+                std::thread::sleep(budget - spent);
+            }
+        }
+        self.end_frame();
 
         Ok(self.run)
     }
 
-    fn redraw(&mut self) -> Result<(), ToolkitError> {
-        self.canvas.set_draw_color(self.bg_color);
-        self.canvas.clear();
+    // Same as tick(), but returns what happened instead of whether to keep
+    // going. The loop is over once UiEvent::Quit shows up.
+    fn tick_events(&mut self) -> Result<Vec<UiEvent>, ToolkitError> {
+        self.tick()?;
+        Ok(std::mem::take(&mut self.ui_events))
+    }
+
+    // Main loop that sleeps in SDL until there is input, instead of polling
+    // every frame. While something is animating, or in RedrawMode::EveryFrame,
+    // it still wakes up once per frame. `f` sees every event after the
+    // Toolkit has handled it.
+    fn run<F: FnMut(&mut Toolkit, &Event)>(&mut self, mut f: F) -> Result<(), ToolkitError> {
+        while self.run {
+            self.ui_events.clear();
+            let mut timeout = if self.frame_due() {
+                self.frame_budget.unwrap_or(IDLE_FRAME).saturating_sub(self.last_frame.elapsed())
+            } else {
+                MAX_WAIT
+            };
+            if let Some(due) = self.timers.next_due() {
+                timeout = timeout.min(due.saturating_duration_since(Instant::now()));
+            }
+            let mut events = Vec::new();
+            if let Some(event) = self.pump.wait_event_timeout(timeout.as_millis() as u32) {
+                events.push(event);
+                events.extend(self.pump.poll_iter());
+            }
+            for event in events {
+                let event = self.to_logical(event);
+                self.handle_event(event.clone());
+                f(self, &event);
+            }
+            self.run_messages();
+            self.fire_timers();
+
+            self.update()?;
+            self.end_frame();
+        }
+        Ok(())
+    }
 
-        for btn in &self.items {
-            btn.draw()?;
+    // Lays out and repaints if needed, returns whether it did repaint
+    fn update(&mut self) -> Result<bool, ToolkitError> {
+        self.sync_bindings();
+        self.advance_animations();
+        let mut presented = self.update_window()?;
+        let mut parked = std::mem::take(&mut self.windows);
+        let mut result = Ok(());
+        for data in parked.iter_mut() {
+            data.swap(self);
+            match self.update_window() {
+                Ok(p) => presented |= p,
+                Err(e) => result = Err(e),
+            }
+            data.swap(self);
         }
+        self.windows = parked;
+        self.finish_animations();
+        result.map(|_| presented)
+    }
 
-        match self.tabs.get(self.tab_pos) {
-            Some(tab) => tab.draw()?,
-            None => /*println!("No tab")*/(),
+    fn update_window(&mut self) -> Result<bool, ToolkitError> {
+        // the window may have moved to another display, or been resized
+        self.dirty |= self.update_scale();
+        // children may have changed size since the last frame
+        self.relayout();
+        self.step_animations();
+        // and moved out from under the pointer or in under it
+        self.update_hover();
+
+        self.dirty |= self.animating();
+        let presented = self.redraw_mode == RedrawMode::EveryFrame || self.dirty;
+        if presented {
+            self.redraw()?;
+            self.dirty = false;
         }
+        Ok(presented)
+    }
 
-        self.canvas.present();
-        
-        Ok(())
+    // Handle for posting work to the UI thread from other threads
+    pub fn event_sender(&self) -> EventSender {
+        self.sender.clone()
     }
 
-    fn render_text<'a>(&'a self, input: &'static str) -> Result<Texture<'a>, ToolkitError> {
-        let surface = self.font.render(input).blended(Color::RGBA(255, 255, 255, 255))?;
-        let texture = self.text_creator.create_texture_from_surface(&surface)?;
+    fn run_messages(&mut self) {
+        while let Ok(message) = self.messages.try_recv() {
+            message(self);
+            self.dirty = true;
+        }
+    }
 
-        Ok(texture)
+    fn fire_timers(&mut self) {
+        let now = Instant::now();
+        for mut timer in self.timers.take_due(now) {
+            // an earlier callback may have cleared it
+            if !self.timers.is_running(&timer) {
+                continue;
+            }
+            (timer.callback)(self);
+            self.timers.reschedule(timer, now);
+            self.dirty = true;
+        }
     }
 
-    pub fn add_tab(&mut self, name: &'static str) -> Result<(), ToolkitError> {
-        let tab = Tab::new(name);
-        self.tabs.push(tab);
-        Ok(())
+    // Runs `f` once after `delay`, from inside tick() or run()
+    pub fn set_timeout<F: FnMut(&mut Toolkit) + 'static>(&mut self, delay: Duration, f: F) -> TimerId {
+        self.timers.add(delay, None, Box::new(f))
     }
 
-/*    pub fn add_btn<'a>(&mut self, name: &'static str) -> Result<(), ToolkitError> {
-        let btn = Button::new(self, name, 0, 0)?;
-        match self.tabs.get_mut(self.tab_pos) {
-            Some(tab) => return Ok(tab.items.push(btn)),
-            None => return Err(ToolkitError::NoTabs),
-        }
-    }*/
-
-    pub fn new<'a>() -> Result<Toolkit<'a>, ToolkitError> {
-        let sdl2 = sdl2::init()?;
-        let video = sdl2.video()?;
-        let window = video.window("rtoolkit window", 480, 320).build()?;
-        let mut canvas = window.into_canvas().present_vsync().build()?;
-        let pump = sdl2.event_pump()?;
-        let bg_color = Color::RGBA(0, 0, 0, 100);
-        let ttf = sdl2::ttf::init()?;
-        let font = ttf.load_font("/usr/share/fonts/liberation/LiberationSans.ttf", 28)?;
-        let text_creator = canvas.texture_creator();
-
-        canvas.set_draw_color(bg_color);
-        canvas.clear();
-        canvas.present();
-
-        Ok(Toolkit {
-            tabs: Vec::new(),
-            tab_pos: 0,
-            items: Vec::new(),
-            run: true,
-            ctx: sdl2,
-            video: video,
-            canvas: canvas,
-            pump: pump,
-            bg_color: bg_color,
-            ttf: ttf,
-            font: font,
-            text_creator: text_creator,
-        })
+    // Runs `f` every `period` until the timer is cleared
+    pub fn set_interval<F: FnMut(&mut Toolkit) + 'static>(&mut self, period: Duration, f: F) -> TimerId {
+        self.timers.add(period, Some(period), Box::new(f))
     }
 
-    pub fn set_alpha(&mut self, alpha: u8) {
-        self.bg_color = Color::RGBA(0, 0, 0, alpha);
+    // Returns false if the timer already fired or was cleared before
+    pub fn clear_timer(&mut self, id: TimerId) -> bool {
+        self.timers.cancel(id)
+    }
+
+    fn end_frame(&mut self) {
+        let now = Instant::now();
+        self.delta = now - self.last_frame;
+        self.last_frame = now;
+        self.debug.record(self.delta);
+    }
+
+    fn animating(&self) -> bool {
+        // the spinner
+        let mut animating = self.is_busy();
+        if let Some(tab) = self.tabs.get(self.tab_pos) {
+            widget::walk(&tab.items, &mut |w| animating |= w.needs_redraw());
+        }
+        animating
+    }
+
+    // Whether the next frame is due no matter if there is input
+    fn frame_due(&self) -> bool {
+        self.redraw_mode == RedrawMode::EveryFrame || self.dirty || self.animating()
+            || !self.animations.is_empty()
+            || self.windows.iter().any(|w| w.is_dirty())
+    }
+
+    fn handle_event(&mut self, event: Event) {
+        trace!("event {:?}", event);
+        if let Event::Window {win_event: WindowEvent::Close, window_id, ..} = event {
+            // SDL follows up closing the last window with a Quit event, which
+            // gets to ask the close handlers
+            if window_id != self.window.0 || !self.windows.is_empty() {
+                self.close_window(WindowId(window_id));
+            }
+            return;
+        }
+        // input for another window is handled with that window made current
+        if let Some(id) = event.get_window_id().map(WindowId).filter(|id| self.windows.iter().any(|w| w.id == *id)) {
+            let current = self.window;
+            self.set_current_window(id).ok();
+            self.handle_event(event);
+            // a callback may have closed the window that was current
+            self.set_current_window(current).ok();
+            return;
+        }
+
+        // anything could look different after input
+        self.dirty = true;
+
+        if self.is_busy() && busy::is_input(&event) {
+            return;
+        }
+
+        if self.touch_event(&event) {
+            return;
+        }
+        let event = self.count_clicks(event);
+        #[cfg(feature = "gamepad")]
+        if self.controller_event(&event) {
+            return;
+        }
+
+        if self.dialogs.is_empty() && self.menu_bar_event(&event) {
+            return;
+        }
+        if let Some(menu) = self.menu.as_mut() {
+            match menu.handle_event(&event, &self.text) {
+                MenuAction::Ignored => { },
+                MenuAction::Consumed => return,
+                MenuAction::Close => {
+                    self.menu = None;
+                    return;
+                },
+                MenuAction::Select(name) => {
+                    self.menu = None;
+                    self.menu_selected(name);
+                    return;
+                },
+            }
+        }
+
+        // a modal dialog swallows everything but quitting and resizing
+        if !self.dialogs.is_empty() && !matches!(event, Event::Quit {..} | Event::Window {..}) {
+            self.dialog_event(event);
+            return;
+        }
+
+        // a click on a toast only takes it down
+        if let Event::MouseButtonDown {mouse_btn: MouseButton::Left, x, y, ..} = event {
+            if let Some(pos) = self.toast_rects().iter().position(|r| r.contains_point((x, y))) {
+                self.toasts.remove(pos);
+                self.dirty = true;
+                return;
+            }
+        }
+
+        if let Event::KeyDown {keycode: Some(key), keymod, ..} = event {
+            if key == Keycode::Escape && self.drag.take().is_some() {
+                return;
+            }
+            if self.run_binding(key, Modifiers::from(keymod)) {
+                return;
+            }
+        }
+
+        match event {
+            Event::Quit {..} => {
+                self.request_quit();
+            },
+            Event::KeyDown {keycode: Some(key), repeat: false, ..} if Some(key) == self.debug.key => {
+                self.set_debug_overlay(!self.debug.enabled);
+            },
+            Event::KeyDown {keycode: Some(Keycode::Tab), keymod, ..} if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+                    self.prev_tab();
+                } else {
+                    self.next_tab();
+                }
+            },
+            Event::KeyDown {keycode: Some(Keycode::Tab), keymod, ..} => {
+                self.cycle_focus(!keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD));
+            },
+            Event::MouseMotion {x, y, ..} => {
+                self.mouse = (x, y);
+                self.mouse_inside = true;
+                if self.drag.is_some() || self.start_drag(x, y) {
+                    return;
+                }
+                // drags keep going to whatever the press started on
+                if let Some(path) = self.pressed.clone() {
+                    self.dispatch(&path, &event);
+                }
+            },
+            Event::MouseButtonDown {mouse_btn: MouseButton::Left, clicks, x, y, ..} => {
+                self.mouse = (x, y);
+                if let Some(pos) = self.tab_bar_rects().iter().position(|r| r.contains_point((x, y))) {
+                    self.set_tab_pos(pos);
+                    return;
+                }
+                let Some(tab) = self.tabs.get(self.tab_pos) else {
+                    return;
+                };
+                // disabled widgets can't be pressed or focused
+                self.pressed = tab.hit(x, y).filter(|path| tab.get(path).is_some_and(|w| w.is_enabled()));
+                if let Some(w) = self.pressed.as_ref().and_then(|path| self.tabs[self.tab_pos].get_mut(path)) {
+                    w.set_pressed(true);
+                }
+                self.drag_press = self.pressed.clone().and_then(|mut path| {
+                    let tab = &self.tabs[self.tab_pos];
+                    while !tab.get(&path).is_some_and(|w| self.draggable.contains(w.name())) {
+                        path.pop()?;
+                    }
+                    Some((path, x, y))
+                });
+                let tab = &self.tabs[self.tab_pos];
+                let focus = self.pressed.clone()
+                    .filter(|path| tab.get(path).is_some_and(|w| w.focusable()));
+                if focus != self.focus {
+                    self.set_focus(focus);
+                }
+                if let Some(path) = self.pressed.clone() {
+                    self.dispatch(&path, &event);
+                    if clicks == 2 {
+                        self.double_clicked(&path);
+                    }
+                }
+            },
+            Event::MouseButtonDown {mouse_btn: MouseButton::Right, x, y, ..} => {
+                self.mouse = (x, y);
+                // the innermost widget under the pointer that has a menu
+                let Some(mut path) = self.tabs.get(self.tab_pos).and_then(|tab| tab.hit(x, y)) else {
+                    return;
+                };
+                while let Some(w) = self.tabs[self.tab_pos].get(&path) {
+                    if let Some(items) = self.context_menus.get(w.name()).filter(|_| w.is_enabled()) {
+                        let items = items.clone();
+                        self.open_context_menu(x, y, items);
+                        return;
+                    }
+                    path.pop();
+                }
+            },
+            Event::MouseButtonUp {mouse_btn: MouseButton::Left, x, y, ..} => {
+                self.mouse = (x, y);
+                self.drag_press = None;
+                if let Some(data) = self.drag.take() {
+                    self.end_drag(data, x, y);
+                    return;
+                }
+                // only a release over the same button that was pressed counts
+                let pressed = self.release_press();
+                if let Some(path) = pressed.as_ref() {
+                    self.dispatch(path, &event);
+                }
+                let clicked = self.tabs.get(self.tab_pos).and_then(|tab| {
+                    tab.hit(x, y)
+                        .filter(|path| Some(path) == pressed.as_ref())
+                        .filter(|path| tab.get(path).is_some_and(|w| w.clickable()))
+                });
+                if let Some(path) = clicked {
+                    self.activate(&path);
+                }
+            },
+            Event::MouseWheel {timestamp, window_id, which, x: 0, y: wheel, direction} => {
+                // shift turns the wheel sideways for mice without a second
+                // one, down going right
+                let shift = self.sdl.keyboard().mod_state().intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+                let event = if shift {
+                    Event::MouseWheel {timestamp, window_id, which, x: -wheel, y: 0, direction}
+                } else {
+                    event
+                };
+                self.wheel(&event);
+            },
+            Event::MouseWheel {..} => self.wheel(&event),
+            Event::Window {win_event: WindowEvent::Resized(..) | WindowEvent::SizeChanged(..), ..} => {
+                self.update_scale();
+                self.relayout();
+                if let Event::Window {win_event: WindowEvent::SizeChanged(..), ..} = event {
+                    let (w, h) = self.size();
+                    self.ui_events.push(UiEvent::WindowResized(w, h));
+                }
+            },
+            Event::DropBegin {..} => {
+                self.mouse = self.pointer_position();
+                self.drop_target = self.drop_target_at(self.mouse);
+            },
+            Event::DropFile {filename, ..} => {
+                self.dropped(Dropped::File(PathBuf::from(filename)));
+            },
+            Event::DropText {filename, ..} => {
+                self.dropped(Dropped::Text(filename));
+            },
+            Event::DropComplete {..} => {
+                self.drop_target = None;
+            },
+            Event::Window {win_event: WindowEvent::Enter, ..} => {
+                self.mouse_inside = true;
+            },
+            Event::Window {win_event: WindowEvent::Leave, ..} => {
+                self.mouse_inside = false;
+            },
+            Event::KeyDown {keycode: Some(key), keymod, ..} => {
+                let Some(path) = self.focus.clone() else {
+                    return;
+                };
+                let ctrl = keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD);
+                if ctrl && matches!(key, Keycode::C | Keycode::X | Keycode::V) && self.clipboard_key(&path, key) {
+                    return;
+                }
+                // Enter and Space click whatever is focused, unless the widget
+                // has a use for them itself
+                let activates = matches!(key, Keycode::Return | Keycode::KpEnter | Keycode::Space);
+                if !self.dispatch(&path, &event) && activates {
+                    self.activate(&path);
+                }
+            },
+            Event::KeyDown {..} | Event::KeyUp {..} | Event::TextInput {..} | Event::TextEditing {..} => {
+                if let Some(path) = self.focus.clone() {
+                    self.dispatch(&path, &event);
+                }
+            },
+            _ => { },
+        }
+    }
+
+    // Lets go of the widget being pressed, if any, returning its path
+    fn release_press(&mut self) -> Option<WidgetPath> {
+        let path = self.pressed.take()?;
+        if let Some(w) = self.tabs.get_mut(self.tab_pos).and_then(|tab| tab.get_mut(&path)) {
+            w.set_pressed(false);
+        }
+        Some(path)
+    }
+
+    fn set_hover(&mut self, hover: Option<WidgetPath>) {
+        if let Some(tab) = self.tabs.get_mut(self.tab_pos) {
+            if let Some(old) = self.hover.as_ref().and_then(|path| tab.get_mut(path)) {
+                old.set_hovered(false);
+            }
+            if let Some(new) = hover.as_ref().and_then(|path| tab.get_mut(path)) {
+                new.set_hovered(true);
+            }
+        }
+        self.hover = hover;
+        self.update_cursor();
+    }
+
+    // Shows the pointer shape the hovered widget wants
+    fn update_cursor(&mut self) {
+        let shape = self.hover.as_ref()
+            .and_then(|path| self.tabs.get(self.tab_pos)?.get(path))
+            .and_then(|w| w.cursor())
+            .unwrap_or(SystemCursor::Arrow);
+        if self.cursor.as_ref().map_or(SystemCursor::Arrow, |(s, _)| *s) == shape {
+            return;
+        }
+        match Cursor::from_system(shape) {
+            Ok(cursor) => {
+                cursor.set();
+                self.cursor = Some((shape, cursor));
+            },
+            Err(e) => warn!("could not change the pointer: {}", e),
+        }
+    }
+
+    // Hover goes to whatever is under the pointer, nothing while it's outside
+    // the window or a dialog covers the tabs
+    fn update_hover(&mut self) {
+        let (x, y) = self.mouse;
+        let hover = if self.mouse_inside && self.dialogs.is_empty() {
+            self.tabs.get(self.tab_pos).and_then(|tab| {
+                tab.hit(x, y).filter(|path| tab.get(path).is_some_and(|w| w.is_enabled()))
+            })
+        } else {
+            None
+        };
+        if hover != self.hover {
+            self.set_hover(hover);
+        }
+    }
+
+    // A completed click on a clickable widget of the current tab, by mouse
+    // or keyboard
+    fn activate(&mut self, path: &[usize]) {
+        let Some(w) = self.tabs.get(self.tab_pos).and_then(|tab| tab.get(path)).filter(|w| w.clickable() && w.is_enabled()) else {
+            return;
+        };
+        let name = w.name().to_string();
+        let group = w.as_any().downcast_ref::<RadioButton>().map(|r| r.group().to_string());
+        if let Some(group) = group {
+            self.select_radio(&group, &name);
+        }
+        if let Some(check) = self.tabs[self.tab_pos].get_mut(path).and_then(|w| w.as_any_mut().downcast_mut::<CheckBox>()) {
+            check.toggle();
+            self.report_change(path);
+        }
+        // links tell the application with LinkClicked alone
+        if let Some(link) = self.tabs[self.tab_pos].get_mut(path).and_then(|w| w.as_any_mut().downcast_mut::<Link>()) {
+            link.follow();
+            self.report_change(path);
+            return;
+        }
+        debug!("clicked {}", name);
+        self.fire(&name);
+        if let Some(id) = self.item_id(path) {
+            self.ui_events.push(UiEvent::ButtonClicked {id, name: name.clone()});
+        }
+        self.clicks.push(name);
+    }
+
+    // Id of the tab item a widget of the current tab is or sits in
+    fn item_id(&self, path: &[usize]) -> Option<WidgetId> {
+        let tab = self.tabs.get(self.tab_pos)?;
+        tab.ids.get(*path.first()?).copied()
+    }
+
+    // Moves the focus to the next (or previous) focusable widget of the
+    // current tab, wrapping around at the ends
+    fn cycle_focus(&mut self, forward: bool) {
+        let Some(tab) = self.tabs.get(self.tab_pos) else {
+            return;
+        };
+        let paths = widget::focusable_paths(&tab.items);
+        if paths.is_empty() {
+            return;
+        }
+        let current = self.focus.as_ref().and_then(|f| paths.iter().position(|p| p == f));
+        let next = match (current, forward) {
+            (Some(i), true) => (i + 1) % paths.len(),
+            (Some(i), false) => (i + paths.len() - 1) % paths.len(),
+            (None, true) => 0,
+            (None, false) => paths.len() - 1,
+        };
+        self.set_focus(Some(paths[next].clone()));
+    }
+
+    // The wheel goes to the widget under the pointer, or the closest parent
+    // that wants it
+    fn wheel(&mut self, event: &Event) {
+        let (x, y) = self.mouse;
+        let Some(mut path) = self.tabs.get(self.tab_pos).and_then(|tab| tab.hit(x, y)) else {
+            return;
+        };
+        while !path.is_empty() && !self.dispatch(&path, event) {
+            path.pop();
+        }
+    }
+
+    // Hands an event to a widget of the current tab and runs its change
+    // handlers if the event changed its value
+    fn dispatch(&mut self, path: &[usize], event: &Event) -> bool {
+        let Some(widget) = self.tabs.get_mut(self.tab_pos).and_then(|tab| tab.get_mut(path)) else {
+            return false;
+        };
+        if !widget.is_enabled() {
+            return false;
+        }
+        let consumed = widget.handle_event(event);
+        self.report_change(path);
+        consumed
+    }
+
+    // Tells the application if the widget at `path` was changed by the user
+    fn report_change(&mut self, path: &[usize]) {
+        self.report_link(path);
+        let Some(widget) = self.tabs.get_mut(self.tab_pos).and_then(|tab| tab.get_mut(path)) else {
+            return;
+        };
+        if widget.take_changed() {
+            let name = widget.name().to_string();
+            let any = widget.as_any();
+            let text = any.downcast_ref::<TextInput>().map(|input| input.value())
+                .or_else(|| any.downcast_ref::<TextArea>().map(|area| area.value()))
+                .map(str::to_string);
+            if let Some(id) = self.item_id(path) {
+                self.ui_events.push(match text {
+                    Some(text) => UiEvent::TextChanged {id, name: name.clone(), text},
+                    None => UiEvent::ValueChanged {id, name: name.clone()},
+                });
+            }
+            self.fire_in(|tk| &mut tk.change_callbacks, &name);
+        }
+    }
+
+    fn report_link(&mut self, path: &[usize]) {
+        let Some(widget) = self.tabs.get_mut(self.tab_pos).and_then(|tab| tab.get_mut(path)) else {
+            return;
+        };
+        let Some(url) = widget.take_link() else {
+            return;
+        };
+        let name = widget.name().to_string();
+        debug!("link to {} clicked in {}", url, name);
+        if let Some(id) = self.item_id(path) {
+            self.ui_events.push(UiEvent::LinkClicked {id, name, url});
+        }
+    }
+
+    // Ctrl+C, Ctrl+X and Ctrl+V on the focused widget, true if it supports
+    // the one pressed
+    fn clipboard_key(&mut self, path: &[usize], key: Keycode) -> bool {
+        let pasted = if key == Keycode::V { self.clipboard_get() } else { None };
+        let Some(widget) = self.tabs.get_mut(self.tab_pos).and_then(|tab| tab.get_mut(path)) else {
+            return false;
+        };
+        if !widget.is_enabled() {
+            return false;
+        }
+        let copied = match key {
+            Keycode::C => widget.copy_text(),
+            Keycode::X => widget.cut_text(),
+            _ => None,
+        };
+        let done = copied.is_some() || pasted.is_some_and(|text| widget.paste_text(&text));
+        if let Some(text) = copied {
+            // nothing the user could do about a clipboard that won't take it
+            if let Err(e) = self.clipboard_set(&text) {
+                warn!("could not copy to the clipboard: {}", e);
+            }
+        }
+        self.report_change(path);
+        done
+    }
+
+    fn fire(&mut self, name: &str) {
+        self.fire_in(|tk| &mut tk.callbacks, name);
+    }
+
+    // Runs the handlers of `name` in one of the callback maps. They are taken
+    // out of the map while running so they can freely borrow the Toolkit.
+    fn fire_in(&mut self, map: fn(&mut Toolkit) -> &mut HashMap<String, Vec<Callback>>, name: &str) {
+        let Some(mut cbs) = map(self).remove(name) else {
+            return;
+        };
+        for cb in cbs.iter_mut() {
+            cb(self);
+        }
+        // keep handlers registered from inside a handler
+        if let Some(added) = map(self).remove(name) {
+            cbs.extend(added);
+        }
+        map(self).insert(name.to_string(), cbs);
+    }
+
+    // Called whenever the user changes the value of widget `name`
+    pub fn on_change<F: FnMut(&mut Toolkit) + 'static>(&mut self, name: &str, f: F) {
+        self.change_callbacks.entry(name.to_string()).or_default().push(Box::new(f));
+    }
+
+    pub fn on_click<F: FnMut(&mut Toolkit) + 'static>(&mut self, name: &str, f: F) {
+        self.callbacks.entry(name.to_string()).or_default().push(Box::new(f));
+    }
+
+    // Runs `f` whenever the key is pressed with exactly these modifiers,
+    // before the focused widget sees it. Replaces what was bound to it before.
+    // Escape is bound to request_quit() from the start.
+    pub fn bind_key<F: FnMut(&mut Toolkit) + 'static>(&mut self, key: Keycode, mods: Modifiers, f: F) {
+        self.bindings.insert((key, mods), Rc::new(RefCell::new(Box::new(f))));
+    }
+
+    pub fn unbind_key(&mut self, key: Keycode, mods: Modifiers) -> bool {
+        self.bindings.remove(&(key, mods)).is_some()
+    }
+
+    pub fn is_bound(&self, key: Keycode, mods: Modifiers) -> bool {
+        self.bindings.contains_key(&(key, mods))
+    }
+
+    // True if a binding took the key. An open popup gets the keyboard before
+    // any shortcut, so Escape closes it rather than the app.
+    fn run_binding(&mut self, key: Keycode, mods: Modifiers) -> bool {
+        let popup = self.focus.as_ref()
+            .and_then(|path| self.tabs.get(self.tab_pos)?.get(path))
+            .is_some_and(|w| w.overlay_rect().is_some());
+        if popup {
+            return false;
+        }
+        let Some(binding) = self.bindings.get(&(key, mods)).cloned() else {
+            return false;
+        };
+        (binding.borrow_mut())(self);
+        true
+    }
+
+    // Ends the main loop after the current tick, no questions asked
+    pub fn quit(&mut self) {
+        self.run = false;
+    }
+
+    // Quits unless a close handler objects, what closing the window and
+    // Escape do. True if the app is going to quit.
+    pub fn request_quit(&mut self) -> bool {
+        let mut cbs = std::mem::take(&mut self.close_callbacks);
+        let allowed = cbs.iter_mut().all(|cb| cb(self));
+        // keep handlers registered from inside a handler
+        cbs.append(&mut self.close_callbacks);
+        self.close_callbacks = cbs;
+        if allowed {
+            self.run = false;
+            self.ui_events.push(UiEvent::Quit);
+        }
+        allowed
+    }
+
+    // Asked whenever the user tries to quit, returning false keeps the app
+    // running, e.g. to ask about unsaved changes first
+    pub fn on_close_requested<F: FnMut(&mut Toolkit) -> bool + 'static>(&mut self, f: F) {
+        self.close_callbacks.push(Box::new(f));
+    }
+
+    // Whether Escape quits the app (through request_quit). On by default.
+    pub fn set_quit_on_escape(&mut self, quit: bool) {
+        if quit {
+            self.bind_key(Keycode::Escape, Modifiers::NONE, |tk| { tk.request_quit(); });
+        } else {
+            self.unbind_key(Keycode::Escape, Modifiers::NONE);
+        }
+    }
+
+    // Makes widget `name` a drop target for files and text dragged in from
+    // other applications. Drops anywhere are reported as UiEvents as well.
+    pub fn on_drop<F: FnMut(&mut Toolkit, &Dropped) + 'static>(&mut self, name: &str, f: F) {
+        self.drop_callbacks.entry(name.to_string()).or_default().push(Box::new(f));
+    }
+
+    // The innermost enabled widget at (x, y) with a handler in `targets`
+    fn target_at<T>(&self, (x, y): (i32, i32), targets: &HashMap<String, T>) -> Option<WidgetPath> {
+        let tab = self.tabs.get(self.tab_pos)?;
+        let mut path = tab.hit(x, y)?;
+        while let Some(w) = tab.get(&path) {
+            if w.is_enabled() && targets.contains_key(w.name()) {
+                return Some(path);
+            }
+            path.pop();
+        }
+        None
+    }
+
+    fn drop_target_at(&self, pos: (i32, i32)) -> Option<WidgetPath> {
+        self.target_at(pos, &self.drop_callbacks)
+    }
+
+    // Lets things be dragged out of widget `name` onto others that take
+    // them. What gets dragged is up to the widget, see Widget::drag_data.
+    pub fn set_draggable(&mut self, name: &str, draggable: bool) {
+        if draggable {
+            self.draggable.insert(name.to_string());
+        } else {
+            self.draggable.remove(name);
+        }
+    }
+
+    // Makes widget `name` take what is dragged onto it from other widgets
+    pub fn on_drag_drop<F: FnMut(&mut Toolkit, &DragData) -> bool + 'static>(&mut self, name: &str, f: F) {
+        self.drag_callbacks.entry(name.to_string()).or_default().push(Box::new(f));
+    }
+
+    pub fn is_dragging(&self) -> bool { self.drag.is_some() }
+
+    // Turns the press on a draggable widget into a drag once the pointer has
+    // moved far enough, true if it did
+    fn start_drag(&mut self, x: i32, y: i32) -> bool {
+        let Some((path, px, py)) = self.drag_press.clone() else {
+            return false;
+        };
+        if (x - px).abs() <= DRAG_THRESHOLD && (y - py).abs() <= DRAG_THRESHOLD {
+            return false;
+        }
+        self.drag_press = None;
+        let Some(data) = self.tabs.get(self.tab_pos).and_then(|tab| tab.get(&path)).and_then(|w| w.drag_data(px, py)) else {
+            return false;
+        };
+        // the widget is done with this press, the drag takes over
+        self.release_press();
+        self.drag = Some(data);
+        true
+    }
+
+    fn end_drag(&mut self, data: DragData, x: i32, y: i32) {
+        let Some(path) = self.target_at((x, y), &self.drag_callbacks) else {
+            return;
+        };
+        let name = self.tabs[self.tab_pos].get(&path).map_or(String::new(), |w| w.name().to_string());
+        let Some(mut cbs) = self.drag_callbacks.remove(&name) else {
+            return;
+        };
+        let mut accepted = false;
+        for cb in cbs.iter_mut() {
+            accepted |= cb(self, &data);
+        }
+        if let Some(added) = self.drag_callbacks.remove(&name) {
+            cbs.extend(added);
+        }
+        self.drag_callbacks.insert(name.clone(), cbs);
+        if accepted {
+            self.ui_events.push(UiEvent::DragDropped {source: data.source, target: name});
+        }
+    }
+
+    // SDL doesn't say where a drop happened and there may have been no
+    // motion events during the drag, so ask where the pointer is now
+    fn pointer_position(&self) -> (i32, i32) {
+        let (mut x, mut y) = (0, 0);
+        unsafe { sdl2::sys::SDL_GetGlobalMouseState(&mut x, &mut y) };
+        let (wx, wy) = self.canvas.window().position();
+        (((x - wx) as f32 / self.ui_scale) as i32, ((y - wy) as f32 / self.ui_scale) as i32)
+    }
+
+    fn dropped(&mut self, dropped: Dropped) {
+        self.ui_events.push(match &dropped {
+            Dropped::File(path) => UiEvent::FileDropped(path.clone()),
+            Dropped::Text(text) => UiEvent::TextDropped(text.clone()),
+        });
+        // some platforms send no DropBegin
+        let target = self.drop_target.clone().or_else(|| self.drop_target_at(self.pointer_position()));
+        let Some(name) = target.and_then(|path| self.tabs.get(self.tab_pos)?.get(&path).map(|w| w.name().to_string())) else {
+            return;
+        };
+        let Some(mut cbs) = self.drop_callbacks.remove(&name) else {
+            return;
+        };
+        for cb in cbs.iter_mut() {
+            cb(self, &dropped);
+        }
+        if let Some(added) = self.drop_callbacks.remove(&name) {
+            cbs.extend(added);
+        }
+        self.drop_callbacks.insert(name, cbs);
+    }
+
+    pub fn add_slider(&mut self, name: &str, x: i32, y: i32, w: i32, min: f64, max: f64) -> Result<WidgetId, ToolkitError> {
+        self.add_widget(Slider::new(name, x, y, w, min, max))
+    }
+
+    pub fn slider_value(&self, name: &str) -> Option<f64> {
+        self.find_widget::<Slider>(name).map(|slider| slider.value())
+    }
+
+    pub fn set_slider_value(&mut self, name: &str, value: f64) -> bool {
+        match self.find_widget_mut::<Slider>(name) {
+            Some(slider) => {
+                slider.set_value(value);
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn add_plot(&mut self, name: &str, x: i32, y: i32, w: u32, h: u32) -> Result<WidgetId, ToolkitError> {
+        self.add_widget(Plot::new(name, x, y, w, h))
+    }
+
+    // Appends `value` to series `series` of plot `name`, see Plot::push
+    pub fn plot_push(&mut self, name: &str, series: usize, value: f64) -> bool {
+        self.find_widget_mut::<Plot>(name).is_some_and(|plot| plot.push(series, value))
+    }
+
+    // Shows `value` on LCD number `name` with its decimals
+    pub fn set_lcd_value(&mut self, name: &str, value: f64) -> bool {
+        match self.find_widget_mut::<LcdNumber>(name) {
+            Some(lcd) => {
+                lcd.set_value(value);
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn add_log_view(&mut self, name: &str, x: i32, y: i32, w: i32, h: i32) -> Result<WidgetId, ToolkitError> {
+        let log = LogView::new(&self.text, name, x, y, w, h);
+        self.add_widget(log)
+    }
+
+    // Adds a line to log view `name`, e.g. from an EventSender
+    pub fn append_log(&mut self, name: &str, severity: Severity, text: &str) -> bool {
+        match self.find_widget_mut::<LogView>(name) {
+            Some(log) => {
+                log.log(severity, text);
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn add_markdown(&mut self, name: &str, x: i32, y: i32, w: i32, h: i32, source: &str) -> Result<WidgetId, ToolkitError> {
+        let view = MarkdownView::new(&self.text, name, x, y, w, h).with_markdown(source);
+        self.add_widget(view)
+    }
+
+    pub fn set_markdown(&mut self, name: &str, source: &str) -> bool {
+        match self.find_widget_mut::<MarkdownView>(name) {
+            Some(view) => {
+                view.set_markdown(source);
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn add_link(&mut self, name: &str, label: &str, url: &str, x: i32, y: i32) -> Result<WidgetId, ToolkitError> {
+        let link = Link::new(&self.text, name, label, url, x, y)?;
+        self.add_widget(link)
+    }
+
+    pub fn add_list<S: Into<String>, I: IntoIterator<Item = S>>(&mut self, name: &str, x: i32, y: i32, w: i32, h: i32, items: I) -> Result<WidgetId, ToolkitError> {
+        let list = ListBox::new(&self.text, name, x, y, w, h).with_items(items);
+        self.add_widget(list)
+    }
+
+    pub fn list_selection(&self, name: &str) -> Option<usize> {
+        self.find_widget::<ListBox>(name).and_then(|list| list.selected())
+    }
+
+    pub fn add_table<M: TableModel + 'static>(&mut self, name: &str, x: i32, y: i32, w: i32, h: i32, model: M) -> Result<WidgetId, ToolkitError> {
+        let table = Table::new(&self.text, name, x, y, w, h, model);
+        self.add_widget(table)
+    }
+
+    // Model row of the selection, whatever the table is sorted by
+    pub fn table_selection(&self, name: &str) -> Option<usize> {
+        self.find_widget::<Table>(name).and_then(|table| table.selected())
+    }
+
+    pub fn add_combo<S: Into<String>, I: IntoIterator<Item = S>>(&mut self, name: &str, x: i32, y: i32, w: i32, items: I) -> Result<WidgetId, ToolkitError> {
+        let combo = ComboBox::new(&self.text, name, x, y, w).with_items(items);
+        self.add_widget(combo)
+    }
+
+    pub fn combo_selection(&self, name: &str) -> Option<usize> {
+        self.find_widget::<ComboBox>(name).and_then(|combo| combo.selected())
+    }
+
+    pub fn add_spin(&mut self, name: &str, x: i32, y: i32, w: i32, min: f64, max: f64) -> Result<WidgetId, ToolkitError> {
+        let spin = SpinBox::new(&self.text, name, x, y, w, min, max);
+        self.add_widget(spin)
+    }
+
+    pub fn spin_value(&self, name: &str) -> Option<f64> {
+        self.find_widget::<SpinBox>(name).map(|spin| spin.value())
+    }
+
+    pub fn set_spin_value(&mut self, name: &str, value: f64) -> bool {
+        match self.find_widget_mut::<SpinBox>(name) {
+            Some(spin) => {
+                spin.set_value(value);
+                true
+            },
+            None => false,
+        }
+    }
+
+    // Called with the new value whenever the user changes spin box `name`
+    pub fn on_spin_change<F: FnMut(&mut Toolkit, f64) + 'static>(&mut self, name: &str, mut f: F) {
+        let spin = name.to_string();
+        self.on_change(name, move |tk| {
+            if let Some(value) = tk.spin_value(&spin) {
+                f(tk, value);
+            }
+        });
+    }
+
+    pub fn add_rich_text(&mut self, name: &str, x: i32, y: i32, spans: Vec<TextSpan>) -> Result<WidgetId, ToolkitError> {
+        let rich = RichText::new(&self.text, name, x, y, spans)?;
+        self.add_widget(rich)
+    }
+
+    // Lets the user select the rich text `name` with the mouse and copy it
+    pub fn set_text_selectable(&mut self, name: &str, selectable: bool) -> bool {
+        match self.find_widget_mut::<RichText>(name) {
+            Some(rich) => {
+                rich.set_selectable(selectable);
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn set_rich_text(&mut self, name: &str, spans: Vec<TextSpan>) -> Result<bool, ToolkitError> {
+        // not find_widget_mut, the renderer has to stay borrowable
+        self.dirty = true;
+        let pred = |w: &dyn Widget| w.name() == name && w.as_any().is::<RichText>();
+        let rich = self.tabs.iter_mut()
+            .find_map(|tab| widget::find_mut(&mut tab.items, &pred))
+            .and_then(|w| w.as_any_mut().downcast_mut::<RichText>());
+        match rich {
+            Some(rich) => {
+                rich.set_spans(&self.text, spans)?;
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    pub fn add_canvas<F: FnMut(&mut Painter) -> Result<(), ToolkitError> + 'static>(&mut self, name: &str, x: i32, y: i32, w: u32, h: u32, paint: F) -> Result<WidgetId, ToolkitError> {
+        self.add_widget(CanvasWidget::new(name, x, y, w, h, paint))
+    }
+
+    #[cfg(feature = "opengl")]
+    pub fn add_gl_viewport<F: FnMut(&GlFrame) + 'static>(&mut self, name: &str, x: i32, y: i32, w: u32, h: u32, paint: F) -> Result<WidgetId, ToolkitError> {
+        self.add_widget(GlViewport::new(name, x, y, w, h, paint))
+    }
+
+    pub fn add_color_picker(&mut self, name: &str, x: i32, y: i32, w: i32, h: i32) -> Result<WidgetId, ToolkitError> {
+        let picker = ColorPicker::new(&self.text, name, x, y, w, h);
+        self.add_widget(picker)
+    }
+
+    pub fn color_value(&self, name: &str) -> Option<Color> {
+        self.find_widget::<ColorPicker>(name).map(|picker| picker.color())
+    }
+
+    pub fn set_color_value(&mut self, name: &str, color: Color) -> bool {
+        match self.find_widget_mut::<ColorPicker>(name) {
+            Some(picker) => {
+                picker.set_color(color);
+                true
+            },
+            None => false,
+        }
+    }
+
+    // Called with the new color whenever the user changes picker `name`
+    pub fn on_color_change<F: FnMut(&mut Toolkit, Color) + 'static>(&mut self, name: &str, mut f: F) {
+        let picker = name.to_string();
+        self.on_change(name, move |tk| {
+            if let Some(color) = tk.color_value(&picker) {
+                f(tk, color);
+            }
+        });
+    }
+
+    pub fn add_image<P: AsRef<std::path::Path>>(&mut self, name: &str, path: P, x: i32, y: i32) -> Result<WidgetId, ToolkitError> {
+        let img = Image::from_file(&self.text, name, path, x, y)?;
+        self.add_widget(img)
+    }
+
+    // Replaces the picture shown by the image called `name`
+    pub fn set_image<P: AsRef<std::path::Path>>(&mut self, name: &str, path: P) -> Result<bool, ToolkitError> {
+        self.dirty = true;
+        let pred = |w: &dyn Widget| w.name() == name && w.as_any().is::<Image>();
+        let text = &self.text;
+        for tab in self.tabs.iter_mut() {
+            if let Some(img) = widget::find_mut(&mut tab.items, &pred).and_then(|w| w.as_any_mut().downcast_mut::<Image>()) {
+                img.load(text, path)?;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    pub fn add_radio(&mut self, group: &str, name: &str, x: i32, y: i32) -> Result<WidgetId, ToolkitError> {
+        let radio = RadioButton::new(&self.text, group, name, x, y)?;
+        self.add_widget(radio)
+    }
+
+    pub fn add_check_box(&mut self, name: &str, label: &str, x: i32, y: i32) -> Result<WidgetId, ToolkitError> {
+        let check = CheckBox::new(&self.text, name, label, x, y)?;
+        self.add_widget(check)
+    }
+
+    pub fn is_checked(&self, name: &str) -> Option<bool> {
+        self.find_widget::<CheckBox>(name).map(|check| check.is_checked())
+    }
+
+    // Doesn't count as a change, the change handlers are left alone
+    pub fn set_checked(&mut self, name: &str, checked: bool) -> bool {
+        match self.find_widget_mut::<CheckBox>(name) {
+            Some(check) => {
+                check.set_checked(checked);
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn radio_group(&self, group: &str) -> Option<&RadioGroup> {
+        self.radio_groups.get(group)
+    }
+
+    // Makes `name` the only selected member of `group`, running the change
+    // handlers if that actually changed anything
+    pub fn select_radio(&mut self, group: &str, name: &str) -> bool {
+        self.dirty = true;
+        let Some(state) = self.radio_groups.get_mut(group) else {
+            return false;
+        };
+        let Some(idx) = state.members.iter().position(|m| *m == name) else {
+            return false;
+        };
+        if state.selected == Some(idx) {
+            return true;
+        }
+        state.selected = Some(idx);
+
+        for tab in self.tabs.iter_mut() {
+            widget::walk_mut(&mut tab.items, &mut |w| {
+                if let Some(radio) = w.as_any_mut().downcast_mut::<RadioButton>() {
+                    if radio.group() == group {
+                        radio.set_selected(radio.name() == name);
+                    }
+                }
+            });
+        }
+
+        let mut cbs = std::mem::take(&mut self.radio_groups.get_mut(group).unwrap().callbacks);
+        for cb in cbs.iter_mut() {
+            cb(self, idx);
+        }
+        if let Some(state) = self.radio_groups.get_mut(group) {
+            cbs.append(&mut state.callbacks);
+            state.callbacks = cbs;
+        }
+        true
+    }
+
+    pub fn on_radio_change<F: FnMut(&mut Toolkit, usize) + 'static>(&mut self, group: &str, f: F) {
+        self.radio_groups.entry(group.to_string()).or_insert_with(RadioGroup::new).callbacks.push(Box::new(f));
+    }
+
+    // Names of the buttons clicked since the last call
+    pub fn take_clicks(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.clicks)
+    }
+
+    pub fn mouse_pos(&self) -> (i32, i32) {
+        self.mouse
+    }
+
+    pub fn redraw_mode(&self) -> RedrawMode { self.redraw_mode }
+
+    // Caps how often tick() runs by sleeping off the rest of each frame, 0
+    // removes the cap. Mostly useful with vsync off.
+    pub fn set_target_fps(&mut self, fps: u32) {
+        self.frame_budget = (fps > 0).then(|| Duration::from_secs(1) / fps);
+    }
+
+    pub fn target_fps(&self) -> Option<u32> {
+        self.frame_budget.map(|budget| (Duration::from_secs(1).as_nanos() / budget.as_nanos().max(1)) as u32)
+    }
+
+    // Time between the ends of the last two ticks, for animating
+    pub fn delta_time(&self) -> Duration { self.delta }
+
+    pub fn set_redraw_mode(&mut self, mode: RedrawMode) {
+        self.redraw_mode = mode;
+        self.dirty = true;
+    }
+
+    // Repaints on the next tick, for changes the Toolkit can't see, e.g. made
+    // through a widget reference kept since before
+    pub fn request_redraw(&mut self) {
+        self.dirty = true;
+    }
+
+    fn redraw(&mut self) -> Result<(), ToolkitError> {
+        self.paint()?;
+        self.canvas.present();
+        Ok(())
+    }
+
+    // Everything redraw does short of showing it
+    fn paint(&mut self) -> Result<(), ToolkitError> {
+        trace!("drawing window {}", self.window.0);
+        self.canvas.set_draw_color(self.theme.background);
+        self.canvas.clear();
+
+        let headers = self.tab_bar_rects();
+        let entries = self.menu_bar_rects();
+        let bar = Rect::new(0, 0, self.size().0, self.menu_bar_height() as u32);
+        let status_bar = self.status_bar_rect();
+        let content = self.content_rect();
+        let drag_target = self.drag.as_ref().and_then(|_| self.target_at(self.mouse, &self.drag_callbacks));
+        let width = self.size().0;
+        let toasts = self.toast_rects();
+        let items = &self.items;
+        let draw_layer = |ctx: &mut DrawCtx, layer: Layer| -> Result<(), ToolkitError> {
+            for item in items.iter().filter(|i| i.layer == layer) {
+                item.drawable.draw(ctx)?;
+            }
+            Ok(())
+        };
+        let mut ctx = DrawCtx::new(&mut self.canvas, &self.text);
+        draw_layer(&mut ctx, Layer::Background)?;
+
+        let switch = self.tab_switch.and_then(|s| Some((s.from, self.tabs.get(s.from)?, s.progress)));
+        // wider widgets stay inside the tab, off the bars around it
+        ctx.push_clip(content);
+        if let Some(tab) = self.tabs.get(self.tab_pos) {
+            match switch {
+                // both tabs are drawn until the new one has taken over
+                Some((from, old, progress)) => {
+                    let opacity = &self.opacity;
+                    if self.transition == Transition::Slide {
+                        let width = content.width() as i32;
+                        let dir = if self.tab_pos > from { 1 } else { -1 };
+                        let shift = dir * (width as f32 * progress) as i32;
+                        ctx.draw_layer(u8::MAX, (-shift, 0), |ctx| old.draw_with(ctx, opacity))?;
+                        ctx.draw_layer(u8::MAX, (dir * width - shift, 0), |ctx| tab.draw_with(ctx, opacity))?;
+                    } else {
+                        let alpha = (progress * 255.0).round() as u8;
+                        ctx.draw_faded(u8::MAX - alpha, |ctx| old.draw_with(ctx, opacity))?;
+                        ctx.draw_faded(alpha, |ctx| tab.draw_with(ctx, opacity))?;
+                    }
+                },
+                None => tab.draw_with(&mut ctx, &self.opacity)?,
+            }
+        }
+        ctx.pop_clip();
+        draw_layer(&mut ctx, Layer::Content)?;
+
+        for (pos, (tab, rect)) in self.tabs.iter().zip(headers).enumerate() {
+            let theme = &self.theme;
+            let fill = if pos == self.tab_pos { theme.active } else { theme.fill };
+            ctx.fill_rounded_rect(rect, theme.corner_radius, fill)?;
+            ctx.draw_rounded_rect(rect, theme.corner_radius, theme.border)?;
+            ctx.tint(&tab.label, theme.text);
+            ctx.copy_centered(&tab.label, rect)?;
+        }
+
+        if !self.menu_bar.is_empty() {
+            let theme = &self.theme;
+            let open = self.menu.as_ref().and_then(|m| m.bar);
+            ctx.canvas().set_draw_color(theme.fill);
+            ctx.canvas().fill_rect(bar)?;
+            for (pos, (item, rect)) in self.menu_bar.iter().zip(entries).enumerate() {
+                if open == Some(pos) {
+                    ctx.canvas().set_draw_color(theme.active);
+                    ctx.canvas().fill_rect(rect)?;
+                }
+                let color = if item.selectable() { theme.text } else { theme.disabled };
+                menu::draw_label(&mut ctx, item.label_text(), rect.x() + theme.padding, rect, color)?;
+            }
+        }
+
+        if let (Some(rect), Some(status)) = (status_bar, self.status.as_ref()) {
+            let theme = &self.theme;
+            let pad = theme.padding;
+            ctx.canvas().set_draw_color(theme.fill);
+            ctx.canvas().fill_rect(rect)?;
+            ctx.canvas().set_draw_color(theme.border);
+            ctx.canvas().draw_line(rect.top_left(), Point::new(rect.right(), rect.top()))?;
+            ctx.push_clip(rect);
+            for (segment, text) in status.iter().enumerate().filter(|(_, t)| !t.is_empty()) {
+                let texture = self.text.render(text)?;
+                let attr = texture.query();
+                let x = match segment {
+                    0 => rect.x() + pad,
+                    1 => rect.center().x() - attr.width as i32 / 2,
+                    _ => rect.right() - pad - attr.width as i32,
+                };
+                let y = rect.y() + (rect.height() as i32 - attr.height as i32) / 2;
+                ctx.tint(&texture, theme.text);
+                ctx.canvas().copy(&texture, None, Rect::new(x, y, attr.width, attr.height))?;
+            }
+            ctx.pop_clip();
+        }
+
+        if let Some(w) = self.drop_target.as_ref().or(drag_target.as_ref()).and_then(|path| self.tabs.get(self.tab_pos)?.get(path)) {
+            let theme = &self.theme;
+            ctx.draw_rounded_rect(w.rect(), theme.corner_radius, theme.active)?;
+        }
+
+        // popups go over everything, tab bar included
+        if let Some(tab) = self.tabs.get(self.tab_pos) {
+            for item in tab.items.iter() {
+                item.draw_overlay(&mut ctx)?;
+            }
+        }
+        draw_layer(&mut ctx, Layer::Overlay)?;
+        toast::draw(&mut ctx, &self.toasts, &toasts, &self.theme)?;
+
+        for dialog in self.dialogs.iter() {
+            dialog.draw(&mut ctx)?;
+        }
+        draw_layer(&mut ctx, Layer::Modal)?;
+        if let Some(menu) = self.menu.as_ref() {
+            menu.draw(&mut ctx)?;
+        }
+        if self.busy_state.is_busy() {
+            busy::draw(&mut ctx, &self.busy_state, content.center(), &self.theme)?;
+        }
+        draw_layer(&mut ctx, Layer::Tooltip)?;
+
+        // what is being dragged follows the pointer, above everything
+        if let Some(drag) = self.drag.as_ref().filter(|d| !d.text.is_empty()) {
+            let theme = &self.theme;
+            let pad = theme.padding;
+            let texture = self.text.render(&drag.text)?;
+            let attr = texture.query();
+            let (x, y) = (self.mouse.0 + 12, self.mouse.1 + 12);
+            let ghost = Rect::new(x, y, attr.width + 2 * pad as u32, attr.height + 2 * pad as u32);
+            ctx.fill_rounded_rect(ghost, theme.corner_radius, theme.fill)?;
+            ctx.draw_rounded_rect(ghost, theme.corner_radius, theme.border)?;
+            ctx.tint(&texture, theme.text);
+            ctx.canvas().copy(&texture, None, Rect::new(x + pad, y + pad, attr.width, attr.height))?;
+        }
+
+        if self.debug.enabled {
+            self.debug.draw(&mut ctx, self.tabs.get(self.tab_pos), width)?;
+        }
+        Ok(())
+    }
+
+    pub fn add_tab(&mut self, name: &str) -> Result<(), ToolkitError> {
+        self.dirty = true;
+        let tab = Tab::new(&self.text, name)?;
+        self.tabs.push(tab);
+        // the first tab brings the tab bar in and shrinks the content area
+        self.relayout();
+        Ok(())
+    }
+
+    pub fn current_tab(&self) -> Option<&str> {
+        self.tabs.get(self.tab_pos).map(|tab| tab.name())
+    }
+
+    pub fn select_tab(&mut self, name: &str) -> Result<(), ToolkitError> {
+        let pos = self.tab_index(name)?;
+        self.set_tab_pos(pos);
+        Ok(())
+    }
+
+    pub fn next_tab(&mut self) {
+        if !self.tabs.is_empty() {
+            self.set_tab_pos((self.tab_pos + 1) % self.tabs.len());
+        }
+    }
+
+    pub fn prev_tab(&mut self) {
+        if !self.tabs.is_empty() {
+            self.set_tab_pos((self.tab_pos + self.tabs.len() - 1) % self.tabs.len());
+        }
+    }
+
+    fn set_tab_pos(&mut self, pos: usize) {
+        self.dirty = true;
+        self.menu = None;
+        self.set_focus(None);
+        self.set_hover(None);
+        // a press that started on the old tab can't complete on this one
+        self.release_press();
+        if pos != self.tab_pos {
+            if let Some(tab) = self.tabs.get(pos) {
+                self.ui_events.push(UiEvent::TabChanged(tab.name().to_string()));
+                self.start_tab_switch(self.tab_pos);
+            }
+        }
+        self.tab_pos = pos;
+    }
+
+    fn set_focus(&mut self, focus: Option<WidgetPath>) {
+        self.dirty = true;
+        if let Some(tab) = self.tabs.get_mut(self.tab_pos) {
+            if let Some(old) = self.focus.as_ref().and_then(|path| tab.get_mut(path)) {
+                old.set_focused(false);
+            }
+            if let Some(new) = focus.as_ref().and_then(|path| tab.get_mut(path)) {
+                new.set_focused(true);
+            }
+            if let Some(path) = focus.as_ref() {
+                tab.reveal(path);
+            }
+        }
+        self.focus = focus;
+        self.sync_text_input();
+    }
+
+    // Only asks SDL for text input (and the on-screen keyboard) while
+    // something wants it
+    fn sync_text_input(&self) {
+        let wants = match self.dialogs.last() {
+            Some(dialog) => dialog.wants_text(),
+            None => self.focus.as_ref()
+                .and_then(|path| self.tabs.get(self.tab_pos)?.get(path))
+                .is_some_and(|w| w.wants_text_input()),
+        };
+        if wants {
+            self.video.text_input().start();
+        } else {
+            self.video.text_input().stop();
+        }
+    }
+
+    fn dialog_event(&mut self, event: Event) {
+        if let Event::MouseMotion {x, y, ..} = event {
+            self.mouse = (x, y);
+        }
+        let Some(dialog) = self.dialogs.last_mut() else {
+            return;
+        };
+        if let Some(result) = dialog.handle_event(&event) {
+            self.close_dialog(result);
+        }
+    }
+
+    fn open_dialog(&mut self, mut dialog: Dialog) -> DialogHandle {
+        self.dirty = true;
+        let (w, h) = self.size();
+        dialog.layout(Rect::new(0, 0, w, h), &self.text);
+        let handle = dialog.handle.clone();
+        self.dialogs.push(dialog);
+        // a press on the widgets below can't finish while the dialog is up
+        self.release_press();
+        self.sync_text_input();
+        handle
+    }
+
+    fn close_dialog(&mut self, result: DialogResult) {
+        self.dirty = true;
+        let Some(mut dialog) = self.dialogs.pop() else {
+            return;
+        };
+        dialog.handle.resolve(result.clone());
+        self.sync_text_input();
+        for cb in dialog.callbacks.iter_mut() {
+            cb(self, &result);
+        }
+    }
+
+    // Shows `text` with a single OK button
+    pub fn message_box(&mut self, title: &str, text: &str) -> Result<DialogHandle, ToolkitError> {
+        let dialog = Dialog::new(&self.text, self.theme, title, text, None, false)?;
+        Ok(self.open_dialog(dialog))
+    }
+
+    // Asks a yes/no question, resolving to DialogResult::Ok or Cancel
+    pub fn confirm(&mut self, title: &str, text: &str) -> Result<DialogHandle, ToolkitError> {
+        let dialog = Dialog::new(&self.text, self.theme, title, text, None, true)?;
+        Ok(self.open_dialog(dialog))
+    }
+
+    // Asks for a line of text, resolving to DialogResult::Text or Cancel
+    pub fn prompt(&mut self, title: &str, text: &str, default: &str) -> Result<DialogHandle, ToolkitError> {
+        let dialog = Dialog::new(&self.text, self.theme, title, text, Some(default), true)?;
+        Ok(self.open_dialog(dialog))
+    }
+
+    // Runs `f` once the dialog behind `handle` is closed, or right away if it
+    // already is
+    pub fn on_dialog_close<F: FnMut(&mut Toolkit, &DialogResult) + 'static>(&mut self, handle: &DialogHandle, mut f: F) {
+        if let Some(result) = handle.result() {
+            f(self, &result);
+            return;
+        }
+        if let Some(dialog) = self.dialogs.iter_mut().find(|d| d.handle.same(handle)) {
+            dialog.callbacks.push(Box::new(f));
+        }
+    }
+
+    pub fn has_modal(&self) -> bool {
+        !self.dialogs.is_empty()
+    }
+
+    // Lets every container re-place its children, e.g. after a resize
+    pub fn relayout(&mut self) {
+        let area = self.content_rect();
+        for tab in self.tabs.iter_mut() {
+            tab.layout(area, &self.placements);
+        }
+        let (w, h) = self.size();
+        for dialog in self.dialogs.iter_mut() {
+            dialog.layout(Rect::new(0, 0, w, h), &self.text);
+        }
+    }
+
+    fn menu_bar_height(&self) -> i32 {
+        if self.menu_bar.is_empty() {
+            0
+        } else {
+            self.text.height() + 2 * self.theme.padding
+        }
+    }
+
+    fn menu_bar_rects(&self) -> Vec<Rect> {
+        let h = self.menu_bar_height() as u32;
+        let mut x = 0;
+        self.menu_bar.iter().map(|item| {
+            let label = self.text.render(&item.text()).map_or(0, |t| t.query().width);
+            let w = label + 2 * self.theme.padding as u32;
+            let rect = Rect::new(x, 0, w, h);
+            x += w as i32;
+            rect
+        }).collect()
+    }
+
+    fn status_bar_rect(&self) -> Option<Rect> {
+        self.status.as_ref()?;
+        let (w, h) = self.size();
+        let bar_h = self.text.height() + self.theme.padding;
+        Some(Rect::new(0, h as i32 - bar_h, w, bar_h as u32))
+    }
+
+    fn tab_bar_height(&self) -> i32 {
+        if self.tabs.is_empty() {
+            0
+        } else {
+            self.text.height() + 2 * self.theme.padding
+        }
+    }
+
+    // Headers are laid out left to right along the top of the window, below
+    // the menu bar
+    fn tab_bar_rects(&self) -> Vec<Rect> {
+        let h = self.tab_bar_height() as u32;
+        let top = self.menu_bar_height();
+        let mut x = 0;
+        self.tabs.iter().map(|tab| {
+            let w = tab.label.query().width + 2 * self.theme.padding as u32;
+            let rect = Rect::new(x, top, w, h);
+            x += w as i32;
+            rect
+        }).collect()
+    }
+
+    // Area of the window left over for tab contents
+    pub fn content_rect(&self) -> Rect {
+        let (w, h) = self.size();
+        let bar = self.menu_bar_height() + self.tab_bar_height();
+        let status = self.status_bar_rect().map_or(0, |r| r.height() as i32);
+        Rect::new(0, bar, w, (h as i32 - bar - status).max(0) as u32)
+    }
+
+    pub fn add_btn(&mut self, name: &str, x: i32, y: i32) -> Result<WidgetId, ToolkitError> {
+        let btn = Button::new(&self.text, name, x, y)?;
+        self.add_widget(btn)
+    }
+
+    pub fn set_btn_label(&mut self, name: &str, label: &str) -> Result<bool, ToolkitError> {
+        self.dirty = true;
+        let pred = |w: &dyn Widget| w.name() == name && w.as_any().is::<Button>();
+        let btn = self.tabs.iter_mut()
+            .find_map(|tab| widget::find_mut(&mut tab.items, &pred))
+            .and_then(|w| w.as_any_mut().downcast_mut::<Button>());
+        match btn {
+            Some(btn) => {
+                btn.set_label(&self.text, label)?;
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    // Loads a picture and puts it on the button called `name`
+    pub fn set_btn_icon<P: AsRef<std::path::Path>>(&mut self, name: &str, path: P) -> Result<bool, ToolkitError> {
+        let surface = image::load_surface(path.as_ref())?;
+        let texture = self.text.texture_from_surface(&surface)?;
+        match self.find_widget_mut::<Button>(name) {
+            Some(btn) => {
+                btn.set_icon(Some(texture));
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    pub fn add_text_input(&mut self, name: &str, x: i32, y: i32, w: i32) -> Result<WidgetId, ToolkitError> {
+        let input = TextInput::new(&self.text, name, x, y, w);
+        self.add_widget(input)
+    }
+
+    // Lists `dir`; what the user picks is in FileBrowser::chosen, and each
+    // pick or move to another directory counts as a change
+    pub fn add_file_browser(&mut self, name: &str, x: i32, y: i32, w: u32, h: u32, dir: &Path) -> Result<WidgetId, ToolkitError> {
+        let browser = FileBrowser::new(&self.text, name, x, y, w, h, dir);
+        self.add_widget(browser)
+    }
+
+    pub fn add_password_input(&mut self, name: &str, x: i32, y: i32, w: i32) -> Result<WidgetId, ToolkitError> {
+        let input = TextInput::new(&self.text, name, x, y, w).password(true);
+        self.add_widget(input)
+    }
+
+    // Adds any widget (or a whole layout) to the current tab
+    pub fn add_widget<W: Widget>(&mut self, widget: W) -> Result<WidgetId, ToolkitError> {
+        self.add_boxed(Box::new(widget))
+    }
+
+    // For widgets whose type is only known at runtime
+    pub fn add_boxed(&mut self, mut widget: Box<dyn Widget>) -> Result<WidgetId, ToolkitError> {
+        self.dirty = true;
+        let area = self.content_rect();
+        let tab = self.tabs.get_mut(self.tab_pos).ok_or(ToolkitError::NoTabs)?;
+        widget.apply_theme(&self.theme);
+        widget.layout(area);
+
+        // radio buttons join their group as they are added
+        let groups = &mut self.radio_groups;
+        let mut join = |w: &dyn Widget| {
+            if let Some(radio) = w.as_any().downcast_ref::<RadioButton>() {
+                groups.entry(radio.group().to_string()).or_insert_with(RadioGroup::new).members.push(radio.name().to_string());
+            }
+        };
+        join(widget.as_ref());
+        widget::walk(widget.children(), &mut join);
+
+        let id = WidgetId(self.next_id);
+        self.next_id += 1;
+        tab.items.push(widget);
+        tab.ids.push(id);
+        // goes under anything stacked above 0
+        self.restack(self.tab_pos);
+        Ok(id)
+    }
+
+    // Draws `drawable` outside of the tabs, on every tab, in `layer`
+    pub fn add_drawable<D: Drawable + 'static>(&mut self, layer: Layer, drawable: D) -> WidgetId {
+        self.dirty = true;
+        let id = WidgetId(self.next_id);
+        self.next_id += 1;
+        self.items.push(LayerItem {id, layer, z: 0, drawable: Box::new(drawable)});
+        self.items.sort_by_key(|i| (i.layer, i.z));
+        id
+    }
+
+    pub fn remove_drawable(&mut self, id: WidgetId) -> bool {
+        self.dirty = true;
+        let len = self.items.len();
+        self.items.retain(|i| i.id != id);
+        self.items.len() != len
+    }
+
+    // Stacks a tab item or drawable above (or below) the others of its tab
+    // or layer. Items with the same z-index keep the order they were added
+    // in. Returns false if there is no such item in this window.
+    pub fn set_z_index(&mut self, id: WidgetId, z: i32) -> bool {
+        if let Some(item) = self.items.iter_mut().find(|i| i.id == id) {
+            item.z = z;
+            self.items.sort_by_key(|i| (i.layer, i.z));
+            self.dirty = true;
+            return true;
+        }
+        let Some(t) = self.tabs.iter().position(|tab| tab.position(id).is_some()) else {
+            return false;
+        };
+        if z == 0 {
+            self.z_index.remove(&id);
+        } else {
+            self.z_index.insert(id, z);
+        }
+        self.restack(t);
+        true
+    }
+
+    // Sizes a tab item relative to the content area, worked out again
+    // whenever the window changes size. Returns false if there is no such
+    // item.
+    pub fn set_size(&mut self, id: WidgetId, width: Size, height: Size) -> bool {
+        if !self.tabs.iter().any(|tab| tab.position(id).is_some()) {
+            return false;
+        }
+        self.placements.sizes.insert(id, (width, height));
+        self.dirty = true;
+        self.relayout();
+        true
+    }
+
+    // Leaves the item at whatever size it has now
+    pub fn clear_size(&mut self, id: WidgetId) -> bool {
+        self.placements.sizes.remove(&id).is_some()
+    }
+
+    // Pins a tab item to an edge, corner or the center of the content area,
+    // keeping the distance it has there now as the window changes size.
+    // Returns false if there is no such item in this window.
+    pub fn set_anchor(&mut self, id: WidgetId, anchor: Anchor) -> bool {
+        let Some(rect) = self.tabs.iter().find_map(|tab| Some(tab.items[tab.position(id)?].rect())) else {
+            return false;
+        };
+        self.placements.anchors.insert(id, Anchored::new(anchor, rect, self.content_rect()));
+        self.dirty = true;
+        true
+    }
+
+    pub fn clear_anchor(&mut self, id: WidgetId) -> bool {
+        self.placements.anchors.remove(&id).is_some()
+    }
+
+    // Gives a tab item a margin, padding and size limits. The margin keeps
+    // Fill and percentage sizes off the edges of the content area, the
+    // padding replaces the theme's for this widget. Returns false if there
+    // is no such item in this window.
+    pub fn set_box_model(&mut self, id: WidgetId, model: BoxModel) -> bool {
+        let theme = model.theme_for(&self.theme);
+        let Some(w) = self.tabs.iter_mut().find_map(|tab| {
+            let pos = tab.position(id)?;
+            Some(tab.items[pos].as_mut())
+        }) else {
+            return false;
+        };
+        w.apply_theme(&theme);
+        self.placements.models.insert(id, model);
+        self.dirty = true;
+        self.relayout();
+        true
+    }
+
+    pub fn box_model(&self, id: WidgetId) -> BoxModel {
+        self.placements.models.get(&id).copied().unwrap_or_default()
+    }
+
+    pub fn z_index(&self, id: WidgetId) -> i32 {
+        match self.items.iter().find(|i| i.id == id) {
+            Some(item) => item.z,
+            None => self.z_index.get(&id).copied().unwrap_or(0),
+        }
+    }
+
+    // Puts tab `t` back in z-index order, keeping paths into it pointing
+    // at the same widgets
+    fn restack(&mut self, t: usize) {
+        let Some(moved) = self.tabs.get_mut(t).and_then(|tab| tab.restack(&self.z_index)) else {
+            return;
+        };
+        self.dirty = true;
+        if t != self.tab_pos {
+            return;
+        }
+        let paths = [&mut self.focus, &mut self.hover, &mut self.pressed, &mut self.drop_target];
+        for path in paths.into_iter().flatten() {
+            path[0] = moved[path[0]];
+        }
+        if let Some((path, _, _)) = self.drag_press.as_mut() {
+            path[0] = moved[path[0]];
+        }
+    }
+
+    // Takes a widget (and anything inside it) out of its tab for good
+    pub fn remove_widget(&mut self, id: WidgetId) -> bool {
+        self.dirty = true;
+        let Some((t, pos)) = self.tabs.iter().enumerate().find_map(|(t, tab)| Some((t, tab.position(id)?))) else {
+            return false;
+        };
+
+        // paths into the current tab either go away with the widget or shift down
+        if t == self.tab_pos {
+            let inside = |path: &Option<WidgetPath>| path.as_ref().is_some_and(|p| p[0] == pos);
+            if inside(&self.focus) {
+                self.set_focus(None);
+            }
+            if inside(&self.hover) {
+                self.set_hover(None);
+            }
+            if inside(&self.pressed) {
+                self.release_press();
+            }
+            if inside(&self.drop_target) {
+                self.drop_target = None;
+            }
+            if self.drag_press.as_ref().is_some_and(|(p, _, _)| p[0] == pos) {
+                self.drag_press = None;
+            }
+            let drag_press = self.drag_press.as_mut().map(|(path, _, _)| path);
+            for path in [self.focus.as_mut(), self.hover.as_mut(), self.pressed.as_mut(), self.drop_target.as_mut(), drag_press].into_iter().flatten() {
+                if path[0] > pos {
+                    path[0] -= 1;
+                }
+            }
+        }
+
+        let widget = self.tabs[t].remove(pos);
+        self.forget_item(id);
+        self.forget_radios(std::slice::from_ref(&widget));
+        true
+    }
+
+    // Drops what the Toolkit keeps about tab item `id` once it is gone
+    fn forget_item(&mut self, id: WidgetId) {
+        self.opacity.remove(&id);
+        self.z_index.remove(&id);
+        self.placements.forget(id);
+    }
+
+    // Removes every widget from the tab called `name`, keeping the tab
+    pub fn clear_tab(&mut self, name: &str) -> Result<(), ToolkitError> {
+        self.dirty = true;
+        let t = self.tab_index(name)?;
+        if t == self.tab_pos {
+            self.forget_paths();
+        }
+        for id in self.tabs[t].ids.clone() {
+            self.forget_item(id);
+        }
+        let items = self.tabs[t].clear();
+        self.forget_radios(&items);
+        Ok(())
+    }
+
+    pub fn remove_tab(&mut self, name: &str) -> Result<(), ToolkitError> {
+        self.dirty = true;
+        let t = self.tab_index(name)?;
+        if t == self.tab_pos {
+            self.forget_paths();
+        }
+        let mut tab = self.tabs.remove(t);
+        self.tab_switch = None;
+        for id in tab.ids.iter() {
+            self.forget_item(*id);
+        }
+        self.forget_radios(&tab.clear());
+
+        // stay on the same tab, or the one that took the removed one's place
+        if t < self.tab_pos || self.tab_pos >= self.tabs.len() {
+            self.tab_pos = self.tab_pos.saturating_sub(1);
+        }
+        // the last tab takes the tab bar with it
+        self.relayout();
+        Ok(())
+    }
+
+    fn tab_index(&self, name: &str) -> Result<usize, ToolkitError> {
+        self.tabs.iter()
+            .position(|tab| tab.name() == name)
+            .ok_or_else(|| ToolkitError::TabNotFound(name.to_string()))
+    }
+
+    // Lets go of focus, hover and press on the current tab
+    fn forget_paths(&mut self) {
+        self.set_focus(None);
+        self.set_hover(None);
+        self.release_press();
+        self.drop_target = None;
+        self.drag_press = None;
+    }
+
+    // Radio buttons leave their groups when they are removed
+    fn forget_radios(&mut self, removed: &[Box<dyn Widget>]) {
+        let groups = &mut self.radio_groups;
+        widget::walk(removed, &mut |w| {
+            let Some(radio) = w.as_any().downcast_ref::<RadioButton>() else {
+                return;
+            };
+            let Some(group) = groups.get_mut(radio.group()) else {
+                return;
+            };
+            let Some(idx) = group.members.iter().position(|m| *m == radio.name()) else {
+                return;
+            };
+            group.members.remove(idx);
+            group.selected = match group.selected {
+                Some(sel) if sel == idx => None,
+                Some(sel) if sel > idx => Some(sel - 1),
+                sel => sel,
+            };
+        });
+    }
+
+    pub fn get_widget(&self, id: WidgetId) -> Option<&dyn Widget> {
+        self.tabs.iter().find_map(|tab| Some(tab.items[tab.position(id)?].as_ref()))
+    }
+
+    // For changing text, position or state of a widget after it was added
+    pub fn get_widget_mut(&mut self, id: WidgetId) -> Option<&mut dyn Widget> {
+        self.dirty = true;
+        self.tabs.iter_mut().find_map(|tab| {
+            let pos = tab.position(id)?;
+            Some(tab.items[pos].as_mut())
+        })
+    }
+
+    // Same as get_widget_mut, for when the widget's type is known
+    pub fn widget_mut<T: Widget>(&mut self, id: WidgetId) -> Option<&mut T> {
+        self.get_widget_mut(id).and_then(|w| w.as_any_mut().downcast_mut::<T>())
+    }
+
+    // Greys out the widget called `name` (of any type) and stops its input,
+    // or brings it back
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        self.dirty = true;
+        let pred = |w: &dyn Widget| w.name() == name;
+        let Some(w) = self.tabs.iter_mut().find_map(|tab| widget::find_mut(&mut tab.items, &pred)) else {
+            return false;
+        };
+        w.set_enabled(enabled);
+
+        // whatever was focused or held down in there has to let go
+        let disabled = |tk: &Toolkit, path: &Option<WidgetPath>| {
+            path.as_ref().and_then(|p| tk.tabs.get(tk.tab_pos)?.get(p)).is_some_and(|w| !w.is_enabled())
+        };
+        if disabled(self, &self.focus) {
+            self.set_focus(None);
+        }
+        if disabled(self, &self.pressed) {
+            self.release_press();
+        }
+        true
+    }
+
+    pub fn is_enabled(&self, name: &str) -> Option<bool> {
+        let pred = |w: &dyn Widget| w.name() == name;
+        self.tabs.iter().find_map(|tab| widget::find(&tab.items, &pred)).map(|w| w.is_enabled())
+    }
+
+    // First widget of type T called `name`, searching every tab
+    fn find_widget<T: Widget>(&self, name: &str) -> Option<&T> {
+        let pred = |w: &dyn Widget| w.name() == name && w.as_any().is::<T>();
+        self.tabs.iter()
+            .find_map(|tab| widget::find(&tab.items, &pred))
+            .and_then(|w| w.as_any().downcast_ref::<T>())
+    }
+
+    fn find_widget_mut<T: Widget>(&mut self, name: &str) -> Option<&mut T> {
+        self.dirty = true;
+        let pred = |w: &dyn Widget| w.name() == name && w.as_any().is::<T>();
+        self.tabs.iter_mut()
+            .find_map(|tab| widget::find_mut(&mut tab.items, &pred))
+            .and_then(|w| w.as_any_mut().downcast_mut::<T>())
+    }
+
+    pub fn add_text_area(&mut self, name: &str, x: i32, y: i32, w: i32, h: i32) -> Result<WidgetId, ToolkitError> {
+        let area = TextArea::new(&self.text, name, x, y, w, h).editable(true);
+        self.add_widget(area)
+    }
+
+    // Works for both single-line inputs and text areas. Password inputs
+    // only give theirs to password_value().
+    pub fn text_value(&self, name: &str) -> Option<&str> {
+        self.find_widget::<TextInput>(name).map(|input| input.value())
+            .or_else(|| self.find_widget::<TextArea>(name).map(|area| area.value()))
+    }
+
+    pub fn password_value(&self, name: &str) -> Option<&str> {
+        self.find_widget::<TextInput>(name).filter(|input| input.is_password()).map(|input| input.plaintext())
+    }
+
+    pub fn set_text_value(&mut self, name: &str, value: &str) -> bool {
+        if let Some(input) = self.find_widget_mut::<TextInput>(name) {
+            input.set_value(value);
+            return true;
+        }
+        match self.find_widget_mut::<TextArea>(name) {
+            Some(area) => {
+                area.set_value(value);
+                true
+            },
+            None => false,
+        }
+    }
+
+    // Text on the system clipboard, None if there is none or it isn't text
+    pub fn clipboard_get(&self) -> Option<String> {
+        let clipboard = self.video.clipboard();
+        if !clipboard.has_clipboard_text() {
+            return None;
+        }
+        clipboard.clipboard_text().ok()
+    }
+
+    pub fn clipboard_set(&self, text: &str) -> Result<(), ToolkitError> {
+        Ok(self.video.clipboard().set_clipboard_text(text)?)
+    }
+
+    pub fn text_renderer(&self) -> &TextRenderer {
+        &self.text
+    }
+
+    // Cap on rendered strings kept for reuse, 0 turns the cache off
+    pub fn set_text_cache_size(&mut self, size: usize) {
+        self.text.set_cache_size(size);
+    }
+
+    pub fn flush_text_cache(&mut self) {
+        self.text.flush_cache();
+    }
+
+    // How much room text takes in a font, without rendering it
+    pub fn measure_text(&self, text: &str, font: FontId) -> Result<(u32, u32), ToolkitError> {
+        self.text.measure(font, text)
+    }
+
+    // Same for text word wrapped to fit `width`
+    pub fn measure_wrapped_text(&self, text: &str, font: FontId, width: u32) -> Result<(u32, u32), ToolkitError> {
+        self.text.measure_wrapped(font, text, width)
+    }
+
+    // Loads a font for widgets to use, or hands back the id of the same one
+    // loaded before. Every window shares the fonts.
+    pub fn load_font(&mut self, source: &FontSource, size: u16, style: TextStyle) -> Result<FontId, ToolkitError> {
+        self.text.fonts().load(source, size, style)
+    }
+
+    pub fn fonts(&self) -> &FontManager {
+        self.text.fonts()
+    }
+
+    // Switches a widget's text to a loaded font. False if there is no such
+    // widget, ones without text accept it and carry on as before.
+    pub fn set_font(&mut self, name: &str, font: FontId) -> Result<bool, ToolkitError> {
+        self.dirty = true;
+        let pred = |w: &dyn Widget| w.name() == name;
+        let Some(w) = self.tabs.iter_mut().find_map(|tab| widget::find_mut(&mut tab.items, &pred)) else {
+            return Ok(false);
+        };
+        w.set_font(&self.text, font)?;
+        // text of another size changes how much room the widget wants
+        self.relayout();
+        Ok(true)
+    }
+
+    pub fn new() -> Result<Toolkit, ToolkitError> {
+        ToolkitBuilder::new().build()
+    }
+
+    pub fn builder() -> ToolkitBuilder {
+        ToolkitBuilder::new()
+    }
+
+    // Pops up a menu with its corner at (x, y), closing any other. It goes
+    // away once an item is picked, or on Escape or a click elsewhere.
+    pub fn open_context_menu(&mut self, x: i32, y: i32, items: Vec<MenuItem>) {
+        self.dirty = true;
+        let (w, h) = self.size();
+        self.menu = Some(Menu::new(&self.text, self.theme, x, y, items, Rect::new(0, 0, w, h)));
+    }
+
+    pub fn close_menu(&mut self) {
+        self.dirty = true;
+        self.menu = None;
+    }
+
+    pub fn has_menu(&self) -> bool {
+        self.menu.is_some()
+    }
+
+    // Right-clicking the widget called `name`, or anything inside it, opens
+    // a menu with `items`
+    pub fn set_context_menu(&mut self, name: &str, items: Vec<MenuItem>) {
+        self.context_menus.insert(name.to_string(), items);
+    }
+
+    pub fn remove_context_menu(&mut self, name: &str) -> bool {
+        self.context_menus.remove(name).is_some()
+    }
+
+    // Shows `text` on the left of the status bar, bringing the bar in if
+    // it isn't there yet
+    pub fn set_status(&mut self, text: &str) {
+        self.set_status_segment(StatusSegment::Left, text);
+    }
+
+    pub fn set_status_segment(&mut self, segment: StatusSegment, text: &str) {
+        self.dirty = true;
+        let shown = self.status.is_some();
+        self.status.get_or_insert_with(Default::default)[segment as usize] = text.to_string();
+        if !shown {
+            self.relayout();
+        }
+    }
+
+    pub fn status(&self, segment: StatusSegment) -> Option<&str> {
+        self.status.as_ref().map(|s| s[segment as usize].as_str())
+    }
+
+    // Takes the status bar away, texts and all
+    pub fn hide_status_bar(&mut self) {
+        self.dirty = true;
+        if self.status.take().is_some() {
+            self.relayout();
+        }
+    }
+
+    // Top level menus, each entry usually a submenu. Picking an entry that
+    // isn't works like picking a menu item.
+    pub fn set_menu_bar(&mut self, items: Vec<MenuItem>) {
+        self.dirty = true;
+        if self.menu.as_ref().is_some_and(|m| m.bar.is_some()) {
+            self.menu = None;
+        }
+        self.menu_bar = items;
+        // the content area just changed size
+        self.relayout();
+    }
+
+    fn open_bar_menu(&mut self, idx: usize, keyboard: bool) {
+        self.dirty = true;
+        let Some(entry) = self.menu_bar.get(idx).filter(|e| e.selectable()) else {
+            return;
+        };
+        if !entry.is_submenu() {
+            let name = entry.name().to_string();
+            self.menu = None;
+            self.menu_selected(name);
+            return;
+        }
+        let rect = self.menu_bar_rects()[idx];
+        let (w, h) = self.size();
+        let menu = Menu::new(&self.text, self.theme, rect.x(), rect.bottom(), entry.children().to_vec(), Rect::new(0, 0, w, h)).bar_entry(idx);
+        self.menu = Some(if keyboard { menu.hover_first() } else { menu });
+    }
+
+    // Clicks on the bar, moving along it with the pointer or arrow keys
+    // while one of its menus is open, and Alt plus a mnemonic
+    fn menu_bar_event(&mut self, event: &Event) -> bool {
+        if self.menu_bar.is_empty() {
+            return false;
+        }
+        let open = self.menu.as_ref().and_then(|m| m.bar);
+        let entry_at = |tk: &Toolkit, x: i32, y: i32| tk.menu_bar_rects().iter().position(|r| r.contains_point((x, y)));
+        match *event {
+            Event::MouseButtonDown {mouse_btn: MouseButton::Left, x, y, ..} => {
+                let Some(idx) = entry_at(self, x, y) else {
+                    return false;
+                };
+                if open == Some(idx) {
+                    self.close_menu();
+                } else {
+                    self.open_bar_menu(idx, false);
+                }
+            },
+            Event::MouseMotion {x, y, ..} if open.is_some() => {
+                match entry_at(self, x, y) {
+                    Some(idx) if open != Some(idx) => self.open_bar_menu(idx, false),
+                    _ => return false,
+                }
+            },
+            Event::KeyDown {keycode: Some(key @ (Keycode::Left | Keycode::Right)), ..} => {
+                let (Some(bar), Some(menu)) = (open, self.menu.as_ref()) else {
+                    return false;
+                };
+                // inside a submenu the keys are the menu's own
+                if menu.depth() > 1 || (key == Keycode::Right && menu.hovering_submenu()) {
+                    return false;
+                }
+                let len = self.menu_bar.len();
+                let next = if key == Keycode::Right { (bar + 1) % len } else { (bar + len - 1) % len };
+                self.open_bar_menu(next, true);
+            },
+            Event::KeyDown {keycode: Some(key), keymod, ..} if keymod.intersects(Mod::LALTMOD | Mod::RALTMOD) => {
+                let c = menu::key_char(key);
+                let Some(idx) = self.menu_bar.iter().position(|e| c.is_some() && e.mnemonic() == c) else {
+                    return false;
+                };
+                self.open_bar_menu(idx, true);
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    fn menu_selected(&mut self, name: String) {
+        self.fire(&name);
+        self.ui_events.push(UiEvent::MenuSelected(name.clone()));
+        self.clicks.push(name);
+    }
+
+    // Opens another window with tabs and widgets of its own. Make it current
+    // to add them.
+    //
+    // A window's renderer is never freed, textures borrow it for the life of
+    // the program. Closed windows are kept hidden instead and opened again
+    // here, so only as many windows are ever made as are open at once. A
+    // reopened window keeps the id it had.
+    pub fn create_window(&mut self, title: &str, w: u32, h: u32) -> Result<WindowId, ToolkitError> {
+        let s = self.ui_scale;
+        let (pw, ph) = ((w as f32 * s) as u32, (h as f32 * s) as u32);
+        if let Some((mut canvas, text)) = self.spare_windows.pop() {
+            let window = canvas.window_mut();
+            window.set_title(title).ok();
+            window.set_size(pw, ph).ok();
+            if !self.headless {
+                window.show();
+            }
+            text.flush_cache();
+            let data = WindowData::new(canvas, text);
+            let id = data.id;
+            debug!("reopened window {} as \"{}\"", id.0, title);
+            self.windows.push(data);
+            return Ok(id);
+        }
+        let mut window = self.video.window(title, pw, ph);
+        window.resizable().allow_highdpi();
+        if self.headless {
+            window.hidden();
+        }
+        let canvas = window::build_canvas(&window, self.renderer, self.vsync && !self.headless).with_context(|| format!("opening window \"{}\"", title))?;
+        let creator: &'static TextureCreator<WindowContext> = Box::leak(Box::new(canvas.texture_creator()));
+        let text = TextRenderer::new(self.text.fonts.clone(), creator);
+        text.set_cache_size(self.text.cache_size());
+
+        let data = WindowData::new(canvas, text);
+        let id = data.id;
+        debug!("opened window {} \"{}\"", id.0, title);
+        self.windows.push(data);
+        Ok(id)
+    }
+
+    // The window tabs and widgets are added to, and looked up in. While an
+    // event is handled, it's the window the event is for.
+    pub fn current_window(&self) -> WindowId { self.window }
+
+    pub fn windows(&self) -> Vec<WindowId> {
+        std::iter::once(self.window).chain(self.windows.iter().map(|w| w.id)).collect()
+    }
+
+    pub fn set_current_window(&mut self, id: WindowId) -> Result<(), ToolkitError> {
+        if id == self.window {
+            return Ok(());
+        }
+        let pos = self.windows.iter().position(|w| w.id == id).ok_or(ToolkitError::WindowNotFound(id.0))?;
+        let mut data = self.windows.swap_remove(pos);
+        data.swap(self);
+        self.windows.push(data);
+        Ok(())
+    }
+
+    // Hides the window and drops its widgets, see create_window. Closing the
+    // last one quits.
+    pub fn close_window(&mut self, id: WindowId) -> bool {
+        if id == self.window {
+            if self.windows.is_empty() {
+                self.run = false;
+                return true;
+            }
+            let next = self.windows[0].id;
+            self.set_current_window(next).ok();
+        }
+        let Some(pos) = self.windows.iter().position(|w| w.id == id) else {
+            return false;
+        };
+        let data = self.windows.remove(pos);
+        self.spare_windows.push(data.retire());
+        true
+    }
+
+    // Ends the setup phase. There has to be at least one tab by now.
+    pub fn start(mut self) -> Result<RunningApp, ToolkitError> {
+        if self.tabs.is_empty() {
+            return Err(ToolkitError::NoTabs);
+        }
+        self.run = true;
+        self.dirty = true;
+        self.relayout();
+        Ok(RunningApp::new(self))
+    }
+
+    // Size in the units everything is laid out in, which is pixels divided
+    // by the UI scale
+    pub fn size(&self) -> (u32, u32) {
+        let (w, h) = self.canvas.window().size();
+        ((w as f32 / self.ui_scale).round() as u32, (h as f32 / self.ui_scale).round() as u32)
+    }
+
+    // Drawable pixels per window coordinate, 2 on a Retina display and the
+    // like, 1 elsewhere
+    pub fn pixel_ratio(&self) -> f32 {
+        let (w, _) = self.canvas.window().size();
+        match self.canvas.output_size() {
+            Ok((dw, _)) if w > 0 => dw as f32 / w as f32,
+            _ => 1.0,
+        }
+    }
+
+    // Horizontal DPI of the display the window is on, if SDL knows it
+    pub fn display_dpi(&self) -> Option<f32> {
+        let display = self.canvas.window().display_index().ok()?;
+        self.video.display_dpi(display).ok().map(|(_, h, _)| h)
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.headless
+    }
+
+    // The renderer of the current window and the main font, as they turned
+    // out after any fallbacks
+    pub fn renderer_info(&self) -> RendererInfo {
+        let info = self.canvas.info();
+        let flag = |f: SDL_RendererFlags| info.flags & f as u32 != 0;
+        let software = flag(SDL_RendererFlags::SDL_RENDERER_SOFTWARE);
+        RendererInfo {
+            renderer: info.name.to_string(),
+            video_driver: self.video.current_video_driver().to_string(),
+            software,
+            vsync: flag(SDL_RendererFlags::SDL_RENDERER_PRESENTVSYNC),
+            max_texture_size: (info.max_texture_width, info.max_texture_height),
+            software_fallback: software && self.renderer == RendererKind::Accelerated,
+            font: self.text.fonts.source(FontId::DEFAULT).unwrap_or(FontSource::Embedded),
+            font_fallback: self.font_fallback,
+        }
+    }
+
+    // How much bigger than normal fonts, widgets and the like are drawn
+    pub fn scale(&self) -> f32 {
+        self.ui_scale
+    }
+
+    // Overrides the scale picked from the display DPI
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = Some(scale.max(0.25));
+        self.dirty |= self.update_scale();
+    }
+
+    // Back to picking the scale from the display DPI
+    pub fn set_auto_scale(&mut self) {
+        self.scale = None;
+        self.dirty |= self.update_scale();
+    }
+
+    // Where the OS scales windows itself (pixel ratio above 1) there's
+    // nothing left to do, elsewhere the DPI decides, in steps of a quarter
+    fn auto_scale(&self) -> f32 {
+        if self.pixel_ratio() > 1.0 {
+            return 1.0;
+        }
+        match self.display_dpi() {
+            Some(dpi) => ((dpi / 96.0 * 4.0).floor() / 4.0).clamp(1.0, 4.0),
+            None => 1.0,
+        }
+    }
+
+    // Scales the renderer so widgets can keep working in logical units. True
+    // if the scale changed.
+    pub(crate) fn update_scale(&mut self) -> bool {
+        self.ui_scale = self.scale.unwrap_or_else(|| self.auto_scale());
+        let total = self.pixel_ratio() * self.ui_scale;
+        if self.canvas.scale() == (total, total) {
+            return false;
+        }
+        if let Err(e) = self.canvas.set_scale(total, total) {
+            warn!("could not scale the renderer to {}: {}", total, e);
+        }
+        true
+    }
+
+    // SDL reports the mouse in window coordinates, widgets want them scaled
+    fn to_logical(&self, event: Event) -> Event {
+        let s = self.ui_scale;
+        if s == 1.0 {
+            return event;
+        }
+        let l = |v: i32| (v as f32 / s).round() as i32;
+        match event {
+            Event::MouseMotion {timestamp, window_id, which, mousestate, x, y, xrel, yrel} => {
+                Event::MouseMotion {timestamp, window_id, which, mousestate, x: l(x), y: l(y), xrel: l(xrel), yrel: l(yrel)}
+            },
+            Event::MouseButtonDown {timestamp, window_id, which, mouse_btn, clicks, x, y} => {
+                Event::MouseButtonDown {timestamp, window_id, which, mouse_btn, clicks, x: l(x), y: l(y)}
+            },
+            Event::MouseButtonUp {timestamp, window_id, which, mouse_btn, clicks, x, y} => {
+                Event::MouseButtonUp {timestamp, window_id, which, mouse_btn, clicks, x: l(x), y: l(y)}
+            },
+            event => event,
+        }
+    }
+
+    pub fn set_resizable(&mut self, resizable: bool) {
+        let flag = if resizable {
+            sdl2::sys::SDL_bool::SDL_TRUE
+        } else {
+            sdl2::sys::SDL_bool::SDL_FALSE
+        };
+        // sdl2 only exposes this on the WindowBuilder
+        unsafe { sdl2::sys::SDL_SetWindowResizable(self.canvas.window().raw(), flag) };
+    }
+
+    pub fn set_min_size(&mut self, w: u32, h: u32) -> Result<(), ToolkitError> {
+        let s = self.ui_scale;
+        self.canvas.window_mut().set_minimum_size((w as f32 * s) as u32, (h as f32 * s) as u32)?;
+        Ok(())
+    }
+
+    pub fn set_alpha(&mut self, alpha: u8) {
+        self.dirty = true;
+        self.theme.background.a = alpha;
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    // Restyles every widget that hasn't been given a theme of its own
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.dirty = true;
+        self.theme = theme;
+        for tab in self.tabs.iter_mut() {
+            for (item, id) in tab.items.iter_mut().zip(tab.ids.iter()) {
+                // with a tab item's own padding in it, if it has one
+                let theme = self.placements.models.get(id).map_or(theme, |m| m.theme_for(&theme));
+                item.apply_theme(&theme);
+            }
+        }
+        for dialog in self.dialogs.iter_mut() {
+            dialog.set_theme(theme);
+        }
+        if let Some(menu) = self.menu.as_mut() {
+            menu.set_theme(theme);
+        }
+        self.relayout();
     }
 }
@@ -0,0 +1,205 @@
+use std::any::Any;
+use std::fmt;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use sdl2::mouse::SystemCursor;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Texture;
+
+use crate::draw::mix;
+use crate::{DrawCtx, Drawable, Role, TextRenderer, Theme, ToolkitError, Widget, WidgetState};
+
+// Underlined text that goes somewhere when clicked. By default that is up to
+// the application, which gets a UiEvent::LinkClicked with the url. With the
+// open-links feature a link can instead be made to open its url in the
+// system's browser.
+pub struct Link {
+    name: String,
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    url: String,
+    label: Rc<Texture<'static>>,
+    text: String,
+    visited: bool,
+    // color once followed, by default halfway from active to disabled
+    visited_color: Option<Color>,
+    #[cfg(feature = "open-links")]
+    open: bool,
+    // url clicked since take_link was last asked
+    clicked: Option<String>,
+    state: WidgetState,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl Link {
+    pub fn new(text: &TextRenderer, name: &str, label: &str, url: &str, x: i32, y: i32) -> Result<Link, ToolkitError> {
+        let texture = text.render(label).map_err(ToolkitError::texture_for(name))?;
+        let attr = texture.query();
+        Ok(Link {
+            name: name.to_string(),
+            x,
+            y,
+            w: attr.width as i32,
+            h: attr.height as i32,
+            url: url.to_string(),
+            label: texture,
+            text: label.to_string(),
+            visited: false,
+            visited_color: None,
+            #[cfg(feature = "open-links")]
+            open: false,
+            clicked: None,
+            state: WidgetState::default(),
+            theme: Theme::default(),
+            own_theme: false,
+        })
+    }
+
+    pub fn visited_color(mut self, color: Color) -> Link {
+        self.visited_color = Some(color);
+        self
+    }
+
+    // Opens http, https and mailto urls with the system's open command
+    // rather than sending an event
+    #[cfg(feature = "open-links")]
+    pub fn open_url(mut self, open: bool) -> Link {
+        self.open = open;
+        self
+    }
+
+    pub fn url(&self) -> &str { &self.url }
+    pub fn is_visited(&self) -> bool { self.visited }
+
+    pub fn set_url(&mut self, url: &str) {
+        self.url = url.to_string();
+        self.visited = false;
+    }
+
+    pub fn set_label(&mut self, text: &TextRenderer, label: &str) -> Result<(), ToolkitError> {
+        self.label = text.render(label).map_err(ToolkitError::texture_for(&self.name))?;
+        let attr = self.label.query();
+        (self.w, self.h) = (attr.width as i32, attr.height as i32);
+        self.text = label.to_string();
+        Ok(())
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+    }
+
+    // What a click does
+    pub(crate) fn follow(&mut self) {
+        self.visited = true;
+        #[cfg(feature = "open-links")]
+        if self.open {
+            open::url(&self.url);
+            return;
+        }
+        self.clicked = Some(self.url.clone());
+    }
+}
+
+#[cfg(feature = "open-links")]
+mod open {
+    use std::process::Command;
+
+    use log::warn;
+
+    // Hands `url` to whatever the desktop opens urls with. Only web and mail
+    // links, a file or program path from a document shouldn't get run.
+    pub(super) fn url(url: &str) {
+        let lower = url.to_ascii_lowercase();
+        if !["http://", "https://", "mailto:"].iter().any(|scheme| lower.starts_with(scheme)) {
+            warn!("not opening {}, only web and mail links are", url);
+            return;
+        }
+        #[cfg(target_os = "macos")]
+        let mut cmd = Command::new("open");
+        #[cfg(target_os = "windows")]
+        let mut cmd = {
+            let mut cmd = Command::new("rundll32");
+            cmd.arg("url.dll,FileProtocolHandler");
+            cmd
+        };
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        let mut cmd = Command::new("xdg-open");
+        if let Err(e) = cmd.arg(url).spawn() {
+            warn!("could not open {}: {}", url, e);
+        }
+    }
+}
+
+impl Debug for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Link")
+            .field("name", &self.name)
+            .field("label", &self.text)
+            .field("url", &self.url)
+            .field("rect", &self.rect())
+            .field("visited", &self.visited)
+            .finish()
+    }
+}
+
+impl Drawable for Link {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let theme = &self.theme;
+        let color = if !self.state.enabled {
+            theme.disabled
+        } else if self.state.hovered || self.state.focused {
+            theme.hover
+        } else if self.visited {
+            self.visited_color.unwrap_or_else(|| mix(theme.active, theme.disabled, 0.5))
+        } else {
+            theme.active
+        };
+        let rect = self.rect();
+        ctx.tint(&self.label, color);
+        ctx.canvas().copy(&self.label, None, rect)?;
+        let y = rect.bottom() - 1;
+        ctx.draw_line((rect.left(), y), (rect.right() - 1, y), color)?;
+        Ok(())
+    }
+}
+
+impl Widget for Link {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { Rect::new(self.x, self.y, self.w as u32, self.h as u32) }
+    fn role(&self) -> Role { Role::Link }
+    fn access_label(&self) -> Option<String> { Some(self.text.clone()).filter(|l| !l.is_empty()) }
+    fn access_value(&self) -> Option<String> { Some(self.url.clone()) }
+    fn clickable(&self) -> bool { true }
+    fn focusable(&self) -> bool { true }
+    fn cursor(&self) -> Option<SystemCursor> { Some(SystemCursor::Hand) }
+    fn take_link(&mut self) -> Option<String> { self.clicked.take() }
+    fn widget_state(&self) -> Option<&WidgetState> { Some(&self.state) }
+    fn widget_state_mut(&mut self) -> Option<&mut WidgetState> { Some(&mut self.state) }
+
+    fn size_hint(&self) -> (u32, u32) {
+        let attr = self.label.query();
+        (attr.width, attr.height)
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.x = rect.x();
+        self.y = rect.y();
+        self.w = rect.width() as i32;
+        self.h = rect.height() as i32;
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
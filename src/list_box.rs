@@ -0,0 +1,290 @@
+use std::any::Any;
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::rect::Rect;
+
+use crate::{DragData, DrawCtx, Drawable, FontId, Role, TextRenderer, Theme, ToolkitError, Widget, WidgetState};
+
+// Pixels the pointer has to travel before a press turns into a drag
+const DRAG_THRESHOLD: i32 = 4;
+const WHEEL_ROWS: i32 = 3;
+
+// Scrollable list of text rows with a single selection
+pub struct ListBox {
+    name: String,
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    pref: (i32, i32),
+    items: Vec<String>,
+    selected: Option<usize>,
+    // pixels scrolled from the top
+    scroll: i32,
+    font: FontId,
+    text_height: i32,
+    // y of the initial press and the scroll offset at that time
+    press: Option<(i32, i32)>,
+    dragging: bool,
+    state: WidgetState,
+    changed: bool,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl ListBox {
+    pub fn new(text: &TextRenderer, name: &str, x: i32, y: i32, w: i32, h: i32) -> ListBox {
+        ListBox {
+            name: name.to_string(),
+            x,
+            y,
+            w,
+            h,
+            pref: (w, h),
+            items: Vec::new(),
+            selected: None,
+            scroll: 0,
+            font: FontId::DEFAULT,
+            text_height: text.height(),
+            press: None,
+            dragging: false,
+            state: WidgetState::default(),
+            changed: false,
+            theme: Theme::default(),
+            own_theme: false,
+        }
+    }
+
+    pub fn with_items<S: Into<String>, I: IntoIterator<Item = S>>(mut self, items: I) -> ListBox {
+        self.items = items.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn items(&self) -> &[String] { &self.items }
+    pub fn selected(&self) -> Option<usize> { self.selected }
+    pub fn selected_item(&self) -> Option<&str> {
+        self.selected.and_then(|idx| self.items.get(idx)).map(|s| s.as_str())
+    }
+
+    pub fn push<S: Into<String>>(&mut self, item: S) {
+        self.items.push(item.into());
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.selected = None;
+        self.scroll = 0;
+    }
+
+    pub fn set_selected(&mut self, selected: Option<usize>) {
+        self.selected = selected.filter(|idx| *idx < self.items.len());
+        self.scroll_to_selected();
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+    }
+
+    fn row_height(&self) -> i32 {
+        self.text_height + self.theme.padding
+    }
+
+    fn max_scroll(&self) -> i32 {
+        (self.items.len() as i32 * self.row_height() - self.h).max(0)
+    }
+
+    fn scroll_by(&mut self, dy: i32) {
+        self.scroll = (self.scroll + dy).clamp(0, self.max_scroll());
+    }
+
+    fn row_at(&self, y: i32) -> Option<usize> {
+        let row = (y - self.y + self.scroll) / self.row_height();
+        (y >= self.y && (row as usize) < self.items.len()).then_some(row as usize)
+    }
+
+    fn scroll_to_selected(&mut self) {
+        let Some(idx) = self.selected else {
+            return;
+        };
+        let top = idx as i32 * self.row_height();
+        let bottom = top + self.row_height();
+        if top < self.scroll {
+            self.scroll = top;
+        } else if bottom > self.scroll + self.h {
+            self.scroll = bottom - self.h;
+        }
+        self.scroll = self.scroll.clamp(0, self.max_scroll());
+    }
+
+    fn select(&mut self, idx: usize) {
+        if self.items.is_empty() {
+            return;
+        }
+        let idx = idx.min(self.items.len() - 1);
+        if self.selected != Some(idx) {
+            self.selected = Some(idx);
+            self.changed = true;
+        }
+        self.scroll_to_selected();
+    }
+
+    fn key(&mut self, key: Keycode) -> bool {
+        let page = (self.h / self.row_height()).max(1) as usize;
+        let cur = self.selected;
+        match key {
+            Keycode::Up => self.select(cur.map_or(0, |i| i.saturating_sub(1))),
+            Keycode::Down => self.select(cur.map_or(0, |i| i + 1)),
+            Keycode::PageUp => self.select(cur.map_or(0, |i| i.saturating_sub(page))),
+            Keycode::PageDown => self.select(cur.map_or(0, |i| i + page)),
+            Keycode::Home => self.select(0),
+            Keycode::End => self.select(self.items.len().saturating_sub(1)),
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl Debug for ListBox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ListBox")
+            .field("name", &self.name)
+            .field("rect", &self.rect())
+            .field("items", &self.items.len())
+            .field("selected", &self.selected)
+            .field("scroll", &self.scroll)
+            .finish()
+    }
+}
+
+impl Drawable for ListBox {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let rect = self.rect();
+        let theme = &self.theme;
+        ctx.fill_rounded_rect(rect, theme.corner_radius, theme.field)?;
+
+        let row_h = self.row_height();
+        let first = (self.scroll / row_h) as usize;
+        let visible = (self.h / row_h + 2) as usize;
+
+        let text = if self.state.enabled { theme.text } else { theme.disabled };
+        ctx.push_clip(rect);
+        for (idx, item) in self.items.iter().enumerate().skip(first).take(visible) {
+            let y = self.y + idx as i32 * row_h - self.scroll;
+            let row = Rect::new(self.x, y, self.w as u32, row_h as u32);
+            if Some(idx) == self.selected {
+                ctx.canvas().set_draw_color(theme.active);
+                ctx.canvas().fill_rect(row)?;
+            }
+            if item.is_empty() {
+                continue;
+            }
+            let texture = ctx.text().render_font(self.font, item)?;
+            let attr = texture.query();
+            ctx.tint(&texture, text);
+            let dst = Rect::new(self.x + theme.padding, y + (row_h - attr.height as i32) / 2, attr.width, attr.height);
+            ctx.canvas().copy(&texture, None, dst)?;
+        }
+        ctx.pop_clip();
+
+        let border = if self.state.focused { theme.active } else { theme.border };
+        ctx.draw_rounded_rect(rect, theme.corner_radius, border)?;
+        Ok(())
+    }
+}
+
+impl Widget for ListBox {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { Rect::new(self.x, self.y, self.w as u32, self.h as u32) }
+    fn role(&self) -> Role { Role::ListBox }
+    fn access_value(&self) -> Option<String> { self.selected_item().map(str::to_string) }
+    fn focusable(&self) -> bool { true }
+    fn widget_state(&self) -> Option<&WidgetState> { Some(&self.state) }
+    fn widget_state_mut(&mut self) -> Option<&mut WidgetState> { Some(&mut self.state) }
+
+    fn size_hint(&self) -> (u32, u32) {
+        (self.pref.0 as u32, self.pref.1 as u32)
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.x = rect.x();
+        self.y = rect.y();
+        self.w = rect.width() as i32;
+        self.h = rect.height() as i32;
+        self.scroll = self.scroll.clamp(0, self.max_scroll());
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+        }
+    }
+
+    fn set_font(&mut self, text: &TextRenderer, font: FontId) -> Result<(), ToolkitError> {
+        self.font = font;
+        self.text_height = text.font_height(font);
+        self.scroll = self.scroll.clamp(0, self.max_scroll());
+        Ok(())
+    }
+
+    fn drag_data(&self, _x: i32, y: i32) -> Option<DragData> {
+        let row = self.row_at(y)?;
+        Some(DragData {
+            source: self.name.clone(),
+            text: self.items[row].clone(),
+            index: Some(row),
+        })
+    }
+
+    fn scroll_pixels(&mut self, _dx: i32, dy: i32) -> bool {
+        let before = self.scroll;
+        self.scroll_by(-dy);
+        self.scroll != before
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::MouseButtonDown {mouse_btn: MouseButton::Left, y, ..} => {
+                self.press = Some((*y, self.scroll));
+                self.dragging = false;
+            },
+            Event::MouseMotion {y, ..} => {
+                let Some((start_y, start_scroll)) = self.press else {
+                    return false;
+                };
+                if (y - start_y).abs() > DRAG_THRESHOLD {
+                    self.dragging = true;
+                }
+                if self.dragging {
+                    self.scroll = (start_scroll - (y - start_y)).clamp(0, self.max_scroll());
+                }
+            },
+            Event::MouseButtonUp {mouse_btn: MouseButton::Left, y, ..} => {
+                // a press that never turned into a drag picks a row
+                if self.press.take().is_some() && !self.dragging {
+                    if let Some(row) = self.row_at(*y) {
+                        self.select(row);
+                    }
+                }
+                self.dragging = false;
+            },
+            Event::MouseWheel {y, ..} => {
+                self.scroll_by(-y * WHEEL_ROWS * self.row_height());
+            },
+            Event::KeyDown {keycode: Some(key), ..} => return self.key(*key),
+            _ => return false,
+        }
+        true
+    }
+
+    fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
@@ -0,0 +1,414 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+use crate::{DrawCtx, Drawable, FontId, Role, Severity, TextRenderer, Theme, ToolkitError, Widget, WidgetState};
+
+const WHEEL_ROWS: i32 = 3;
+const DEFAULT_CAPACITY: usize = 10_000;
+
+#[derive(Debug, Clone)]
+struct LogLine {
+    text: String,
+    severity: Severity,
+}
+
+// Read-only lines of output, e.g. from a tool run in the background. Only
+// the newest `capacity` lines are kept. While following, the view stays at
+// the bottom as lines come in; scrolling up stops that and scrolling back
+// down to the end starts it again. Lines can be searched for, and the ones
+// picked with the mouse (dragging picks several) or Ctrl+A copied.
+pub struct LogView {
+    name: String,
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    pref: (i32, i32),
+    lines: VecDeque<LogLine>,
+    capacity: usize,
+    follow: bool,
+    // pixels scrolled from the top
+    scroll: i32,
+    // picked lines, from the one clicked first to the last, as indices
+    // into `lines`
+    selection: Option<(usize, usize)>,
+    search: String,
+    // line of the match last moved to
+    found: Option<usize>,
+    font: FontId,
+    text_height: i32,
+    state: WidgetState,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl LogView {
+    pub fn new(text: &TextRenderer, name: &str, x: i32, y: i32, w: i32, h: i32) -> LogView {
+        LogView {
+            name: name.to_string(),
+            x,
+            y,
+            w,
+            h,
+            pref: (w, h),
+            lines: VecDeque::new(),
+            capacity: DEFAULT_CAPACITY,
+            follow: true,
+            scroll: 0,
+            selection: None,
+            search: String::new(),
+            found: None,
+            font: FontId::DEFAULT,
+            text_height: text.height(),
+            state: WidgetState::default(),
+            theme: Theme::default(),
+            own_theme: false,
+        }
+    }
+
+    pub fn capacity(mut self, capacity: usize) -> LogView {
+        self.set_capacity(capacity);
+        self
+    }
+
+    pub fn follow(mut self, follow: bool) -> LogView {
+        self.set_follow(follow);
+        self
+    }
+
+    pub fn len(&self) -> usize { self.lines.len() }
+    pub fn is_empty(&self) -> bool { self.lines.is_empty() }
+    pub fn is_following(&self) -> bool { self.follow }
+
+    pub fn line(&self, idx: usize) -> Option<&str> {
+        self.lines.get(idx).map(|l| l.text.as_str())
+    }
+
+    // Adds a line, or one per line of `text`
+    pub fn push(&mut self, text: &str) {
+        self.log(Severity::Info, text);
+    }
+
+    pub fn log(&mut self, severity: Severity, text: &str) {
+        for line in text.split('\n') {
+            self.lines.push_back(LogLine {text: line.trim_end_matches('\r').to_string(), severity});
+        }
+        self.trim();
+        if self.follow {
+            self.scroll = self.max_scroll();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.scroll = 0;
+        self.selection = None;
+        self.found = None;
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.trim();
+    }
+
+    pub fn set_follow(&mut self, follow: bool) {
+        self.follow = follow;
+        if follow {
+            self.scroll = self.max_scroll();
+        }
+    }
+
+    // Looks for `query` from the top, ignoring case, and shows the first
+    // line that has it. Lines with it are highlighted until the next
+    // search, an empty one ends it. Returns how many lines have it.
+    pub fn find(&mut self, query: &str) -> usize {
+        self.search = query.to_lowercase();
+        self.found = None;
+        if self.search.is_empty() {
+            return 0;
+        }
+        let count = (0..self.lines.len()).filter(|&i| self.matches(i)).count();
+        if count > 0 {
+            self.find_next();
+        }
+        count
+    }
+
+    // Moves to the next line with the search text, wrapping around. False
+    // if there is none.
+    pub fn find_next(&mut self) -> bool {
+        let n = self.lines.len();
+        let start = self.found.map_or(0, |i| i + 1);
+        let hit = (0..n).map(|k| (start + k) % n).find(|&i| self.matches(i));
+        self.reveal(hit)
+    }
+
+    pub fn find_prev(&mut self) -> bool {
+        let n = self.lines.len();
+        let start = self.found.unwrap_or(0) + n;
+        let hit = (1..=n).map(|k| (start - k) % n).find(|&i| self.matches(i));
+        self.reveal(hit)
+    }
+
+    // The picked lines, one per line
+    pub fn selected_text(&self) -> Option<String> {
+        let (a, b) = self.selection?;
+        let (from, to) = (a.min(b), a.max(b));
+        let lines: Vec<&str> = self.lines.range(from..=to).map(|l| l.text.as_str()).collect();
+        Some(lines.join("\n"))
+    }
+
+    pub fn select_all(&mut self) {
+        self.selection = (!self.lines.is_empty()).then(|| (0, self.lines.len() - 1));
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+    }
+
+    fn matches(&self, idx: usize) -> bool {
+        !self.search.is_empty() && self.lines[idx].text.to_lowercase().contains(&self.search)
+    }
+
+    // Scrolls line `hit` into view and makes it the current match
+    fn reveal(&mut self, hit: Option<usize>) -> bool {
+        let Some(idx) = hit else {
+            return false;
+        };
+        self.found = Some(idx);
+        let top = idx as i32 * self.row_height();
+        if top < self.scroll || top + self.row_height() > self.scroll + self.h {
+            // in the middle, with some of what came before
+            self.scroll_to(top - self.h / 2);
+        }
+        true
+    }
+
+    // Drops the oldest lines past the capacity, keeping the view on the
+    // lines it shows
+    fn trim(&mut self) {
+        let extra = self.lines.len().saturating_sub(self.capacity);
+        if extra == 0 {
+            return;
+        }
+        self.lines.drain(..extra);
+        self.selection = self.selection.and_then(|selection| shift_selection(selection, extra));
+        self.found = self.found.and_then(|i| shift_line(i, extra));
+        self.scroll = (self.scroll - extra as i32 * self.row_height()).clamp(0, self.max_scroll());
+    }
+
+    fn row_height(&self) -> i32 {
+        self.text_height + self.theme.padding / 2
+    }
+
+    fn max_scroll(&self) -> i32 {
+        (self.lines.len() as i32 * self.row_height() - self.h + self.theme.padding).max(0)
+    }
+
+    // Following stops when scrolling away from the bottom and starts again
+    // at it
+    fn scroll_to(&mut self, scroll: i32) {
+        self.scroll = scroll.clamp(0, self.max_scroll());
+        self.follow = self.scroll == self.max_scroll();
+    }
+
+    fn row_at(&self, y: i32) -> Option<usize> {
+        let row = (y - self.y - self.theme.padding / 2 + self.scroll) / self.row_height();
+        (y >= self.y && (row as usize) < self.lines.len()).then_some(row as usize)
+    }
+
+    fn color_of(&self, severity: Severity) -> Color {
+        match severity {
+            Severity::Info => self.theme.text,
+            severity => severity.color(&self.theme),
+        }
+    }
+
+    fn key(&mut self, key: Keycode, keymod: Mod) -> bool {
+        let page = self.h - self.row_height();
+        match key {
+            Keycode::Up => self.scroll_to(self.scroll - self.row_height()),
+            Keycode::Down => self.scroll_to(self.scroll + self.row_height()),
+            Keycode::PageUp => self.scroll_to(self.scroll - page),
+            Keycode::PageDown => self.scroll_to(self.scroll + page),
+            Keycode::Home => self.scroll_to(0),
+            Keycode::End => self.set_follow(true),
+            Keycode::F3 if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) => return self.find_prev(),
+            Keycode::F3 => return self.find_next(),
+            Keycode::A if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => self.select_all(),
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl Debug for LogView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogView")
+            .field("name", &self.name)
+            .field("rect", &self.rect())
+            .field("lines", &self.lines.len())
+            .field("capacity", &self.capacity)
+            .field("follow", &self.follow)
+            .field("search", &self.search)
+            .finish()
+    }
+}
+
+impl Drawable for LogView {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let rect = self.rect();
+        let theme = &self.theme;
+        ctx.fill_rounded_rect(rect, theme.corner_radius, theme.field)?;
+
+        let row_h = self.row_height();
+        let first = (self.scroll / row_h) as usize;
+        let visible = (self.h / row_h + 2) as usize;
+        let selected = self.selection.map(|(a, b)| a.min(b)..=a.max(b));
+
+        ctx.push_clip(rect);
+        for (idx, line) in self.lines.iter().enumerate().skip(first).take(visible) {
+            let y = self.y + theme.padding / 2 + idx as i32 * row_h - self.scroll;
+            let row = Rect::new(self.x, y, self.w as u32, row_h as u32);
+            let picked = selected.as_ref().is_some_and(|r| r.contains(&idx));
+            if picked || Some(idx) == self.found {
+                ctx.fill_rect(row, theme.active)?;
+            } else if self.matches(idx) {
+                ctx.fill_rect(row, theme.hover)?;
+            }
+            if line.text.is_empty() {
+                continue;
+            }
+            let color = if picked { theme.text } else { self.color_of(line.severity) };
+            let texture = ctx.text().render_font(self.font, &line.text)?;
+            let attr = texture.query();
+            ctx.tint(&texture, color);
+            let dst = Rect::new(self.x + theme.padding, y + (row_h - attr.height as i32) / 2, attr.width, attr.height);
+            ctx.canvas().copy(&texture, None, dst)?;
+        }
+        ctx.pop_clip();
+
+        let border = if self.state.focused { theme.active } else { theme.border };
+        ctx.draw_rounded_rect(rect, theme.corner_radius, border)?;
+        Ok(())
+    }
+}
+
+impl Widget for LogView {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { Rect::new(self.x, self.y, self.w as u32, self.h as u32) }
+    fn role(&self) -> Role { Role::TextArea }
+    fn access_value(&self) -> Option<String> { self.lines.back().map(|l| l.text.clone()) }
+    fn focusable(&self) -> bool { true }
+    fn widget_state(&self) -> Option<&WidgetState> { Some(&self.state) }
+    fn widget_state_mut(&mut self) -> Option<&mut WidgetState> { Some(&mut self.state) }
+    fn copy_text(&self) -> Option<String> { self.selected_text() }
+
+    fn size_hint(&self) -> (u32, u32) {
+        (self.pref.0 as u32, self.pref.1 as u32)
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.x = rect.x();
+        self.y = rect.y();
+        self.w = rect.width() as i32;
+        self.h = rect.height() as i32;
+        self.scroll = if self.follow { self.max_scroll() } else { self.scroll.clamp(0, self.max_scroll()) };
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+        }
+    }
+
+    fn set_font(&mut self, text: &TextRenderer, font: FontId) -> Result<(), ToolkitError> {
+        self.font = font;
+        self.text_height = text.font_height(font);
+        self.scroll = if self.follow { self.max_scroll() } else { self.scroll.clamp(0, self.max_scroll()) };
+        Ok(())
+    }
+
+    fn scroll_pixels(&mut self, _dx: i32, dy: i32) -> bool {
+        let before = self.scroll;
+        self.scroll_to(self.scroll - dy);
+        self.scroll != before
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::MouseButtonDown {mouse_btn: MouseButton::Left, y, ..} => {
+                self.selection = self.row_at(*y).map(|row| (row, row));
+            },
+            // dragging with the button down picks a range
+            Event::MouseMotion {mousestate, y, ..} if mousestate.left() => {
+                if let (Some((anchor, _)), Some(row)) = (self.selection, self.row_at(*y)) {
+                    self.selection = Some((anchor, row));
+                }
+            },
+            Event::MouseWheel {y, ..} => {
+                self.scroll_to(self.scroll - y * WHEEL_ROWS * self.row_height());
+            },
+            Event::KeyDown {keycode: Some(key), keymod, ..} => return self.key(*key, *keymod),
+            _ => return false,
+        }
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+// Where line `i` is after `extra` lines were evicted, if it's still there
+fn shift_line(i: usize, extra: usize) -> Option<usize> {
+    i.checked_sub(extra)
+}
+
+// Moves a selection up by `extra` evicted lines. Either end may be the
+// anchor; whichever ends were evicted land on the first line left, and the
+// selection only goes when all of it was evicted.
+fn shift_selection((a, b): (usize, usize), extra: usize) -> Option<(usize, usize)> {
+    (a.max(b) >= extra).then(|| (a.saturating_sub(extra), b.saturating_sub(extra)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_moves_with_its_line_or_goes() {
+        assert_eq!(shift_line(7, 3), Some(4));
+        assert_eq!(shift_line(3, 3), Some(0));
+        assert_eq!(shift_line(2, 3), None);
+    }
+
+    #[test]
+    fn selection_moves_with_its_lines() {
+        assert_eq!(shift_selection((5, 8), 3), Some((2, 5)));
+        assert_eq!(shift_selection((8, 5), 3), Some((5, 2)));
+        assert_eq!(shift_selection((3, 3), 3), Some((0, 0)));
+    }
+
+    #[test]
+    fn evicted_end_is_clamped_whichever_it_is() {
+        // the anchor went
+        assert_eq!(shift_selection((1, 6), 3), Some((0, 3)));
+        // the end went, dragged upwards past the anchor
+        assert_eq!(shift_selection((6, 1), 3), Some((3, 0)));
+    }
+
+    #[test]
+    fn selection_goes_when_all_of_it_went() {
+        assert_eq!(shift_selection((0, 2), 3), None);
+        assert_eq!(shift_selection((2, 0), 3), None);
+    }
+}
@@ -0,0 +1,811 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::fmt::Debug;
+
+use log::warn;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Texture;
+
+use crate::{DrawCtx, Drawable, FontId, FontSource, Role, TextRenderer, TextSpan, TextStyle, Theme, ToolkitError, Widget, WidgetState};
+
+const WHEEL_LINES: i32 = 3;
+// Heading sizes for levels 1, 2 and 3 on, in quarters of the body size
+const HEADING_QUARTERS: [u16; 3] = [8, 6, 5];
+// Width of a level of list indentation, in spaces of the body font
+const INDENT_SPACES: i32 = 4;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Style {
+    bold: bool,
+    italic: bool,
+    code: bool,
+    link: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Inline {
+    text: String,
+    style: Style,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Block {
+    Heading(usize, Vec<Inline>),
+    Paragraph(Vec<Inline>),
+    // depth, then the bullet or number it starts with
+    Item(usize, String, Vec<Inline>),
+    Quote(Vec<Inline>),
+    Code(Vec<String>),
+    Rule,
+}
+
+// A block whose lines are still being collected
+enum Open {
+    Paragraph,
+    Item(usize, String),
+    Quote,
+}
+
+fn close(open: &mut Option<(Open, String)>, blocks: &mut Vec<Block>) {
+    let Some((kind, text)) = open.take() else {
+        return;
+    };
+    let inlines = inlines(&text);
+    blocks.push(match kind {
+        Open::Paragraph => Block::Paragraph(inlines),
+        Open::Item(depth, marker) => Block::Item(depth, marker, inlines),
+        Open::Quote => Block::Quote(inlines),
+    });
+}
+
+// "## Title ##" is level 2
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[level..];
+    ((1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' ')))
+        .then(|| (level, rest.trim().trim_end_matches('#').trim_end()))
+}
+
+// Three or more of -, * or _ and nothing else but spaces
+fn is_rule(line: &str) -> bool {
+    let marks: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    marks.len() >= 3 && ['-', '*', '_'].contains(&marks[0]) && marks.iter().all(|&c| c == marks[0])
+}
+
+// Depth, marker and text of a "- item" or "1. item" line
+fn list_item(line: &str) -> Option<(usize, String, &str)> {
+    let indent: usize = line.chars().take_while(|c| c.is_whitespace()).map(|c| if c == '\t' { 4 } else { 1 }).sum();
+    let rest = line.trim_start();
+    let depth = indent / 2;
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(text) = rest.strip_prefix(bullet) {
+            return Some((depth, "\u{2022}".to_string(), text.trim()));
+        }
+    }
+    let digits = rest.chars().take_while(char::is_ascii_digit).count();
+    let after = &rest[digits..];
+    if digits > 0 && (after.starts_with(". ") || after.starts_with(") ")) {
+        return Some((depth, format!("{}.", &rest[..digits]), after[2..].trim()));
+    }
+    None
+}
+
+fn parse(src: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut open: Option<(Open, String)> = None;
+    let mut code: Option<Vec<String>> = None;
+    for line in src.lines() {
+        if let Some(lines) = code.as_mut() {
+            if line.trim_start().starts_with("```") {
+                blocks.push(Block::Code(std::mem::take(lines)));
+                code = None;
+            } else {
+                lines.push(line.replace('\t', "    "));
+            }
+            continue;
+        }
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            close(&mut open, &mut blocks);
+            code = Some(Vec::new());
+        } else if trimmed.is_empty() {
+            close(&mut open, &mut blocks);
+        } else if let Some((level, text)) = heading(trimmed) {
+            close(&mut open, &mut blocks);
+            blocks.push(Block::Heading(level, inlines(text)));
+        } else if is_rule(trimmed) {
+            close(&mut open, &mut blocks);
+            blocks.push(Block::Rule);
+        } else if let Some((depth, marker, text)) = list_item(line) {
+            close(&mut open, &mut blocks);
+            open = Some((Open::Item(depth, marker), text.to_string()));
+        } else if let Some(text) = trimmed.strip_prefix('>') {
+            if !matches!(open, Some((Open::Quote, _))) {
+                close(&mut open, &mut blocks);
+                open = Some((Open::Quote, String::new()));
+            }
+            if let Some((_, quote)) = open.as_mut() {
+                if !quote.is_empty() {
+                    quote.push(' ');
+                }
+                quote.push_str(text.trim());
+            }
+        } else {
+            // lines run on into the paragraph, item or quote before
+            match open.as_mut() {
+                Some((_, text)) => {
+                    text.push(' ');
+                    text.push_str(trimmed);
+                },
+                None => open = Some((Open::Paragraph, trimmed.to_string())),
+            }
+        }
+    }
+    // a fence nobody closed runs to the end
+    if let Some(lines) = code {
+        blocks.push(Block::Code(lines));
+    }
+    close(&mut open, &mut blocks);
+    blocks
+}
+
+fn inlines(src: &str) -> Vec<Inline> {
+    let mut out = Vec::new();
+    parse_inline(src, Style::default(), &mut out);
+    out
+}
+
+fn find(chars: &[char], from: usize, c: char) -> Option<usize> {
+    chars.iter().skip(from).position(|&x| x == c).map(|p| p + from)
+}
+
+// Where the ] and the ) of a [text](url) starting at `at` are. Brackets
+// inside the text pair up, so a link can hold another.
+fn link_at(chars: &[char], at: usize) -> Option<(usize, usize)> {
+    let mut depth = 0;
+    let close = chars.iter().enumerate().skip(at + 1).find_map(|(i, &c)| match c {
+        '[' => {
+            depth += 1;
+            None
+        },
+        ']' if depth == 0 => Some(i),
+        ']' => {
+            depth -= 1;
+            None
+        },
+        _ => None,
+    })?;
+    if chars.get(close + 1) != Some(&'(') {
+        return None;
+    }
+    Some((close, find(chars, close + 2, ')')?))
+}
+
+fn parse_inline(src: &str, base: Style, out: &mut Vec<Inline>) {
+    let chars: Vec<char> = src.chars().collect();
+    let mut style = base;
+    let mut buf = String::new();
+    let flush = |buf: &mut String, style: &Style, out: &mut Vec<Inline>| {
+        if !buf.is_empty() {
+            out.push(Inline {text: std::mem::take(buf), style: style.clone()});
+        }
+    };
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\\' && i + 1 < chars.len() {
+            buf.push(chars[i + 1]);
+            i += 2;
+            continue;
+        }
+        if c == '`' {
+            if let Some(end) = find(&chars, i + 1, '`') {
+                flush(&mut buf, &style, out);
+                out.push(Inline {text: chars[i + 1..end].iter().collect(), style: Style {code: true, ..style.clone()}});
+                i = end + 1;
+                continue;
+            }
+        }
+        if c == '*' || c == '_' {
+            let double = chars.get(i + 1) == Some(&c);
+            let prev = i.checked_sub(1).map(|p| chars[p]);
+            let next = chars.get(i + if double { 2 } else { 1 }).copied();
+            let on = if double { style.bold } else { style.italic };
+            // opening needs text after it and a closing one somewhere,
+            // closing needs text before it, and underscores inside words
+            // are just underscores
+            let len = if double { 2 } else { 1 };
+            let fits = if on {
+                prev.is_some_and(|p| !p.is_whitespace())
+            } else {
+                next.is_some_and(|n| !n.is_whitespace()) && chars[i + len..].windows(len).any(|w| w.iter().all(|&x| x == c))
+            };
+            let in_word = c == '_' && prev.is_some_and(char::is_alphanumeric) && next.is_some_and(char::is_alphanumeric);
+            if fits && !in_word {
+                flush(&mut buf, &style, out);
+                if double {
+                    style.bold = !style.bold;
+                } else {
+                    style.italic = !style.italic;
+                }
+                i += len;
+                continue;
+            }
+        }
+        if c == '[' {
+            if let Some((text_end, url_end)) = link_at(&chars, i) {
+                flush(&mut buf, &style, out);
+                let label: String = chars[i + 1..text_end].iter().collect();
+                let url: String = chars[text_end + 2..url_end].iter().collect();
+                parse_inline(&label, Style {link: Some(url.trim().to_string()), ..style.clone()}, out);
+                i = url_end + 1;
+                continue;
+            }
+        }
+        if c == '<' {
+            if let Some(end) = find(&chars, i + 1, '>') {
+                let url: String = chars[i + 1..end].iter().collect();
+                if url.contains("://") || url.starts_with("mailto:") {
+                    flush(&mut buf, &style, out);
+                    out.push(Inline {text: url.clone(), style: Style {link: Some(url), ..style.clone()}});
+                    i = end + 1;
+                    continue;
+                }
+            }
+        }
+        buf.push(c);
+        i += 1;
+    }
+    flush(&mut buf, &style, out);
+}
+
+// Fonts for the parts of a document
+#[derive(Debug, Clone, Copy)]
+struct Fonts {
+    body: FontId,
+    code: FontId,
+    headings: [FontId; 3],
+}
+
+impl Fonts {
+    // Headings in bold and code in monospace, in sizes going by `body`.
+    // Those that fail to load are replaced by the body font.
+    fn load(text: &TextRenderer, body: FontId) -> Fonts {
+        let fonts = text.fonts();
+        let source = fonts.source(body).unwrap_or(FontSource::Embedded);
+        let size = fonts.size(body).unwrap_or(16);
+        let load = |source: &FontSource, size: u16, style: TextStyle| {
+            fonts.load(source, size, style).unwrap_or_else(|e| {
+                warn!("markdown font not available, using the body font: {}", e.report());
+                body
+            })
+        };
+        Fonts {
+            body,
+            code: load(&FontSource::Monospace, size, TextStyle::Regular),
+            headings: HEADING_QUARTERS.map(|q| load(&source, size * q / 4, TextStyle::Bold)),
+        }
+    }
+
+    fn heading(&self, level: usize) -> FontId {
+        self.headings[level.clamp(1, 3) - 1]
+    }
+}
+
+// A rendered word (or part of one) at its place in the document
+struct Piece {
+    rect: Rect,
+    texture: Texture<'static>,
+    link: Option<String>,
+    code: bool,
+}
+
+enum Decor {
+    Fill(Rect, Color),
+    Line(i32, i32, i32, Color),
+}
+
+// The document laid out for one width, in document coordinates
+struct Layout {
+    width: i32,
+    pieces: Vec<Piece>,
+    decor: Vec<Decor>,
+    height: i32,
+}
+
+// Renders a subset of Markdown: # headings, **bold**, *italic*, `code`,
+// fenced code blocks, - and 1. lists, > quotes, --- rules and
+// [links](url). Clicking a link sends UiEvent::LinkClicked with its url.
+// Text wraps to the width of the widget, which scrolls when it is taller.
+pub struct MarkdownView {
+    name: String,
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    pref: (i32, i32),
+    source: String,
+    blocks: Vec<Block>,
+    fonts: Fonts,
+    // made when drawing, whenever the width or the content changed
+    layout: RefCell<Option<Layout>>,
+    // height of the last layout, for scrolling
+    content_height: Cell<i32>,
+    scroll: i32,
+    clicked: Option<String>,
+    state: WidgetState,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl MarkdownView {
+    pub fn new(text: &TextRenderer, name: &str, x: i32, y: i32, w: i32, h: i32) -> MarkdownView {
+        MarkdownView {
+            name: name.to_string(),
+            x,
+            y,
+            w,
+            h,
+            pref: (w, h),
+            source: String::new(),
+            blocks: Vec::new(),
+            fonts: Fonts::load(text, FontId::DEFAULT),
+            layout: RefCell::new(None),
+            content_height: Cell::new(0),
+            scroll: 0,
+            clicked: None,
+            state: WidgetState::default(),
+            theme: Theme::default(),
+            own_theme: false,
+        }
+    }
+
+    pub fn with_markdown(mut self, source: &str) -> MarkdownView {
+        self.set_markdown(source);
+        self
+    }
+
+    pub fn markdown(&self) -> &str { &self.source }
+
+    // Replaces the document and goes back to its top
+    pub fn set_markdown(&mut self, source: &str) {
+        self.source = source.to_string();
+        self.blocks = parse(source);
+        self.scroll = 0;
+        self.layout.replace(None);
+    }
+
+    // The document without its markup, a line per block
+    pub fn plain_text(&self) -> String {
+        let text = |inlines: &[Inline]| inlines.iter().map(|i| i.text.as_str()).collect::<String>();
+        let lines: Vec<String> = self.blocks.iter().map(|block| match block {
+            Block::Heading(_, inlines) | Block::Paragraph(inlines) | Block::Quote(inlines) => text(inlines),
+            Block::Item(_, marker, inlines) => format!("{} {}", marker, text(inlines)),
+            Block::Code(lines) => lines.join("\n"),
+            Block::Rule => String::new(),
+        }).collect();
+        lines.join("\n")
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+        self.layout.replace(None);
+    }
+
+    fn max_scroll(&self) -> i32 {
+        (self.content_height.get() - self.h).max(0)
+    }
+
+    fn scroll_by(&mut self, dy: i32) {
+        self.scroll = (self.scroll + dy).clamp(0, self.max_scroll());
+    }
+
+    fn line_height(&self) -> i32 {
+        self.layout.borrow().as_ref().and_then(|l| l.pieces.first()).map_or(16, |p| p.rect.height() as i32)
+    }
+
+    fn lay_out(&self, text: &TextRenderer) -> Result<Layout, ToolkitError> {
+        let theme = &self.theme;
+        let pad = theme.padding;
+        let mut out = Layout {width: self.w, pieces: Vec::new(), decor: Vec::new(), height: 0};
+        let right = self.w - pad;
+        let indent = text.size_of_font(self.fonts.body, " ")?.0 as i32 * INDENT_SPACES;
+        let mut y = pad;
+        for block in self.blocks.iter() {
+            match block {
+                Block::Heading(level, inlines) => {
+                    y = self.flow(text, &mut out, inlines, self.fonts.heading(*level), pad, right, y)? + pad;
+                },
+                Block::Paragraph(inlines) => {
+                    y = self.flow(text, &mut out, inlines, self.fonts.body, pad, right, y)? + pad;
+                },
+                Block::Item(depth, marker, inlines) => {
+                    let left = pad + (*depth as i32 + 1) * indent;
+                    if let Some(texture) = text.render_spans(self.fonts.body, &[TextSpan::new(marker, theme.text)])? {
+                        let attr = texture.query();
+                        let rect = Rect::new(left - attr.width as i32 - pad, y, attr.width, attr.height);
+                        out.pieces.push(Piece {rect, texture, link: None, code: false});
+                    }
+                    // an empty item still takes its line
+                    let below = self.flow(text, &mut out, inlines, self.fonts.body, left, right, y)?;
+                    y = below.max(y + text.font_height(self.fonts.body)) + pad / 2;
+                },
+                Block::Quote(inlines) => {
+                    let top = y;
+                    y = self.flow(text, &mut out, inlines, self.fonts.body, pad + indent, right, y)?;
+                    out.decor.push(Decor::Fill(Rect::new(pad + indent / 3, top, 3, (y - top).max(1) as u32), theme.border));
+                    y += pad;
+                },
+                Block::Code(lines) => {
+                    let top = y;
+                    y += pad / 2;
+                    let line_h = text.font_height(self.fonts.code);
+                    for line in lines.iter() {
+                        if let Some(texture) = text.render_spans(self.fonts.code, &[TextSpan::new(line, theme.text)])? {
+                            let attr = texture.query();
+                            out.pieces.push(Piece {rect: Rect::new(2 * pad, y, attr.width, attr.height), texture, link: None, code: false});
+                        }
+                        y += line_h;
+                    }
+                    y += pad / 2;
+                    out.decor.push(Decor::Fill(Rect::new(pad, top, (right - pad).max(1) as u32, (y - top) as u32), theme.fill));
+                    y += pad;
+                },
+                Block::Rule => {
+                    out.decor.push(Decor::Line(pad, right, y + pad / 2, theme.border));
+                    y += pad * 2;
+                },
+            }
+        }
+        out.height = y;
+        Ok(out)
+    }
+
+    // Lays out `inlines` as wrapped lines between `left` and `right` from
+    // `y` on, and returns the y below them
+    #[allow(clippy::too_many_arguments)]
+    fn flow(&self, text: &TextRenderer, out: &mut Layout, inlines: &[Inline], font: FontId, left: i32, right: i32, mut y: i32) -> Result<i32, ToolkitError> {
+        // words, each made of parts in different styles
+        let mut words: Vec<Vec<(&str, &Style)>> = vec![Vec::new()];
+        for inline in inlines {
+            for (i, word) in inline.text.split(' ').enumerate() {
+                if i > 0 {
+                    words.push(Vec::new());
+                }
+                if !word.is_empty() {
+                    words.last_mut().into_iter().for_each(|w| w.push((word, &inline.style)));
+                }
+            }
+        }
+
+        let base_h = text.font_height(font);
+        let space = text.size_of_font(font, " ")?.0 as i32;
+        let (mut x, mut line_h) = (left, base_h);
+        let mut line: Vec<Piece> = Vec::new();
+        // puts the pieces of a full line on a common bottom
+        let finish = |line: &mut Vec<Piece>, out: &mut Layout, y: i32, line_h: i32| {
+            for mut piece in line.drain(..) {
+                piece.rect.set_y(y + line_h - piece.rect.height() as i32);
+                out.pieces.push(piece);
+            }
+        };
+        for word in words.iter().filter(|w| !w.is_empty()) {
+            let mut rendered = Vec::new();
+            let mut width = 0;
+            for &(part, style) in word.iter() {
+                let color = if style.link.is_some() { self.theme.active } else { self.theme.text };
+                let span = TextSpan {text: part.to_string(), color, bold: style.bold, italic: style.italic};
+                let part_font = if style.code { self.fonts.code } else { font };
+                if let Some(texture) = text.render_spans(part_font, &[span])? {
+                    width += texture.query().width as i32;
+                    rendered.push((texture, style));
+                }
+            }
+            if x > left && x + space + width > right {
+                finish(&mut line, out, y, line_h);
+                y += line_h;
+                (x, line_h) = (left, base_h);
+            }
+            if x > left {
+                x += space;
+            }
+            for (texture, style) in rendered {
+                let attr = texture.query();
+                line_h = line_h.max(attr.height as i32);
+                line.push(Piece {rect: Rect::new(x, 0, attr.width, attr.height), texture, link: style.link.clone(), code: style.code});
+                x += attr.width as i32;
+            }
+        }
+        if !line.is_empty() {
+            finish(&mut line, out, y, line_h);
+            y += line_h;
+        }
+        Ok(y)
+    }
+
+    // Link under a point of the widget
+    fn link_at(&self, x: i32, y: i32) -> Option<String> {
+        let layout = self.layout.borrow();
+        let (dx, dy) = (x - self.x, y - self.y + self.scroll);
+        layout.as_ref()?.pieces.iter()
+            .find(|p| p.link.is_some() && p.rect.contains_point((dx, dy)))
+            .and_then(|p| p.link.clone())
+    }
+
+    fn key(&mut self, key: Keycode) -> bool {
+        let line = self.line_height();
+        match key {
+            Keycode::Up => self.scroll_by(-line),
+            Keycode::Down => self.scroll_by(line),
+            Keycode::PageUp => self.scroll_by(-(self.h - line)),
+            Keycode::PageDown => self.scroll_by(self.h - line),
+            Keycode::Home => self.scroll = 0,
+            Keycode::End => self.scroll = self.max_scroll(),
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl Debug for MarkdownView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MarkdownView")
+            .field("name", &self.name)
+            .field("rect", &self.rect())
+            .field("blocks", &self.blocks.len())
+            .field("scroll", &self.scroll)
+            .finish()
+    }
+}
+
+impl Drawable for MarkdownView {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let rect = self.rect();
+        let theme = &self.theme;
+        ctx.fill_rounded_rect(rect, theme.corner_radius, theme.field)?;
+
+        let stale = self.layout.borrow().as_ref().is_none_or(|l| l.width != self.w);
+        if stale {
+            let layout = self.lay_out(ctx.text())?;
+            self.content_height.set(layout.height);
+            self.layout.replace(Some(layout));
+        }
+        let layout = self.layout.borrow();
+        let Some(layout) = layout.as_ref() else {
+            return Ok(());
+        };
+
+        // from document to screen coordinates
+        let (ox, oy) = (self.x, self.y - self.scroll);
+        let on_screen = |r: Rect| Rect::new(r.x() + ox, r.y() + oy, r.width(), r.height());
+        ctx.push_clip(rect);
+        for decor in layout.decor.iter() {
+            match *decor {
+                Decor::Fill(r, color) => ctx.fill_rect(on_screen(r), color)?,
+                Decor::Line(x1, x2, y, color) => ctx.draw_line((x1 + ox, y + oy), (x2 + ox, y + oy), color)?,
+            }
+        }
+        for piece in layout.pieces.iter() {
+            let r = on_screen(piece.rect);
+            if r.bottom() < rect.top() || r.top() > rect.bottom() {
+                continue;
+            }
+            if piece.code {
+                ctx.fill_rect(r, theme.fill)?;
+            }
+            ctx.canvas().copy(&piece.texture, None, r)?;
+            if piece.link.is_some() {
+                ctx.draw_line((r.left(), r.bottom() - 1), (r.right() - 1, r.bottom() - 1), theme.active)?;
+            }
+        }
+        ctx.pop_clip();
+
+        let border = if self.state.focused { theme.active } else { theme.border };
+        ctx.draw_rounded_rect(rect, theme.corner_radius, border)?;
+        Ok(())
+    }
+}
+
+impl Widget for MarkdownView {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { Rect::new(self.x, self.y, self.w as u32, self.h as u32) }
+    fn role(&self) -> Role { Role::TextArea }
+    fn access_value(&self) -> Option<String> { Some(self.plain_text()) }
+    fn focusable(&self) -> bool { true }
+    fn widget_state(&self) -> Option<&WidgetState> { Some(&self.state) }
+    fn widget_state_mut(&mut self) -> Option<&mut WidgetState> { Some(&mut self.state) }
+    fn take_link(&mut self) -> Option<String> { self.clicked.take() }
+
+    fn size_hint(&self) -> (u32, u32) {
+        (self.pref.0 as u32, self.pref.1 as u32)
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.x = rect.x();
+        self.y = rect.y();
+        self.w = rect.width() as i32;
+        self.h = rect.height() as i32;
+        self.scroll = self.scroll.clamp(0, self.max_scroll());
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+            // the colors are in the textures
+            self.layout.replace(None);
+        }
+    }
+
+    fn set_font(&mut self, text: &TextRenderer, font: FontId) -> Result<(), ToolkitError> {
+        self.fonts = Fonts::load(text, font);
+        self.layout.replace(None);
+        Ok(())
+    }
+
+    fn scroll_pixels(&mut self, _dx: i32, dy: i32) -> bool {
+        let before = self.scroll;
+        self.scroll_by(-dy);
+        self.scroll != before
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::MouseButtonDown {mouse_btn: MouseButton::Left, x, y, ..} => {
+                self.clicked = self.link_at(*x, *y);
+            },
+            Event::MouseWheel {y, ..} => {
+                self.scroll_by(-y * WHEEL_LINES * self.line_height());
+            },
+            Event::KeyDown {keycode: Some(key), ..} => return self.key(*key),
+            _ => return false,
+        }
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(text: &str) -> Inline {
+        Inline {text: text.to_string(), style: Style::default()}
+    }
+
+    fn styled(text: &str, f: impl FnOnce(&mut Style)) -> Inline {
+        let mut style = Style::default();
+        f(&mut style);
+        Inline {text: text.to_string(), style}
+    }
+
+    fn link(text: &str, url: &str) -> Inline {
+        styled(text, |s| s.link = Some(url.to_string()))
+    }
+
+    #[test]
+    fn emphasis() {
+        assert_eq!(inlines("**b** *i* __b__ _i_"), vec![
+            styled("b", |s| s.bold = true),
+            plain(" "),
+            styled("i", |s| s.italic = true),
+            plain(" "),
+            styled("b", |s| s.bold = true),
+            plain(" "),
+            styled("i", |s| s.italic = true),
+        ]);
+        assert_eq!(inlines("**bold *both***"), vec![
+            styled("bold ", |s| s.bold = true),
+            styled("both", |s| {
+                s.bold = true;
+                s.italic = true;
+            }),
+        ]);
+    }
+
+    #[test]
+    fn emphasis_inside_words() {
+        assert_eq!(inlines("snake_case_name"), vec![plain("snake_case_name")]);
+        assert_eq!(inlines("un*frigging*believable"), vec![plain("un"), styled("frigging", |s| s.italic = true), plain("believable")]);
+    }
+
+    #[test]
+    fn unmatched_markers_stay() {
+        assert_eq!(inlines("*unclosed"), vec![plain("*unclosed")]);
+        assert_eq!(inlines("2 * 3 * 4"), vec![plain("2 * 3 * 4")]);
+        assert_eq!(inlines("`open code"), vec![plain("`open code")]);
+        assert_eq!(inlines("[text](url"), vec![plain("[text](url")]);
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(inlines(r"\*not\* \[x](y) a\\b"), vec![plain("*not* [x](y) a\\b")]);
+    }
+
+    #[test]
+    fn code_spans_keep_markup() {
+        assert_eq!(inlines("run `a*b*_c_`"), vec![plain("run "), styled("a*b*_c_", |s| s.code = true)]);
+    }
+
+    #[test]
+    fn links() {
+        assert_eq!(inlines("see [the *docs*]( https://x.org ) now"), vec![
+            plain("see "),
+            link("the ", "https://x.org"),
+            styled("docs", |s| {
+                s.italic = true;
+                s.link = Some("https://x.org".to_string());
+            }),
+            plain(" now"),
+        ]);
+        assert_eq!(inlines("<https://x.org> <mailto:a@b.c> <b>"), vec![
+            link("https://x.org", "https://x.org"),
+            plain(" "),
+            link("mailto:a@b.c", "mailto:a@b.c"),
+            plain(" <b>"),
+        ]);
+    }
+
+    #[test]
+    fn nested_links() {
+        assert_eq!(inlines("[a [b](u1) c](u2)"), vec![link("a ", "u2"), link("b", "u1"), link(" c", "u2")]);
+        assert_eq!(inlines("[a [b] c](u)"), vec![link("a [b] c", "u")]);
+    }
+
+    #[test]
+    fn headings_and_rules() {
+        assert_eq!(parse("# One #\n### Three\n#nope\n\n---\n* * *"), vec![
+            Block::Heading(1, vec![plain("One")]),
+            Block::Heading(3, vec![plain("Three")]),
+            Block::Paragraph(vec![plain("#nope")]),
+            Block::Rule,
+            Block::Rule,
+        ]);
+    }
+
+    #[test]
+    fn paragraphs_join_lines() {
+        assert_eq!(parse("one\ntwo\n\nthree"), vec![
+            Block::Paragraph(vec![plain("one two")]),
+            Block::Paragraph(vec![plain("three")]),
+        ]);
+    }
+
+    #[test]
+    fn lists() {
+        assert_eq!(parse("- a\n  continued\n  * b\n1) c\n22. d\n3.no"), vec![
+            Block::Item(0, "\u{2022}".to_string(), vec![plain("a continued")]),
+            Block::Item(1, "\u{2022}".to_string(), vec![plain("b")]),
+            Block::Item(0, "1.".to_string(), vec![plain("c")]),
+            Block::Item(0, "22.".to_string(), vec![plain("d 3.no")]),
+        ]);
+    }
+
+    #[test]
+    fn quotes() {
+        assert_eq!(parse("> a\n>b\n\n> c"), vec![
+            Block::Quote(vec![plain("a b")]),
+            Block::Quote(vec![plain("c")]),
+        ]);
+    }
+
+    #[test]
+    fn code_fences() {
+        assert_eq!(parse("text\n```rust\nlet *a* = 1;\n\tb\n```\nafter"), vec![
+            Block::Paragraph(vec![plain("text")]),
+            Block::Code(vec!["let *a* = 1;".to_string(), "    b".to_string()]),
+            Block::Paragraph(vec![plain("after")]),
+        ]);
+        // a fence nobody closes runs to the end
+        assert_eq!(parse("```\ncode\n\n# not a heading"), vec![
+            Block::Code(vec!["code".to_string(), String::new(), "# not a heading".to_string()]),
+        ]);
+    }
+}
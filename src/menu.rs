@@ -0,0 +1,418 @@
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+
+use crate::{DrawCtx, Drawable, TextRenderer, Theme, ToolkitError};
+
+// Room on the right of a row for the arrow of a submenu
+const ARROW_WIDTH: i32 = 12;
+const MIN_WIDTH: i32 = 80;
+
+// One entry of a menu. Picking it counts as a click on a widget called
+// `name`, so Toolkit::on_click handlers run for it. A '&' in the label
+// marks the next letter as the item's mnemonic, "&&" is a plain '&'.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuItem {
+    name: String,
+    label: String,
+    enabled: bool,
+    separator: bool,
+    children: Vec<MenuItem>,
+}
+
+impl MenuItem {
+    pub fn new(name: &str) -> MenuItem {
+        MenuItem {
+            name: name.to_string(),
+            label: name.to_string(),
+            enabled: true,
+            separator: false,
+            children: Vec::new(),
+        }
+    }
+
+    // A line between groups of items
+    pub fn separator() -> MenuItem {
+        MenuItem {
+            separator: true,
+            ..MenuItem::new("")
+        }
+    }
+
+    pub fn label(mut self, label: &str) -> MenuItem {
+        self.label = label.to_string();
+        self
+    }
+
+    pub fn enabled(mut self, enabled: bool) -> MenuItem {
+        self.enabled = enabled;
+        self
+    }
+
+    // Items that open another menu next to this one when hovered
+    pub fn submenu<I: IntoIterator<Item = MenuItem>>(mut self, items: I) -> MenuItem {
+        self.children = items.into_iter().collect();
+        self
+    }
+
+    pub fn name(&self) -> &str { &self.name }
+    pub fn is_submenu(&self) -> bool { !self.children.is_empty() }
+
+    pub(crate) fn label_text(&self) -> &str { &self.label }
+    pub(crate) fn children(&self) -> &[MenuItem] { &self.children }
+
+    pub(crate) fn text(&self) -> String {
+        parse_label(&self.label).0
+    }
+
+    pub(crate) fn mnemonic(&self) -> Option<char> {
+        parse_label(&self.label).1.map(|(_, c)| c)
+    }
+
+    pub(crate) fn selectable(&self) -> bool {
+        self.enabled && !self.separator
+    }
+}
+
+// Splits a label into the text shown and its mnemonic, if any, along with
+// the byte offset of the mnemonic in the text
+fn parse_label(label: &str) -> (String, Option<(usize, char)>) {
+    let mut text = String::with_capacity(label.len());
+    let mut mnemonic = None;
+    let mut chars = label.chars();
+    while let Some(c) = chars.next() {
+        if c != '&' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('&') => text.push('&'),
+            Some(c) => {
+                if mnemonic.is_none() {
+                    mnemonic = Some((text.len(), c.to_ascii_lowercase()));
+                }
+                text.push(c);
+            },
+            None => { },
+        }
+    }
+    (text, mnemonic)
+}
+
+// The letter a key types, for matching mnemonics
+pub(crate) fn key_char(key: Keycode) -> Option<char> {
+    char::from_u32(key as i32 as u32).filter(|c| c.is_ascii_alphanumeric())
+}
+
+// Draws a label at `x`, centered in `row` vertically, with its mnemonic
+// underlined
+pub(crate) fn draw_label(ctx: &mut DrawCtx, label: &str, x: i32, row: Rect, color: Color) -> Result<(), ToolkitError> {
+    let (text, mnemonic) = parse_label(label);
+    if text.is_empty() {
+        return Ok(());
+    }
+    let texture = ctx.text().render(&text)?;
+    let attr = texture.query();
+    let y = row.y() + (row.height() as i32 - attr.height as i32) / 2;
+    ctx.tint(&texture, color);
+    ctx.canvas().copy(&texture, None, Rect::new(x, y, attr.width, attr.height))?;
+
+    if let Some((idx, c)) = mnemonic {
+        let before = if idx == 0 { 0 } else { ctx.text().render(&text[..idx])?.query().width as i32 };
+        let width = ctx.text().render(&text[idx..idx + c.len_utf8()])?.query().width as i32;
+        let line_y = y + attr.height as i32 - 2;
+        ctx.canvas().set_draw_color(color);
+        ctx.canvas().draw_line(Point::new(x + before, line_y), Point::new(x + before + width, line_y))?;
+    }
+    Ok(())
+}
+
+// What the Toolkit should do after handing an event to an open menu
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum MenuAction {
+    // not for the menu, handle it as usual
+    Ignored,
+    Consumed,
+    Close,
+    Select(String),
+}
+
+// One open menu, the first is the one that was opened, the others are
+// submenus each opened from a row of the one before
+struct Level {
+    items: Vec<MenuItem>,
+    rect: Rect,
+    hover: Option<usize>,
+    // row of the previous level this one belongs to
+    parent: Option<usize>,
+}
+
+// A floating menu drawn above everything. It takes all input while open.
+pub(crate) struct Menu {
+    levels: Vec<Level>,
+    // entry of the menu bar this dropped down from
+    pub(crate) bar: Option<usize>,
+    // window area menus have to stay inside
+    area: Rect,
+    text_height: i32,
+    theme: Theme,
+}
+
+impl Menu {
+    pub(crate) fn new(text: &TextRenderer, theme: Theme, x: i32, y: i32, items: Vec<MenuItem>, area: Rect) -> Menu {
+        let mut menu = Menu {
+            levels: Vec::new(),
+            bar: None,
+            area,
+            text_height: text.height(),
+            theme,
+        };
+        let rect = menu.level_rect(text, &items, x, y, None);
+        menu.levels.push(Level {items, rect, hover: None, parent: None});
+        menu
+    }
+
+    pub(crate) fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    // Opened from the menu bar's entry `idx`
+    pub(crate) fn bar_entry(mut self, idx: usize) -> Menu {
+        self.bar = Some(idx);
+        self
+    }
+
+    // Highlights the first row, for menus opened from the keyboard
+    pub(crate) fn hover_first(mut self) -> Menu {
+        self.step(true);
+        self
+    }
+
+    pub(crate) fn depth(&self) -> usize { self.levels.len() }
+
+    // Whether the highlighted row of the innermost level opens a submenu
+    pub(crate) fn hovering_submenu(&self) -> bool {
+        let level = self.levels.last().unwrap();
+        level.hover.is_some_and(|row| level.items[row].is_submenu())
+    }
+
+    fn row_height(&self) -> i32 {
+        self.text_height + self.theme.padding
+    }
+
+    fn item_height(&self, item: &MenuItem) -> i32 {
+        if item.separator { self.theme.padding } else { self.row_height() }
+    }
+
+    // Places a menu with its top left at (x, y), or wherever it fits. A
+    // submenu that doesn't fit to the right of `parent` goes to its left.
+    fn level_rect(&self, text: &TextRenderer, items: &[MenuItem], x: i32, y: i32, parent: Option<Rect>) -> Rect {
+        let pad = self.theme.padding;
+        let label_w = items.iter()
+            .map(|item| item.text())
+            .filter(|label| !label.is_empty())
+            .filter_map(|label| text.render(&label).ok())
+            .map(|texture| texture.query().width as i32)
+            .max()
+            .unwrap_or(0);
+        let w = (label_w + 2 * pad + ARROW_WIDTH).max(MIN_WIDTH);
+        let h: i32 = items.iter().map(|item| self.item_height(item)).sum();
+
+        let area = self.area;
+        let mut x = x;
+        if x + w > area.right() {
+            x = match parent {
+                Some(parent) => parent.left() - w,
+                None => area.right() - w,
+            };
+        }
+        let x = x.max(area.left());
+        let y = y.min(area.bottom() - h).max(area.top());
+        Rect::new(x, y, w as u32, h.max(0) as u32)
+    }
+
+    fn row_rect(&self, level: &Level, idx: usize) -> Rect {
+        let y: i32 = level.items[..idx].iter().map(|item| self.item_height(item)).sum();
+        let h = self.item_height(&level.items[idx]);
+        Rect::new(level.rect.x(), level.rect.y() + y, level.rect.width(), h as u32)
+    }
+
+    fn row_at(&self, level: &Level, x: i32, y: i32) -> Option<usize> {
+        (0..level.items.len()).find(|idx| self.row_rect(level, *idx).contains_point((x, y)))
+    }
+
+    // Topmost open level under the point
+    fn level_at(&self, x: i32, y: i32) -> Option<usize> {
+        self.levels.iter().rposition(|level| level.rect.contains_point((x, y)))
+    }
+
+    pub(crate) fn contains(&self, x: i32, y: i32) -> bool {
+        self.level_at(x, y).is_some()
+    }
+
+    fn open_submenu(&mut self, text: &TextRenderer, level: usize, row: usize) {
+        self.levels.truncate(level + 1);
+        let item = &self.levels[level].items[row];
+        if !item.selectable() || !item.is_submenu() {
+            return;
+        }
+        let items = item.children.clone();
+        let parent = self.levels[level].rect;
+        let row_rect = self.row_rect(&self.levels[level], row);
+        let rect = self.level_rect(text, &items, parent.right(), row_rect.y(), Some(parent));
+        self.levels.push(Level {items, rect, hover: None, parent: Some(row)});
+    }
+
+    // Moves the highlight of the innermost level to the next selectable row
+    fn step(&mut self, forward: bool) {
+        let level = self.levels.last_mut().unwrap();
+        let len = level.items.len();
+        if len == 0 {
+            return;
+        }
+        let mut idx = level.hover.unwrap_or(if forward { len - 1 } else { 0 });
+        for _ in 0..len {
+            idx = if forward { (idx + 1) % len } else { (idx + len - 1) % len };
+            if level.items[idx].selectable() {
+                level.hover = Some(idx);
+                return;
+            }
+        }
+    }
+
+    // Picks the highlighted row of the innermost level, opening it if it
+    // is a submenu
+    fn activate(&mut self, text: &TextRenderer) -> MenuAction {
+        let depth = self.levels.len() - 1;
+        let level = &self.levels[depth];
+        let Some(row) = level.hover.filter(|row| level.items[*row].selectable()) else {
+            return MenuAction::Consumed;
+        };
+        if level.items[row].is_submenu() {
+            self.open_submenu(text, depth, row);
+            self.step(true);
+            return MenuAction::Consumed;
+        }
+        MenuAction::Select(level.items[row].name.clone())
+    }
+
+    pub(crate) fn handle_event(&mut self, event: &Event, text: &TextRenderer) -> MenuAction {
+        match event {
+            Event::MouseMotion {x, y, ..} => {
+                let Some(depth) = self.level_at(*x, *y) else {
+                    return MenuAction::Consumed;
+                };
+                let row = self.row_at(&self.levels[depth], *x, *y);
+                let level = &mut self.levels[depth];
+                level.hover = row.filter(|row| level.items[*row].selectable());
+                // leave an already open submenu of the hovered row alone
+                let open = self.levels.get(depth + 1).and_then(|sub| sub.parent);
+                match row {
+                    Some(row) if open == Some(row) => { },
+                    Some(row) => self.open_submenu(text, depth, row),
+                    None => self.levels.truncate(depth + 1),
+                }
+            },
+            Event::MouseButtonDown {x, y, ..} => {
+                if !self.contains(*x, *y) {
+                    return MenuAction::Close;
+                }
+            },
+            Event::MouseButtonUp {mouse_btn: MouseButton::Left, x, y, ..} => {
+                let Some(depth) = self.level_at(*x, *y) else {
+                    return MenuAction::Consumed;
+                };
+                let level = &self.levels[depth];
+                if let Some(item) = self.row_at(level, *x, *y).map(|row| &level.items[row]) {
+                    if item.selectable() && !item.is_submenu() {
+                        return MenuAction::Select(item.name.clone());
+                    }
+                }
+            },
+            Event::KeyDown {keycode: Some(key), ..} => {
+                match key {
+                    Keycode::Escape | Keycode::Left if self.levels.len() > 1 => {
+                        self.levels.pop();
+                    },
+                    Keycode::Escape => return MenuAction::Close,
+                    Keycode::Up => self.step(false),
+                    Keycode::Down => self.step(true),
+                    Keycode::Right => {
+                        let level = self.levels.last().unwrap();
+                        if level.hover.is_some_and(|row| level.items[row].is_submenu()) {
+                            return self.activate(text);
+                        }
+                    },
+                    Keycode::Return | Keycode::KpEnter | Keycode::Space => return self.activate(text),
+                    key => {
+                        // the mnemonic picks a row of the innermost level
+                        let Some(c) = key_char(*key) else {
+                            return MenuAction::Consumed;
+                        };
+                        let level = self.levels.last_mut().unwrap();
+                        if let Some(row) = level.items.iter().position(|item| item.selectable() && item.mnemonic() == Some(c)) {
+                            level.hover = Some(row);
+                            return self.activate(text);
+                        }
+                    },
+                }
+            },
+            Event::KeyUp {..} | Event::TextInput {..} | Event::TextEditing {..} | Event::MouseWheel {..} | Event::MouseButtonUp {..} => { },
+            _ => return MenuAction::Ignored,
+        }
+        MenuAction::Consumed
+    }
+}
+
+impl Debug for Menu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Menu")
+            .field("levels", &self.levels.iter().map(|l| l.rect).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Drawable for Menu {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let theme = &self.theme;
+        let pad = theme.padding;
+        for level in self.levels.iter() {
+            ctx.canvas().set_draw_color(theme.field);
+            ctx.canvas().fill_rect(level.rect)?;
+
+            for (idx, item) in level.items.iter().enumerate() {
+                let row = self.row_rect(level, idx);
+                if item.separator {
+                    let y = row.y() + row.height() as i32 / 2;
+                    ctx.canvas().set_draw_color(theme.border);
+                    ctx.canvas().draw_line(Point::new(row.left() + pad, y), Point::new(row.right() - pad, y))?;
+                    continue;
+                }
+                if level.hover == Some(idx) {
+                    ctx.canvas().set_draw_color(theme.active);
+                    ctx.canvas().fill_rect(row)?;
+                }
+                let color = if item.enabled { theme.text } else { theme.disabled };
+                draw_label(ctx, &item.label, row.x() + pad, row, color)?;
+                if item.is_submenu() {
+                    // a small ">" at the right end
+                    let cx = row.right() - pad - ARROW_WIDTH / 4;
+                    let cy = row.center().y();
+                    let s = ARROW_WIDTH / 4;
+                    ctx.canvas().set_draw_color(color);
+                    ctx.canvas().draw_line(Point::new(cx - s, cy - s), Point::new(cx, cy))?;
+                    ctx.canvas().draw_line(Point::new(cx, cy), Point::new(cx - s, cy + s))?;
+                }
+            }
+
+            ctx.canvas().set_draw_color(theme.border);
+            ctx.canvas().draw_rect(level.rect)?;
+        }
+        Ok(())
+    }
+}
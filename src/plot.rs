@@ -0,0 +1,553 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+use crate::{DrawCtx, Drawable, Role, Theme, ToolkitError, Widget};
+
+// For series without a color of their own, in the order they were added
+const PALETTE: [Color; 6] = [
+    Color::RGB(66, 133, 244),
+    Color::RGB(234, 67, 53),
+    Color::RGB(52, 168, 83),
+    Color::RGB(251, 188, 5),
+    Color::RGB(171, 71, 188),
+    Color::RGB(0, 172, 193),
+];
+// Share of the room at each x that bars take up, the rest is a gap
+const BAR_FILL: f64 = 0.8;
+// Roughly how far apart tick labels are, in pixels
+const X_TICK_SPACING: i32 = 80;
+const Y_TICK_SPACING: i32 = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlotKind {
+    Line,
+    Bar,
+}
+
+// One set of (x, y) points, drawn as a line or as bars
+#[derive(Debug, Clone)]
+pub struct Series {
+    label: String,
+    kind: PlotKind,
+    color: Option<Color>,
+    points: VecDeque<(f64, f64)>,
+}
+
+impl Series {
+    pub fn new(label: &str, kind: PlotKind) -> Series {
+        Series {
+            label: label.to_string(),
+            kind,
+            color: None,
+            points: VecDeque::new(),
+        }
+    }
+
+    pub fn line(label: &str) -> Series {
+        Series::new(label, PlotKind::Line)
+    }
+
+    pub fn bars(label: &str) -> Series {
+        Series::new(label, PlotKind::Bar)
+    }
+
+    pub fn color(mut self, color: Color) -> Series {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn with_values(mut self, values: &[f64]) -> Series {
+        self.set_values(values);
+        self
+    }
+
+    pub fn with_points(mut self, points: &[(f64, f64)]) -> Series {
+        self.set_points(points);
+        self
+    }
+
+    pub fn label(&self) -> &str { &self.label }
+    pub fn kind(&self) -> PlotKind { self.kind }
+    pub fn points(&self) -> &VecDeque<(f64, f64)> { &self.points }
+
+    // Values are plotted at x = 0, 1, 2...
+    pub fn set_values(&mut self, values: &[f64]) {
+        self.points = values.iter().enumerate().map(|(i, &y)| (i as f64, y)).collect();
+    }
+
+    pub fn set_points(&mut self, points: &[(f64, f64)]) {
+        self.points = points.iter().copied().collect();
+    }
+
+    // Adds a value one past the last x
+    pub fn push(&mut self, y: f64) {
+        let x = self.points.back().map_or(0.0, |&(x, _)| x + 1.0);
+        self.points.push_back((x, y));
+    }
+
+    pub fn push_point(&mut self, x: f64, y: f64) {
+        self.points.push_back((x, y));
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+}
+
+// Ticks of one axis: the range rounded out to whole steps
+#[derive(Debug, Clone, Copy)]
+struct Axis {
+    lo: f64,
+    hi: f64,
+    step: f64,
+}
+
+impl Axis {
+    // Steps of 1, 2 or 5 times a power of ten, about `count` of them
+    fn nice(lo: f64, hi: f64, count: i32) -> Axis {
+        let (lo, hi) = if hi - lo > f64::EPSILON * hi.abs().max(1.0) { (lo, hi) } else { (lo - 1.0, hi + 1.0) };
+        let raw = (hi - lo) / count.max(1) as f64;
+        let mag = 10f64.powf(raw.log10().floor());
+        let step = match raw / mag {
+            n if n <= 1.0 => 1.0,
+            n if n <= 2.0 => 2.0,
+            n if n <= 5.0 => 5.0,
+            _ => 10.0,
+        } * mag;
+        Axis {lo: (lo / step).floor() * step, hi: (hi / step).ceil() * step, step}
+    }
+
+    // Exactly lo to hi, with ticks wherever whole steps fall in it
+    fn fixed(lo: f64, hi: f64, count: i32) -> Axis {
+        let nice = Axis::nice(lo, hi, count);
+        Axis {lo, hi: if hi > lo { hi } else { lo + 1.0 }, step: nice.step}
+    }
+
+    fn ticks(&self) -> Vec<f64> {
+        let first = (self.lo / self.step).ceil() as i64;
+        let last = (self.hi / self.step).floor() as i64;
+        (first..=last).map(|i| i as f64 * self.step).collect()
+    }
+
+    // Just enough decimals for the step
+    fn label(&self, value: f64) -> String {
+        let decimals = (-self.step.log10().floor()).max(0.0) as usize;
+        let label = format!("{:.*}", decimals, value);
+        // no "-0", whatever rounded to it
+        match label.strip_prefix('-') {
+            Some(zero) if zero.chars().all(|c| c == '0' || c == '.') => zero.to_string(),
+            _ => label,
+        }
+    }
+
+    // Where `value` falls between `from` and `to`, in pixels
+    fn map(&self, value: f64, from: i32, to: i32) -> i32 {
+        let t = (value - self.lo) / (self.hi - self.lo);
+        from + ((to - from) as f64 * t).round() as i32
+    }
+}
+
+// Line and bar charts with axes scaled to the data, tick labels and a
+// legend. Points can be appended as they come in, and with max_points set
+// the oldest go, which makes for a scrolling chart of live data.
+pub struct Plot {
+    name: String,
+    rect: Rect,
+    series: Vec<Series>,
+    max_points: Option<usize>,
+    // fixed ranges, otherwise they follow the data
+    x_range: Option<(f64, f64)>,
+    y_range: Option<(f64, f64)>,
+    legend: bool,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl Plot {
+    pub fn new(name: &str, x: i32, y: i32, w: u32, h: u32) -> Plot {
+        Plot {
+            name: name.to_string(),
+            rect: Rect::new(x, y, w, h),
+            series: Vec::new(),
+            max_points: None,
+            x_range: None,
+            y_range: None,
+            legend: true,
+            theme: Theme::default(),
+            own_theme: false,
+        }
+    }
+
+    pub fn with_series(mut self, series: Series) -> Plot {
+        self.add_series(series);
+        self
+    }
+
+    pub fn max_points(mut self, max: usize) -> Plot {
+        self.set_max_points(Some(max));
+        self
+    }
+
+    pub fn x_range(mut self, min: f64, max: f64) -> Plot {
+        self.x_range = Some((min, max));
+        self
+    }
+
+    pub fn y_range(mut self, min: f64, max: f64) -> Plot {
+        self.y_range = Some((min, max));
+        self
+    }
+
+    pub fn legend(mut self, legend: bool) -> Plot {
+        self.legend = legend;
+        self
+    }
+
+    // Returns the index push and series_mut take
+    pub fn add_series(&mut self, series: Series) -> usize {
+        self.series.push(series);
+        self.trim();
+        self.series.len() - 1
+    }
+
+    pub fn series(&self) -> &[Series] { &self.series }
+
+    // Points added here are not held to max_points until the next push
+    pub fn series_mut(&mut self, idx: usize) -> Option<&mut Series> {
+        self.series.get_mut(idx)
+    }
+
+    // Appends to series `idx`, see Series::push
+    pub fn push(&mut self, idx: usize, y: f64) -> bool {
+        let Some(series) = self.series.get_mut(idx) else {
+            return false;
+        };
+        series.push(y);
+        self.trim();
+        true
+    }
+
+    pub fn push_point(&mut self, idx: usize, x: f64, y: f64) -> bool {
+        let Some(series) = self.series.get_mut(idx) else {
+            return false;
+        };
+        series.push_point(x, y);
+        self.trim();
+        true
+    }
+
+    pub fn set_max_points(&mut self, max: Option<usize>) {
+        self.max_points = max;
+        self.trim();
+    }
+
+    // None goes back to following the data
+    pub fn set_x_range(&mut self, range: Option<(f64, f64)>) {
+        self.x_range = range;
+    }
+
+    pub fn set_y_range(&mut self, range: Option<(f64, f64)>) {
+        self.y_range = range;
+    }
+
+    // Empties every series, keeping the series themselves
+    pub fn clear(&mut self) {
+        for series in self.series.iter_mut() {
+            series.clear();
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+    }
+
+    fn trim(&mut self) {
+        let Some(max) = self.max_points else {
+            return;
+        };
+        for series in self.series.iter_mut() {
+            while series.points.len() > max {
+                series.points.pop_front();
+            }
+        }
+    }
+
+    fn color_of(&self, idx: usize) -> Color {
+        self.series[idx].color.unwrap_or(PALETTE[idx % PALETTE.len()])
+    }
+
+    fn finite_points(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.series.iter()
+            .flat_map(|s| s.points.iter().copied())
+            .filter(|(x, y)| x.is_finite() && y.is_finite())
+    }
+
+    // Closest two bars of a series get to each other, in x units
+    fn bar_slot(&self) -> f64 {
+        let mut slot = f64::INFINITY;
+        for series in self.series.iter().filter(|s| s.kind == PlotKind::Bar) {
+            for (a, b) in series.points.iter().zip(series.points.iter().skip(1)) {
+                let dx = (b.0 - a.0).abs();
+                if dx > 0.0 {
+                    slot = slot.min(dx);
+                }
+            }
+        }
+        if slot.is_finite() { slot } else { 1.0 }
+    }
+
+    // Ranges of the data, with room for whole bars and their baseline
+    fn data_range(&self) -> ((f64, f64), (f64, f64)) {
+        let (mut x0, mut x1, mut y0, mut y1) = (f64::INFINITY, f64::NEG_INFINITY, f64::INFINITY, f64::NEG_INFINITY);
+        for (x, y) in self.finite_points() {
+            x0 = x0.min(x);
+            x1 = x1.max(x);
+            y0 = y0.min(y);
+            y1 = y1.max(y);
+        }
+        if !x0.is_finite() {
+            return ((0.0, 1.0), (0.0, 1.0));
+        }
+        if self.series.iter().any(|s| s.kind == PlotKind::Bar && !s.points.is_empty()) {
+            let half = self.bar_slot() / 2.0;
+            x0 -= half;
+            x1 += half;
+            y0 = y0.min(0.0);
+            y1 = y1.max(0.0);
+        }
+        ((x0, x1), (y0, y1))
+    }
+
+    fn draw_legend(&self, ctx: &mut DrawCtx, area: Rect) -> Result<(), ToolkitError> {
+        let labeled: Vec<usize> = (0..self.series.len()).filter(|&i| !self.series[i].label.is_empty()).collect();
+        if !self.legend || labeled.is_empty() {
+            return Ok(());
+        }
+        let pad = self.theme.padding;
+        let line_h = ctx.text().height();
+        let swatch = line_h / 2;
+        let mut w = 0;
+        for &i in labeled.iter() {
+            w = w.max(ctx.text().size_of(&self.series[i].label)?.0 as i32);
+        }
+        let (w, h) = (w + swatch + 3 * pad, labeled.len() as i32 * line_h + 2 * pad);
+        let bx = Rect::new(area.right() - w - pad, area.y() + pad, w as u32, h as u32);
+        ctx.fill_rect(bx, self.theme.field)?;
+        ctx.draw_rect(bx, self.theme.border)?;
+        for (row, &i) in labeled.iter().enumerate() {
+            let y = bx.y() + pad + row as i32 * line_h;
+            let sq = Rect::new(bx.x() + pad, y + (line_h - swatch) / 2, swatch as u32, swatch as u32);
+            ctx.fill_rect(sq, self.color_of(i))?;
+            ctx.draw_text_at(sq.right() + pad, y, &self.series[i].label, self.theme.text)?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for Plot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Plot")
+            .field("name", &self.name)
+            .field("rect", &self.rect)
+            .field("series", &self.series.len())
+            .field("max_points", &self.max_points)
+            .field("x_range", &self.x_range)
+            .field("y_range", &self.y_range)
+            .finish()
+    }
+}
+
+impl Drawable for Plot {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let theme = &self.theme;
+        let r = self.rect;
+        ctx.fill_rect(r, theme.field)?;
+        ctx.draw_rect(r, theme.border)?;
+
+        let pad = theme.padding;
+        let text_h = ctx.text().height();
+        let ((dx0, dx1), (dy0, dy1)) = self.data_range();
+        let y_count = (r.height() as i32 - text_h) / Y_TICK_SPACING;
+        let y_axis = match self.y_range {
+            Some((lo, hi)) => Axis::fixed(lo, hi, y_count),
+            None => Axis::nice(dy0, dy1, y_count),
+        };
+        let y_ticks = y_axis.ticks();
+        let mut label_w = 0;
+        for &v in y_ticks.iter() {
+            label_w = label_w.max(ctx.text().size_of(&y_axis.label(v))?.0 as i32);
+        }
+
+        let area = Rect::new(
+            r.x() + 2 * pad + label_w,
+            r.y() + pad + text_h / 2,
+            (r.width() as i32 - 3 * pad - label_w).max(0) as u32,
+            (r.height() as i32 - 2 * pad - text_h - text_h / 2).max(0) as u32,
+        );
+        if area.width() < 4 || area.height() < 4 {
+            return Ok(());
+        }
+        let x_count = area.width() as i32 / X_TICK_SPACING;
+        let x_axis = match self.x_range {
+            Some((lo, hi)) => Axis::fixed(lo, hi, x_count),
+            // bars stay where the data puts them, lines get round ends
+            None if self.series.iter().any(|s| s.kind == PlotKind::Bar) => Axis::fixed(dx0, dx1, x_count),
+            None => Axis::nice(dx0, dx1, x_count),
+        };
+        let (left, right, top, bottom) = (area.left(), area.right() - 1, area.top(), area.bottom() - 1);
+
+        for &v in y_ticks.iter() {
+            let y = y_axis.map(v, bottom, top);
+            ctx.draw_line((left, y), (right, y), theme.border)?;
+            let label = y_axis.label(v);
+            let w = ctx.text().size_of(&label)?.0 as i32;
+            ctx.draw_text_at(left - pad - w, y - text_h / 2, &label, theme.text)?;
+        }
+        for v in x_axis.ticks() {
+            let x = x_axis.map(v, left, right);
+            ctx.draw_line((x, bottom), (x, bottom + pad / 2), theme.border)?;
+            let label = x_axis.label(v);
+            let w = ctx.text().size_of(&label)?.0 as i32;
+            ctx.draw_text_at(x - w / 2, bottom + pad / 2, &label, theme.text)?;
+        }
+        ctx.draw_rect(area, theme.border)?;
+
+        ctx.clipped(area, |ctx| {
+            let bars: Vec<usize> = (0..self.series.len()).filter(|&i| self.series[i].kind == PlotKind::Bar).collect();
+            if !bars.is_empty() {
+                let slot = x_axis.map(x_axis.lo + self.bar_slot(), left, right) - left;
+                let bar_w = ((slot as f64 * BAR_FILL) as i32 / bars.len() as i32).max(1);
+                let base = y_axis.map(0.0f64.clamp(y_axis.lo, y_axis.hi), bottom, top);
+                for (j, &i) in bars.iter().enumerate() {
+                    let offset = j as i32 * bar_w - bar_w * bars.len() as i32 / 2;
+                    for &(x, y) in self.series[i].points.iter().filter(|(x, y)| x.is_finite() && y.is_finite()) {
+                        let (bx, by) = (x_axis.map(x, left, right) + offset, y_axis.map(y, bottom, top));
+                        let bar = Rect::new(bx, by.min(base), bar_w as u32, (by - base).unsigned_abs().max(1));
+                        ctx.fill_rect(bar, self.color_of(i))?;
+                    }
+                }
+            }
+            for (i, series) in self.series.iter().enumerate().filter(|(_, s)| s.kind == PlotKind::Line) {
+                let color = self.color_of(i);
+                let points: Vec<(i32, i32)> = series.points.iter()
+                    .filter(|(x, y)| x.is_finite() && y.is_finite())
+                    .map(|&(x, y)| (x_axis.map(x, left, right), y_axis.map(y, bottom, top)))
+                    .collect();
+                if let [only] = points.as_slice() {
+                    ctx.fill_circle(*only, 2, color)?;
+                }
+                for pair in points.windows(2) {
+                    ctx.draw_line(pair[0], pair[1], color)?;
+                }
+            }
+            Ok(())
+        })?;
+        self.draw_legend(ctx, area)
+    }
+}
+
+impl Widget for Plot {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { self.rect }
+    fn role(&self) -> Role { Role::Image }
+    fn set_rect(&mut self, rect: Rect) { self.rect = rect; }
+    fn size_hint(&self) -> (u32, u32) { (self.rect.width(), self.rect.height()) }
+
+    fn access_label(&self) -> Option<String> {
+        let labels: Vec<&str> = self.series.iter().map(|s| s.label.as_str()).filter(|l| !l.is_empty()).collect();
+        Some(labels.join(", ")).filter(|l| !l.is_empty())
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: &[f64], b: &[f64]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-9)
+    }
+
+    #[test]
+    fn nice_steps_are_1_2_or_5() {
+        let axis = Axis::nice(0.0, 97.0, 5);
+        assert_eq!((axis.lo, axis.hi, axis.step), (0.0, 100.0, 20.0));
+        let axis = Axis::nice(0.0, 0.37, 4);
+        assert!((axis.step - 0.1).abs() < 1e-12 && (axis.hi - 0.4).abs() < 1e-12);
+        let axis = Axis::nice(3.0, 4.0, 5);
+        assert!((axis.step - 0.2).abs() < 1e-12);
+        assert_eq!(Axis::nice(0.0, 1000.0, 2).step, 500.0);
+    }
+
+    #[test]
+    fn degenerate_range_is_widened() {
+        let axis = Axis::nice(5.0, 5.0, 5);
+        assert!(axis.lo < 5.0 && axis.hi > 5.0 && axis.step > 0.0);
+        let axis = Axis::nice(0.0, 0.0, 5);
+        assert!(axis.lo < 0.0 && axis.hi > 0.0);
+        assert!(close(&axis.ticks(), &[-1.0, -0.5, 0.0, 0.5, 1.0]));
+        let axis = Axis::fixed(2.0, 2.0, 5);
+        assert_eq!((axis.lo, axis.hi), (2.0, 3.0));
+    }
+
+    #[test]
+    fn negative_ranges() {
+        let axis = Axis::nice(-7.3, -2.1, 5);
+        assert_eq!((axis.lo, axis.hi, axis.step), (-8.0, -2.0, 2.0));
+        assert!(close(&axis.ticks(), &[-8.0, -6.0, -4.0, -2.0]));
+        let axis = Axis::nice(-15.0, 12.0, 3);
+        assert_eq!((axis.lo, axis.hi, axis.step), (-20.0, 20.0, 10.0));
+        assert!(close(&axis.ticks(), &[-20.0, -10.0, 0.0, 10.0, 20.0]));
+    }
+
+    #[test]
+    fn fixed_range_keeps_its_ends() {
+        let axis = Axis::fixed(-0.5, 2.5, 3);
+        assert_eq!((axis.lo, axis.hi), (-0.5, 2.5));
+        assert!(close(&axis.ticks(), &[0.0, 1.0, 2.0]));
+    }
+
+    #[test]
+    fn labels_have_just_enough_decimals() {
+        let axis = Axis::nice(0.0, 0.37, 4);
+        let labels: Vec<String> = axis.ticks().iter().map(|&v| axis.label(v)).collect();
+        assert_eq!(labels, ["0.0", "0.1", "0.2", "0.3", "0.4"]);
+        assert_eq!(Axis::nice(0.0, 97.0, 5).label(40.0), "40");
+    }
+
+    #[test]
+    fn labels_never_say_minus_zero() {
+        let axis = Axis::nice(-1.0, 1.0, 4);
+        for value in [-0.0, -1e-12, -0.04, 0.0] {
+            assert!(!axis.label(value).starts_with('-'), "{}", value);
+        }
+        let axis = Axis::nice(-0.3, 0.3, 6);
+        assert_eq!(axis.label(-0.04), "0.0");
+        assert_eq!(axis.label(-0.06), "-0.1");
+        for value in axis.ticks() {
+            assert_ne!(axis.label(value), "-0.0");
+        }
+    }
+
+    #[test]
+    fn map_spans_the_pixels() {
+        let axis = Axis::fixed(0.0, 10.0, 5);
+        assert_eq!(axis.map(0.0, 100, 0), 100);
+        assert_eq!(axis.map(10.0, 100, 0), 0);
+        assert_eq!(axis.map(5.0, 100, 0), 50);
+    }
+}
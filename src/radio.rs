@@ -0,0 +1,159 @@
+use std::any::Any;
+use std::fmt;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use sdl2::rect::Rect;
+use sdl2::render::Texture;
+
+use crate::{DrawCtx, Drawable, Role, TextRenderer, Theme, Toolkit, ToolkitError, Widget, WidgetState};
+
+pub type RadioCallback = Box<dyn FnMut(&mut Toolkit, usize)>;
+
+// Shared state of all radio buttons with the same group name
+pub struct RadioGroup {
+    pub(crate) members: Vec<String>,
+    pub(crate) selected: Option<usize>,
+    pub(crate) callbacks: Vec<RadioCallback>,
+}
+
+impl RadioGroup {
+    pub(crate) fn new() -> RadioGroup {
+        RadioGroup {
+            members: Vec::new(),
+            selected: None,
+            callbacks: Vec::new(),
+        }
+    }
+
+    pub fn selected_index(&self) -> Option<usize> { self.selected }
+    pub fn selected_name(&self) -> Option<&str> {
+        self.selected.and_then(|idx| self.members.get(idx)).map(|s| s.as_str())
+    }
+    pub fn members(&self) -> &[String] { &self.members }
+}
+
+impl Debug for RadioGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RadioGroup")
+            .field("members", &self.members)
+            .field("selected", &self.selected)
+            .finish()
+    }
+}
+
+// One option out of a RadioGroup, only one per group can be selected
+pub struct RadioButton {
+    name: String,
+    group: String,
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    selected: bool,
+    state: WidgetState,
+    label: Rc<Texture<'static>>,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl RadioButton {
+    pub fn new(text: &TextRenderer, group: &str, name: &str, x: i32, y: i32) -> Result<RadioButton, ToolkitError> {
+        let mut radio = RadioButton {
+            name: name.to_string(),
+            group: group.to_string(),
+            x,
+            y,
+            w: 0,
+            h: 0,
+            selected: false,
+            state: WidgetState::default(),
+            label: text.render(name).map_err(ToolkitError::texture_for(name))?,
+            theme: Theme::default(),
+            own_theme: false,
+        };
+        (radio.w, radio.h) = radio.natural_size();
+        Ok(radio)
+    }
+
+    pub fn group(&self) -> &str { &self.group }
+    pub fn selected(&self) -> bool { self.selected }
+    pub(crate) fn set_selected(&mut self, selected: bool) { self.selected = selected; }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+    }
+
+    fn natural_size(&self) -> (i32, i32) {
+        let attr = self.label.query();
+        let h = attr.height as i32;
+        (h + self.theme.padding + attr.width as i32, h)
+    }
+}
+
+impl Debug for RadioButton {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RadioButton")
+            .field("name", &self.name)
+            .field("group", &self.group)
+            .field("rect", &self.rect())
+            .field("selected", &self.selected)
+            .finish()
+    }
+}
+
+impl Drawable for RadioButton {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let theme = &self.theme;
+        let d = self.h * 2 / 3;
+        let circle = Rect::new(self.x + (self.h - d) / 2, self.y + (self.h - d) / 2, d as u32, d as u32);
+        ctx.fill_rounded_rect(circle, d / 2, theme.field)?;
+        let border = if self.state.focused { theme.active } else { theme.border };
+        ctx.draw_rounded_rect(circle, d / 2, border)?;
+        if self.selected {
+            let dot = d / 2;
+            let inner = Rect::from_center(circle.center(), dot as u32, dot as u32);
+            let dot_color = if self.state.enabled { theme.active } else { theme.disabled };
+            ctx.fill_rounded_rect(inner, dot / 2, dot_color)?;
+        }
+
+        let attr = self.label.query();
+        let label = Rect::new(self.x + self.h + theme.padding, self.y, attr.width, attr.height);
+        ctx.tint(&self.label, if self.state.enabled { theme.text } else { theme.disabled });
+        ctx.canvas().copy(&self.label, None, label)?;
+        Ok(())
+    }
+}
+
+impl Widget for RadioButton {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { Rect::new(self.x, self.y, self.w as u32, self.h as u32) }
+    fn role(&self) -> Role { Role::RadioButton }
+    fn access_checked(&self) -> Option<bool> { Some(self.selected) }
+    fn clickable(&self) -> bool { true }
+    fn focusable(&self) -> bool { true }
+    fn widget_state(&self) -> Option<&WidgetState> { Some(&self.state) }
+    fn widget_state_mut(&mut self) -> Option<&mut WidgetState> { Some(&mut self.state) }
+
+    fn size_hint(&self) -> (u32, u32) {
+        let (w, h) = self.natural_size();
+        (w as u32, h as u32)
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.x = rect.x();
+        self.y = rect.y();
+        self.w = rect.width() as i32;
+        self.h = rect.height() as i32;
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
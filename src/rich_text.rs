@@ -0,0 +1,256 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt;
+use std::fmt::Debug;
+use std::ops::Range;
+
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Texture;
+use sdl2::ttf::FontStyle;
+
+use crate::{DrawCtx, Drawable, FontId, Role, TextRenderer, Theme, ToolkitError, Widget};
+
+// A run of text with its own color and style within a line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextSpan {
+    pub text: String,
+    pub color: Color,
+    pub bold: bool,
+    pub italic: bool,
+}
+
+impl TextSpan {
+    pub fn new(text: &str, color: Color) -> TextSpan {
+        TextSpan {
+            text: text.to_string(),
+            color,
+            bold: false,
+            italic: false,
+        }
+    }
+
+    pub fn bold(mut self) -> TextSpan {
+        self.bold = true;
+        self
+    }
+
+    pub fn italic(mut self) -> TextSpan {
+        self.italic = true;
+        self
+    }
+
+    pub(crate) fn style(&self) -> FontStyle {
+        let mut style = FontStyle::NORMAL;
+        if self.bold {
+            style |= FontStyle::BOLD;
+        }
+        if self.italic {
+            style |= FontStyle::ITALIC;
+        }
+        style
+    }
+}
+
+// One line made of differently colored or styled spans, rendered into a
+// single texture whenever the spans change
+pub struct RichText {
+    name: String,
+    x: i32,
+    y: i32,
+    spans: Vec<TextSpan>,
+    font: FontId,
+    texture: Option<Texture<'static>>,
+    size: (u32, u32),
+    // the mouse selects text and Ctrl+C copies it, the text takes focus then
+    selectable: bool,
+    // selection as char offsets, the same when there is none
+    anchor: usize,
+    cursor: usize,
+    // the mouse button is down and dragging moves the cursor
+    selecting: bool,
+    // x of every char boundary, measured on the first draw after a change
+    offsets: RefCell<Option<Vec<i32>>>,
+    theme: Theme,
+}
+
+impl RichText {
+    pub fn new(text: &TextRenderer, name: &str, x: i32, y: i32, spans: Vec<TextSpan>) -> Result<RichText, ToolkitError> {
+        let mut rich = RichText {
+            name: name.to_string(),
+            x,
+            y,
+            spans: Vec::new(),
+            font: FontId::DEFAULT,
+            texture: None,
+            size: (0, text.height() as u32),
+            selectable: false,
+            anchor: 0,
+            cursor: 0,
+            selecting: false,
+            offsets: RefCell::new(None),
+            theme: Theme::default(),
+        };
+        rich.set_spans(text, spans)?;
+        Ok(rich)
+    }
+
+    pub fn selectable(mut self, selectable: bool) -> RichText {
+        self.set_selectable(selectable);
+        self
+    }
+
+    pub fn spans(&self) -> &[TextSpan] { &self.spans }
+    pub fn is_selectable(&self) -> bool { self.selectable }
+
+    pub fn set_selectable(&mut self, selectable: bool) {
+        self.selectable = selectable;
+        self.select(0..0);
+    }
+
+    // Char range of the selected text, empty when nothing is selected
+    pub fn selection(&self) -> Range<usize> {
+        self.anchor.min(self.cursor)..self.anchor.max(self.cursor)
+    }
+
+    pub fn selected_text(&self) -> String {
+        let range = self.selection();
+        self.spans.iter().flat_map(|s| s.text.chars()).skip(range.start).take(range.len()).collect()
+    }
+
+    // Offsets past the end are clamped
+    pub fn select(&mut self, range: Range<usize>) {
+        let len = self.len();
+        self.anchor = range.start.min(len);
+        self.cursor = range.end.min(len);
+    }
+
+    pub fn select_all(&mut self) {
+        self.select(0..self.len());
+    }
+
+    fn len(&self) -> usize {
+        self.spans.iter().map(|s| s.text.chars().count()).sum()
+    }
+
+    // Char boundary closest to window x `x`, going by the text as last drawn
+    fn offset_at(&self, x: i32) -> usize {
+        let offsets = self.offsets.borrow();
+        let Some(xs) = offsets.as_ref() else {
+            return 0;
+        };
+        let x = x - self.x;
+        xs.windows(2).position(|w| x < (w[0] + w[1]) / 2).unwrap_or(xs.len() - 1)
+    }
+
+    pub fn set_spans(&mut self, text: &TextRenderer, spans: Vec<TextSpan>) -> Result<(), ToolkitError> {
+        self.texture = text.render_spans(self.font, &spans)?;
+        self.size = match &self.texture {
+            Some(texture) => {
+                let attr = texture.query();
+                (attr.width, attr.height)
+            },
+            None => (0, text.font_height(self.font) as u32),
+        };
+        self.spans = spans;
+        self.offsets.replace(None);
+        self.select(0..0);
+        Ok(())
+    }
+}
+
+impl Debug for RichText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RichText")
+            .field("name", &self.name)
+            .field("rect", &self.rect())
+            .field("spans", &self.spans)
+            .field("selection", &self.selection())
+            .finish()
+    }
+}
+
+impl Drawable for RichText {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let rect = self.rect();
+        if self.selectable && self.offsets.borrow().is_none() {
+            self.offsets.replace(Some(ctx.text().span_offsets(self.font, &self.spans)?));
+        }
+        let selection = self.selection();
+        if let Some(xs) = self.offsets.borrow().as_ref().filter(|_| !selection.is_empty()) {
+            let (x0, x1) = (xs[selection.start], xs[selection.end]);
+            ctx.fill_rect(Rect::new(rect.x() + x0, rect.y(), (x1 - x0) as u32, rect.height()), self.theme.active)?;
+        }
+        if let Some(texture) = &self.texture {
+            ctx.canvas().copy(texture, None, rect)?;
+        }
+        Ok(())
+    }
+}
+
+impl Widget for RichText {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { Rect::new(self.x, self.y, self.size.0, self.size.1) }
+    fn role(&self) -> Role { Role::Label }
+    fn access_label(&self) -> Option<String> { Some(self.spans.iter().map(|s| s.text.as_str()).collect()) }
+    fn focusable(&self) -> bool { self.selectable }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.x = rect.x();
+        self.y = rect.y();
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.theme = *theme;
+    }
+
+    // a double click selects everything
+    fn handle_event(&mut self, event: &Event) -> bool {
+        if !self.selectable {
+            return false;
+        }
+        match event {
+            Event::MouseButtonDown {mouse_btn: MouseButton::Left, clicks, x, ..} => {
+                match clicks {
+                    1 => {
+                        let at = self.offset_at(*x);
+                        self.select(at..at);
+                    },
+                    _ => self.select_all(),
+                }
+                self.selecting = *clicks == 1;
+                true
+            },
+            Event::MouseMotion {x, ..} if self.selecting => {
+                self.cursor = self.offset_at(*x);
+                true
+            },
+            Event::MouseButtonUp {mouse_btn: MouseButton::Left, ..} => {
+                self.selecting = false;
+                false
+            },
+            Event::KeyDown {keycode: Some(Keycode::A), keymod, ..} if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                self.select_all();
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn copy_text(&self) -> Option<String> {
+        let text = self.selected_text();
+        (!text.is_empty()).then_some(text)
+    }
+
+    fn set_font(&mut self, text: &TextRenderer, font: FontId) -> Result<(), ToolkitError> {
+        self.font = font;
+        let spans = std::mem::take(&mut self.spans);
+        self.set_spans(text, spans)
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
@@ -0,0 +1,325 @@
+use std::any::Any;
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::event::Event;
+use sdl2::mouse::MouseButton;
+use sdl2::rect::Rect;
+
+use crate::{DrawCtx, Drawable, Orientation, Role, Theme, ToolkitError, Widget};
+
+const BAR_WIDTH: i32 = 12;
+const MIN_THUMB: i32 = 16;
+// Pixels scrolled per wheel notch
+const WHEEL_STEP: i32 = 40;
+
+// Scrollbar being dragged, with where on the thumb it was grabbed
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    orientation: Orientation,
+    grab: i32,
+}
+
+// Shows a part of a child that is bigger than the area itself. The child
+// gets its full hinted size and is moved around underneath the viewport.
+pub struct ScrollArea {
+    name: String,
+    rect: Rect,
+    fixed: bool,
+    pref: (u32, u32),
+    scroll: (i32, i32),
+    // size the child was laid out at
+    content: (i32, i32),
+    drag: Option<Drag>,
+    children: Vec<Box<dyn Widget>>,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl ScrollArea {
+    pub fn new<W: Widget>(name: &str, w: u32, h: u32, content: W) -> ScrollArea {
+        ScrollArea {
+            name: name.to_string(),
+            rect: Rect::new(0, 0, w, h),
+            fixed: false,
+            pref: (w, h),
+            scroll: (0, 0),
+            content: (0, 0),
+            drag: None,
+            children: vec![Box::new(content)],
+            theme: Theme::default(),
+            own_theme: false,
+        }
+    }
+
+    pub fn content(&self) -> &dyn Widget { self.children[0].as_ref() }
+    pub fn content_mut(&mut self) -> &mut dyn Widget { self.children[0].as_mut() }
+
+    pub fn scroll_offset(&self) -> (i32, i32) { self.scroll }
+
+    pub fn scroll_to(&mut self, x: i32, y: i32) {
+        self.scroll = (x, y);
+        self.place();
+    }
+
+    // Scrolls as little as needed to bring `rect` (in window coordinates,
+    // like the child's own rects) into view
+    pub fn scroll_into_view(&mut self, rect: Rect) {
+        let view = self.viewport_rect();
+        let (mut x, mut y) = self.scroll;
+        if rect.right() > view.right() {
+            x += rect.right() - view.right();
+        }
+        if rect.left() < view.left() {
+            x -= view.left() - rect.left();
+        }
+        if rect.bottom() > view.bottom() {
+            y += rect.bottom() - view.bottom();
+        }
+        if rect.top() < view.top() {
+            y -= view.top() - rect.top();
+        }
+        self.scroll_to(x, y);
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+    }
+
+    // Which scrollbars the child's size calls for. Each bar takes room the
+    // other direction could have used, so one can bring in the other.
+    fn bars(&self) -> (bool, bool) {
+        let (w, h) = (self.rect.width() as i32, self.rect.height() as i32);
+        let (cw, ch) = self.children[0].size_hint();
+        let (cw, ch) = (cw as i32, ch as i32);
+        let mut vertical = ch > h;
+        let horizontal = cw > w - if vertical { BAR_WIDTH } else { 0 };
+        vertical |= horizontal && ch > h - BAR_WIDTH;
+        (horizontal, vertical)
+    }
+
+    fn viewport_rect(&self) -> Rect {
+        let (horizontal, vertical) = self.bars();
+        let w = self.rect.width() as i32 - if vertical { BAR_WIDTH } else { 0 };
+        let h = self.rect.height() as i32 - if horizontal { BAR_WIDTH } else { 0 };
+        Rect::new(self.rect.x(), self.rect.y(), w.max(0) as u32, h.max(0) as u32)
+    }
+
+    fn max_scroll(&self) -> (i32, i32) {
+        let view = self.viewport_rect();
+        (
+            (self.content.0 - view.width() as i32).max(0),
+            (self.content.1 - view.height() as i32).max(0),
+        )
+    }
+
+    // Lays the child out at its hinted size, stretched to fill the viewport,
+    // and shifted by the scroll offset
+    fn place(&mut self) {
+        let view = self.viewport_rect();
+        let (cw, ch) = self.children[0].size_hint();
+        self.content = ((cw as i32).max(view.width() as i32), (ch as i32).max(view.height() as i32));
+        let (mx, my) = self.max_scroll();
+        self.scroll = (self.scroll.0.clamp(0, mx), self.scroll.1.clamp(0, my));
+
+        let rect = Rect::new(
+            view.x() - self.scroll.0,
+            view.y() - self.scroll.1,
+            self.content.0 as u32,
+            self.content.1 as u32,
+        );
+        self.children[0].set_rect(rect);
+        self.children[0].layout(rect);
+    }
+
+    fn track_rect(&self, orientation: Orientation) -> Option<Rect> {
+        let (horizontal, vertical) = self.bars();
+        let view = self.viewport_rect();
+        match orientation {
+            Orientation::Vertical if vertical => Some(Rect::new(view.right(), view.y(), BAR_WIDTH as u32, view.height())),
+            Orientation::Horizontal if horizontal => Some(Rect::new(view.x(), view.bottom(), view.width(), BAR_WIDTH as u32)),
+            _ => None,
+        }
+    }
+
+    // The track, plus offset and length of the thumb along it
+    fn thumb(&self, orientation: Orientation) -> Option<(Rect, i32, i32)> {
+        let track = self.track_rect(orientation)?;
+        let view = self.viewport_rect();
+        let (track_len, view_len, content, scroll, max) = match orientation {
+            Orientation::Vertical => (track.height() as i32, view.height() as i32, self.content.1, self.scroll.1, self.max_scroll().1),
+            Orientation::Horizontal => (track.width() as i32, view.width() as i32, self.content.0, self.scroll.0, self.max_scroll().0),
+        };
+        let len = (track_len * view_len / content.max(1)).clamp(MIN_THUMB.min(track_len), track_len);
+        let pos = if max > 0 { (track_len - len) * scroll / max } else { 0 };
+        Some((track, pos, len))
+    }
+
+    fn thumb_rect(&self, orientation: Orientation) -> Option<Rect> {
+        let (track, pos, len) = self.thumb(orientation)?;
+        Some(match orientation {
+            Orientation::Vertical => Rect::new(track.x(), track.y() + pos, track.width(), len as u32),
+            Orientation::Horizontal => Rect::new(track.x() + pos, track.y(), len as u32, track.height()),
+        })
+    }
+
+    // Moves the thumb so the grabbed spot lands on `at` along the track
+    fn drag_to(&mut self, drag: Drag, at: i32) {
+        let Some((track, _, len)) = self.thumb(drag.orientation) else {
+            return;
+        };
+        let (start, track_len) = match drag.orientation {
+            Orientation::Vertical => (track.y(), track.height() as i32),
+            Orientation::Horizontal => (track.x(), track.width() as i32),
+        };
+        let free = (track_len - len).max(1);
+        let pos = at - start - drag.grab;
+        let (mx, my) = self.max_scroll();
+        let (x, y) = match drag.orientation {
+            Orientation::Vertical => (self.scroll.0, pos * my / free),
+            Orientation::Horizontal => (pos * mx / free, self.scroll.1),
+        };
+        self.scroll_to(x, y);
+    }
+
+    fn press_bar(&mut self, orientation: Orientation, x: i32, y: i32) {
+        let Some(thumb) = self.thumb_rect(orientation) else {
+            return;
+        };
+        let view = self.viewport_rect();
+        let (at, thumb_start, thumb_end, page) = match orientation {
+            Orientation::Vertical => (y, thumb.top(), thumb.bottom(), view.height() as i32),
+            Orientation::Horizontal => (x, thumb.left(), thumb.right(), view.width() as i32),
+        };
+        if at >= thumb_start && at < thumb_end {
+            self.drag = Some(Drag {orientation, grab: at - thumb_start});
+            return;
+        }
+        // clicks on the track beside the thumb move a page at a time
+        let step = if at < thumb_start { -page } else { page };
+        let (sx, sy) = self.scroll;
+        match orientation {
+            Orientation::Vertical => self.scroll_to(sx, sy + step),
+            Orientation::Horizontal => self.scroll_to(sx + step, sy),
+        }
+    }
+
+    fn draw_bar(&self, ctx: &mut DrawCtx, orientation: Orientation) -> Result<(), ToolkitError> {
+        let (Some(track), Some(thumb)) = (self.track_rect(orientation), self.thumb_rect(orientation)) else {
+            return Ok(());
+        };
+        let theme = &self.theme;
+        ctx.canvas().set_draw_color(theme.field);
+        ctx.canvas().fill_rect(track)?;
+        let dragging = self.drag.is_some_and(|d| d.orientation == orientation);
+        let color = if dragging { theme.active } else { theme.fill };
+        let radius = BAR_WIDTH / 2 - 2;
+        let inset = Rect::new(thumb.x() + 2, thumb.y() + 2, thumb.width().saturating_sub(4), thumb.height().saturating_sub(4));
+        ctx.fill_rounded_rect(inset, radius, color)?;
+        Ok(())
+    }
+}
+
+impl Debug for ScrollArea {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScrollArea")
+            .field("name", &self.name)
+            .field("rect", &self.rect)
+            .field("scroll", &self.scroll)
+            .field("content", &self.children[0])
+            .finish()
+    }
+}
+
+impl Drawable for ScrollArea {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let view = self.viewport_rect();
+        ctx.clipped(view, |ctx| self.children[0].draw(ctx))?;
+
+        self.draw_bar(ctx, Orientation::Vertical)?;
+        self.draw_bar(ctx, Orientation::Horizontal)?;
+        if let (Some(_), Some(_)) = (self.track_rect(Orientation::Vertical), self.track_rect(Orientation::Horizontal)) {
+            let corner = Rect::new(view.right(), view.bottom(), BAR_WIDTH as u32, BAR_WIDTH as u32);
+            ctx.canvas().set_draw_color(self.theme.fill);
+            ctx.canvas().fill_rect(corner)?;
+        }
+        Ok(())
+    }
+}
+
+impl Widget for ScrollArea {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { self.rect }
+    fn role(&self) -> Role { Role::ScrollArea }
+    fn size_hint(&self) -> (u32, u32) { self.pref }
+    fn viewport(&self) -> Option<Rect> { Some(self.viewport_rect()) }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        self.fixed = true;
+    }
+
+    fn layout(&mut self, area: Rect) {
+        if !self.fixed {
+            self.rect = area;
+        }
+        self.place();
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+        }
+        for child in self.children.iter_mut() {
+            child.apply_theme(theme);
+        }
+    }
+
+    fn scroll_pixels(&mut self, dx: i32, dy: i32) -> bool {
+        let before = self.scroll;
+        self.scroll_to(self.scroll.0 - dx, self.scroll.1 - dy);
+        self.scroll != before
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::MouseWheel {x, y, ..} => {
+                // shift+wheel comes as x, the toolkit turns it sideways
+                let before = self.scroll;
+                self.scroll_to(self.scroll.0 + x * WHEEL_STEP, self.scroll.1 - y * WHEEL_STEP);
+                return self.scroll != before;
+            },
+            Event::MouseButtonDown {mouse_btn: MouseButton::Left, x, y, ..} => {
+                for orientation in [Orientation::Vertical, Orientation::Horizontal] {
+                    if self.track_rect(orientation).is_some_and(|r| r.contains_point((*x, *y))) {
+                        self.press_bar(orientation, *x, *y);
+                        return true;
+                    }
+                }
+                return false;
+            },
+            Event::MouseMotion {x, y, ..} => {
+                let Some(drag) = self.drag else {
+                    return false;
+                };
+                let at = match drag.orientation {
+                    Orientation::Vertical => *y,
+                    Orientation::Horizontal => *x,
+                };
+                self.drag_to(drag, at);
+            },
+            Event::MouseButtonUp {mouse_btn: MouseButton::Left, ..} => {
+                return self.drag.take().is_some();
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] { &self.children }
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] { &mut self.children }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
@@ -0,0 +1,54 @@
+use std::fmt;
+use std::fmt::Debug;
+use std::ptr;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+
+use sdl2::event::Event;
+
+use crate::{Toolkit, ToolkitError};
+
+// Work posted from another thread, run on the UI thread with the Toolkit
+pub type Message = Box<dyn FnOnce(&mut Toolkit) + Send>;
+
+// Lets background threads hand work to the UI thread. Messages are run in
+// the order they were sent, during the next tick() or run() iteration.
+#[derive(Clone)]
+pub struct EventSender {
+    tx: Sender<Message>,
+    // SDL's own sender, only used to wake up a RunningApp::run waiting for input
+    sdl: Arc<sdl2::event::EventSender>,
+    wake_type: u32,
+}
+
+impl EventSender {
+    pub(crate) fn new(tx: Sender<Message>, sdl: sdl2::event::EventSender, wake_type: u32) -> EventSender {
+        EventSender {
+            tx,
+            sdl: Arc::new(sdl),
+            wake_type,
+        }
+    }
+
+    // Fails once the Toolkit has been dropped
+    pub fn send<F: FnOnce(&mut Toolkit) + Send + 'static>(&self, f: F) -> Result<(), ToolkitError> {
+        self.tx.send(Box::new(f)).map_err(|_| ToolkitError::Disconnected)?;
+        self.sdl.push_event(Event::User {
+            timestamp: 0,
+            window_id: 0,
+            type_: self.wake_type,
+            code: 0,
+            data1: ptr::null_mut(),
+            data2: ptr::null_mut(),
+        })?;
+        Ok(())
+    }
+}
+
+impl Debug for EventSender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventSender")
+            .field("wake_type", &self.wake_type)
+            .finish()
+    }
+}
@@ -0,0 +1,156 @@
+use std::any::Any;
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::rect::{Point, Rect};
+
+use crate::{DrawCtx, Drawable, Orientation, Role, Theme, ToolkitError, Widget};
+
+// Thin line to split a box layout into groups. It runs across the box it is
+// in, so a horizontal separator belongs in a vertical box and vice versa.
+pub struct Separator {
+    name: String,
+    orientation: Orientation,
+    rect: Rect,
+    // empty space on either side of the line
+    margin: i32,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl Separator {
+    pub fn new(name: &str, orientation: Orientation) -> Separator {
+        Separator {
+            name: name.to_string(),
+            orientation,
+            rect: Rect::new(0, 0, 0, 0),
+            margin: 2,
+            theme: Theme::default(),
+            own_theme: false,
+        }
+    }
+
+    pub fn horizontal() -> Separator {
+        Separator::new("", Orientation::Horizontal)
+    }
+
+    pub fn vertical() -> Separator {
+        Separator::new("", Orientation::Vertical)
+    }
+
+    pub fn margin(mut self, margin: i32) -> Separator {
+        self.margin = margin.max(0);
+        self
+    }
+
+    pub fn orientation(&self) -> Orientation { self.orientation }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+    }
+}
+
+impl Debug for Separator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Separator")
+            .field("name", &self.name)
+            .field("orientation", &self.orientation)
+            .field("rect", &self.rect)
+            .finish()
+    }
+}
+
+impl Drawable for Separator {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let r = self.rect;
+        if r.width() == 0 || r.height() == 0 {
+            return Ok(());
+        }
+        let c = r.center();
+        let (from, to) = match self.orientation {
+            Orientation::Horizontal => (Point::new(r.left(), c.y()), Point::new(r.right() - 1, c.y())),
+            Orientation::Vertical => (Point::new(c.x(), r.top()), Point::new(c.x(), r.bottom() - 1)),
+        };
+        ctx.canvas().set_draw_color(self.theme.border);
+        ctx.canvas().draw_line(from, to)?;
+        Ok(())
+    }
+}
+
+impl Widget for Separator {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { self.rect }
+    fn role(&self) -> Role { Role::Separator }
+    fn set_rect(&mut self, rect: Rect) { self.rect = rect; }
+    fn fills_cross(&self) -> bool { true }
+
+    fn size_hint(&self) -> (u32, u32) {
+        let thickness = (2 * self.margin + 1) as u32;
+        match self.orientation {
+            Orientation::Horizontal => (0, thickness),
+            Orientation::Vertical => (thickness, 0),
+        }
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+// Empty room in a box layout. A fixed spacer always takes its size, a
+// flexible one also soaks up whatever space the box has left over,
+// shared with other flexible spacers by their stretch factor.
+#[derive(Debug)]
+pub struct Spacer {
+    name: String,
+    rect: Rect,
+    size: u32,
+    stretch: u32,
+}
+
+impl Spacer {
+    pub fn new(name: &str, size: u32, stretch: u32) -> Spacer {
+        Spacer {
+            name: name.to_string(),
+            rect: Rect::new(0, 0, 0, 0),
+            size,
+            stretch,
+        }
+    }
+
+    pub fn fixed(size: u32) -> Spacer {
+        Spacer::new("", size, 0)
+    }
+
+    pub fn flexible() -> Spacer {
+        Spacer::new("", 0, 1)
+    }
+
+    pub fn stretch_factor(mut self, stretch: u32) -> Spacer {
+        self.stretch = stretch;
+        self
+    }
+}
+
+impl Drawable for Spacer {
+    fn draw(&self, _ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        Ok(())
+    }
+}
+
+impl Widget for Spacer {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { self.rect }
+    fn set_rect(&mut self, rect: Rect) { self.rect = rect; }
+    fn size_hint(&self) -> (u32, u32) { (self.size, self.size) }
+    fn stretch(&self) -> u32 { self.stretch }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
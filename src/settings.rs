@@ -0,0 +1,299 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use log::warn;
+use serde::de::value::StringDeserializer;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::{forward_to_deserialize_any, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::{CheckBox, ComboBox, Grid, RichText, ScrollArea, SpinBox, TextInput, TextSpan, Toolkit, ToolkitError};
+
+const FIELD_WIDTH: i32 = 200;
+// Range of the spin boxes for floats, as there is no telling f32 from f64
+const FLOAT_LIMIT: f64 = 1e9;
+// Beyond this an f64, and so a spin box, skips integers
+const EXACT_LIMIT: i64 = 1 << 53;
+// Ends of the integer types, tried in turn to find those of a field
+const INT_MAXES: [i64; 7] = [i8::MAX as i64, u8::MAX as i64, i16::MAX as i64, u16::MAX as i64, i32::MAX as i64, u32::MAX as i64, EXACT_LIMIT];
+const INT_MINS: [i64; 4] = [i8::MIN as i64, i16::MIN as i64, i32::MIN as i64, -EXACT_LIMIT];
+
+#[derive(Error, Debug)]
+pub enum SettingsError {
+    #[error("Settings have to be a struct with named fields")]
+    NotAStruct,
+    #[error("Invalid settings: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error(transparent)]
+    Toolkit(#[from] ToolkitError),
+}
+
+// Which widget edits a field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    Bool,
+    Int,
+    Float,
+    // a number too big for a spin box, typed in instead
+    Number,
+    // a unit-only enum, picked from its variants
+    Choice,
+    Text,
+}
+
+type Variants = RefCell<HashMap<String, &'static [&'static str]>>;
+
+// Deserializes like serde_json's own Value, but notes down the variants of
+// every enum field of the top level struct on the way, which the
+// serialized settings don't tell
+struct Probe<'a> {
+    value: Value,
+    field: Option<String>,
+    variants: &'a Variants,
+}
+
+impl<'de> Deserializer<'de> for Probe<'_> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.value.deserialize_any(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, name: &'static str, variants: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+        if let Some(field) = self.field {
+            self.variants.borrow_mut().insert(field, variants);
+        }
+        self.value.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::Object(map) if self.field.is_none() => visitor.visit_map(ProbeMap {
+                entries: map.into_iter(),
+                next: None,
+                variants: self.variants,
+            }),
+            value => value.deserialize_struct(name, fields, visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map identifier ignored_any
+    }
+}
+
+struct ProbeMap<'a> {
+    entries: serde_json::map::IntoIter,
+    next: Option<(String, Value)>,
+    variants: &'a Variants,
+}
+
+impl<'de> MapAccess<'de> for ProbeMap<'_> {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        let de: StringDeserializer<serde_json::Error> = key.clone().into_deserializer();
+        let key_value = seed.deserialize(de)?;
+        self.next = Some((key, value));
+        Ok(Some(key_value))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        let (key, value) = self.next.take().ok_or_else(|| de::Error::custom("value without a key"))?;
+        seed.deserialize(Probe {value, field: Some(key), variants: self.variants})
+    }
+}
+
+// Whether T would still deserialize with `field` set to `value`
+fn accepts<T: DeserializeOwned>(fields: &Map<String, Value>, field: &str, value: Value) -> bool {
+    let mut fields = fields.clone();
+    fields.insert(field.to_string(), value);
+    serde_json::from_value::<T>(Value::Object(fields)).is_ok()
+}
+
+// Range a spin box for an integer field can take: as far as the field's type
+// goes, up to what an f64 holds exactly
+fn int_range<T: DeserializeOwned>(fields: &Map<String, Value>, field: &str) -> (f64, f64) {
+    let fits = |limit: &i64| accepts::<T>(fields, field, Value::from(*limit));
+    let max = INT_MAXES.iter().take_while(|limit| fits(limit)).last().copied().unwrap_or(0);
+    let min = if fits(&-1) { INT_MINS.iter().take_while(|limit| fits(limit)).last().copied().unwrap_or(-1) } else { 0 };
+    (min as f64, max as f64)
+}
+
+// "max_fps" becomes "Max fps"
+fn label_for(field: &str) -> String {
+    let words = field.replace('_', " ");
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => words,
+    }
+}
+
+impl Toolkit {
+    // Adds a tab with a row for each field of `settings`: check boxes for
+    // bools, spin boxes for numbers, combo boxes for enums without data and
+    // text inputs for strings. Other fields are left out. Edits go straight
+    // back into `settings`, as long as it can hold what was entered.
+    pub fn add_settings_tab<T: Serialize + DeserializeOwned + 'static>(&mut self, tab: &str, settings: Rc<RefCell<T>>) -> Result<(), SettingsError> {
+        let value = serde_json::to_value(&*settings.borrow())?;
+        let Value::Object(fields) = value else {
+            return Err(SettingsError::NotAStruct);
+        };
+        let variants = RefCell::new(HashMap::new());
+        // only wanted for what it notes down on the way
+        T::deserialize(Probe {value: Value::Object(fields.clone()), field: None, variants: &variants}).ok();
+        let variants = variants.into_inner();
+
+        let mut grid = Grid::new(&format!("{}.fields", tab)).stretch(1, 1);
+        let mut editors = Vec::new();
+        for (field, value) in fields.iter() {
+            // left out fields take no row
+            let row = editors.len();
+            let name = format!("{}.{}", tab, field);
+            let label = label_for(field);
+            let kind = match value {
+                Value::Bool(checked) => {
+                    grid.place(row, 1, CheckBox::new(&self.text, &name, &label, 0, 0)?.checked(*checked));
+                    FieldKind::Bool
+                },
+                Value::Number(n) => {
+                    let float = n.is_f64();
+                    let (min, max) = if float {
+                        let min = if accepts::<T>(&fields, field, Value::from(-1.0)) { -FLOAT_LIMIT } else { 0.0 };
+                        (min, FLOAT_LIMIT)
+                    } else {
+                        int_range::<T>(&fields, field)
+                    };
+                    let current = n.as_f64().unwrap_or_default();
+                    if (min..=max).contains(&current) {
+                        let mut spin = SpinBox::new(&self.text, &name, 0, 0, FIELD_WIDTH, min, max);
+                        if float {
+                            spin = spin.step(0.1).decimals(2);
+                        }
+                        spin.set_value(current);
+                        grid.place(row, 1, spin);
+                        if float { FieldKind::Float } else { FieldKind::Int }
+                    } else {
+                        // a spin box would clamp it, or round it
+                        let mut input = TextInput::new(&self.text, &name, 0, 0, FIELD_WIDTH);
+                        input.set_value(&n.to_string());
+                        grid.place(row, 1, input);
+                        FieldKind::Number
+                    }
+                },
+                Value::String(s) => match variants.get(field) {
+                    Some(names) => {
+                        let mut combo = ComboBox::new(&self.text, &name, 0, 0, FIELD_WIDTH).with_items(names.iter().copied());
+                        combo.set_selected(names.iter().position(|v| v == s));
+                        grid.place(row, 1, combo);
+                        FieldKind::Choice
+                    },
+                    None => {
+                        let mut input = TextInput::new(&self.text, &name, 0, 0, FIELD_WIDTH);
+                        input.set_value(s);
+                        grid.place(row, 1, input);
+                        FieldKind::Text
+                    },
+                },
+                _ => continue,
+            };
+            if kind != FieldKind::Bool {
+                grid.place(row, 0, RichText::new(&self.text, "", 0, 0, vec![TextSpan::new(&label, self.theme.text)])?);
+            }
+            editors.push((name, field.clone(), kind));
+        }
+
+        let previous = self.current_tab().map(str::to_string);
+        self.add_tab(tab)?;
+        self.select_tab(tab)?;
+        let (w, h) = self.content_rect().size();
+        self.add_widget(ScrollArea::new(&format!("{}.scroll", tab), w, h, grid))?;
+        if let Some(previous) = previous {
+            self.select_tab(&previous)?;
+        }
+
+        for (name, field, kind) in editors {
+            let settings = settings.clone();
+            self.on_change(&name.clone(), move |tk| {
+                let value = match kind {
+                    FieldKind::Bool => tk.is_checked(&name).map(Value::from),
+                    FieldKind::Int => tk.find_widget::<SpinBox>(&name).map(|spin| Value::from(spin.value_i64())),
+                    FieldKind::Float => tk.find_widget::<SpinBox>(&name).map(|spin| Value::from(spin.value())),
+                    FieldKind::Number => tk.find_widget::<TextInput>(&name).and_then(|input| input.value().trim().parse().ok()).filter(Value::is_number),
+                    FieldKind::Choice => tk.find_widget::<ComboBox>(&name).and_then(|combo| combo.selected_item()).map(Value::from),
+                    FieldKind::Text => tk.find_widget::<TextInput>(&name).map(|input| Value::from(input.value())),
+                };
+                let Some(value) = value else {
+                    return;
+                };
+                let Ok(Value::Object(mut fields)) = serde_json::to_value(&*settings.borrow()) else {
+                    return;
+                };
+                fields.insert(field.clone(), value);
+                match serde_json::from_value(Value::Object(fields)) {
+                    Ok(updated) => *settings.borrow_mut() = updated,
+                    Err(e) => warn!("{} left as it was: {}", name, e),
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Ints {
+        small: u8,
+        signed: i16,
+        big: u64,
+        wide: i64,
+    }
+
+    fn range_of(field: &str) -> (f64, f64) {
+        let fields = serde_json::json!({"small": 1, "signed": 1, "big": 1, "wide": 1});
+        let Value::Object(fields) = fields else { unreachable!() };
+        int_range::<Ints>(&fields, field)
+    }
+
+    #[test]
+    fn int_ranges_follow_the_field_type() {
+        assert_eq!(range_of("small"), (0.0, 255.0));
+        assert_eq!(range_of("signed"), (-32768.0, 32767.0));
+    }
+
+    #[test]
+    fn wide_ints_stop_where_f64_does() {
+        let exact = EXACT_LIMIT as f64;
+        assert_eq!(range_of("big"), (0.0, exact));
+        assert_eq!(range_of("wide"), (-exact, exact));
+    }
+
+    #[test]
+    fn labels_are_sentence_case() {
+        assert_eq!(label_for("max_fps"), "Max fps");
+        assert_eq!(label_for(""), "");
+    }
+}
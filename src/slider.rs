@@ -0,0 +1,222 @@
+use std::any::Any;
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::rect::Rect;
+
+use crate::{DrawCtx, Drawable, Role, Theme, ToolkitError, Widget, WidgetState};
+
+const SLIDER_HEIGHT: i32 = 24;
+const TRACK_HEIGHT: i32 = 6;
+
+// Horizontal slider picking a number between min and max
+pub struct Slider {
+    name: String,
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    pref_w: i32,
+    min: f64,
+    max: f64,
+    // 0 means continuous
+    step: f64,
+    value: f64,
+    dragging: bool,
+    state: WidgetState,
+    changed: bool,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl Slider {
+    pub fn new(name: &str, x: i32, y: i32, w: i32, min: f64, max: f64) -> Slider {
+        Slider {
+            name: name.to_string(),
+            x,
+            y,
+            w,
+            h: SLIDER_HEIGHT,
+            pref_w: w,
+            min,
+            max: max.max(min),
+            step: 0.0,
+            value: min,
+            dragging: false,
+            state: WidgetState::default(),
+            changed: false,
+            theme: Theme::default(),
+            own_theme: false,
+        }
+    }
+
+    pub fn step(mut self, step: f64) -> Slider {
+        self.step = step.max(0.0);
+        self.value = self.snap(self.value);
+        self
+    }
+
+    pub fn value(&self) -> f64 { self.value }
+    pub fn min(&self) -> f64 { self.min }
+    pub fn max(&self) -> f64 { self.max }
+
+    pub fn set_value(&mut self, value: f64) {
+        self.value = self.snap(value);
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+    }
+
+    fn snap(&self, value: f64) -> f64 {
+        let value = if self.step > 0.0 {
+            self.min + ((value - self.min) / self.step).round() * self.step
+        } else {
+            value
+        };
+        value.clamp(self.min, self.max)
+    }
+
+    fn thumb_size(&self) -> i32 {
+        self.h
+    }
+
+    // Pixel range the center of the thumb can travel
+    fn travel(&self) -> (i32, i32) {
+        let half = self.thumb_size() / 2;
+        (self.x + half, self.x + self.w - half)
+    }
+
+    fn value_at(&self, x: i32) -> f64 {
+        let (start, end) = self.travel();
+        if end <= start {
+            return self.min;
+        }
+        let t = (x - start) as f64 / (end - start) as f64;
+        self.min + t.clamp(0.0, 1.0) * (self.max - self.min)
+    }
+
+    fn thumb_x(&self) -> i32 {
+        let (start, end) = self.travel();
+        let range = self.max - self.min;
+        let t = if range > 0.0 { (self.value - self.min) / range } else { 0.0 };
+        start + ((end - start) as f64 * t).round() as i32
+    }
+
+    fn update(&mut self, value: f64) {
+        let value = self.snap(value);
+        if value != self.value {
+            self.value = value;
+            self.changed = true;
+        }
+    }
+
+    // Keyboard steps fall back to a hundredth of the range for continuous sliders
+    fn key_step(&self) -> f64 {
+        if self.step > 0.0 { self.step } else { (self.max - self.min) / 100.0 }
+    }
+}
+
+impl Debug for Slider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Slider")
+            .field("name", &self.name)
+            .field("rect", &self.rect())
+            .field("min", &self.min)
+            .field("max", &self.max)
+            .field("step", &self.step)
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl Drawable for Slider {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let theme = &self.theme;
+        let (start, end) = self.travel();
+        let cy = self.y + self.h / 2;
+        let track = Rect::new(start, cy - TRACK_HEIGHT / 2, (end - start).max(0) as u32, TRACK_HEIGHT as u32);
+        ctx.fill_rounded_rect(track, TRACK_HEIGHT / 2, theme.field)?;
+        ctx.draw_rounded_rect(track, TRACK_HEIGHT / 2, theme.border)?;
+
+        let thumb_x = self.thumb_x();
+        let filled = Rect::new(start, track.y(), (thumb_x - start).max(0) as u32, TRACK_HEIGHT as u32);
+        if filled.width() > 0 {
+            let color = if self.state.enabled { theme.active } else { theme.disabled };
+            ctx.fill_rounded_rect(filled, TRACK_HEIGHT / 2, color)?;
+        }
+
+        let size = self.thumb_size();
+        let thumb = Rect::from_center((thumb_x, cy), size as u32, size as u32);
+        let fill = if self.dragging { theme.active } else { theme.fill };
+        ctx.fill_rounded_rect(thumb, size / 2, fill)?;
+        let border = if self.state.focused { theme.active } else { theme.border };
+        ctx.draw_rounded_rect(thumb, size / 2, border)?;
+        Ok(())
+    }
+}
+
+impl Widget for Slider {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { Rect::new(self.x, self.y, self.w as u32, self.h as u32) }
+    fn role(&self) -> Role { Role::Slider }
+    fn access_value(&self) -> Option<String> { Some(self.value.to_string()) }
+    fn focusable(&self) -> bool { true }
+    fn widget_state(&self) -> Option<&WidgetState> { Some(&self.state) }
+    fn widget_state_mut(&mut self) -> Option<&mut WidgetState> { Some(&mut self.state) }
+
+    fn size_hint(&self) -> (u32, u32) {
+        (self.pref_w as u32, SLIDER_HEIGHT as u32)
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.x = rect.x();
+        self.y = rect.y();
+        self.w = rect.width() as i32;
+        self.h = rect.height() as i32;
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::MouseButtonDown {mouse_btn: MouseButton::Left, x, ..} => {
+                self.dragging = true;
+                self.update(self.value_at(*x));
+            },
+            Event::MouseMotion {x, ..} if self.dragging => {
+                self.update(self.value_at(*x));
+            },
+            Event::MouseButtonUp {mouse_btn: MouseButton::Left, ..} => {
+                self.dragging = false;
+            },
+            Event::KeyDown {keycode: Some(key), ..} => {
+                let step = self.key_step();
+                match key {
+                    Keycode::Left | Keycode::Down => self.update(self.value - step),
+                    Keycode::Right | Keycode::Up => self.update(self.value + step),
+                    Keycode::Home => self.update(self.min),
+                    Keycode::End => self.update(self.max),
+                    _ => return false,
+                }
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
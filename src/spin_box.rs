@@ -0,0 +1,290 @@
+use std::any::Any;
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::rect::{Point, Rect};
+
+use crate::{DrawCtx, Drawable, FontId, Role, TextInput, TextRenderer, Theme, ToolkitError, Widget, WidgetState};
+
+// Width of the column holding the up and down arrows
+const ARROWS_WIDTH: i32 = 18;
+const PAGE_STEPS: f64 = 10.0;
+
+// Number field with arrows to step the value up and down. Typed values are
+// taken over on Enter or when the field loses focus.
+pub struct SpinBox {
+    name: String,
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    pref_w: i32,
+    min: f64,
+    max: f64,
+    step: f64,
+    decimals: usize,
+    value: f64,
+    input: TextInput,
+    // arrow held down, 1 for up, -1 for down
+    held: i32,
+    state: WidgetState,
+    changed: bool,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl SpinBox {
+    pub fn new(text: &TextRenderer, name: &str, x: i32, y: i32, w: i32, min: f64, max: f64) -> SpinBox {
+        let input = TextInput::new(text, name, x, y, (w - ARROWS_WIDTH).max(0));
+        let (_, h) = input.size_hint();
+        let mut spin = SpinBox {
+            name: name.to_string(),
+            x,
+            y,
+            w,
+            h: h as i32,
+            pref_w: w,
+            min,
+            max: max.max(min),
+            step: 1.0,
+            decimals: 0,
+            value: min,
+            input,
+            held: 0,
+            state: WidgetState::default(),
+            changed: false,
+            theme: Theme::default(),
+            own_theme: false,
+        };
+        spin.show_value();
+        spin
+    }
+
+    pub fn step(mut self, step: f64) -> SpinBox {
+        self.step = step.max(0.0);
+        self.value = self.snap(self.value);
+        self.show_value();
+        self
+    }
+
+    // Digits shown after the decimal point
+    pub fn decimals(mut self, decimals: usize) -> SpinBox {
+        self.decimals = decimals;
+        self.show_value();
+        self
+    }
+
+    pub fn value(&self) -> f64 { self.value }
+    pub fn value_i64(&self) -> i64 { self.value.round() as i64 }
+    pub fn min(&self) -> f64 { self.min }
+    pub fn max(&self) -> f64 { self.max }
+
+    pub fn set_value(&mut self, value: f64) {
+        self.value = self.snap(value);
+        self.show_value();
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.set_theme_inner(theme);
+    }
+
+    fn set_theme_inner(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.input.set_theme(theme);
+        self.h = self.input.size_hint().1 as i32;
+        self.place_input();
+    }
+
+    fn snap(&self, value: f64) -> f64 {
+        let value = if self.step > 0.0 {
+            self.min + ((value - self.min) / self.step).round() * self.step
+        } else {
+            value
+        };
+        value.clamp(self.min, self.max)
+    }
+
+    fn show_value(&mut self) {
+        self.input.set_value(&format!("{:.*}", self.decimals, self.value));
+    }
+
+    fn update(&mut self, value: f64) {
+        let value = self.snap(value);
+        if value != self.value {
+            self.value = value;
+            self.changed = true;
+        }
+        self.show_value();
+    }
+
+    // Takes over whatever was typed, or puts the old value back if it
+    // isn't a number
+    fn commit(&mut self) {
+        match self.input.value().trim().parse::<f64>() {
+            Ok(value) if value.is_finite() => self.update(value),
+            _ => self.show_value(),
+        }
+    }
+
+    fn place_input(&mut self) {
+        self.input.set_rect(Rect::new(self.x, self.y, (self.w - ARROWS_WIDTH).max(0) as u32, self.h as u32));
+    }
+
+    fn arrow_rects(&self) -> (Rect, Rect) {
+        let x = self.x + self.w - ARROWS_WIDTH;
+        let half = self.h / 2;
+        (
+            Rect::new(x, self.y, ARROWS_WIDTH as u32, half as u32),
+            Rect::new(x, self.y + half, ARROWS_WIDTH as u32, (self.h - half) as u32),
+        )
+    }
+
+    fn draw_arrow(&self, ctx: &mut DrawCtx, rect: Rect, up: bool, held: bool) -> Result<(), ToolkitError> {
+        let theme = &self.theme;
+        ctx.canvas().set_draw_color(if held { theme.active } else { theme.fill });
+        ctx.canvas().fill_rect(rect)?;
+        ctx.canvas().set_draw_color(theme.border);
+        ctx.canvas().draw_rect(rect)?;
+
+        let c = rect.center();
+        let s = (rect.height() as i32 / 4).clamp(1, ARROWS_WIDTH / 4);
+        let dy = if up { -s / 2 } else { s / 2 };
+        ctx.canvas().set_draw_color(if self.state.enabled { theme.text } else { theme.disabled });
+        ctx.canvas().draw_line(Point::new(c.x() - s, c.y() - dy), Point::new(c.x(), c.y() + dy))?;
+        ctx.canvas().draw_line(Point::new(c.x(), c.y() + dy), Point::new(c.x() + s, c.y() - dy))?;
+        Ok(())
+    }
+}
+
+impl Debug for SpinBox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpinBox")
+            .field("name", &self.name)
+            .field("rect", &self.rect())
+            .field("min", &self.min)
+            .field("max", &self.max)
+            .field("step", &self.step)
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl Drawable for SpinBox {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        self.input.draw(ctx)?;
+        let (up, down) = self.arrow_rects();
+        self.draw_arrow(ctx, up, true, self.held > 0)?;
+        self.draw_arrow(ctx, down, false, self.held < 0)?;
+        Ok(())
+    }
+}
+
+impl Widget for SpinBox {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { Rect::new(self.x, self.y, self.w as u32, self.h as u32) }
+    fn role(&self) -> Role { Role::SpinBox }
+    fn access_value(&self) -> Option<String> { Some(self.value.to_string()) }
+    fn focusable(&self) -> bool { true }
+    fn widget_state(&self) -> Option<&WidgetState> { Some(&self.state) }
+    fn widget_state_mut(&mut self) -> Option<&mut WidgetState> { Some(&mut self.state) }
+    fn wants_text_input(&self) -> bool { true }
+
+    fn size_hint(&self) -> (u32, u32) {
+        (self.pref_w as u32, self.input.size_hint().1)
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.x = rect.x();
+        self.y = rect.y();
+        self.w = rect.width() as i32;
+        self.h = rect.height() as i32;
+        self.place_input();
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.set_theme_inner(*theme);
+        }
+    }
+
+    fn set_font(&mut self, text: &TextRenderer, font: FontId) -> Result<(), ToolkitError> {
+        self.input.set_font(text, font)?;
+        self.h = self.input.size_hint().1 as i32;
+        self.place_input();
+        Ok(())
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.state.enabled = enabled;
+        self.state.hovered &= enabled;
+        self.state.pressed &= enabled;
+        self.input.set_enabled(enabled);
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        if self.state.focused && !focused {
+            self.commit();
+        }
+        self.state.focused = focused;
+        self.input.set_focused(focused);
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::MouseButtonDown {mouse_btn: MouseButton::Left, x, y, ..} => {
+                let (up, down) = self.arrow_rects();
+                if up.contains_point((*x, *y)) {
+                    self.held = 1;
+                } else if down.contains_point((*x, *y)) {
+                    self.held = -1;
+                } else {
+                    return false;
+                }
+                self.commit();
+                self.update(self.value + self.held as f64 * self.step);
+            },
+            Event::MouseButtonUp {mouse_btn: MouseButton::Left, ..} => {
+                self.held = 0;
+            },
+            Event::MouseWheel {y, ..} => {
+                self.commit();
+                self.update(self.value + *y as f64 * self.step);
+            },
+            Event::KeyDown {keycode: Some(key), ..} => {
+                let steps = match key {
+                    Keycode::Up => 1.0,
+                    Keycode::Down => -1.0,
+                    Keycode::PageUp => PAGE_STEPS,
+                    Keycode::PageDown => -PAGE_STEPS,
+                    Keycode::Return | Keycode::KpEnter => {
+                        self.commit();
+                        return true;
+                    },
+                    _ => return self.input.handle_event(event),
+                };
+                self.commit();
+                self.update(self.value + steps * self.step);
+            },
+            Event::TextInput {text, ..} => {
+                // only what could be part of a number gets through
+                if !text.chars().all(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')) {
+                    return true;
+                }
+                return self.input.handle_event(event);
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
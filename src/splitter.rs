@@ -0,0 +1,292 @@
+use std::any::Any;
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::rect::Rect;
+
+use crate::{DrawCtx, Drawable, Orientation, Role, Theme, ToolkitError, Widget};
+
+// Thickness of the divider between the panes
+const HANDLE: i32 = 6;
+const GRIP_DOTS: i32 = 3;
+// Share of the room moved by one arrow key press on the focused divider
+const KEY_STEP: f32 = 0.05;
+
+// Two panes with a divider between them that can be dragged to share the
+// room differently. A horizontal splitter puts them side by side, a
+// vertical one above each other, like a BoxLayout of the same orientation.
+// The split is kept as a share of the room, so it stays the same when the
+// splitter is resized, and split_ratio() and set_ratio() are all it takes
+// to save and restore it.
+pub struct Splitter {
+    name: String,
+    rect: Rect,
+    fixed: bool,
+    orientation: Orientation,
+    // share of the room, handle left out, that goes to the first pane
+    ratio: f32,
+    // neither pane gets smaller than this, as long as there is room
+    min: (u32, u32),
+    // where on the handle it was grabbed, while being dragged
+    drag: Option<i32>,
+    focused: bool,
+    changed: bool,
+    children: Vec<Box<dyn Widget>>,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl Splitter {
+    pub fn new<A: Widget, B: Widget>(name: &str, orientation: Orientation, first: A, second: B) -> Splitter {
+        Splitter {
+            name: name.to_string(),
+            rect: Rect::new(0, 0, 0, 0),
+            fixed: false,
+            orientation,
+            ratio: 0.5,
+            min: (0, 0),
+            drag: None,
+            focused: false,
+            changed: false,
+            children: vec![Box::new(first), Box::new(second)],
+            theme: Theme::default(),
+            own_theme: false,
+        }
+    }
+
+    pub fn horizontal<A: Widget, B: Widget>(name: &str, first: A, second: B) -> Splitter {
+        Splitter::new(name, Orientation::Horizontal, first, second)
+    }
+
+    pub fn vertical<A: Widget, B: Widget>(name: &str, first: A, second: B) -> Splitter {
+        Splitter::new(name, Orientation::Vertical, first, second)
+    }
+
+    pub fn ratio(mut self, ratio: f32) -> Splitter {
+        self.set_ratio(ratio);
+        self
+    }
+
+    pub fn min_sizes(mut self, first: u32, second: u32) -> Splitter {
+        self.set_min_sizes(first, second);
+        self
+    }
+
+    pub fn orientation(&self) -> Orientation { self.orientation }
+    pub fn split_ratio(&self) -> f32 { self.ratio }
+
+    pub fn first(&self) -> &dyn Widget { self.children[0].as_ref() }
+    pub fn first_mut(&mut self) -> &mut dyn Widget { self.children[0].as_mut() }
+    pub fn second(&self) -> &dyn Widget { self.children[1].as_ref() }
+    pub fn second_mut(&mut self) -> &mut dyn Widget { self.children[1].as_mut() }
+
+    // 0.0 gives all the room to the second pane and 1.0 all to the first,
+    // as far as the minimum sizes let them
+    pub fn set_ratio(&mut self, ratio: f32) {
+        self.ratio = if ratio.is_finite() { ratio.clamp(0.0, 1.0) } else { 0.5 };
+        self.place();
+    }
+
+    pub fn set_min_sizes(&mut self, first: u32, second: u32) {
+        self.min = (first, second);
+        self.place();
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+    }
+
+    // Start and length of the splitter along its main axis
+    fn main_axis(&self) -> (i32, i32) {
+        match self.orientation {
+            Orientation::Horizontal => (self.rect.x(), self.rect.width() as i32),
+            Orientation::Vertical => (self.rect.y(), self.rect.height() as i32),
+        }
+    }
+
+    // Room for the two panes together
+    fn room(&self) -> i32 {
+        (self.main_axis().1 - HANDLE).max(0)
+    }
+
+    // Size of the first pane for `ratio`, kept within the minimum sizes.
+    // When both don't fit the first one gets its minimum.
+    fn first_size(&self, ratio: f32) -> i32 {
+        let room = self.room();
+        let size = (room as f32 * ratio).round() as i32;
+        size.min(room - self.min.1 as i32).max(self.min.0 as i32).min(room).max(0)
+    }
+
+    fn handle_rect(&self) -> Rect {
+        let at = self.main_axis().0 + self.first_size(self.ratio);
+        match self.orientation {
+            Orientation::Horizontal => Rect::new(at, self.rect.y(), HANDLE as u32, self.rect.height()),
+            Orientation::Vertical => Rect::new(self.rect.x(), at, self.rect.width(), HANDLE as u32),
+        }
+    }
+
+    fn place(&mut self) {
+        let first = self.first_size(self.ratio);
+        let second = (self.room() - first).max(0);
+        let r = self.rect;
+        let (a, b) = match self.orientation {
+            Orientation::Horizontal => (
+                Rect::new(r.x(), r.y(), first as u32, r.height()),
+                Rect::new(r.x() + first + HANDLE, r.y(), second as u32, r.height()),
+            ),
+            Orientation::Vertical => (
+                Rect::new(r.x(), r.y(), r.width(), first as u32),
+                Rect::new(r.x(), r.y() + first + HANDLE, r.width(), second as u32),
+            ),
+        };
+        for (child, rect) in self.children.iter_mut().zip([a, b]) {
+            child.set_rect(rect);
+            child.layout(rect);
+        }
+    }
+
+    // Takes the first pane to `size` pixels, or as close as the minimum
+    // sizes allow
+    fn resize_first(&mut self, size: i32) {
+        let room = self.room();
+        if room == 0 {
+            return;
+        }
+        let size = self.first_size(size as f32 / room as f32);
+        let ratio = size as f32 / room as f32;
+        if ratio != self.ratio {
+            self.ratio = ratio;
+            self.changed = true;
+            self.place();
+        }
+    }
+}
+
+impl Debug for Splitter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Splitter")
+            .field("name", &self.name)
+            .field("rect", &self.rect)
+            .field("orientation", &self.orientation)
+            .field("ratio", &self.ratio)
+            .field("first", &self.children[0])
+            .field("second", &self.children[1])
+            .finish()
+    }
+}
+
+impl Drawable for Splitter {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        for child in self.children.iter() {
+            ctx.clipped(child.rect(), |ctx| child.draw(ctx))?;
+        }
+
+        let theme = &self.theme;
+        let handle = self.handle_rect();
+        let color = if self.drag.is_some() || self.focused { theme.active } else { theme.fill };
+        ctx.fill_rect(handle, color)?;
+        // a few dots in the middle to show it can be grabbed
+        let center = handle.center();
+        for n in -(GRIP_DOTS / 2)..=GRIP_DOTS / 2 {
+            let (x, y) = match self.orientation {
+                Orientation::Horizontal => (center.x(), center.y() + n * HANDLE),
+                Orientation::Vertical => (center.x() + n * HANDLE, center.y()),
+            };
+            ctx.fill_circle((x, y), 1, theme.border)?;
+        }
+        Ok(())
+    }
+}
+
+impl Widget for Splitter {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { self.rect }
+    fn role(&self) -> Role { Role::Splitter }
+    fn focusable(&self) -> bool { true }
+    fn take_changed(&mut self) -> bool { std::mem::take(&mut self.changed) }
+    fn access_value(&self) -> Option<String> { Some(format!("{}%", (self.ratio * 100.0).round())) }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        self.fixed = true;
+    }
+
+    fn size_hint(&self) -> (u32, u32) {
+        let (w0, h0) = self.children[0].size_hint();
+        let (w1, h1) = self.children[1].size_hint();
+        match self.orientation {
+            Orientation::Horizontal => (w0.max(self.min.0) + w1.max(self.min.1) + HANDLE as u32, h0.max(h1)),
+            Orientation::Vertical => (w0.max(w1), h0.max(self.min.0) + h1.max(self.min.1) + HANDLE as u32),
+        }
+    }
+
+    fn layout(&mut self, area: Rect) {
+        if !self.fixed {
+            self.rect = area;
+        }
+        self.place();
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+        }
+        for child in self.children.iter_mut() {
+            child.apply_theme(theme);
+        }
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    // Only the handle gets input of its own, everything over a pane goes
+    // to that pane
+    fn handle_event(&mut self, event: &Event) -> bool {
+        let along = |x: i32, y: i32| match self.orientation {
+            Orientation::Horizontal => x,
+            Orientation::Vertical => y,
+        };
+        match event {
+            Event::MouseButtonDown {mouse_btn: MouseButton::Left, x, y, ..} => {
+                let handle = self.handle_rect();
+                if !handle.contains_point((*x, *y)) {
+                    return false;
+                }
+                let start = along(handle.x(), handle.y());
+                self.drag = Some(along(*x, *y) - start);
+            },
+            Event::MouseMotion {x, y, ..} => {
+                let Some(grab) = self.drag else {
+                    return false;
+                };
+                let size = along(*x, *y) - grab - self.main_axis().0;
+                self.resize_first(size);
+            },
+            Event::MouseButtonUp {mouse_btn: MouseButton::Left, ..} => {
+                return self.drag.take().is_some();
+            },
+            Event::KeyDown {keycode: Some(key), ..} => {
+                let step = match (self.orientation, *key) {
+                    (Orientation::Horizontal, Keycode::Left) | (Orientation::Vertical, Keycode::Up) => -KEY_STEP,
+                    (Orientation::Horizontal, Keycode::Right) | (Orientation::Vertical, Keycode::Down) => KEY_STEP,
+                    _ => return false,
+                };
+                let size = ((self.ratio + step) * self.room() as f32).round() as i32;
+                self.resize_first(size);
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] { &self.children }
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] { &mut self.children }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
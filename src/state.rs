@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use log::warn;
+use sdl2::video::WindowPos;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::widget;
+use crate::{CheckBox, DockArea, PanelLayout, ScrollArea, Splitter, Toolkit};
+
+#[derive(Error, Debug)]
+pub enum StateError {
+    #[error("Could not read or write the state file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid state file: {0}")]
+    Format(#[from] serde_json::Error),
+}
+
+// Position and size of the window, in screen coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowState {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// What the user can change about the UI without the application's help.
+// Widgets are keyed by name, so only named ones are kept, and anything that
+// isn't there any more when it is applied is skipped.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    pub window: Option<WindowState>,
+    pub tab: Option<String>,
+    pub splitters: BTreeMap<String, f32>,
+    pub scroll: BTreeMap<String, (i32, i32)>,
+    pub checks: BTreeMap<String, bool>,
+    // selected member of each radio group
+    pub radios: BTreeMap<String, String>,
+    pub docks: BTreeMap<String, Vec<PanelLayout>>,
+}
+
+impl Toolkit {
+    // The state of the current window, see UiState
+    pub fn ui_state(&self) -> UiState {
+        let window = self.canvas.window();
+        let (x, y) = window.position();
+        let (width, height) = window.size();
+        let mut state = UiState {
+            window: Some(WindowState {x, y, width, height}),
+            tab: self.current_tab().map(str::to_string),
+            ..UiState::default()
+        };
+        for tab in self.tabs.iter() {
+            widget::walk(&tab.items, &mut |w| {
+                if w.name().is_empty() {
+                    return;
+                }
+                let any = w.as_any();
+                if let Some(splitter) = any.downcast_ref::<Splitter>() {
+                    state.splitters.insert(w.name().to_string(), splitter.split_ratio());
+                } else if let Some(check) = any.downcast_ref::<CheckBox>() {
+                    state.checks.insert(w.name().to_string(), check.is_checked());
+                } else if let Some(area) = any.downcast_ref::<ScrollArea>() {
+                    state.scroll.insert(w.name().to_string(), area.scroll_offset());
+                } else if let Some(dock) = any.downcast_ref::<DockArea>() {
+                    state.docks.insert(w.name().to_string(), dock.panel_layout());
+                }
+            });
+        }
+        for (name, group) in self.radio_groups.iter() {
+            if let Some(selected) = group.selected_name() {
+                state.radios.insert(name.clone(), selected.to_string());
+            }
+        }
+        state
+    }
+
+    // Puts the window back the way `state` has it. Radio groups run their
+    // change handlers as if the user had made the choice.
+    pub fn apply_state(&mut self, state: &UiState) {
+        self.dirty = true;
+        if let Some(win) = state.window {
+            let window = self.canvas.window_mut();
+            if let Err(e) = window.set_size(win.width, win.height) {
+                warn!("could not restore the window size: {}", e);
+            }
+            window.set_position(WindowPos::Positioned(win.x), WindowPos::Positioned(win.y));
+        }
+        if let Some(pos) = state.tab.as_deref().and_then(|name| self.tab_index(name).ok()) {
+            self.set_tab_pos(pos);
+            // no transition when starting up where the user left off
+            self.tab_switch = None;
+        }
+        // the splitters and scroll areas need their new size first
+        self.relayout();
+        for tab in self.tabs.iter_mut() {
+            widget::walk_mut(&mut tab.items, &mut |w| {
+                let name = w.name().to_string();
+                let any = w.as_any_mut();
+                if let Some(splitter) = any.downcast_mut::<Splitter>() {
+                    if let Some(&ratio) = state.splitters.get(&name) {
+                        splitter.set_ratio(ratio);
+                    }
+                } else if let Some(check) = any.downcast_mut::<CheckBox>() {
+                    if let Some(&checked) = state.checks.get(&name) {
+                        check.set_checked(checked);
+                    }
+                } else if let Some(area) = any.downcast_mut::<ScrollArea>() {
+                    if let Some(&(x, y)) = state.scroll.get(&name) {
+                        area.scroll_to(x, y);
+                    }
+                } else if let Some(dock) = any.downcast_mut::<DockArea>() {
+                    if let Some(layout) = state.docks.get(&name) {
+                        dock.restore_panel_layout(layout);
+                    }
+                }
+            });
+        }
+        for (group, selected) in state.radios.iter() {
+            self.select_radio(group, selected);
+        }
+        self.relayout();
+    }
+
+    // Writes ui_state() to `path` as JSON
+    pub fn save_state<P: AsRef<Path>>(&self, path: P) -> Result<(), StateError> {
+        let json = serde_json::to_string_pretty(&self.ui_state())?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+
+    // Reads a file written by save_state and applies it
+    pub fn load_state<P: AsRef<Path>>(&mut self, path: P) -> Result<(), StateError> {
+        let json = fs::read_to_string(path)?;
+        let state: UiState = serde_json::from_str(&json)?;
+        self.apply_state(&state);
+        Ok(())
+    }
+}
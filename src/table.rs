@@ -0,0 +1,503 @@
+use std::any::Any;
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+
+use crate::{DragData, DrawCtx, Drawable, FontId, Role, TextRenderer, Theme, ToolkitError, Widget, WidgetState};
+
+const MIN_COLUMN: i32 = 24;
+const DEFAULT_COLUMN: i32 = 80;
+// How close to a header divider a press has to be to grab it
+const DIVIDER_GRAB: i32 = 4;
+const WHEEL_ROWS: i32 = 3;
+
+// Where a Table gets its data from. Only the rows on screen are asked for
+// their cells, so a model can be large or compute cells on the fly.
+pub trait TableModel {
+    fn columns(&self) -> usize;
+    fn rows(&self) -> usize;
+    fn header(&self, col: usize) -> String;
+    fn cell(&self, row: usize, col: usize) -> String;
+
+    // Order of two rows when sorting by `col`. Cells that both read as
+    // numbers are compared as such, everything else as text.
+    fn compare(&self, a: usize, b: usize, col: usize) -> Ordering {
+        let (a, b) = (self.cell(a, col), self.cell(b, col));
+        match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+            (Ok(x), Ok(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+            _ => a.cmp(&b),
+        }
+    }
+}
+
+// Plain rows of strings, for when the data already sits in memory
+#[derive(Debug, Clone, Default)]
+pub struct TableData {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl TableData {
+    pub fn new<S: Into<String>, I: IntoIterator<Item = S>>(headers: I) -> TableData {
+        TableData {
+            headers: headers.into_iter().map(Into::into).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push<S: Into<String>, I: IntoIterator<Item = S>>(&mut self, row: I) {
+        self.rows.push(row.into_iter().map(Into::into).collect());
+    }
+
+    pub fn with<S: Into<String>, I: IntoIterator<Item = S>>(mut self, row: I) -> TableData {
+        self.push(row);
+        self
+    }
+}
+
+impl TableModel for TableData {
+    fn columns(&self) -> usize { self.headers.len() }
+    fn rows(&self) -> usize { self.rows.len() }
+    fn header(&self, col: usize) -> String { self.headers.get(col).cloned().unwrap_or_default() }
+
+    fn cell(&self, row: usize, col: usize) -> String {
+        self.rows.get(row).and_then(|r| r.get(col)).cloned().unwrap_or_default()
+    }
+}
+
+// Column held down in the header, either on its divider to resize it or
+// on the title to sort by it
+#[derive(Debug, Clone, Copy)]
+enum HeaderPress {
+    Resize {col: usize, start_x: i32, start_w: i32},
+    Sort(usize),
+}
+
+// Rows and columns of text from a TableModel, with a header to sort by and
+// resize columns, and single row selection
+pub struct Table {
+    name: String,
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    pref: (i32, i32),
+    model: Box<dyn TableModel>,
+    widths: Vec<i32>,
+    // model rows in the order they are shown
+    order: Vec<usize>,
+    // column and whether it is ascending
+    sort: Option<(usize, bool)>,
+    // model row, so it sticks to its data when sorting
+    selected: Option<usize>,
+    scroll: i32,
+    font: FontId,
+    text_height: i32,
+    header_press: Option<HeaderPress>,
+    state: WidgetState,
+    changed: bool,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl Table {
+    pub fn new<M: TableModel + 'static>(text: &TextRenderer, name: &str, x: i32, y: i32, w: i32, h: i32, model: M) -> Table {
+        let mut table = Table {
+            name: name.to_string(),
+            x,
+            y,
+            w,
+            h,
+            pref: (w, h),
+            model: Box::new(model),
+            widths: Vec::new(),
+            order: Vec::new(),
+            sort: None,
+            selected: None,
+            scroll: 0,
+            font: FontId::DEFAULT,
+            text_height: text.height(),
+            header_press: None,
+            state: WidgetState::default(),
+            changed: false,
+            theme: Theme::default(),
+            own_theme: false,
+        };
+        table.fit_columns(text);
+        table.refresh();
+        table
+    }
+
+    pub fn column_width(mut self, col: usize, width: i32) -> Table {
+        self.set_column_width(col, width);
+        self
+    }
+
+    pub fn model(&self) -> &dyn TableModel { self.model.as_ref() }
+
+    pub fn set_model<M: TableModel + 'static>(&mut self, text: &TextRenderer, model: M) {
+        self.model = Box::new(model);
+        self.selected = None;
+        self.scroll = 0;
+        self.fit_columns(text);
+        self.refresh();
+    }
+
+    // Changes the model in place, then sorts again and drops a selection
+    // that no longer exists
+    pub fn update_model<F: FnOnce(&mut dyn TableModel)>(&mut self, f: F) {
+        f(self.model.as_mut());
+        self.refresh();
+    }
+
+    pub fn selected(&self) -> Option<usize> { self.selected }
+    pub fn sort_column(&self) -> Option<(usize, bool)> { self.sort }
+    pub fn column_widths(&self) -> &[i32] { &self.widths }
+
+    pub fn set_selected(&mut self, row: Option<usize>) {
+        self.selected = row.filter(|r| *r < self.model.rows());
+        self.scroll_to_selected();
+    }
+
+    pub fn set_column_width(&mut self, col: usize, width: i32) {
+        if let Some(w) = self.widths.get_mut(col) {
+            *w = width.max(MIN_COLUMN);
+        }
+    }
+
+    // Sorts by `col`, or goes back to model order with None
+    pub fn sort_by(&mut self, sort: Option<(usize, bool)>) {
+        self.sort = sort.filter(|(col, _)| *col < self.model.columns());
+        self.refresh();
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+    }
+
+    // Gives every column the width of its header
+    fn fit_columns(&mut self, text: &TextRenderer) {
+        let pad = self.theme.padding;
+        self.widths = (0..self.model.columns())
+            .map(|col| {
+                let title = self.model.header(col);
+                let w = text.size_of_font(self.font, &title).map(|(w, _)| w as i32 + 3 * pad).unwrap_or(DEFAULT_COLUMN);
+                w.max(DEFAULT_COLUMN)
+            })
+            .collect();
+    }
+
+    // Rebuilds the display order from the model
+    fn refresh(&mut self) {
+        let rows = self.model.rows();
+        let cols = self.model.columns();
+        self.widths.resize(cols, DEFAULT_COLUMN);
+        self.order = (0..rows).collect();
+        if let Some((col, ascending)) = self.sort.filter(|(col, _)| *col < cols) {
+            let model = &self.model;
+            self.order.sort_by(|a, b| {
+                let ord = model.compare(*a, *b, col);
+                if ascending { ord } else { ord.reverse() }
+            });
+        }
+        self.selected = self.selected.filter(|r| *r < rows);
+        self.scroll = self.scroll.clamp(0, self.max_scroll());
+    }
+
+    fn row_height(&self) -> i32 {
+        self.text_height + self.theme.padding
+    }
+
+    fn header_rect(&self) -> Rect {
+        Rect::new(self.x, self.y, self.w as u32, self.row_height() as u32)
+    }
+
+    pub(crate) fn body_rect(&self) -> Rect {
+        let top = self.row_height();
+        Rect::new(self.x, self.y + top, self.w as u32, (self.h - top).max(0) as u32)
+    }
+
+    fn max_scroll(&self) -> i32 {
+        (self.order.len() as i32 * self.row_height() - self.body_rect().height() as i32).max(0)
+    }
+
+    // x of each column's left edge
+    fn column_x(&self) -> Vec<i32> {
+        self.widths.iter().scan(self.x, |x, w| {
+            let at = *x;
+            *x += w;
+            Some(at)
+        }).collect()
+    }
+
+    fn divider_at(&self, x: i32) -> Option<usize> {
+        self.column_x().iter().zip(self.widths.iter())
+            .position(|(left, w)| (left + w - x).abs() <= DIVIDER_GRAB)
+    }
+
+    fn column_at(&self, x: i32) -> Option<usize> {
+        self.column_x().iter().zip(self.widths.iter())
+            .position(|(left, w)| x >= *left && x < left + w)
+    }
+
+    // Position in display order of the row under `y`
+    fn row_at(&self, y: i32) -> Option<usize> {
+        let body = self.body_rect();
+        if y < body.y() {
+            return None;
+        }
+        let pos = ((y - body.y() + self.scroll) / self.row_height()) as usize;
+        (pos < self.order.len()).then_some(pos)
+    }
+
+    fn selected_pos(&self) -> Option<usize> {
+        let row = self.selected?;
+        self.order.iter().position(|r| *r == row)
+    }
+
+    fn scroll_to_selected(&mut self) {
+        let Some(pos) = self.selected_pos() else {
+            return;
+        };
+        let body_h = self.body_rect().height() as i32;
+        let top = pos as i32 * self.row_height();
+        let bottom = top + self.row_height();
+        if top < self.scroll {
+            self.scroll = top;
+        } else if bottom > self.scroll + body_h {
+            self.scroll = bottom - body_h;
+        }
+        self.scroll = self.scroll.clamp(0, self.max_scroll());
+    }
+
+    fn select_pos(&mut self, pos: usize) {
+        if self.order.is_empty() {
+            return;
+        }
+        let row = self.order[pos.min(self.order.len() - 1)];
+        if self.selected != Some(row) {
+            self.selected = Some(row);
+            self.changed = true;
+        }
+        self.scroll_to_selected();
+    }
+
+    fn toggle_sort(&mut self, col: usize) {
+        let ascending = match self.sort {
+            Some((c, ascending)) if c == col => !ascending,
+            _ => true,
+        };
+        self.sort_by(Some((col, ascending)));
+        self.scroll_to_selected();
+    }
+
+    fn key(&mut self, key: Keycode) -> bool {
+        let page = (self.body_rect().height() as i32 / self.row_height()).max(1) as usize;
+        let cur = self.selected_pos();
+        match key {
+            Keycode::Up => self.select_pos(cur.map_or(0, |i| i.saturating_sub(1))),
+            Keycode::Down => self.select_pos(cur.map_or(0, |i| i + 1)),
+            Keycode::PageUp => self.select_pos(cur.map_or(0, |i| i.saturating_sub(page))),
+            Keycode::PageDown => self.select_pos(cur.map_or(0, |i| i + page)),
+            Keycode::Home => self.select_pos(0),
+            Keycode::End => self.select_pos(self.order.len().saturating_sub(1)),
+            _ => return false,
+        }
+        true
+    }
+
+    fn draw_cell(&self, ctx: &mut DrawCtx, text: &str, cell: Rect, color: Color) -> Result<(), ToolkitError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        let pad = self.theme.padding;
+        let texture = ctx.text().render_font(self.font, text)?;
+        let attr = texture.query();
+        // long cells are cut off at the column edge
+        let w = attr.width.min((cell.width() as i32 - pad).max(0) as u32);
+        if w == 0 {
+            return Ok(());
+        }
+        ctx.tint(&texture, color);
+        let dst = Rect::new(cell.x() + pad / 2, cell.y() + (cell.height() as i32 - attr.height as i32) / 2, w, attr.height);
+        ctx.canvas().copy(&texture, Rect::new(0, 0, w, attr.height), dst)?;
+        Ok(())
+    }
+}
+
+impl Debug for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Table")
+            .field("name", &self.name)
+            .field("rect", &self.rect())
+            .field("columns", &self.widths.len())
+            .field("rows", &self.order.len())
+            .field("sort", &self.sort)
+            .field("selected", &self.selected)
+            .finish()
+    }
+}
+
+impl Drawable for Table {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let rect = self.rect();
+        let theme = &self.theme;
+        let text = if self.state.enabled { theme.text } else { theme.disabled };
+        ctx.fill_rounded_rect(rect, theme.corner_radius, theme.field)?;
+        let xs = self.column_x();
+        let row_h = self.row_height();
+
+        // only the rows that can be seen are rendered
+        let body = self.body_rect();
+        ctx.push_clip(body);
+        let first = (self.scroll / row_h) as usize;
+        let visible = (body.height() as i32 / row_h + 2) as usize;
+        for (pos, row) in self.order.iter().enumerate().skip(first).take(visible) {
+            let y = body.y() + pos as i32 * row_h - self.scroll;
+            if Some(*row) == self.selected {
+                ctx.canvas().set_draw_color(theme.active);
+                ctx.canvas().fill_rect(Rect::new(self.x, y, self.w as u32, row_h as u32))?;
+            }
+            for (col, (x, w)) in xs.iter().zip(self.widths.iter()).enumerate() {
+                if *x >= body.right() {
+                    break;
+                }
+                let cell = self.model.cell(*row, col);
+                self.draw_cell(ctx, &cell, Rect::new(*x, y, *w as u32, row_h as u32), text)?;
+            }
+        }
+        ctx.pop_clip();
+
+        let header = self.header_rect();
+        ctx.push_clip(header);
+        ctx.canvas().set_draw_color(theme.fill);
+        ctx.canvas().fill_rect(header)?;
+        for (col, (x, w)) in xs.iter().zip(self.widths.iter()).enumerate() {
+            let cell = Rect::new(*x, header.y(), *w as u32, header.height());
+            let mut title = self.model.header(col);
+            match self.sort {
+                Some((c, true)) if c == col => title.push_str(" ^"),
+                Some((c, false)) if c == col => title.push_str(" v"),
+                _ => { },
+            }
+            self.draw_cell(ctx, &title, cell, text)?;
+            ctx.canvas().set_draw_color(theme.border);
+            let right = x + w - 1;
+            ctx.canvas().draw_line(Point::new(right, header.top()), Point::new(right, header.bottom() - 1))?;
+        }
+        ctx.canvas().set_draw_color(theme.border);
+        ctx.canvas().draw_line(Point::new(header.left(), header.bottom() - 1), Point::new(header.right() - 1, header.bottom() - 1))?;
+        ctx.pop_clip();
+
+        let border = if self.state.focused { theme.active } else { theme.border };
+        ctx.draw_rounded_rect(rect, theme.corner_radius, border)?;
+        Ok(())
+    }
+}
+
+impl Widget for Table {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { Rect::new(self.x, self.y, self.w as u32, self.h as u32) }
+    fn role(&self) -> Role { Role::Table }
+    fn focusable(&self) -> bool { true }
+    fn widget_state(&self) -> Option<&WidgetState> { Some(&self.state) }
+    fn widget_state_mut(&mut self) -> Option<&mut WidgetState> { Some(&mut self.state) }
+
+    fn size_hint(&self) -> (u32, u32) {
+        (self.pref.0 as u32, self.pref.1 as u32)
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.x = rect.x();
+        self.y = rect.y();
+        self.w = rect.width() as i32;
+        self.h = rect.height() as i32;
+        self.scroll = self.scroll.clamp(0, self.max_scroll());
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+        }
+    }
+
+    // Column widths stay as they are, they may have been set by hand
+    fn set_font(&mut self, text: &TextRenderer, font: FontId) -> Result<(), ToolkitError> {
+        self.font = font;
+        self.text_height = text.font_height(font);
+        self.scroll = self.scroll.clamp(0, self.max_scroll());
+        Ok(())
+    }
+
+    // Carries the model row, with its cells separated by tabs as text
+    fn drag_data(&self, _x: i32, y: i32) -> Option<DragData> {
+        let row = self.order[self.row_at(y)?];
+        let text = (0..self.model.columns())
+            .map(|col| self.model.cell(row, col))
+            .collect::<Vec<_>>()
+            .join("\t");
+        Some(DragData {
+            source: self.name.clone(),
+            text,
+            index: Some(row),
+        })
+    }
+
+    fn scroll_pixels(&mut self, _dx: i32, dy: i32) -> bool {
+        let before = self.scroll;
+        self.scroll = (self.scroll - dy).clamp(0, self.max_scroll());
+        self.scroll != before
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::MouseButtonDown {mouse_btn: MouseButton::Left, x, y, ..} => {
+                if self.header_rect().contains_point((*x, *y)) {
+                    self.header_press = match (self.divider_at(*x), self.column_at(*x)) {
+                        (Some(col), _) => Some(HeaderPress::Resize {col, start_x: *x, start_w: self.widths[col]}),
+                        (None, Some(col)) => Some(HeaderPress::Sort(col)),
+                        (None, None) => None,
+                    };
+                } else if let Some(pos) = self.row_at(*y) {
+                    self.select_pos(pos);
+                }
+            },
+            Event::MouseMotion {x, ..} => {
+                let Some(HeaderPress::Resize {col, start_x, start_w}) = self.header_press else {
+                    return false;
+                };
+                self.set_column_width(col, start_w + x - start_x);
+            },
+            Event::MouseButtonUp {mouse_btn: MouseButton::Left, x, y, ..} => {
+                // a sort only happens when the press ends on the same header
+                if let Some(HeaderPress::Sort(col)) = self.header_press.take() {
+                    if self.header_rect().contains_point((*x, *y)) && self.column_at(*x) == Some(col) {
+                        self.toggle_sort(col);
+                    }
+                }
+            },
+            Event::MouseWheel {y, ..} => {
+                let before = self.scroll;
+                self.scroll = (self.scroll - y * WHEEL_ROWS * self.row_height()).clamp(0, self.max_scroll());
+                return self.scroll != before;
+            },
+            Event::KeyDown {keycode: Some(key), ..} => return self.key(*key),
+            _ => return false,
+        }
+        true
+    }
+
+    fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
@@ -0,0 +1,693 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Debug;
+use std::ops::Range;
+
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::MouseButton;
+use sdl2::rect::Rect;
+use sdl2::ttf::FontStyle;
+
+use crate::undo::{self, EditKind, UndoStack};
+use crate::{DrawCtx, Drawable, FontId, Role, TextRenderer, Theme, ToolkitError, Widget, WidgetState};
+
+const WHEEL_LINES: i32 = 3;
+
+// Multi-line text, word wrapped to the widget's width. Read-only unless made
+// editable, which makes it usable as a log or console view as well.
+pub struct TextArea {
+    name: String,
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    pref: (i32, i32),
+    value: String,
+    // byte offset into `value`, always on a char boundary
+    cursor: usize,
+    // other end of the selection, the same as `cursor` when there is none
+    anchor: usize,
+    // the mouse button is down and dragging moves the cursor
+    selecting: bool,
+    history: UndoStack,
+    editable: bool,
+    state: WidgetState,
+    changed: bool,
+    font: FontId,
+    text_height: i32,
+    theme: Theme,
+    own_theme: bool,
+
+    // Wrapping needs the font, which is only at hand while drawing, so the
+    // line breaks are worked out there and kept until the text or width changes
+    lines: RefCell<Vec<Range<usize>>>,
+    wrapped_at: Cell<Option<i32>>,
+    scroll: Cell<i32>,
+    // scroll the cursor into view on the next draw
+    reveal: Cell<bool>,
+    // x of every char boundary of the lines drawn since the last wrap, by
+    // line, so clicks can be placed without the font
+    offsets: RefCell<HashMap<usize, Vec<i32>>>,
+}
+
+// Breaks `s` into lines no wider than `width`. SDL_ttf's own wrapped mode
+// doesn't say where it broke the lines, which the cursor and scrolling need.
+pub(crate) fn wrap(text: &TextRenderer, font: FontId, s: &str, width: i32) -> Result<Vec<Range<usize>>, ToolkitError> {
+    // trailing spaces may hang past the edge
+    let fits = |range: Range<usize>| -> Result<bool, ToolkitError> {
+        let part = s[range].trim_end_matches(' ');
+        Ok(part.is_empty() || text.size_of_font(font, part)?.0 as i32 <= width)
+    };
+
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for para in s.split('\n') {
+        let end = start + para.len();
+        let mut line = start;
+        while line < end {
+            // take whole words (trailing spaces included) while they fit
+            let mut brk = line;
+            for (i, c) in s[line..end].char_indices() {
+                let at = line + i + c.len_utf8();
+                let word_end = c == ' ' && !s[at..end].starts_with(' ') || at == end;
+                if word_end {
+                    if fits(line..at)? || brk == line {
+                        brk = at;
+                    } else {
+                        break;
+                    }
+                }
+            }
+            // a single word wider than the line gets cut wherever it has to be
+            if !fits(line..brk)? {
+                let mut cut = line;
+                for (i, c) in s[line..brk].char_indices() {
+                    let at = line + i + c.len_utf8();
+                    if cut != line && !fits(line..at)? {
+                        break;
+                    }
+                    cut = at;
+                }
+                brk = cut;
+            }
+            lines.push(line..brk);
+            line = brk;
+        }
+        if para.is_empty() {
+            lines.push(start..start);
+        }
+        start = end + 1;
+    }
+    Ok(lines)
+}
+
+impl TextArea {
+    pub fn new(text: &TextRenderer, name: &str, x: i32, y: i32, w: i32, h: i32) -> TextArea {
+        TextArea {
+            name: name.to_string(),
+            x,
+            y,
+            w,
+            h,
+            pref: (w, h),
+            value: String::new(),
+            cursor: 0,
+            anchor: 0,
+            selecting: false,
+            history: UndoStack::new(),
+            editable: false,
+            state: WidgetState::default(),
+            changed: false,
+            font: FontId::DEFAULT,
+            text_height: text.height(),
+            theme: Theme::default(),
+            own_theme: false,
+            lines: RefCell::new(Vec::new()),
+            wrapped_at: Cell::new(None),
+            scroll: Cell::new(0),
+            reveal: Cell::new(false),
+            offsets: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn editable(mut self, editable: bool) -> TextArea {
+        self.editable = editable;
+        self
+    }
+
+    pub fn with_text(mut self, value: &str) -> TextArea {
+        self.set_value(value);
+        self
+    }
+
+    pub fn value(&self) -> &str { &self.value }
+    pub fn cursor(&self) -> usize { self.cursor }
+
+    // Byte range of the selected text, empty when nothing is selected
+    pub fn selection(&self) -> Range<usize> {
+        self.anchor.min(self.cursor)..self.anchor.max(self.cursor)
+    }
+
+    pub fn selected_text(&self) -> &str {
+        &self.value[self.selection()]
+    }
+
+    // Offsets past the end are clamped, ones inside a character move back
+    // to where it starts
+    pub fn select(&mut self, range: Range<usize>) {
+        let fix = |mut at: usize| {
+            at = at.min(self.value.len());
+            while !self.value.is_char_boundary(at) {
+                at -= 1;
+            }
+            at
+        };
+        self.anchor = fix(range.start);
+        self.cursor = fix(range.end);
+        self.reveal.set(true);
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = 0;
+        self.cursor = self.value.len();
+    }
+
+    pub fn is_editable(&self) -> bool { self.editable }
+
+    pub fn set_editable(&mut self, editable: bool) {
+        self.editable = editable;
+    }
+
+    // This, push_line and clear forget the undo history
+    pub fn set_value(&mut self, value: &str) {
+        self.value = value.to_string();
+        self.cursor = self.value.len();
+        self.anchor = self.cursor;
+        self.history.clear();
+        self.rewrap();
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some((value, cursor)) = self.history.undo(&self.value, self.cursor) else {
+            return false;
+        };
+        self.restore(value, cursor);
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let Some((value, cursor)) = self.history.redo(&self.value, self.cursor) else {
+            return false;
+        };
+        self.restore(value, cursor);
+        true
+    }
+
+    pub fn can_undo(&self) -> bool { self.history.can_undo() }
+    pub fn can_redo(&self) -> bool { self.history.can_redo() }
+
+    // Undo steps kept, 100 unless set. 0 turns undo off.
+    pub fn set_undo_depth(&mut self, depth: usize) {
+        self.history.set_depth(depth);
+    }
+
+    fn restore(&mut self, value: String, cursor: usize) {
+        self.value = value;
+        self.cursor = cursor;
+        self.anchor = cursor;
+        self.changed = true;
+        self.rewrap();
+        self.reveal.set(true);
+    }
+
+    fn record(&mut self, kind: EditKind) {
+        self.history.record(kind, &self.value, self.cursor);
+    }
+
+    // Typed text, a step per word. Typing over a selection is a step of
+    // its own.
+    fn type_text(&mut self, s: &str) {
+        self.record(if self.selection().is_empty() { EditKind::Typing } else { EditKind::Other });
+        self.insert(s);
+        if s.ends_with(char::is_whitespace) {
+            self.history.break_group();
+        }
+        self.reveal.set(true);
+    }
+
+    // Appends a line and follows it if the view was already at the bottom,
+    // the usual behaviour for logs
+    pub fn push_line(&mut self, line: &str) {
+        let at_bottom = self.scroll.get() >= self.max_scroll();
+        if !self.value.is_empty() {
+            self.value.push('\n');
+        }
+        self.value.push_str(line);
+        self.history.clear();
+        self.rewrap();
+        if at_bottom {
+            self.scroll.set(i32::MAX);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.value.clear();
+        self.cursor = 0;
+        self.anchor = 0;
+        self.history.clear();
+        self.scroll.set(0);
+        self.rewrap();
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.theme = theme;
+        self.rewrap();
+    }
+
+    fn rewrap(&self) {
+        self.wrapped_at.set(None);
+    }
+
+    fn text_width(&self) -> i32 {
+        (self.w - 2 * self.theme.padding).max(1)
+    }
+
+    fn max_scroll(&self) -> i32 {
+        let content = self.lines.borrow().len() as i32 * self.text_height;
+        (content - self.h + 2 * self.theme.padding).max(0)
+    }
+
+    fn scroll_by(&mut self, dy: i32) {
+        self.scroll.set((self.scroll.get() + dy).clamp(0, self.max_scroll()));
+    }
+
+    // Wrapped lines from the last draw, or plain paragraphs if the text has
+    // changed since and not been wrapped again yet
+    fn current_lines(&self) -> Vec<Range<usize>> {
+        if self.wrapped_at.get().is_some() {
+            return self.lines.borrow().clone();
+        }
+        let mut start = 0;
+        self.value.split('\n').map(|para| {
+            let line = start..start + para.len();
+            start = line.end + 1;
+            line
+        }).collect()
+    }
+
+    // Line the cursor sits on. At a wrap point it belongs to the next line.
+    fn cursor_line(&self, lines: &[Range<usize>]) -> usize {
+        lines.iter().rposition(|l| l.start <= self.cursor).unwrap_or(0)
+    }
+
+    fn prev_boundary(&self) -> usize {
+        self.value[..self.cursor].char_indices().next_back().map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.value[self.cursor..].chars().next().map_or(self.cursor, |c| self.cursor + c.len_utf8())
+    }
+
+    fn insert(&mut self, s: &str) {
+        self.delete_selection();
+        self.value.insert_str(self.cursor, s);
+        self.cursor += s.len();
+        self.anchor = self.cursor;
+        self.changed = true;
+        self.rewrap();
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        let range = self.selection();
+        if range.is_empty() {
+            return false;
+        }
+        self.value.replace_range(range.clone(), "");
+        self.cursor = range.start;
+        self.anchor = range.start;
+        self.changed = true;
+        self.rewrap();
+        true
+    }
+
+    // Offset of the char boundary closest to a point, going by the lines as
+    // last drawn
+    fn offset_at(&self, x: i32, y: i32) -> usize {
+        let lines = self.current_lines();
+        if lines.is_empty() {
+            return 0;
+        }
+        let pad = self.theme.padding;
+        let row = ((y - self.y - pad + self.scroll.get()) / self.text_height).clamp(0, lines.len() as i32 - 1) as usize;
+        let line = lines[row].clone();
+        let offsets = self.offsets.borrow();
+        let Some(xs) = offsets.get(&row).filter(|_| self.wrapped_at.get().is_some()) else {
+            return line.start;
+        };
+        let x = x - self.x - pad;
+        let col = xs.windows(2).position(|w| x < (w[0] + w[1]) / 2).unwrap_or(xs.len() - 1);
+        let at = line.start + self.value[line].chars().take(col).map(char::len_utf8).sum::<usize>();
+        at.min(self.line_end(&lines, row))
+    }
+
+    // The word around `at`, or just the character there if it isn't part
+    // of one
+    fn word_at(&self, at: usize) -> Range<usize> {
+        let is_word = |c: char| c.is_alphanumeric() || c == '_';
+        let Some(c) = self.value[at..].chars().next() else {
+            return at..at;
+        };
+        if !is_word(c) {
+            return at..at + c.len_utf8();
+        }
+        let start = self.value[..at].char_indices().rev().take_while(|&(_, c)| is_word(c)).last().map_or(at, |(i, _)| i);
+        let end = self.value[at..].char_indices().find(|&(_, c)| !is_word(c)).map_or(self.value.len(), |(i, _)| at + i);
+        start..end
+    }
+
+    fn paragraph_at(&self, at: usize) -> Range<usize> {
+        let start = self.value[..at].rfind('\n').map_or(0, |i| i + 1);
+        let end = self.value[at..].find('\n').map_or(self.value.len(), |i| at + i);
+        start..end
+    }
+
+    // x of each char boundary of line `idx`, measured the first time the
+    // line is drawn after a wrap
+    fn line_offsets(&self, text: &TextRenderer, idx: usize, line: Range<usize>) -> Result<Vec<i32>, ToolkitError> {
+        if let Some(xs) = self.offsets.borrow().get(&idx) {
+            return Ok(xs.clone());
+        }
+        let xs = text.char_offsets(self.font, &self.value[line], FontStyle::NORMAL)?;
+        self.offsets.borrow_mut().insert(idx, xs.clone());
+        Ok(xs)
+    }
+
+    // Where the cursor goes at the end of a line: before the space or newline
+    // that ends it, if any
+    fn line_end(&self, lines: &[Range<usize>], idx: usize) -> usize {
+        match lines.get(idx + 1) {
+            Some(next) if self.value[..next.start].ends_with([' ', '\n']) => next.start - 1,
+            Some(next) => next.start,
+            None => self.value.len(),
+        }
+    }
+
+    // Moves up or down by `delta` lines, keeping the column in characters
+    fn move_lines(&mut self, delta: i32) {
+        let lines = self.current_lines();
+        if lines.is_empty() {
+            return;
+        }
+        let cur = self.cursor_line(&lines);
+        let col = self.value[lines[cur].start..self.cursor].chars().count();
+        let target = (cur as i32 + delta).clamp(0, lines.len() as i32 - 1) as usize;
+        let start = lines[target].start;
+        let text = &self.value[start..self.line_end(&lines, target)];
+        self.cursor = start + text.chars().take(col).map(char::len_utf8).sum::<usize>();
+    }
+
+    fn key(&mut self, key: Keycode) -> bool {
+        if !self.editable {
+            match key {
+                Keycode::Up => self.scroll_by(-self.text_height),
+                Keycode::Down => self.scroll_by(self.text_height),
+                Keycode::PageUp => self.scroll_by(-self.h),
+                Keycode::PageDown => self.scroll_by(self.h),
+                Keycode::Home => self.scroll.set(0),
+                Keycode::End => self.scroll.set(self.max_scroll()),
+                _ => return false,
+            }
+            return true;
+        }
+
+        if matches!(key, Keycode::Backspace | Keycode::Delete) && !self.selection().is_empty() {
+            self.record(EditKind::Other);
+            self.delete_selection();
+            self.reveal.set(true);
+            return true;
+        }
+        let page = (self.h / self.text_height).max(1);
+        match key {
+            Keycode::Backspace => {
+                let start = self.prev_boundary();
+                if start == self.cursor {
+                    return true;
+                }
+                self.record(EditKind::Deleting);
+                self.changed = true;
+                self.value.replace_range(start..self.cursor, "");
+                self.cursor = start;
+                self.rewrap();
+            },
+            Keycode::Delete => {
+                let end = self.next_boundary();
+                if end == self.cursor {
+                    return true;
+                }
+                self.record(EditKind::Deleting);
+                self.changed = true;
+                self.value.replace_range(self.cursor..end, "");
+                self.rewrap();
+            },
+            Keycode::Return | Keycode::KpEnter => self.type_text("\n"),
+            Keycode::Left => self.cursor = self.prev_boundary(),
+            Keycode::Right => self.cursor = self.next_boundary(),
+            Keycode::Up => self.move_lines(-1),
+            Keycode::Down => self.move_lines(1),
+            Keycode::PageUp => self.move_lines(-page),
+            Keycode::PageDown => self.move_lines(page),
+            Keycode::Home => {
+                let lines = self.current_lines();
+                self.cursor = lines.get(self.cursor_line(&lines)).map_or(0, |l| l.start);
+            },
+            Keycode::End => {
+                let lines = self.current_lines();
+                self.cursor = self.line_end(&lines, self.cursor_line(&lines));
+            },
+            _ => return false,
+        }
+        if !matches!(key, Keycode::Backspace | Keycode::Delete | Keycode::Return | Keycode::KpEnter) {
+            self.history.break_group();
+        }
+        self.anchor = self.cursor;
+        self.reveal.set(true);
+        true
+    }
+}
+
+impl Debug for TextArea {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TextArea")
+            .field("name", &self.name)
+            .field("rect", &self.rect())
+            .field("value", &self.value)
+            .field("cursor", &self.cursor)
+            .field("selection", &self.selection())
+            .field("editable", &self.editable)
+            .field("focused", &self.state.focused)
+            .finish()
+    }
+}
+
+impl Drawable for TextArea {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let rect = self.rect();
+        let theme = &self.theme;
+        let pad = theme.padding;
+        ctx.fill_rounded_rect(rect, theme.corner_radius, theme.field)?;
+
+        let width = self.text_width();
+        if self.wrapped_at.get() != Some(width) {
+            *self.lines.borrow_mut() = wrap(ctx.text(), self.font, &self.value, width)?;
+            self.offsets.borrow_mut().clear();
+            self.wrapped_at.set(Some(width));
+        }
+
+        let line_h = self.text_height;
+        let view_h = self.h - 2 * pad;
+        let lines = self.lines.borrow();
+        let cursor_line = self.cursor_line(&lines) as i32;
+        if self.reveal.take() {
+            let top = cursor_line * line_h;
+            let scroll = self.scroll.get().min(top).max(top + line_h - view_h);
+            self.scroll.set(scroll);
+        }
+        self.scroll.set(self.scroll.get().clamp(0, self.max_scroll()));
+        let scroll = self.scroll.get();
+
+        let color = if self.state.enabled { theme.text } else { theme.disabled };
+        let inner = Rect::new(self.x + pad, self.y + pad, width as u32, view_h.max(0) as u32);
+        ctx.push_clip(inner);
+        let first = (scroll / line_h) as usize;
+        let visible = (view_h / line_h + 2) as usize;
+        let selection = self.selection();
+        for (idx, line) in lines.iter().enumerate().skip(first).take(visible) {
+            let y = inner.y() + idx as i32 * line_h - scroll;
+            let (start, end) = (selection.start.max(line.start), selection.end.min(line.end));
+            if start < end {
+                let xs = self.line_offsets(ctx.text(), idx, line.clone())?;
+                let col = |at: usize| xs[self.value[line.start..at].chars().count()];
+                let (x0, x1) = (col(start), col(end));
+                ctx.fill_rect(Rect::new(inner.x() + x0, y, (x1 - x0) as u32, line_h as u32), theme.active)?;
+            } else if !self.offsets.borrow().contains_key(&idx) {
+                self.line_offsets(ctx.text(), idx, line.clone())?;
+            }
+            let text = self.value[line.clone()].trim_end_matches(' ');
+            if text.is_empty() {
+                continue;
+            }
+            let texture = ctx.text().render_font(self.font, text)?;
+            let attr = texture.query();
+            ctx.tint(&texture, color);
+            let dst = Rect::new(inner.x(), y, attr.width, attr.height);
+            ctx.canvas().copy(&texture, None, dst)?;
+        }
+
+        if self.editable && self.state.focused {
+            let line = lines.get(cursor_line as usize).map_or(0, |l| l.start);
+            let x = inner.x() + ctx.text().size_of_font(self.font, &self.value[line..self.cursor])?.0 as i32;
+            let y = inner.y() + cursor_line * line_h - scroll;
+            ctx.canvas().set_draw_color(theme.text);
+            ctx.canvas().draw_line((x, y), (x, y + line_h))?;
+        }
+        ctx.pop_clip();
+
+        let border = if self.state.focused { theme.active } else { theme.border };
+        ctx.draw_rounded_rect(rect, theme.corner_radius, border)?;
+        Ok(())
+    }
+}
+
+impl Widget for TextArea {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { Rect::new(self.x, self.y, self.w as u32, self.h as u32) }
+    fn role(&self) -> Role { if self.editable { Role::TextArea } else { Role::Label } }
+    fn access_value(&self) -> Option<String> { Some(self.value.clone()) }
+    fn focusable(&self) -> bool { true }
+    fn widget_state(&self) -> Option<&WidgetState> { Some(&self.state) }
+    fn widget_state_mut(&mut self) -> Option<&mut WidgetState> { Some(&mut self.state) }
+    fn wants_text_input(&self) -> bool { self.editable }
+
+    fn size_hint(&self) -> (u32, u32) {
+        (self.pref.0 as u32, self.pref.1 as u32)
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.x = rect.x();
+        self.y = rect.y();
+        self.w = rect.width() as i32;
+        self.h = rect.height() as i32;
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.theme = *theme;
+            self.rewrap();
+        }
+    }
+
+    fn set_font(&mut self, text: &TextRenderer, font: FontId) -> Result<(), ToolkitError> {
+        self.font = font;
+        self.text_height = text.font_height(font);
+        self.rewrap();
+        Ok(())
+    }
+
+    fn scroll_pixels(&mut self, _dx: i32, dy: i32) -> bool {
+        let before = self.scroll.get();
+        self.scroll_by(-dy);
+        self.scroll.get() != before
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::MouseWheel {y, ..} => {
+                self.scroll_by(-y * WHEEL_LINES * self.text_height);
+                true
+            },
+            // a double click selects a word, a triple click the paragraph
+            Event::MouseButtonDown {mouse_btn: MouseButton::Left, clicks, x, y, ..} => {
+                let at = self.offset_at(*x, *y);
+                let range = match clicks {
+                    2 => self.word_at(at),
+                    3 => self.paragraph_at(at),
+                    _ => at..at,
+                };
+                self.anchor = range.start;
+                self.cursor = range.end;
+                self.selecting = true;
+                self.history.break_group();
+                true
+            },
+            Event::MouseMotion {x, y, ..} if self.selecting => {
+                self.cursor = self.offset_at(*x, *y);
+                self.reveal.set(true);
+                true
+            },
+            Event::MouseButtonUp {mouse_btn: MouseButton::Left, ..} => {
+                self.selecting = false;
+                false
+            },
+            Event::KeyDown {keycode: Some(Keycode::A), keymod, ..} if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                self.select_all();
+                true
+            },
+            Event::TextInput {text, ..} if self.editable => {
+                self.type_text(text);
+                true
+            },
+            Event::KeyDown {keycode: Some(key), keymod, ..} => match undo::undo_key(*key, *keymod).filter(|_| self.editable) {
+                Some(redo) => {
+                    if redo {
+                        self.redo();
+                    } else {
+                        self.undo();
+                    }
+                    true
+                },
+                None => self.key(*key),
+            },
+            _ => false,
+        }
+    }
+
+    fn copy_text(&self) -> Option<String> {
+        let text = self.selected_text();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
+    fn cut_text(&mut self) -> Option<String> {
+        if !self.editable {
+            return None;
+        }
+        let text = self.copy_text()?;
+        self.record(EditKind::Other);
+        self.delete_selection();
+        self.reveal.set(true);
+        Some(text)
+    }
+
+    fn paste_text(&mut self, text: &str) -> bool {
+        if !self.editable {
+            return false;
+        }
+        let text = text.replace("\r\n", "\n");
+        if !text.is_empty() {
+            self.record(EditKind::Other);
+            self.insert(&text);
+            self.reveal.set(true);
+        }
+        true
+    }
+
+    fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
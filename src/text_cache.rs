@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use log::trace;
+use sdl2::pixels::Color;
+use sdl2::render::Texture;
+
+use crate::FontId;
+
+pub(crate) const DEFAULT_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct TextKey {
+    text: String,
+    color: Color,
+    font: FontId,
+}
+
+struct Entry {
+    texture: Rc<Texture<'static>>,
+    last_used: u64,
+}
+
+// Rendered strings kept around for reuse, the least recently used one is
+// dropped once there are more than `capacity`
+pub(crate) struct TextCache {
+    entries: HashMap<TextKey, Entry>,
+    capacity: usize,
+    clock: u64,
+}
+
+impl TextCache {
+    pub(crate) fn new() -> TextCache {
+        TextCache {
+            entries: HashMap::new(),
+            capacity: DEFAULT_CAPACITY,
+            clock: 0,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize { self.entries.len() }
+    pub(crate) fn capacity(&self) -> usize { self.capacity }
+
+    pub(crate) fn get(&mut self, text: &str, color: Color, font: FontId) -> Option<Rc<Texture<'static>>> {
+        self.clock += 1;
+        let key = TextKey { text: text.to_string(), color, font };
+        let entry = self.entries.get_mut(&key)?;
+        entry.last_used = self.clock;
+        Some(entry.texture.clone())
+    }
+
+    pub(crate) fn insert(&mut self, text: &str, color: Color, font: FontId, texture: Rc<Texture<'static>>) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.clock += 1;
+        let key = TextKey { text: text.to_string(), color, font };
+        self.entries.insert(key, Entry { texture, last_used: self.clock });
+        self.evict();
+    }
+
+    // 0 turns caching off
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    // Textures still held by widgets live on until those let go of them
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity {
+            let oldest = self.entries.iter()
+                .min_by_key(|(_, e)| e.last_used)
+                .map(|(k, _)| k.clone());
+            match oldest {
+                Some(key) => {
+                    trace!("text cache dropped {:?}", key.text);
+                    self.entries.remove(&key)
+                },
+                None => break,
+            };
+        }
+    }
+}
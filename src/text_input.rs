@@ -0,0 +1,439 @@
+use std::any::Any;
+use std::fmt;
+use std::fmt::Debug;
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::rect::Rect;
+use sdl2::ttf::FontStyle;
+
+use crate::font;
+use crate::undo::{self, EditKind, UndoStack};
+use crate::{DrawCtx, Drawable, FontId, InputMask, Role, TextRenderer, Theme, ToolkitError, Validator, Widget, WidgetState};
+
+// Shown for each character of a password, '*' with fonts that lack it
+const BULLET: char = '\u{2022}';
+
+// Single-line editable text field
+pub struct TextInput {
+    name: String,
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    // size asked for at creation, layouts may hand out more
+    pref_w: i32,
+    pref_h: i32,
+    value: String,
+    // byte offset into `value`, always on a char boundary
+    cursor: usize,
+    history: UndoStack,
+    validators: Vec<Validator>,
+    mask: Option<InputMask>,
+    // shows bullets, can't be copied from, and only hands out its text
+    // through plaintext()
+    password: bool,
+    state: WidgetState,
+    changed: bool,
+    font: FontId,
+    text_height: i32,
+    theme: Theme,
+    own_theme: bool,
+}
+
+impl TextInput {
+    pub fn new(text: &TextRenderer, name: &str, x: i32, y: i32, w: i32) -> TextInput {
+        let theme = Theme::default();
+        let h = text.height() + 2 * theme.padding;
+        TextInput {
+            name: name.to_string(),
+            x,
+            y,
+            w,
+            h,
+            pref_w: w,
+            pref_h: h,
+            value: String::new(),
+            cursor: 0,
+            history: UndoStack::new(),
+            validators: Vec::new(),
+            mask: None,
+            password: false,
+            state: WidgetState::default(),
+            changed: false,
+            font: FontId::DEFAULT,
+            text_height: text.height(),
+            theme,
+            own_theme: false,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.own_theme = true;
+        self.set_theme_inner(theme);
+    }
+
+    fn set_theme_inner(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.pref_h = self.text_height + 2 * theme.padding;
+        self.h = self.pref_h;
+    }
+
+    // Empty for password inputs, see plaintext()
+    pub fn value(&self) -> &str { if self.password { "" } else { &self.value } }
+
+    // The text as typed, password or not
+    pub fn plaintext(&self) -> &str { &self.value }
+
+    pub fn is_password(&self) -> bool { self.password }
+    pub fn cursor(&self) -> usize { self.cursor }
+    pub fn focused(&self) -> bool { self.state.focused }
+
+    pub fn password(mut self, password: bool) -> TextInput {
+        self.password = password;
+        self
+    }
+
+    pub fn set_password(&mut self, password: bool) {
+        self.password = password;
+    }
+
+    pub fn validator(mut self, validator: Validator) -> TextInput {
+        self.add_validator(validator);
+        self
+    }
+
+    pub fn mask(mut self, pattern: &str) -> TextInput {
+        self.set_mask(Some(pattern));
+        self
+    }
+
+    pub fn add_validator(&mut self, validator: Validator) {
+        self.validators.push(validator);
+        self.revalidate();
+    }
+
+    pub fn clear_validators(&mut self) {
+        self.validators.clear();
+        self.revalidate();
+    }
+
+    pub fn set_mask(&mut self, pattern: Option<&str>) {
+        self.mask = pattern.map(InputMask::new);
+        self.revalidate();
+    }
+
+    // Whether every validator is met and the mask filled in. An empty field
+    // counts as valid, it just hasn't been filled in yet.
+    pub fn is_valid(&self) -> bool {
+        self.value.is_empty()
+            || self.validators.iter().all(|v| v.is_valid(&self.value)) && self.mask.as_ref().is_none_or(|m| m.is_complete(&self.value))
+    }
+
+    fn revalidate(&mut self) {
+        self.state.invalid = !self.is_valid();
+    }
+
+    // Also forgets the undo history
+    pub fn set_value(&mut self, value: &str) {
+        self.value = value.to_string();
+        self.cursor = self.value.len();
+        self.history.clear();
+        self.revalidate();
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some((value, cursor)) = self.history.undo(&self.value, self.cursor) else {
+            return false;
+        };
+        self.restore(value, cursor);
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let Some((value, cursor)) = self.history.redo(&self.value, self.cursor) else {
+            return false;
+        };
+        self.restore(value, cursor);
+        true
+    }
+
+    pub fn can_undo(&self) -> bool { self.history.can_undo() }
+    pub fn can_redo(&self) -> bool { self.history.can_redo() }
+
+    // Undo steps kept, 100 unless set. 0 turns undo off.
+    pub fn set_undo_depth(&mut self, depth: usize) {
+        self.history.set_depth(depth);
+    }
+
+    fn restore(&mut self, value: String, cursor: usize) {
+        self.value = value;
+        self.cursor = cursor;
+        self.changed = true;
+        self.revalidate();
+    }
+
+    fn record(&mut self, kind: EditKind) {
+        self.history.record(kind, &self.value, self.cursor);
+    }
+
+    // Typed text, a step per word
+    fn type_text(&mut self, s: &str) {
+        if !self.allows(s) {
+            return;
+        }
+        self.record(EditKind::Typing);
+        self.insert(s);
+        if s.ends_with(char::is_whitespace) {
+            self.history.break_group();
+        }
+    }
+
+    fn prev_boundary(&self) -> usize {
+        self.value[..self.cursor].char_indices().next_back().map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.value[self.cursor..].chars().next().map_or(self.cursor, |c| self.cursor + c.len_utf8())
+    }
+
+    // Whether inserting `s` at the cursor keeps the value one the validators
+    // and mask could still take
+    fn allows(&self, s: &str) -> bool {
+        let value = match &self.mask {
+            Some(mask) => match mask.insert(&self.value, self.cursor, s) {
+                Some((value, _)) => value,
+                None => return false,
+            },
+            None => {
+                let mut value = self.value.clone();
+                value.insert_str(self.cursor, s);
+                value
+            },
+        };
+        self.validators.iter().all(|v| v.accepts(&value))
+    }
+
+    // Callers check allows() first
+    fn insert(&mut self, s: &str) {
+        match self.mask.as_ref().and_then(|mask| mask.insert(&self.value, self.cursor, s)) {
+            Some((value, cursor)) => {
+                self.value = value;
+                self.cursor = cursor;
+            },
+            None => {
+                self.value.insert_str(self.cursor, s);
+                self.cursor += s.len();
+            },
+        }
+        self.changed = true;
+        self.revalidate();
+    }
+
+    // Takes out the bytes from `start` to `end`. A mask's literals are
+    // stepped over instead, in the direction of `backwards`.
+    fn delete(&mut self, start: usize, end: usize, backwards: bool) {
+        let value = match &self.mask {
+            Some(mask) => match mask.remove(&self.value, start, end) {
+                Some(value) => value,
+                None => return,
+            },
+            None => {
+                let mut value = self.value.clone();
+                value.replace_range(start..end, "");
+                value
+            },
+        };
+        if value == self.value {
+            self.cursor = if backwards { start } else { end };
+            return;
+        }
+        self.record(EditKind::Deleting);
+        self.changed = true;
+        self.value = value;
+        self.cursor = start;
+        self.revalidate();
+    }
+
+    fn key(&mut self, key: Keycode) -> bool {
+        match key {
+            Keycode::Backspace => {
+                let start = self.prev_boundary();
+                if start == self.cursor {
+                    return true;
+                }
+                self.delete(start, self.cursor, true);
+            },
+            Keycode::Delete => {
+                let end = self.next_boundary();
+                if end == self.cursor {
+                    return true;
+                }
+                self.delete(self.cursor, end, false);
+            },
+            Keycode::Left => self.cursor = self.prev_boundary(),
+            Keycode::Right => self.cursor = self.next_boundary(),
+            Keycode::Home => self.cursor = 0,
+            Keycode::End => self.cursor = self.value.len(),
+            _ => return false,
+        }
+        if !matches!(key, Keycode::Backspace | Keycode::Delete) {
+            self.history.break_group();
+        }
+        true
+    }
+}
+
+impl Debug for TextInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TextInput")
+            .field("name", &self.name)
+            .field("rect", &self.rect())
+            .field("value", &self.value())
+            .field("cursor", &self.cursor)
+            .field("password", &self.password)
+            .field("focused", &self.state.focused)
+            .finish()
+    }
+}
+
+impl Drawable for TextInput {
+    fn draw(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        let rect = self.rect();
+        let theme = &self.theme;
+        let pad = theme.padding;
+        let border = if self.state.invalid {
+            theme.error
+        } else if self.state.focused {
+            theme.active
+        } else {
+            theme.border
+        };
+        let text = if self.state.enabled { theme.text } else { theme.disabled };
+        ctx.fill_rounded_rect(rect, theme.corner_radius, theme.field)?;
+        ctx.draw_rounded_rect(rect, theme.corner_radius, border)?;
+
+        // passwords never reach the text cache, only their bullets do
+        let masked;
+        let (shown, cursor) = if self.password {
+            let bullet = ctx.text().fonts().with_font(self.font, 0, FontStyle::NORMAL, |f| font::covers(f, BULLET));
+            let bullet = if bullet { BULLET } else { '*' };
+            masked = bullet.to_string().repeat(self.value.chars().count());
+            (masked.as_str(), self.value[..self.cursor].chars().count() * bullet.len_utf8())
+        } else {
+            (self.value.as_str(), self.cursor)
+        };
+
+        // scroll the text so the cursor always stays inside the box
+        let visible = (self.w - 2 * pad).max(0);
+        let cursor_x = ctx.text().size_of_font(self.font, &shown[..cursor])?.0 as i32;
+        let scroll = (cursor_x - visible + 1).max(0);
+
+        if !shown.is_empty() {
+            let texture = ctx.text().render_font(self.font, shown)?;
+            ctx.tint(&texture, text);
+            let attr = texture.query();
+            let w = (attr.width as i32 - scroll).min(visible).max(0) as u32;
+            if w > 0 {
+                let src = Rect::new(scroll, 0, w, attr.height);
+                let dst = Rect::new(self.x + pad, self.y + (self.h - attr.height as i32) / 2, w, attr.height);
+                ctx.canvas().copy(&texture, src, dst)?;
+            }
+        }
+
+        if self.state.focused {
+            let x = self.x + pad + cursor_x - scroll;
+            ctx.canvas().set_draw_color(theme.text);
+            ctx.canvas().draw_line((x, self.y + pad), (x, self.y + self.h - pad))?;
+        }
+        Ok(())
+    }
+}
+
+impl Widget for TextInput {
+    fn name(&self) -> &str { &self.name }
+    fn rect(&self) -> Rect { Rect::new(self.x, self.y, self.w as u32, self.h as u32) }
+    fn role(&self) -> Role { if self.password { Role::PasswordInput } else { Role::TextInput } }
+    fn access_value(&self) -> Option<String> { (!self.password).then(|| self.value.clone()) }
+    fn focusable(&self) -> bool { true }
+    fn widget_state(&self) -> Option<&WidgetState> { Some(&self.state) }
+    fn widget_state_mut(&mut self) -> Option<&mut WidgetState> { Some(&mut self.state) }
+    fn wants_text_input(&self) -> bool { true }
+
+    fn size_hint(&self) -> (u32, u32) {
+        (self.pref_w as u32, self.pref_h as u32)
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.x = rect.x();
+        self.y = rect.y();
+        self.w = rect.width() as i32;
+        self.h = rect.height() as i32;
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        if !self.own_theme {
+            self.set_theme_inner(*theme);
+        }
+    }
+
+    fn set_font(&mut self, text: &TextRenderer, font: FontId) -> Result<(), ToolkitError> {
+        self.font = font;
+        self.text_height = text.font_height(font);
+        self.set_theme_inner(self.theme);
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::TextInput {text, ..} => {
+                self.type_text(text);
+                true
+            },
+            Event::KeyDown {keycode: Some(key), keymod, ..} => match undo::undo_key(*key, *keymod) {
+                Some(redo) => {
+                    if redo {
+                        self.redo();
+                    } else {
+                        self.undo();
+                    }
+                    true
+                },
+                None => self.key(*key),
+            },
+            _ => false,
+        }
+    }
+
+    // Until there is a selection these work on the whole value
+    fn copy_text(&self) -> Option<String> {
+        (!self.password && !self.value.is_empty()).then(|| self.value.clone())
+    }
+
+    fn cut_text(&mut self) -> Option<String> {
+        let text = self.copy_text()?;
+        self.record(EditKind::Other);
+        self.value.clear();
+        self.cursor = 0;
+        self.changed = true;
+        self.revalidate();
+        Some(text)
+    }
+
+    // A single line, so line breaks become spaces
+    fn paste_text(&mut self, text: &str) -> bool {
+        let text = text.replace("\r\n", " ").replace(['\r', '\n'], " ");
+        if !text.is_empty() && self.allows(&text) {
+            self.record(EditKind::Other);
+            self.insert(&text);
+        }
+        true
+    }
+
+    fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
@@ -0,0 +1,67 @@
+use sdl2::pixels::Color;
+
+// Colors and metrics shared by every widget. Set one globally with
+// Toolkit::set_theme, or give a single widget its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub background: Color,
+    // widget bodies (buttons, inactive tabs)
+    pub fill: Color,
+    // the inside of editable fields
+    pub field: Color,
+    pub border: Color,
+    pub text: Color,
+    pub hover: Color,
+    // pressed buttons, the current tab, focused fields
+    pub active: Color,
+    pub disabled: Color,
+    // the border of fields holding something invalid
+    pub error: Color,
+    pub corner_radius: i32,
+    // room inside widgets around their content
+    pub padding: i32,
+    // room layout containers leave around each child
+    pub margin: i32,
+}
+
+impl Theme {
+    pub fn dark() -> Theme {
+        Theme {
+            background: Color::RGBA(0, 0, 0, 100),
+            fill: Color::RGB(60, 60, 60),
+            field: Color::RGB(25, 25, 25),
+            border: Color::RGB(150, 150, 150),
+            text: Color::RGB(255, 255, 255),
+            hover: Color::RGB(80, 80, 80),
+            active: Color::RGB(90, 90, 140),
+            disabled: Color::RGB(100, 100, 100),
+            error: Color::RGB(220, 70, 70),
+            corner_radius: 6,
+            padding: 8,
+            margin: 0,
+        }
+    }
+
+    pub fn light() -> Theme {
+        Theme {
+            background: Color::RGB(235, 235, 235),
+            fill: Color::RGB(215, 215, 215),
+            field: Color::RGB(255, 255, 255),
+            border: Color::RGB(120, 120, 120),
+            text: Color::RGB(0, 0, 0),
+            hover: Color::RGB(200, 210, 230),
+            active: Color::RGB(120, 150, 220),
+            disabled: Color::RGB(170, 170, 170),
+            error: Color::RGB(200, 40, 40),
+            corner_radius: 6,
+            padding: 8,
+            margin: 0,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::dark()
+    }
+}
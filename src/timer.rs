@@ -0,0 +1,94 @@
+use std::fmt;
+use std::fmt::Debug;
+use std::time::{Duration, Instant};
+
+use crate::Callback;
+
+// Handle to a scheduled callback, used to cancel it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+pub(crate) struct Timer {
+    id: TimerId,
+    due: Instant,
+    // repeats every so often until cancelled
+    interval: Option<Duration>,
+    pub(crate) callback: Callback,
+}
+
+// Callbacks waiting for their time to come, fired by the Toolkit between
+// handling input and drawing
+#[derive(Default)]
+pub(crate) struct Timers {
+    timers: Vec<Timer>,
+    // taken out to be fired, cancelling one drops it from here
+    running: Vec<TimerId>,
+    next_id: u64,
+}
+
+impl Timers {
+    pub(crate) fn add(&mut self, delay: Duration, interval: Option<Duration>, callback: Callback) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+        self.timers.push(Timer {
+            id,
+            due: Instant::now() + delay,
+            interval,
+            callback,
+        });
+        id
+    }
+
+    pub(crate) fn cancel(&mut self, id: TimerId) -> bool {
+        if let Some(pos) = self.timers.iter().position(|t| t.id == id) {
+            self.timers.remove(pos);
+            return true;
+        }
+        let len = self.running.len();
+        self.running.retain(|r| *r != id);
+        self.running.len() != len
+    }
+
+    pub(crate) fn next_due(&self) -> Option<Instant> {
+        self.timers.iter().map(|t| t.due).min()
+    }
+
+    // Removes every timer that is due, oldest first
+    pub(crate) fn take_due(&mut self, now: Instant) -> Vec<Timer> {
+        let (mut due, rest) = std::mem::take(&mut self.timers).into_iter().partition::<Vec<_>, _>(|t| t.due <= now);
+        self.timers = rest;
+        due.sort_by_key(|t| t.due);
+        self.running = due.iter().map(|t| t.id).collect();
+        due
+    }
+
+    // False once a timer taken out by take_due was cancelled
+    pub(crate) fn is_running(&self, timer: &Timer) -> bool {
+        self.running.contains(&timer.id)
+    }
+
+    // Puts intervals back after they fired, unless they got cancelled meanwhile
+    pub(crate) fn reschedule(&mut self, mut timer: Timer, now: Instant) {
+        let Some(interval) = timer.interval else {
+            return;
+        };
+        if !self.is_running(&timer) {
+            return;
+        }
+        // a loop that fell behind skips the missed runs instead of catching up
+        timer.due += interval;
+        if timer.due <= now {
+            timer.due = now + interval;
+        }
+        self.timers.push(timer);
+    }
+}
+
+impl Debug for Timers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timers")
+            .field("pending", &self.timers.len())
+            .field("next_due", &self.next_due())
+            .finish()
+    }
+}
@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+use crate::{Anchor, DrawCtx, Theme, Toolkit, ToolkitError};
+
+// Room between toasts, and between them and the edge of the content area
+const GAP: i32 = 8;
+// Width of the colored strip down the left side
+const STRIP: i32 = 4;
+const MAX_WIDTH: u32 = 400;
+
+// How much a notification matters, which sets the color of its strip
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Severity {
+    #[default]
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub(crate) fn color(self, theme: &Theme) -> Color {
+        match self {
+            Severity::Info => theme.active,
+            Severity::Success => Color::RGB(70, 170, 90),
+            Severity::Warning => Color::RGB(230, 160, 40),
+            Severity::Error => theme.error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ToastId(u64);
+
+#[derive(Debug, Clone)]
+pub(crate) struct Toast {
+    id: ToastId,
+    text: String,
+    severity: Severity,
+}
+
+// Removes the toast from `toasts`, true if it was there
+pub(crate) fn remove(toasts: &mut Vec<Toast>, id: ToastId) -> bool {
+    let before = toasts.len();
+    toasts.retain(|t| t.id != id);
+    toasts.len() != before
+}
+
+// Draws the toasts into the rects from Toolkit::toast_rects
+pub(crate) fn draw(ctx: &mut DrawCtx, toasts: &[Toast], rects: &[Rect], theme: &Theme) -> Result<(), ToolkitError> {
+    for (toast, &rect) in toasts.iter().zip(rects) {
+        ctx.fill_rounded_rect(rect, theme.corner_radius, theme.fill)?;
+        ctx.fill_rect(Rect::new(rect.x(), rect.y() + theme.corner_radius / 2, STRIP as u32, rect.height().saturating_sub(theme.corner_radius as u32)), toast.severity.color(theme))?;
+        ctx.draw_rounded_rect(rect, theme.corner_radius, theme.border)?;
+        ctx.push_clip(Rect::new(rect.x() + STRIP, rect.y(), rect.width().saturating_sub(STRIP as u32 + theme.padding as u32), rect.height()));
+        let y = rect.y() + (rect.height() as i32 - ctx.text().height()) / 2;
+        ctx.draw_text_at(rect.x() + STRIP + theme.padding, y, &toast.text, theme.text)?;
+        ctx.pop_clip();
+    }
+    Ok(())
+}
+
+impl Toolkit {
+    // Shows `text` in a banner in a corner of the current window for
+    // `duration`, or until clicked. Duration::ZERO keeps it up until
+    // clicked or dismissed. Newer toasts stack on top of older ones.
+    pub fn notify(&mut self, text: &str, severity: Severity, duration: Duration) -> ToastId {
+        self.dirty = true;
+        let id = ToastId(self.next_id);
+        self.next_id += 1;
+        self.toasts.push(Toast {id, text: text.to_string(), severity});
+        if !duration.is_zero() {
+            self.timers.add(duration, None, Box::new(move |tk| {
+                tk.dismiss_toast(id);
+            }));
+        }
+        id
+    }
+
+    // Takes a toast down early, from whichever window it is in. False if it
+    // is already gone.
+    pub fn dismiss_toast(&mut self, id: ToastId) -> bool {
+        if remove(&mut self.toasts, id) {
+            self.dirty = true;
+            return true;
+        }
+        self.windows.iter_mut().any(|w| w.dismiss_toast(id))
+    }
+
+    // Where toasts pile up inside the content area, the bottom right corner
+    // unless set. Top and Bottom center them, the rest go by their side.
+    pub fn set_toast_corner(&mut self, corner: Anchor) {
+        self.toast_corner = corner;
+        self.dirty = true;
+    }
+
+    // Where each toast of the current window goes, newest first in the
+    // stack
+    pub(crate) fn toast_rects(&self) -> Vec<Rect> {
+        let area = self.content_rect();
+        let pad = self.theme.padding;
+        let max_w = MAX_WIDTH.min(area.width().saturating_sub(2 * GAP as u32));
+        let h = self.text.height() + 2 * pad;
+        let top = matches!(self.toast_corner, Anchor::TopLeft | Anchor::Top | Anchor::TopRight);
+        let mut rects = vec![Rect::new(0, 0, 0, 0); self.toasts.len()];
+        for (n, (toast, rect)) in self.toasts.iter().zip(rects.iter_mut()).rev().enumerate() {
+            let text_w = self.text.size_of(&toast.text).map_or(0, |(w, _)| w);
+            let w = (text_w + (STRIP + 2 * pad) as u32).min(max_w);
+            let x = match self.toast_corner {
+                Anchor::TopLeft | Anchor::Left | Anchor::BottomLeft => area.x() + GAP,
+                Anchor::Top | Anchor::Center | Anchor::Bottom => area.center().x() - w as i32 / 2,
+                _ => area.right() - GAP - w as i32,
+            };
+            let offset = GAP + n as i32 * (h + GAP);
+            let y = if top { area.y() + offset } else { area.bottom() - offset - h };
+            *rect = Rect::new(x, y, w, h as u32);
+        }
+        rects
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use sdl2::event::Event;
+use sdl2::mouse::MouseButton;
+
+use crate::Toolkit;
+
+// Pixels a finger may wander before a tap becomes a swipe
+const TAP_SLOP: i32 = 8;
+// What SDL puts in `which` of mouse events that came from a touch
+const TOUCH_MOUSE_ID: u32 = u32::MAX;
+
+// Two fingers moving apart or together, relative to where they started.
+// `scale` is above 1 when zooming in, (x, y) is the point between them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pinch {
+    pub scale: f32,
+    pub x: i32,
+    pub y: i32,
+}
+
+pub type PinchCallback = Box<dyn FnMut(&mut Toolkit, Pinch)>;
+
+// Fingers on the screen, in window pixels
+#[derive(Debug, Default)]
+pub(crate) struct Touch {
+    fingers: HashMap<i64, (i32, i32)>,
+    // the finger that went down first and where, while it could still tap
+    // or swipe
+    start: Option<(i64, (i32, i32))>,
+    swiping: bool,
+    // distance between the first two fingers when they went down
+    pinch: Option<f32>,
+}
+
+impl Touch {
+    fn spread(&self) -> Option<(f32, (i32, i32))> {
+        let mut fingers = self.fingers.values();
+        let (a, b) = (fingers.next()?, fingers.next()?);
+        let (dx, dy) = ((b.0 - a.0) as f32, (b.1 - a.1) as f32);
+        Some(((dx * dx + dy * dy).sqrt(), ((a.0 + b.0) / 2, (a.1 + b.1) / 2)))
+    }
+}
+
+impl Toolkit {
+    // Called while two fingers pinch, with the scale since they went down
+    pub fn on_pinch<F: FnMut(&mut Toolkit, Pinch) + 'static>(&mut self, f: F) {
+        self.pinch_callbacks.push(Box::new(f));
+    }
+
+    // Finger positions come normalized to the window
+    fn finger_pos(&self, x: f32, y: f32) -> (i32, i32) {
+        let (w, h) = self.size();
+        ((x * w as f32) as i32, (y * h as f32) as i32)
+    }
+
+    // A tap is a click, one finger swiping scrolls whatever is under it and
+    // two fingers pinch. True if the event was a touch.
+    pub(crate) fn touch_event(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::FingerDown {finger_id, x, y, ..} => {
+                let pos = self.finger_pos(x, y);
+                self.touch.fingers.insert(finger_id, pos);
+                match self.touch.fingers.len() {
+                    1 => {
+                        self.touch.start = Some((finger_id, pos));
+                        self.touch.swiping = false;
+                    },
+                    2 => {
+                        // a second finger is never a tap
+                        self.touch.start = None;
+                        self.touch.pinch = self.touch.spread().map(|(d, _)| d);
+                    },
+                    _ => { },
+                }
+            },
+            Event::FingerMotion {finger_id, x, y, ..} => {
+                let pos = self.finger_pos(x, y);
+                let Some(old) = self.touch.fingers.insert(finger_id, pos) else {
+                    return true;
+                };
+                if let (Some(start), Some((now, (cx, cy)))) = (self.touch.pinch, self.touch.spread()) {
+                    if start > 0.0 {
+                        self.pinched(Pinch {scale: now / start, x: cx, y: cy});
+                    }
+                    return true;
+                }
+                let Some((id, (sx, sy))) = self.touch.start else {
+                    return true;
+                };
+                if id != finger_id {
+                    return true;
+                }
+                if !self.touch.swiping && ((pos.0 - sx).abs() > TAP_SLOP || (pos.1 - sy).abs() > TAP_SLOP) {
+                    self.touch.swiping = true;
+                }
+                if self.touch.swiping {
+                    self.swipe((sx, sy), pos.0 - old.0, pos.1 - old.1);
+                }
+            },
+            Event::FingerUp {finger_id, x, y, ..} => {
+                self.touch.fingers.remove(&finger_id);
+                if self.touch.fingers.len() < 2 {
+                    self.touch.pinch = None;
+                }
+                if self.touch.start.is_some_and(|(id, _)| id == finger_id) {
+                    self.touch.start = None;
+                    if !self.touch.swiping {
+                        let (x, y) = self.finger_pos(x, y);
+                        self.tap(x, y);
+                    }
+                }
+            },
+            // SDL's own translation is turned off, but other layers may
+            // still send these
+            Event::MouseMotion {which: TOUCH_MOUSE_ID, ..}
+            | Event::MouseButtonDown {which: TOUCH_MOUSE_ID, ..}
+            | Event::MouseButtonUp {which: TOUCH_MOUSE_ID, ..} => { },
+            _ => return false,
+        }
+        true
+    }
+
+    fn tap(&mut self, x: i32, y: i32) {
+        let button = |down: bool| {
+            let (timestamp, window_id, which, mouse_btn, clicks) = (0, self.window.0, 0, MouseButton::Left, 1);
+            if down {
+                Event::MouseButtonDown {timestamp, window_id, which, mouse_btn, clicks, x, y}
+            } else {
+                Event::MouseButtonUp {timestamp, window_id, which, mouse_btn, clicks, x, y}
+            }
+        };
+        let (down, up) = (button(true), button(false));
+        self.handle_event(down);
+        self.handle_event(up);
+    }
+
+    // Scrolls the innermost widget under `at` that can still move that way
+    fn swipe(&mut self, at: (i32, i32), dx: i32, dy: i32) {
+        if !self.dialogs.is_empty() || self.menu.is_some() {
+            return;
+        }
+        let Some(tab) = self.tabs.get_mut(self.tab_pos) else {
+            return;
+        };
+        let Some(mut path) = tab.hit(at.0, at.1) else {
+            return;
+        };
+        while let Some(w) = tab.get_mut(&path) {
+            if w.is_enabled() && w.scroll_pixels(dx, dy) {
+                break;
+            }
+            path.pop();
+        }
+    }
+
+    fn pinched(&mut self, pinch: Pinch) {
+        let mut cbs = std::mem::take(&mut self.pinch_callbacks);
+        for cb in cbs.iter_mut() {
+            cb(self, pinch);
+        }
+        // keep handlers registered from inside a handler
+        cbs.append(&mut self.pinch_callbacks);
+        self.pinch_callbacks = cbs;
+    }
+}
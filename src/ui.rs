@@ -0,0 +1,330 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{BoxLayout, Button, Callback, ComboBox, Image, ListBox, RadioButton, Slider, TextArea, TextInput, TextRenderer, Toolkit, ToolkitError, Widget};
+
+const DEFAULT_WIDTH: i32 = 200;
+const DEFAULT_HEIGHT: i32 = 150;
+
+#[derive(Error, Debug)]
+pub enum UiError {
+    #[error("Invalid UI description: {0}")]
+    Parse(String),
+    #[error("Tab {0} exists already")]
+    DuplicateTab(String),
+    #[error("Widget id {0} is used more than once")]
+    DuplicateId(String),
+    #[error("{id}: no handler called {handler}")]
+    UnknownHandler {id: String, handler: String},
+    #[error("{id}: {reason}")]
+    Invalid {id: String, reason: String},
+    #[error(transparent)]
+    Toolkit(#[from] ToolkitError),
+}
+
+// A whole description. In TOML every tab is a [[tab]] table, its widgets
+// [[tab.widget]] tables, and so on down into layouts.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UiSpec {
+    #[serde(default, rename = "tab", alias = "tabs")]
+    pub tabs: Vec<TabSpec>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TabSpec {
+    pub name: String,
+    #[serde(default, rename = "widget", alias = "widgets")]
+    pub widgets: Vec<WidgetSpec>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WidgetKind {
+    Button,
+    TextInput,
+    TextArea,
+    Slider,
+    List,
+    Combo,
+    Radio,
+    Image,
+    Vbox,
+    Hbox,
+}
+
+// One widget. Which of the optional fields mean anything depends on `kind`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WidgetSpec {
+    #[serde(rename = "type")]
+    pub kind: WidgetKind,
+    // the widget's name in the Toolkit
+    pub id: String,
+    #[serde(default)]
+    pub x: i32,
+    #[serde(default)]
+    pub y: i32,
+    pub w: Option<i32>,
+    pub h: Option<i32>,
+    // button label or initial text
+    pub text: Option<String>,
+    #[serde(default)]
+    pub items: Vec<String>,
+    pub selected: Option<usize>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub value: Option<f64>,
+    pub group: Option<String>,
+    pub path: Option<String>,
+    pub editable: Option<bool>,
+    // text inputs only
+    pub password: Option<bool>,
+    pub spacing: Option<i32>,
+    pub padding: Option<i32>,
+    // names of handlers given to build()
+    pub on_click: Option<String>,
+    pub on_change: Option<String>,
+    #[serde(default, rename = "widget", alias = "children")]
+    pub children: Vec<WidgetSpec>,
+}
+
+impl WidgetSpec {
+    fn invalid(&self, reason: &str) -> UiError {
+        UiError::Invalid {id: self.id.clone(), reason: reason.to_string()}
+    }
+
+    fn is_layout(&self) -> bool {
+        matches!(self.kind, WidgetKind::Vbox | WidgetKind::Hbox)
+    }
+
+    fn range(&self) -> (f64, f64) {
+        (self.min.unwrap_or(0.0), self.max.unwrap_or(100.0))
+    }
+
+    fn validate(&self, ids: &mut HashSet<String>, handlers: &Handlers) -> Result<(), UiError> {
+        if self.id.is_empty() {
+            return Err(self.invalid("empty id"));
+        }
+        if !ids.insert(self.id.clone()) {
+            return Err(UiError::DuplicateId(self.id.clone()));
+        }
+        for handler in self.on_click.iter().chain(self.on_change.iter()) {
+            if !handlers.map.contains_key(handler) {
+                return Err(UiError::UnknownHandler {id: self.id.clone(), handler: handler.clone()});
+            }
+        }
+        if self.w.is_some_and(|w| w < 0) || self.h.is_some_and(|h| h < 0) {
+            return Err(self.invalid("negative size"));
+        }
+        if !self.children.is_empty() && !self.is_layout() {
+            return Err(self.invalid("only vbox and hbox can hold widgets"));
+        }
+        match self.kind {
+            WidgetKind::Slider => {
+                let (min, max) = self.range();
+                if min >= max {
+                    return Err(self.invalid("min has to be below max"));
+                }
+            },
+            WidgetKind::Radio if self.group.is_none() => return Err(self.invalid("radio buttons need a group")),
+            WidgetKind::Image if self.path.is_none() => return Err(self.invalid("images need a path")),
+            WidgetKind::List | WidgetKind::Combo if self.selected.is_some_and(|idx| idx >= self.items.len()) => {
+                return Err(self.invalid("selected is past the last item"));
+            },
+            _ => { },
+        }
+        for child in self.children.iter() {
+            child.validate(ids, handlers)?;
+        }
+        Ok(())
+    }
+
+    fn make(&self, text: &TextRenderer) -> Result<Box<dyn Widget>, UiError> {
+        let (x, y) = (self.x, self.y);
+        let w = self.w.unwrap_or(DEFAULT_WIDTH);
+        let h = self.h.unwrap_or(DEFAULT_HEIGHT);
+        let widget: Box<dyn Widget> = match self.kind {
+            WidgetKind::Button => {
+                let mut btn = Button::new(text, &self.id, x, y)?;
+                if let Some(label) = self.text.as_deref() {
+                    btn.set_label(text, label)?;
+                }
+                Box::new(btn)
+            },
+            WidgetKind::TextInput => {
+                let mut input = TextInput::new(text, &self.id, x, y, w).password(self.password.unwrap_or(false));
+                input.set_value(self.text.as_deref().unwrap_or(""));
+                Box::new(input)
+            },
+            WidgetKind::TextArea => {
+                let area = TextArea::new(text, &self.id, x, y, w, h)
+                    .editable(self.editable.unwrap_or(true))
+                    .with_text(self.text.as_deref().unwrap_or(""));
+                Box::new(area)
+            },
+            WidgetKind::Slider => {
+                let (min, max) = self.range();
+                let mut slider = Slider::new(&self.id, x, y, w, min, max);
+                slider.set_value(self.value.unwrap_or(min));
+                Box::new(slider)
+            },
+            WidgetKind::List => {
+                let mut list = ListBox::new(text, &self.id, x, y, w, h).with_items(self.items.iter().cloned());
+                list.set_selected(self.selected);
+                Box::new(list)
+            },
+            WidgetKind::Combo => {
+                let mut combo = ComboBox::new(text, &self.id, x, y, w).with_items(self.items.iter().cloned());
+                combo.set_selected(self.selected);
+                Box::new(combo)
+            },
+            WidgetKind::Radio => {
+                let group = self.group.as_deref().unwrap_or_default();
+                Box::new(RadioButton::new(text, group, &self.id, x, y)?)
+            },
+            WidgetKind::Image => {
+                let path = self.path.as_deref().unwrap_or_default();
+                Box::new(Image::from_file(text, &self.id, path, x, y)?)
+            },
+            WidgetKind::Vbox | WidgetKind::Hbox => {
+                let mut layout = match self.kind {
+                    WidgetKind::Vbox => BoxLayout::vertical(&self.id),
+                    _ => BoxLayout::horizontal(&self.id),
+                };
+                if let Some(spacing) = self.spacing {
+                    layout = layout.spacing(spacing);
+                }
+                if let Some(padding) = self.padding {
+                    layout = layout.padding(padding);
+                }
+                for child in self.children.iter() {
+                    layout.push_boxed(child.make(text)?);
+                }
+                Box::new(layout)
+            },
+        };
+        Ok(widget)
+    }
+
+    // Hooks up this widget's handlers and those of everything inside it
+    fn connect(&self, tk: &mut Toolkit, handlers: &Handlers) {
+        if let Some(f) = self.on_click.as_deref().and_then(|h| handlers.get(h)) {
+            tk.on_click(&self.id, f);
+        }
+        if let Some(f) = self.on_change.as_deref().and_then(|h| handlers.get(h)) {
+            tk.on_change(&self.id, f);
+        }
+        for child in self.children.iter() {
+            child.connect(tk, handlers);
+        }
+    }
+}
+
+impl UiSpec {
+    // Checks the description for names and handlers build() would trip
+    // over. Files like images are only found out about when the widgets are
+    // made, which build() also does before it adds anything.
+    pub fn validate(&self, tk: &Toolkit, handlers: &Handlers) -> Result<(), UiError> {
+        let mut tabs = HashSet::new();
+        let mut ids = HashSet::new();
+        for tab in self.tabs.iter() {
+            if tab.name.is_empty() {
+                return Err(UiError::Invalid {id: String::new(), reason: "tab without a name".to_string()});
+            }
+            if !tabs.insert(tab.name.as_str()) || tk.tab_index(&tab.name).is_ok() {
+                return Err(UiError::DuplicateTab(tab.name.clone()));
+            }
+            for widget in tab.widgets.iter() {
+                widget.validate(&mut ids, handlers)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Callbacks a description can refer to by name. One handler may be used by
+// any number of widgets.
+#[derive(Default)]
+pub struct Handlers {
+    map: HashMap<String, Rc<RefCell<Callback>>>,
+}
+
+impl Handlers {
+    pub fn new() -> Handlers {
+        Handlers::default()
+    }
+
+    pub fn with<F: FnMut(&mut Toolkit) + 'static>(mut self, name: &str, f: F) -> Handlers {
+        self.insert(name, f);
+        self
+    }
+
+    pub fn insert<F: FnMut(&mut Toolkit) + 'static>(&mut self, name: &str, f: F) {
+        self.map.insert(name.to_string(), Rc::new(RefCell::new(Box::new(f))));
+    }
+
+    fn get(&self, name: &str) -> Option<impl FnMut(&mut Toolkit) + 'static> {
+        let handler = self.map.get(name)?.clone();
+        // a handler that ends up triggering itself is skipped the second time
+        Some(move |tk: &mut Toolkit| {
+            if let Ok(mut f) = handler.try_borrow_mut() {
+                f(tk);
+            }
+        })
+    }
+}
+
+impl Debug for Handlers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.map.keys()).finish()
+    }
+}
+
+pub fn parse_toml(src: &str) -> Result<UiSpec, UiError> {
+    toml::from_str(src).map_err(|e| UiError::Parse(e.to_string()))
+}
+
+pub fn parse_json(src: &str) -> Result<UiSpec, UiError> {
+    serde_json::from_str(src).map_err(|e| UiError::Parse(e.to_string()))
+}
+
+// Adds the tabs and widgets of `spec` to the Toolkit. The tab that was
+// current before stays current, or the first new one if there was none.
+// Nothing is added unless every widget could be made.
+pub fn build(tk: &mut Toolkit, spec: &UiSpec, handlers: &Handlers) -> Result<(), UiError> {
+    spec.validate(tk, handlers)?;
+    let mut made = Vec::new();
+    for tab in spec.tabs.iter() {
+        let widgets = tab.widgets.iter()
+            .map(|spec| Ok((spec.make(tk.text_renderer())?, spec)))
+            .collect::<Result<Vec<_>, UiError>>()?;
+        made.push((&tab.name, widgets));
+    }
+
+    let previous = tk.current_tab().map(str::to_string);
+    for (name, widgets) in made {
+        tk.add_tab(name)?;
+        tk.select_tab(name)?;
+        for (widget, spec) in widgets {
+            tk.add_boxed(widget)?;
+            spec.connect(tk, handlers);
+        }
+    }
+    if let Some(name) = previous.or_else(|| spec.tabs.first().map(|t| t.name.clone())) {
+        tk.select_tab(&name)?;
+    }
+    Ok(())
+}
+
+// Parses a TOML description and builds it
+pub fn load_from_str(tk: &mut Toolkit, src: &str, handlers: &Handlers) -> Result<(), UiError> {
+    build(tk, &parse_toml(src)?, handlers)
+}
@@ -0,0 +1,228 @@
+use std::collections::VecDeque;
+
+use sdl2::keyboard::{Keycode, Mod};
+
+use crate::Modifiers;
+
+// Steps kept unless a widget is given another depth
+pub(crate) const DEFAULT_DEPTH: usize = 100;
+
+// What an edit did, for merging runs of the same thing into one step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EditKind {
+    Typing,
+    Deleting,
+    // pastes, cuts and replacing a selection, always a step of their own
+    Other,
+}
+
+#[derive(Debug, Clone)]
+struct Snapshot {
+    value: String,
+    cursor: usize,
+}
+
+// Undo and redo history of a text field, as whole copies of the text.
+// Typing or deleting in a row makes one step, which ends when the kind of
+// edit changes, the cursor is moved or a word is finished.
+#[derive(Debug)]
+pub(crate) struct UndoStack {
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+    depth: usize,
+    // what the step being added to is made of, if it's still open
+    group: Option<EditKind>,
+}
+
+impl UndoStack {
+    pub(crate) fn new() -> UndoStack {
+        UndoStack {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            depth: DEFAULT_DEPTH,
+            group: None,
+        }
+    }
+
+    // Called before each edit, with the text as it was
+    pub(crate) fn record(&mut self, kind: EditKind, value: &str, cursor: usize) {
+        self.redo.clear();
+        if kind != EditKind::Other && self.group == Some(kind) {
+            return;
+        }
+        self.push(Snapshot {value: value.to_string(), cursor});
+        self.group = (kind != EditKind::Other).then_some(kind);
+    }
+
+    pub(crate) fn break_group(&mut self) {
+        self.group = None;
+    }
+
+    fn push(&mut self, snapshot: Snapshot) {
+        self.undo.push_back(snapshot);
+        while self.undo.len() > self.depth {
+            self.undo.pop_front();
+        }
+    }
+
+    // Text and cursor to go back to, given the current ones
+    pub(crate) fn undo(&mut self, value: &str, cursor: usize) -> Option<(String, usize)> {
+        let step = self.undo.pop_back()?;
+        self.redo.push(Snapshot {value: value.to_string(), cursor});
+        self.group = None;
+        Some((step.value, step.cursor))
+    }
+
+    pub(crate) fn redo(&mut self, value: &str, cursor: usize) -> Option<(String, usize)> {
+        let step = self.redo.pop()?;
+        self.push(Snapshot {value: value.to_string(), cursor});
+        self.group = None;
+        Some((step.value, step.cursor))
+    }
+
+    pub(crate) fn can_undo(&self) -> bool { !self.undo.is_empty() }
+    pub(crate) fn can_redo(&self) -> bool { !self.redo.is_empty() }
+
+    // 0 turns undo off
+    pub(crate) fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        while self.undo.len() > depth {
+            self.undo.pop_front();
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.group = None;
+    }
+}
+
+// Ctrl+Z undoes, Ctrl+Shift+Z and Ctrl+Y redo. Some(true) for a redo.
+pub(crate) fn undo_key(key: Keycode, keymod: Mod) -> Option<bool> {
+    let mods = Modifiers::from(keymod);
+    match key {
+        Keycode::Z if mods == Modifiers::CTRL => Some(false),
+        Keycode::Z if mods == Modifiers::CTRL | Modifiers::SHIFT => Some(true),
+        Keycode::Y if mods == Modifiers::CTRL => Some(true),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Runs edits through the stack the way a text field does, recording
+    // the value as it was before each one
+    fn edit(stack: &mut UndoStack, value: &mut String, kind: EditKind, new: &str) {
+        stack.record(kind, value, value.len());
+        *value = new.to_string();
+    }
+
+    fn undo(stack: &mut UndoStack, value: &mut String) -> bool {
+        match stack.undo(value, value.len()) {
+            Some((old, _)) => {
+                *value = old;
+                true
+            },
+            None => false,
+        }
+    }
+
+    #[test]
+    fn typing_in_a_row_is_one_step() {
+        let (mut stack, mut value) = (UndoStack::new(), String::new());
+        for new in ["a", "ab", "abc"] {
+            edit(&mut stack, &mut value, EditKind::Typing, new);
+        }
+        assert!(undo(&mut stack, &mut value));
+        assert_eq!(value, "");
+        assert!(!stack.can_undo());
+    }
+
+    #[test]
+    fn a_finished_word_ends_the_step() {
+        let (mut stack, mut value) = (UndoStack::new(), String::new());
+        edit(&mut stack, &mut value, EditKind::Typing, "a");
+        edit(&mut stack, &mut value, EditKind::Typing, "a ");
+        stack.break_group();
+        edit(&mut stack, &mut value, EditKind::Typing, "a b");
+        undo(&mut stack, &mut value);
+        assert_eq!(value, "a ");
+        undo(&mut stack, &mut value);
+        assert_eq!(value, "");
+    }
+
+    #[test]
+    fn switching_between_typing_and_deleting_ends_the_step() {
+        let (mut stack, mut value) = (UndoStack::new(), String::new());
+        edit(&mut stack, &mut value, EditKind::Typing, "ab");
+        edit(&mut stack, &mut value, EditKind::Deleting, "a");
+        edit(&mut stack, &mut value, EditKind::Deleting, "");
+        undo(&mut stack, &mut value);
+        assert_eq!(value, "ab");
+    }
+
+    #[test]
+    fn other_edits_are_always_a_step_of_their_own() {
+        let (mut stack, mut value) = (UndoStack::new(), String::new());
+        edit(&mut stack, &mut value, EditKind::Other, "x");
+        edit(&mut stack, &mut value, EditKind::Other, "xy");
+        edit(&mut stack, &mut value, EditKind::Typing, "xyz");
+        undo(&mut stack, &mut value);
+        assert_eq!(value, "xy");
+        undo(&mut stack, &mut value);
+        assert_eq!(value, "x");
+        // typing after a paste doesn't join the paste's step
+        edit(&mut stack, &mut value, EditKind::Typing, "xa");
+        undo(&mut stack, &mut value);
+        assert_eq!(value, "x");
+    }
+
+    #[test]
+    fn redo_goes_back_and_a_new_edit_clears_it() {
+        let (mut stack, mut value) = (UndoStack::new(), String::new());
+        edit(&mut stack, &mut value, EditKind::Other, "a");
+        undo(&mut stack, &mut value);
+        assert_eq!(stack.redo(&value, 0), Some(("a".to_string(), 1)));
+        assert!(stack.can_undo() && !stack.can_redo());
+
+        undo(&mut stack, &mut value);
+        assert!(stack.can_redo());
+        edit(&mut stack, &mut value, EditKind::Typing, "b");
+        assert!(!stack.can_redo());
+        assert_eq!(stack.redo(&value, 1), None);
+    }
+
+    #[test]
+    fn depth_drops_the_oldest_steps() {
+        let (mut stack, mut value) = (UndoStack::new(), String::new());
+        stack.set_depth(2);
+        for new in ["a", "ab", "abc"] {
+            edit(&mut stack, &mut value, EditKind::Other, new);
+        }
+        assert!(undo(&mut stack, &mut value) && undo(&mut stack, &mut value));
+        assert_eq!(value, "a");
+        assert!(!undo(&mut stack, &mut value));
+    }
+
+    #[test]
+    fn depth_zero_turns_undo_off() {
+        let (mut stack, mut value) = (UndoStack::new(), String::new());
+        edit(&mut stack, &mut value, EditKind::Other, "a");
+        stack.set_depth(0);
+        assert!(!stack.can_undo());
+        edit(&mut stack, &mut value, EditKind::Typing, "ab");
+        assert!(!stack.can_undo());
+    }
+
+    #[test]
+    fn keys() {
+        assert_eq!(undo_key(Keycode::Z, Mod::LCTRLMOD), Some(false));
+        assert_eq!(undo_key(Keycode::Z, Mod::LCTRLMOD | Mod::RSHIFTMOD), Some(true));
+        assert_eq!(undo_key(Keycode::Y, Mod::RCTRLMOD), Some(true));
+        assert_eq!(undo_key(Keycode::Z, Mod::NOMOD), None);
+        assert_eq!(undo_key(Keycode::Y, Mod::LCTRLMOD | Mod::LALTMOD), None);
+    }
+}
@@ -0,0 +1,266 @@
+use std::fmt;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+// A rule for what a TextInput may hold. Keystrokes that would take the
+// value somewhere the rule can never be met again are refused; the rest of
+// the time the input shows whether it is met (see TextInput::is_valid).
+#[derive(Clone)]
+pub enum Validator {
+    // digits with an optional leading minus
+    Integer,
+    // anything f64 parses except inf and NaN
+    Float,
+    // at most this many characters
+    MaxLength(usize),
+    // the whole value has to match, see Validator::regex. There is no
+    // telling a good start from a bad one, so this only decides validity and
+    // never refuses keystrokes.
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+    // validity only, like Regex
+    Custom(Rc<dyn Fn(&str) -> bool>),
+}
+
+impl Validator {
+    pub fn custom<F: Fn(&str) -> bool + 'static>(f: F) -> Validator {
+        Validator::Custom(Rc::new(f))
+    }
+
+    // A Regex validator for `pattern`, anchored at both ends so that the
+    // whole value has to match rather than the leftmost alternative
+    #[cfg(feature = "regex")]
+    pub fn regex(pattern: &str) -> Result<Validator, regex::Error> {
+        regex::Regex::new(&format!("^(?:{})$", pattern)).map(Validator::Regex)
+    }
+
+    // Whether `value` could still be made valid by typing more
+    pub fn accepts(&self, value: &str) -> bool {
+        match self {
+            Validator::Integer => {
+                let digits = value.strip_prefix('-').unwrap_or(value);
+                digits.chars().all(|c| c.is_ascii_digit())
+            },
+            Validator::Float => float_prefix(value),
+            Validator::MaxLength(max) => value.chars().count() <= *max,
+            #[cfg(feature = "regex")]
+            Validator::Regex(_) => true,
+            Validator::Custom(_) => true,
+        }
+    }
+
+    pub fn is_valid(&self, value: &str) -> bool {
+        match self {
+            Validator::Integer => self.accepts(value) && value.chars().any(|c| c.is_ascii_digit()),
+            Validator::Float => float_prefix(value) && value.parse::<f64>().is_ok_and(f64::is_finite),
+            Validator::MaxLength(_) => self.accepts(value),
+            #[cfg(feature = "regex")]
+            Validator::Regex(re) => re.is_match(value),
+            Validator::Custom(f) => f(value),
+        }
+    }
+}
+
+impl Debug for Validator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Validator::Integer => f.write_str("Integer"),
+            Validator::Float => f.write_str("Float"),
+            Validator::MaxLength(max) => f.debug_tuple("MaxLength").field(max).finish(),
+            #[cfg(feature = "regex")]
+            Validator::Regex(re) => f.debug_tuple("Regex").field(&re.as_str()).finish(),
+            Validator::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+// The start of a decimal number, e.g. "-", "1." or "2e"
+fn float_prefix(value: &str) -> bool {
+    let value = value.strip_prefix('-').unwrap_or(value);
+    let (mantissa, exponent) = match value.find(['e', 'E']) {
+        Some(i) => (&value[..i], Some(&value[i + 1..])),
+        None => (value, None),
+    };
+    let mut dot = false;
+    for c in mantissa.chars() {
+        match c {
+            '.' if !dot => dot = true,
+            c if c.is_ascii_digit() => { },
+            _ => return false,
+        }
+    }
+    let Some(exponent) = exponent else {
+        return true;
+    };
+    let exponent = exponent.strip_prefix(['-', '+']).unwrap_or(exponent);
+    mantissa.chars().any(|c| c.is_ascii_digit()) && exponent.chars().all(|c| c.is_ascii_digit())
+}
+
+// A fixed format like a date or a phone number. In the pattern 9 stands
+// for a digit, a for a letter and * for anything; every other character is
+// filled in by itself as the user types, e.g. "99/99/9999".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputMask {
+    slots: Vec<char>,
+}
+
+impl InputMask {
+    pub fn new(pattern: &str) -> InputMask {
+        InputMask {slots: pattern.chars().collect()}
+    }
+
+    pub fn pattern(&self) -> String {
+        self.slots.iter().collect()
+    }
+
+    fn is_literal(slot: char) -> bool {
+        !matches!(slot, '9' | 'a' | '*')
+    }
+
+    fn fits(slot: char, c: char) -> bool {
+        match slot {
+            '9' => c.is_ascii_digit(),
+            'a' => c.is_alphabetic(),
+            '*' => true,
+            literal => c == literal,
+        }
+    }
+
+    // Whether `value` follows the pattern as far as it goes
+    pub fn accepts(&self, value: &str) -> bool {
+        value.chars().count() <= self.slots.len() && value.chars().zip(self.slots.iter()).all(|(c, &slot)| InputMask::fits(slot, c))
+    }
+
+    pub fn is_complete(&self, value: &str) -> bool {
+        value.chars().count() == self.slots.len() && self.accepts(value)
+    }
+
+    // `value` with `typed` put in at byte offset `at`, literals added where
+    // the pattern has them, and the offset just past the insert. None if
+    // some of it doesn't fit.
+    pub(crate) fn insert(&self, value: &str, at: usize, typed: &str) -> Option<(String, usize)> {
+        let mut chars: Vec<char> = value.chars().collect();
+        let mut pos = value[..at].chars().count();
+        for c in typed.chars() {
+            loop {
+                let slot = *self.slots.get(pos)?;
+                if InputMask::fits(slot, c) {
+                    chars.insert(pos, c);
+                    pos += 1;
+                    break;
+                }
+                if !InputMask::is_literal(slot) {
+                    return None;
+                }
+                if chars.get(pos) != Some(&slot) {
+                    chars.insert(pos, slot);
+                }
+                pos += 1;
+            }
+        }
+        let value: String = chars.iter().collect();
+        let cursor = chars[..pos].iter().map(|c| c.len_utf8()).sum();
+        self.accepts(&value).then_some((value, cursor))
+    }
+
+    // `value` without the bytes from `start` to `end`, what was typed after
+    // moving up into the slots left free. Literals aren't deleted, so taking
+    // out only those gives `value` back. None if what moves up doesn't fit.
+    pub(crate) fn remove(&self, value: &str, start: usize, end: usize) -> Option<String> {
+        let slot = |i: usize| self.slots.get(i).copied().unwrap_or('*');
+        let chars: Vec<char> = value.chars().collect();
+        let (from, to) = (value[..start].chars().count(), value[..end].chars().count());
+        if (from..to).all(|i| InputMask::is_literal(slot(i))) {
+            return Some(value.to_string());
+        }
+        let head: String = chars[..from].iter().collect();
+        let typed: String = chars.iter().enumerate()
+            .skip(to)
+            .filter(|&(i, _)| !InputMask::is_literal(slot(i)))
+            .map(|(_, c)| c)
+            .collect();
+        self.insert(&head, head.len(), &typed).map(|(value, _)| value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer() {
+        let v = Validator::Integer;
+        assert!(v.accepts("") && v.accepts("-") && v.accepts("-12"));
+        assert!(!v.accepts("1.") && !v.accepts("1-"));
+        assert!(v.is_valid("-12") && !v.is_valid("-") && !v.is_valid(""));
+    }
+
+    #[test]
+    fn float() {
+        let v = Validator::Float;
+        for start in ["", "-", "1.", ".5", "2e", "2e-", "-1.5E+3"] {
+            assert!(v.accepts(start), "{}", start);
+        }
+        assert!(!v.accepts("1.2.") && !v.accepts("e5") && !v.accepts("1x"));
+        assert!(v.is_valid("-1.5E+3") && v.is_valid(".5"));
+        assert!(!v.is_valid("2e") && !v.is_valid("-") && !v.is_valid("inf"));
+    }
+
+    #[test]
+    fn max_length() {
+        let v = Validator::MaxLength(3);
+        assert!(v.is_valid("äöü") && !v.accepts("abcd"));
+    }
+
+    #[test]
+    fn custom() {
+        let v = Validator::custom(|s| s.starts_with('x'));
+        assert!(v.accepts("y") && v.is_valid("xy") && !v.is_valid("yx"));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_matches_whole_value() {
+        let v = Validator::regex("a|ab").unwrap();
+        assert!(v.is_valid("a") && v.is_valid("ab"));
+        assert!(!v.is_valid("abc") && !v.is_valid("xab"));
+        assert!(v.accepts("anything"));
+    }
+
+    #[test]
+    fn mask_accepts() {
+        let mask = InputMask::new("99/99/9999");
+        assert!(mask.accepts("") && mask.accepts("12/3") && !mask.accepts("12-3"));
+        assert!(mask.is_complete("12/34/5678") && !mask.is_complete("12/34/567"));
+        assert!(!mask.accepts("12/34/56789"));
+    }
+
+    #[test]
+    fn mask_insert_fills_literals() {
+        let mask = InputMask::new("99/99/9999");
+        assert_eq!(mask.insert("12", 2, "3"), Some(("12/3".to_string(), 4)));
+        assert_eq!(mask.insert("12", 2, "/3"), Some(("12/3".to_string(), 4)));
+        assert_eq!(mask.insert("", 0, "12345678"), Some(("12/34/5678".to_string(), 10)));
+        assert_eq!(mask.insert("12", 2, "x"), None);
+        assert_eq!(mask.insert("12/34/5678", 10, "9"), None);
+        let mask = InputMask::new("aa-*");
+        assert_eq!(mask.insert("", 0, "äb?"), Some(("äb-?".to_string(), 5)));
+    }
+
+    #[test]
+    fn mask_remove_moves_rest_up() {
+        let mask = InputMask::new("99/99/9999");
+        assert_eq!(mask.remove("12/34/5678", 3, 4).as_deref(), Some("12/45/678"));
+        assert_eq!(mask.remove("12/34/5678", 0, 1).as_deref(), Some("23/45/678"));
+        assert_eq!(mask.remove("12/34/5678", 9, 10).as_deref(), Some("12/34/567"));
+        assert_eq!(mask.remove("12/3", 3, 4).as_deref(), Some("12/"));
+    }
+
+    #[test]
+    fn mask_remove_keeps_literals() {
+        let mask = InputMask::new("99/99/9999");
+        assert_eq!(mask.remove("12/34/5678", 2, 3).as_deref(), Some("12/34/5678"));
+        let mask = InputMask::new("9a");
+        assert_eq!(mask.remove("1b", 0, 1), None);
+    }
+}
@@ -0,0 +1,300 @@
+use std::any::Any;
+
+use sdl2::event::Event;
+use sdl2::mouse::SystemCursor;
+use sdl2::rect::Rect;
+
+use crate::{DrawCtx, Drawable, FontId, Role, TextRenderer, Theme, ToolkitError};
+
+// Interaction state kept the same way by every widget that has one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WidgetState {
+    pub enabled: bool,
+    pub focused: bool,
+    pub hovered: bool,
+    pub pressed: bool,
+    // holds something its validators don't allow
+    pub invalid: bool,
+}
+
+impl Default for WidgetState {
+    fn default() -> WidgetState {
+        WidgetState {
+            enabled: true,
+            focused: false,
+            hovered: false,
+            pressed: false,
+            invalid: false,
+        }
+    }
+}
+
+// What is being dragged from one widget to another: the widget it came
+// from, the text shown under the pointer, and the row or item it was if any
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DragData {
+    pub source: String,
+    pub text: String,
+    pub index: Option<usize>,
+}
+
+// Anything that can live inside a tab and receive input
+pub trait Widget: Drawable + Any {
+    fn name(&self) -> &str;
+    fn rect(&self) -> Rect;
+    fn set_rect(&mut self, rect: Rect);
+
+    // Size the widget would like to have, used by layout containers
+    fn size_hint(&self) -> (u32, u32) {
+        let rect = self.rect();
+        (rect.width(), rect.height())
+    }
+
+    // Called with the area the parent has to offer. Containers position
+    // their children here, plain widgets keep wherever they were put.
+    fn layout(&mut self, _area: Rect) { }
+
+    // Share of a box layout's leftover space along its main axis, 0 to keep
+    // the hinted size
+    fn stretch(&self) -> u32 { 0 }
+    // Take the box layout's full breadth whatever its alignment
+    fn fills_cross(&self) -> bool { false }
+
+    fn children(&self) -> &[Box<dyn Widget>] { &[] }
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] { &mut [] }
+    // Area the children show through, if the container clips them. Input
+    // outside it stays with the container.
+    fn viewport(&self) -> Option<Rect> { None }
+
+    // Hands down the global theme. Widgets with a theme of their own ignore
+    // it, containers just pass it on.
+    fn apply_theme(&mut self, theme: &Theme) {
+        for child in self.children_mut() {
+            child.apply_theme(theme);
+        }
+    }
+
+    // Switches the text to another font loaded into the FontManager.
+    // Widgets without text ignore it.
+    fn set_font(&mut self, _text: &TextRenderer, _font: FontId) -> Result<(), ToolkitError> {
+        Ok(())
+    }
+
+    // Popups and other things that draw on top of everything else. Input
+    // inside the overlay rect goes to the widget before anything below it.
+    fn overlay_rect(&self) -> Option<Rect> { None }
+    fn draw_overlay(&self, ctx: &mut DrawCtx) -> Result<(), ToolkitError> {
+        for child in self.children() {
+            child.draw_overlay(ctx)?;
+        }
+        Ok(())
+    }
+
+    // Whether a completed press/release over the widget is reported as a click
+    fn clickable(&self) -> bool { false }
+    // Widgets with a WidgetState hand it out here, the setters below work on it
+    fn widget_state(&self) -> Option<&WidgetState> { None }
+    fn widget_state_mut(&mut self) -> Option<&mut WidgetState> { None }
+
+    // Disabled widgets are drawn greyed out and get no input. Containers
+    // pass it on to their children.
+    fn is_enabled(&self) -> bool {
+        self.widget_state().is_none_or(|s| s.enabled)
+    }
+    fn set_enabled(&mut self, enabled: bool) {
+        match self.widget_state_mut() {
+            Some(state) => {
+                state.enabled = enabled;
+                state.hovered &= enabled;
+                state.pressed &= enabled;
+            },
+            None => {
+                for child in self.children_mut() {
+                    child.set_enabled(enabled);
+                }
+            },
+        }
+    }
+
+    // Whether the widget can take keyboard focus
+    fn focusable(&self) -> bool { false }
+    fn set_focused(&mut self, focused: bool) {
+        if let Some(state) = self.widget_state_mut() {
+            state.focused = focused;
+        }
+    }
+    // Pointer over the widget, and a press on it that hasn't been released yet
+    fn set_hovered(&mut self, hovered: bool) {
+        if let Some(state) = self.widget_state_mut() {
+            state.hovered = hovered;
+        }
+    }
+    fn set_pressed(&mut self, pressed: bool) {
+        if let Some(state) = self.widget_state_mut() {
+            state.pressed = pressed;
+        }
+    }
+
+    // Whether typed text is wanted while focused. SDL text input (and the
+    // on-screen keyboard) is only on for those.
+    fn wants_text_input(&self) -> bool { false }
+
+    // Gets mouse events that land on the widget and keyboard events while it
+    // is focused. Returns true if the event was consumed.
+    fn handle_event(&mut self, _event: &Event) -> bool { false }
+
+    // Pointer shape while over the widget, the arrow if None
+    fn cursor(&self) -> Option<SystemCursor> { None }
+
+    // Asks for a repaint in RedrawMode::OnDemand, e.g. while animating
+    fn needs_redraw(&self) -> bool { false }
+
+    // Moves the content along with a finger dragged (dx, dy) pixels across
+    // it. True if it moved, otherwise the swipe goes on to the parent.
+    fn scroll_pixels(&mut self, _dx: i32, _dy: i32) -> bool { false }
+
+    // Shows `value` on the way to the one set, for Property::Value
+    // animations. Widgets without a value to show ignore it.
+    fn animate_value(&mut self, _value: f64) { }
+
+    // What a drag starting at (x, y) picks up, for widgets made draggable
+    // with Toolkit::set_draggable. None leaves the press to the widget.
+    fn drag_data(&self, _x: i32, _y: i32) -> Option<DragData> { None }
+
+    // Clipboard shortcuts on the focused widget. copy_text and cut_text give
+    // the text to put on the clipboard, paste_text is true if it took the text.
+    fn copy_text(&self) -> Option<String> { None }
+    fn cut_text(&mut self) -> Option<String> { None }
+    fn paste_text(&mut self, _text: &str) -> bool { false }
+
+    // True once after the user changed the widget's value, checked by the
+    // Toolkit after every event it hands to the widget
+    fn take_changed(&mut self) -> bool { false }
+
+    // A link the user clicked in the widget since it was last asked, checked
+    // along with take_changed
+    fn take_link(&mut self) -> Option<String> { None }
+
+    // How assistive technology sees the widget: what it is, the text to read
+    // out (the name if None), its current value and whether it is checked
+    fn role(&self) -> Role {
+        if self.children().is_empty() { Role::Generic } else { Role::Group }
+    }
+    fn access_label(&self) -> Option<String> { None }
+    fn access_value(&self) -> Option<String> { None }
+    fn access_checked(&self) -> Option<bool> { None }
+
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl std::fmt::Debug for dyn Widget {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Widget")
+            .field("name", &self.name())
+            .field("rect", &self.rect())
+            .field("children", &self.children())
+            .finish()
+    }
+}
+
+// Indices leading from a tab's item list down to a nested widget
+pub(crate) type WidgetPath = Vec<usize>;
+
+// Deepest widget under the point. Widgets drawn last are on top and win.
+pub(crate) fn hit_path(items: &[Box<dyn Widget>], x: i32, y: i32) -> Option<WidgetPath> {
+    let idx = items.iter().rposition(|w| w.rect().contains_point((x, y)))?;
+    let mut path = vec![idx];
+    if items[idx].viewport().is_some_and(|r| !r.contains_point((x, y))) {
+        return Some(path);
+    }
+    if let Some(rest) = hit_path(items[idx].children(), x, y) {
+        path.extend(rest);
+    }
+    Some(path)
+}
+
+// Widget whose open overlay covers the point, those sit above everything
+pub(crate) fn overlay_hit(items: &[Box<dyn Widget>], x: i32, y: i32) -> Option<WidgetPath> {
+    items.iter().enumerate().rev().find_map(|(idx, w)| {
+        if let Some(mut path) = overlay_hit(w.children(), x, y) {
+            path.insert(0, idx);
+            return Some(path);
+        }
+        w.overlay_rect()
+            .filter(|r| r.contains_point((x, y)))
+            .map(|_| vec![idx])
+    })
+}
+
+// Every focusable widget in tree order, which is the Tab key's order
+pub(crate) fn focusable_paths(items: &[Box<dyn Widget>]) -> Vec<WidgetPath> {
+    let mut paths = Vec::new();
+    for (idx, w) in items.iter().enumerate() {
+        if w.focusable() && w.is_enabled() {
+            paths.push(vec![idx]);
+        }
+        for mut path in focusable_paths(w.children()) {
+            path.insert(0, idx);
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+pub(crate) fn get<'a>(items: &'a [Box<dyn Widget>], path: &[usize]) -> Option<&'a dyn Widget> {
+    let (first, rest) = path.split_first()?;
+    let widget = items.get(*first)?;
+    if rest.is_empty() {
+        Some(widget.as_ref())
+    } else {
+        get(widget.children(), rest)
+    }
+}
+
+pub(crate) fn get_mut<'a>(items: &'a mut [Box<dyn Widget>], path: &[usize]) -> Option<&'a mut dyn Widget> {
+    let (first, rest) = path.split_first()?;
+    let widget = items.get_mut(*first)?;
+    if rest.is_empty() {
+        Some(widget.as_mut())
+    } else {
+        get_mut(widget.children_mut(), rest)
+    }
+}
+
+// Depth-first search through the whole tree
+pub(crate) fn find<'a>(items: &'a [Box<dyn Widget>], pred: &dyn Fn(&dyn Widget) -> bool) -> Option<&'a dyn Widget> {
+    items.iter().find_map(|w| {
+        if pred(w.as_ref()) {
+            Some(w.as_ref())
+        } else {
+            find(w.children(), pred)
+        }
+    })
+}
+
+pub(crate) fn find_mut<'a>(items: &'a mut [Box<dyn Widget>], pred: &dyn Fn(&dyn Widget) -> bool) -> Option<&'a mut dyn Widget> {
+    for w in items.iter_mut() {
+        if pred(w.as_ref()) {
+            return Some(w.as_mut());
+        }
+        if let Some(found) = find_mut(w.children_mut(), pred) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+pub(crate) fn walk(items: &[Box<dyn Widget>], f: &mut dyn FnMut(&dyn Widget)) {
+    for w in items.iter() {
+        f(w.as_ref());
+        walk(w.children(), f);
+    }
+}
+
+pub(crate) fn walk_mut(items: &mut [Box<dyn Widget>], f: &mut dyn FnMut(&mut dyn Widget)) {
+    for w in items.iter_mut() {
+        f(w.as_mut());
+        walk_mut(w.children_mut(), f);
+    }
+}
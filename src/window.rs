@@ -0,0 +1,144 @@
+use std::fmt;
+use std::fmt::Debug;
+use std::mem;
+
+use log::warn;
+use sdl2::render::Canvas;
+use sdl2::video::{Window, WindowBuilder};
+
+use crate::anim::TabSwitch;
+use crate::dialog::Dialog;
+use crate::menu::Menu;
+use crate::toast::{self, Toast};
+use crate::widget::WidgetPath;
+use crate::{DragData, ErrorContext, LayerItem, MenuItem, RendererKind, Tab, TextRenderer, ToastId, Toolkit, ToolkitError};
+
+// SDL's id of a window, as found in the window_id of its events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WindowId(pub(crate) u32);
+
+// Makes the window and a renderer of the kind asked for. If no accelerated
+// renderer can be had it falls back to software, with a window made afresh
+// since the first one went down with the renderer.
+pub(crate) fn build_canvas(window: &WindowBuilder, kind: RendererKind, vsync: bool) -> Result<Canvas<Window>, ToolkitError> {
+    let canvas = window.build().context("creating the window")?.into_canvas();
+    let canvas = if vsync { canvas.present_vsync() } else { canvas };
+    if kind == RendererKind::Software {
+        return canvas.software().build().context("creating the software renderer");
+    }
+    match canvas.accelerated().build() {
+        Ok(canvas) => Ok(canvas),
+        Err(e) => {
+            warn!("no accelerated renderer ({}), falling back to software", e);
+            let canvas = window.build().context("creating the window")?.into_canvas().software();
+            let canvas = if vsync { canvas.present_vsync() } else { canvas };
+            canvas.build().context("creating the software renderer")
+        },
+    }
+}
+
+// Everything that belongs to one window. The Toolkit keeps the window it is
+// working on in its own fields and the others parked in these, swapping them
+// around whenever it turns to another window.
+pub(crate) struct WindowData {
+    pub(crate) id: WindowId,
+    canvas: Canvas<Window>,
+    // textures only work with the renderer that made them
+    text: TextRenderer,
+    tabs: Vec<Tab>,
+    tab_pos: usize,
+    tab_switch: Option<TabSwitch>,
+    items: Vec<LayerItem>,
+    mouse: (i32, i32),
+    mouse_inside: bool,
+    hover: Option<WidgetPath>,
+    pressed: Option<WidgetPath>,
+    focus: Option<WidgetPath>,
+    drop_target: Option<WidgetPath>,
+    drag_press: Option<(WidgetPath, i32, i32)>,
+    drag: Option<DragData>,
+    dialogs: Vec<Dialog>,
+    toasts: Vec<Toast>,
+    menu: Option<Menu>,
+    menu_bar: Vec<MenuItem>,
+    status: Option<[String; 3]>,
+    dirty: bool,
+}
+
+impl WindowData {
+    pub(crate) fn new(canvas: Canvas<Window>, text: TextRenderer) -> WindowData {
+        WindowData {
+            id: WindowId(canvas.window().id()),
+            canvas,
+            text,
+            tabs: Vec::new(),
+            tab_pos: 0,
+            tab_switch: None,
+            items: Vec::new(),
+            mouse: (0, 0),
+            mouse_inside: false,
+            hover: None,
+            pressed: None,
+            focus: None,
+            drop_target: None,
+            drag_press: None,
+            drag: None,
+            dialogs: Vec::new(),
+            toasts: Vec::new(),
+            menu: None,
+            menu_bar: Vec::new(),
+            status: None,
+            dirty: true,
+        }
+    }
+
+    pub(crate) fn is_dirty(&self) -> bool { self.dirty }
+
+    pub(crate) fn dismiss_toast(&mut self, id: ToastId) -> bool {
+        let removed = toast::remove(&mut self.toasts, id);
+        self.dirty |= removed;
+        removed
+    }
+
+    // Hides the window and hands back its canvas and renderer for the next
+    // window to be opened, dropping everything that was on it
+    pub(crate) fn retire(mut self) -> (Canvas<Window>, TextRenderer) {
+        self.canvas.window_mut().hide();
+        (self.canvas, self.text)
+    }
+
+    // Trades places with the window the Toolkit is working on
+    pub(crate) fn swap(&mut self, tk: &mut Toolkit) {
+        mem::swap(&mut self.id, &mut tk.window);
+        mem::swap(&mut self.canvas, &mut tk.canvas);
+        mem::swap(&mut self.text, &mut tk.text);
+        mem::swap(&mut self.tabs, &mut tk.tabs);
+        mem::swap(&mut self.tab_pos, &mut tk.tab_pos);
+        mem::swap(&mut self.tab_switch, &mut tk.tab_switch);
+        mem::swap(&mut self.items, &mut tk.items);
+        mem::swap(&mut self.mouse, &mut tk.mouse);
+        mem::swap(&mut self.mouse_inside, &mut tk.mouse_inside);
+        mem::swap(&mut self.hover, &mut tk.hover);
+        mem::swap(&mut self.pressed, &mut tk.pressed);
+        mem::swap(&mut self.focus, &mut tk.focus);
+        mem::swap(&mut self.drop_target, &mut tk.drop_target);
+        mem::swap(&mut self.drag_press, &mut tk.drag_press);
+        mem::swap(&mut self.drag, &mut tk.drag);
+        mem::swap(&mut self.dialogs, &mut tk.dialogs);
+        mem::swap(&mut self.toasts, &mut tk.toasts);
+        mem::swap(&mut self.menu, &mut tk.menu);
+        mem::swap(&mut self.menu_bar, &mut tk.menu_bar);
+        mem::swap(&mut self.status, &mut tk.status);
+        mem::swap(&mut self.dirty, &mut tk.dirty);
+    }
+}
+
+impl Debug for WindowData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WindowData")
+            .field("id", &self.id)
+            .field("tabs", &self.tabs)
+            .field("tab_pos", &self.tab_pos)
+            .finish()
+    }
+}